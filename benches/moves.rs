@@ -0,0 +1,50 @@
+// Cost of one move at each board size, next to the linear scan for the blank
+// that moves used to do. A move should cost the same whatever the size,
+// while the scan grows with the number of tiles.
+//
+//     cargo bench --bench moves
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+use tile_game_rs::board::{Board, Direction};
+
+const MOVES: u32 = 1_000_000;
+
+// The blank circles a 2x2 block in the corner, so every move is legal
+const CYCLE: [Direction; 4] = [
+    Direction::Down,
+    Direction::Right,
+    Direction::Up,
+    Direction::Left,
+];
+
+fn per_move(elapsed: Duration) -> f64 {
+    elapsed.as_nanos() as f64 / MOVES as f64
+}
+
+fn main() {
+    println!("{:>5}  {:>10}  {:>10}", "size", "move ns", "scan ns");
+    for size in [3, 8, 16, 32] {
+        let mut board = Board::goal_state(size);
+        let started = Instant::now();
+        for i in 0..MOVES {
+            black_box(board.move_tile(CYCLE[i as usize % CYCLE.len()]));
+        }
+        let moving = started.elapsed();
+
+        let started = Instant::now();
+        for _ in 0..MOVES {
+            black_box(black_box(&board).tiles().iter().position(|&tile| tile == 0));
+        }
+        let scanning = started.elapsed();
+
+        println!(
+            "{:>5}  {:>10.1}  {:>10.1}",
+            size,
+            per_move(moving),
+            per_move(scanning)
+        );
+    }
+}
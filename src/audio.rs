@@ -0,0 +1,55 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+    thread::sleep,
+    time::Duration,
+};
+use tile_game_rs::config::{Cue, Sound};
+
+// Terminals merge bells that arrive together, so a double bell needs a gap
+const DOUBLE_BELL_GAP: Duration = Duration::from_millis(150);
+
+// One bit per cue in Cue::ALL order. Zero until enable is called, which only
+// the terminal UI does, so pipe and line mode stay silent.
+static ENABLED: AtomicU8 = AtomicU8::new(0);
+
+// Held while a cue is written so two can never interleave
+static OUTPUT: Mutex<()> = Mutex::new(());
+
+fn bit(cue: Cue) -> u8 {
+    1 << Cue::ALL.iter().position(|&c| c == cue).unwrap_or(0)
+}
+
+pub fn enable(sound: Sound) {
+    let bits = Cue::ALL
+        .into_iter()
+        .filter(|&cue| sound.plays(cue))
+        .fold(0, |bits, cue| bits | bit(cue));
+    ENABLED.store(bits, Ordering::SeqCst);
+}
+
+// Every sound the game makes goes through here. Call it after the frame it
+// belongs to has been rendered: each renderer flushes stdout before
+// returning, so the bell can't land in the middle of a frame.
+pub fn cue(cue: Cue) {
+    if ENABLED.load(Ordering::SeqCst) & bit(cue) == 0 {
+        return;
+    }
+    log::trace!("sound cue {}", cue.name());
+    let _lock = OUTPUT.lock();
+    // A bell that can't be written isn't worth stopping the game for
+    let _ = bell();
+    if cue == Cue::Win {
+        sleep(DOUBLE_BELL_GAP);
+        let _ = bell();
+    }
+}
+
+fn bell() -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(b"\x07")?;
+    stdout.flush()
+}
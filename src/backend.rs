@@ -0,0 +1,215 @@
+use crate::{
+    theme::{self, Theme},
+    ui::{self, Cell, CellStyle},
+};
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    style::{Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+    QueueableCommand,
+};
+use std::io::{self, Write};
+
+// The few drawing operations the renderer needs, so it can target either a
+// real terminal or an in-memory grid
+pub trait Backend {
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()>;
+    fn print_styled(&mut self, text: &str, style: CellStyle) -> io::Result<()>;
+    fn clear(&mut self) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+    fn size(&self) -> io::Result<(u16, u16)>;
+}
+
+// Queues crossterm commands on any writer, normally stdout
+pub struct CrosstermBackend<W: Write> {
+    writer: W,
+    theme: Theme,
+    truecolor: bool, // Otherwise RGB colors are brought down to 256
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    pub fn new(writer: W, theme: Theme) -> Self {
+        Self {
+            writer,
+            theme,
+            truecolor: theme::truecolor_supported(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+}
+
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.writer.queue(MoveTo(x, y))?;
+        Ok(())
+    }
+
+    fn print_styled(&mut self, text: &str, style: CellStyle) -> io::Result<()> {
+        let (mut foreground, mut background) = self.theme.style_colors(style);
+        if !self.truecolor {
+            foreground = foreground.map(theme::to_256);
+            background = background.map(theme::to_256);
+        }
+        if let Some(color) = foreground {
+            self.writer.queue(SetForegroundColor(color))?;
+        }
+        if let Some(color) = background {
+            self.writer.queue(SetBackgroundColor(color))?;
+        }
+        self.writer.queue(Print(text))?;
+        self.writer.queue(ResetColor)?;
+        Ok(())
+    }
+
+    // Also hides the cursor while the frame is drawn
+    fn clear(&mut self) -> io::Result<()> {
+        self.writer.queue(Clear(ClearType::All))?;
+        self.writer.queue(Hide)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.queue(Show)?;
+        self.writer.flush()
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+}
+
+// Records drawing into a fixed-size grid of cells for assertions in tests
+pub struct CaptureBackend {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    cursor: (u16, u16),
+}
+
+impl CaptureBackend {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+            cursor: (0, 0),
+        }
+    }
+
+    pub fn cell(&self, x: u16, y: u16) -> Cell {
+        self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    // Rows with trailing spaces and empty rows at the bottom removed, matching
+    // ui::render_to_string without markers
+    pub fn to_plain_string(&self) -> String {
+        let lines = self
+            .cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.ch)
+                    .filter(|&ch| ch != ui::WIDE)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+
+        let used = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |last| last + 1);
+        lines[..used]
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+}
+
+impl Backend for CaptureBackend {
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.cursor = (x, y);
+        Ok(())
+    }
+
+    // Text past the right edge is dropped, like a terminal with wrapping off
+    fn print_styled(&mut self, text: &str, style: CellStyle) -> io::Result<()> {
+        // Wide characters cover the next cell too, as the frame has them
+        for ch in text.chars() {
+            let width = ui::char_width(ch) as u16;
+            for (offset, ch) in [ch, ui::WIDE].into_iter().take(width as usize).enumerate() {
+                let (x, y) = (self.cursor.0 + offset as u16, self.cursor.1);
+                if x < self.width && y < self.height {
+                    self.cells[y as usize * self.width as usize + x as usize] = Cell { ch, style };
+                }
+            }
+            self.cursor.0 = self.cursor.0.saturating_add(width);
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.cells.fill(Cell::default());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok((self.width, self.height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_backend() {
+        let mut backend = CaptureBackend::new(8, 3);
+        backend.move_to(2, 1).unwrap();
+        backend.print_styled("tile game", CellStyle::Tile).unwrap();
+
+        assert_eq!(backend.to_plain_string(), "\n  tile g\n");
+        assert_eq!(
+            backend.cell(2, 1),
+            Cell {
+                ch: 't',
+                style: CellStyle::Tile
+            }
+        );
+        assert_eq!(backend.cell(0, 0), Cell::default());
+
+        backend.clear().unwrap();
+        assert_eq!(backend.to_plain_string(), "");
+    }
+
+    #[test]
+    fn test_crossterm_backend_writes_text() {
+        let mut backend = CrosstermBackend::new(Vec::new(), Theme::Classic);
+        backend.move_to(0, 0).unwrap();
+        backend.print_styled("You won", CellStyle::Win).unwrap();
+        backend.flush().unwrap();
+        assert!(String::from_utf8_lossy(&backend.writer).contains("You won"));
+    }
+
+    #[test]
+    fn test_crossterm_backend_256_colors() {
+        let block = CellStyle::Block([255, 0, 0]);
+        let mut backend = CrosstermBackend::new(Vec::new(), Theme::Picture);
+        backend.truecolor = true;
+        backend.print_styled(" ", block).unwrap();
+        assert!(String::from_utf8_lossy(&backend.writer).contains("48;2;255;0;0m"));
+
+        backend.writer.clear();
+        backend.truecolor = false;
+        backend.print_styled(" ", block).unwrap();
+        assert!(String::from_utf8_lossy(&backend.writer).contains("48;5;196m"));
+    }
+}
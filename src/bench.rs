@@ -0,0 +1,237 @@
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+use std::{fmt::Write, time::Instant};
+use tile_game_rs::{
+    board::Board,
+    solver::{Heuristic, Solver, SolverResult},
+};
+
+use crate::cli::BenchArgs;
+
+// How every heuristic did on the same set of scrambles, printed by
+// bench-heuristics. Everything except the timings depends only on the seed.
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub size: u8,
+    pub count: u32,
+    pub seed: u64,
+    pub moves: usize,
+    pub solver: &'static str,
+    pub heuristics: Vec<HeuristicStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeuristicStats {
+    pub heuristic: &'static str,
+    pub mean_nodes: f64,
+    pub median_nodes: u64,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub mean_length: f64,
+    pub wins: u32, // Boards where no other heuristic expanded fewer nodes
+}
+
+// The same boards for a given seed on every machine
+fn scrambles(args: &BenchArgs) -> Vec<Board> {
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    (0..args.count)
+        .map(|_| {
+            let mut board = Board::goal_state(args.size as usize);
+            board.scramble_from_position(&mut rng, args.moves);
+            board
+        })
+        .collect()
+}
+
+pub fn run(args: &BenchArgs) -> BenchReport {
+    let boards = scrambles(args);
+    let solvers: Vec<Box<dyn Solver>> = Heuristic::ALL
+        .into_iter()
+        .map(|heuristic| args.solver.with(heuristic))
+        .collect();
+
+    // Each indexed [heuristic][board]
+    let mut nodes = vec![Vec::new(); Heuristic::ALL.len()];
+    let mut millis = vec![Vec::new(); Heuristic::ALL.len()];
+    let mut lengths = vec![Vec::new(); Heuristic::ALL.len()];
+    for (index, board) in boards.iter().enumerate() {
+        for (h, (heuristic, solver)) in Heuristic::ALL.into_iter().zip(&solvers).enumerate() {
+            let started = Instant::now();
+            let (nodes_expanded, length) = match solver.solve(board) {
+                SolverResult::Solved(search) => (search.nodes_expanded, search.moves.len()),
+                // A scramble can walk back to solved
+                SolverResult::AlreadySolved => (0, 0),
+                result => panic!("scrambles from solved are solvable, not {:?}", result),
+            };
+            millis[h].push(started.elapsed().as_secs_f64() * 1000.0);
+            nodes[h].push(nodes_expanded);
+            lengths[h].push(length);
+            log::debug!(
+                "board {} with {} and {}: {} nodes",
+                index,
+                solver.name(),
+                heuristic.name(),
+                nodes_expanded
+            );
+        }
+    }
+
+    let mut wins = vec![0; Heuristic::ALL.len()];
+    for b in 0..boards.len() {
+        let fewest = nodes.iter().map(|counts| counts[b]).min().unwrap_or(0);
+        for (h, counts) in nodes.iter().enumerate() {
+            if counts[b] == fewest {
+                wins[h] += 1;
+            }
+        }
+    }
+
+    let heuristics = Heuristic::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(h, heuristic)| HeuristicStats {
+            heuristic: heuristic.name(),
+            mean_nodes: mean(nodes[h].iter().map(|&n| n as f64)),
+            median_nodes: median(&nodes[h]),
+            mean_ms: mean(millis[h].iter().copied()),
+            median_ms: median(&millis[h]),
+            mean_length: mean(lengths[h].iter().map(|&n| n as f64)),
+            wins: wins[h],
+        })
+        .collect();
+
+    BenchReport {
+        size: args.size,
+        count: args.count,
+        seed: args.seed,
+        moves: args.moves,
+        solver: solvers.first().map_or("", |solver| solver.name()),
+        heuristics,
+    }
+}
+
+impl BenchReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("reports only contain plain data")
+    }
+
+    pub fn table(&self) -> String {
+        let mut out = format!(
+            "{}x{}, {} scrambles of {} moves, seed {}, solved by {}\n\n",
+            self.size, self.size, self.count, self.moves, self.seed, self.solver
+        );
+        let _ = writeln!(
+            out,
+            "{:<10} {:>12} {:>12} {:>10} {:>10} {:>8}",
+            "heuristic", "mean nodes", "median nodes", "mean ms", "median ms", "length"
+        );
+        for stats in &self.heuristics {
+            let _ = writeln!(
+                out,
+                "{:<10} {:>12.1} {:>12} {:>10.2} {:>10.2} {:>8.1}",
+                stats.heuristic,
+                stats.mean_nodes,
+                stats.median_nodes,
+                stats.mean_ms,
+                stats.median_ms,
+                stats.mean_length
+            );
+        }
+
+        let wins: Vec<_> = self
+            .heuristics
+            .iter()
+            .map(|stats| format!("{} {}/{}", stats.heuristic, stats.wins, self.count))
+            .collect();
+        let _ = writeln!(
+            out,
+            "\nFewest nodes (ties count for each): {}",
+            wins.join(", ")
+        );
+        out
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+fn median<T: Copy + PartialOrd + Default>(values: &[T]) -> T {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("timings and counts are never NaN"));
+    sorted.get(sorted.len() / 2).copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{JsonArgs, SolverKind};
+
+    fn args(seed: u64) -> BenchArgs {
+        BenchArgs {
+            size: 3,
+            count: 6,
+            seed,
+            moves: 14,
+            solver: SolverKind::IdaStar,
+            json: JsonArgs {
+                json: false,
+                json_out: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_report_is_reproducible() {
+        let first = run(&args(7));
+        let second = run(&args(7));
+        for (a, b) in first.heuristics.iter().zip(&second.heuristics) {
+            assert_eq!(a.heuristic, b.heuristic);
+            assert_eq!(a.mean_nodes, b.mean_nodes);
+            assert_eq!(a.median_nodes, b.median_nodes);
+            assert_eq!(a.wins, b.wins);
+        }
+        assert_eq!(scrambles(&args(7)), scrambles(&args(7)));
+        assert_ne!(scrambles(&args(7)), scrambles(&args(8)));
+    }
+
+    #[test]
+    fn test_report_contents() {
+        let report = run(&args(1));
+        assert_eq!(report.heuristics.len(), Heuristic::ALL.len());
+        // Both heuristics are admissible, so they agree on the shortest length
+        assert_eq!(
+            report.heuristics[0].mean_length,
+            report.heuristics[1].mean_length
+        );
+        let wins: u32 = report.heuristics.iter().map(|stats| stats.wins).sum();
+        assert!(wins >= report.count);
+
+        let table = report.table();
+        assert!(table.starts_with("3x3, 6 scrambles of 14 moves, seed 1, solved by ida*\n"));
+        assert!(table.contains("manhattan"));
+        assert!(report.to_json().contains("\"median_nodes\":"));
+
+        // A* finds the same lengths, with nodes of its own
+        let a_star = run(&BenchArgs {
+            solver: SolverKind::AStar,
+            ..args(1)
+        });
+        assert_eq!(a_star.solver, "a*");
+        for (a, ida) in a_star.heuristics.iter().zip(&report.heuristics) {
+            assert_eq!(a.mean_length, ida.mean_length);
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[5, 1, 3]), 3);
+        assert_eq!(median::<u64>(&[]), 0);
+        assert_eq!(mean([1.0, 2.0].into_iter()), 1.5);
+    }
+}
@@ -0,0 +1,2381 @@
+use crate::{
+    difficulty::{self, DifficultyRating, DifficultyWeights},
+    zobrist,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+    fmt,
+    str::FromStr,
+};
+
+pub const DEFAULT_SIZE: usize = 3;
+pub const MIN_SIZE: usize = 2;
+// Kept well below what Tile can number because solvability checks and
+// shuffles grow with the square of the tile count
+pub const MAX_SIZE: usize = 32;
+
+// A tile number, with 0 for the blank
+pub type Tile = u16;
+
+// A cell as (row, column), counting from the top left
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position(pub usize, pub usize);
+
+impl Position {
+    pub fn row(&self) -> usize {
+        self.0
+    }
+
+    pub fn col(&self) -> usize {
+        self.1
+    }
+
+    // The index into a board's tiles, which are stored row by row
+    pub fn to_index(&self, board_size: usize) -> usize {
+        self.row() * board_size + self.col()
+    }
+
+    pub fn from_index(index: usize, board_size: usize) -> Position {
+        Position(index / board_size, index % board_size)
+    }
+
+    pub fn manhattan_distance_to(&self, other: &Position) -> usize {
+        self.row().abs_diff(other.row()) + self.col().abs_diff(other.col())
+    }
+
+    // The same on a board whose edges join up, where each axis can be
+    // crossed either way round
+    pub fn toroidal_distance_to(&self, other: &Position, board_size: usize) -> usize {
+        let wrapped = |distance: usize| distance.min(board_size - distance);
+        wrapped(self.row().abs_diff(other.row())) + wrapped(self.col().abs_diff(other.col()))
+    }
+}
+
+// from_random walks this many moves per tile, enough to thoroughly mix the board
+const RANDOM_SCRAMBLE_STEPS_PER_TILE: usize = 25;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    PositionOutOfBounds { position: usize, len: usize },
+    WrongTileCount { expected: usize, found: usize },
+    NotSquare { found: usize },
+    InvalidSize { size: usize }, // 0, or past MAX_SIZE
+    InvalidTile { tile: Tile },
+    DuplicateTile { tile: Tile },
+    InvalidNumber { token: String },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::PositionOutOfBounds { position, len } => {
+                write!(
+                    f,
+                    "position {} is out of bounds for a board of {} tiles",
+                    position, len
+                )
+            }
+            BoardError::WrongTileCount { expected, found } => {
+                write!(f, "expected {} tiles but found {}", expected, found)
+            }
+            BoardError::NotSquare { found } => {
+                write!(f, "{} tiles cannot form a square board", found)
+            }
+            BoardError::InvalidSize { size } => write!(
+                f,
+                "boards are 1x1 to {max}x{max}, not {size}x{size}",
+                max = MAX_SIZE,
+                size = size
+            ),
+            BoardError::InvalidTile { tile } => {
+                write!(f, "tile {} is out of range for this board", tile)
+            }
+            BoardError::DuplicateTile { tile } => write!(f, "tile {} appears more than once", tile),
+            BoardError::InvalidNumber { token } => write!(f, "'{}' is not a tile number", token),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+// Problems with move notation; `index` is the byte offset into the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidChar { ch: char, index: usize },
+    IllegalMove { direction: Direction, index: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidChar { ch, index } => {
+                write!(
+                    f,
+                    "'{}' at byte {} is not a move (use U, D, L, R)",
+                    ch, index
+                )
+            }
+            ParseError::IllegalMove { direction, index } => {
+                write!(
+                    f,
+                    "move '{}' at byte {} can't be made on this board",
+                    direction.to_char(),
+                    index
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// What was wrong with the move that stopped apply_move_sequence_checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    InvalidChar(char),
+    IllegalMove(Direction),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::InvalidChar(ch) => write!(f, "'{}' is not a move (use U, D, L, R)", ch),
+            MoveError::IllegalMove(direction) => {
+                write!(f, "move '{}' can't be made here", direction.to_char())
+            }
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+// Serialized as a flat tile list, validated again on the way back in
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "Vec<Tile>", try_from = "Vec<Tile>")]
+pub struct Board {
+    tiles: Vec<Tile>, // 0 represents the blank tile
+    size: usize,
+    // By cell, whether the tile there is locked in place. Locks go with
+    // their tile and aren't serialized.
+    locked: Vec<bool>,
+    // Where the 0 is, kept up to date by every change to `tiles` so moves
+    // don't have to search for it
+    blank_pos: usize,
+    // Whether the edges join up, as in the wraparound variant, so distances
+    // are measured across them. Like locks, not serialized.
+    wraps: bool,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Self::goal_state(DEFAULT_SIZE)
+    }
+
+    // Where `tile` sits in goal_state(size): numbered from 1 row by row, with
+    // the blank last
+    #[inline]
+    pub const fn goal_position_of(tile: Tile, size: usize) -> Position {
+        let index = match tile {
+            0 => size * size - 1,
+            _ => tile as usize - 1,
+        };
+        Position(index / size, index % size)
+    }
+
+    // The solved board: tiles in order with the blank in the bottom-right corner
+    pub fn goal_state(size: usize) -> Self {
+        let last = (size * size) as Tile;
+        let mut tiles = (1..last).collect::<Vec<_>>();
+        tiles.push(0); // Add the blank tile
+        Self::unlocked(tiles, size)
+    }
+
+    // The solved board under another goal convention. Panics if the pattern
+    // doesn't fit the size, which only CenterBlank can fail to do.
+    pub fn goal_state_for_pattern(size: usize, pattern: Pattern) -> Self {
+        assert!(
+            pattern.fits(size),
+            "{:?} doesn't fit a {}x{} board",
+            pattern,
+            size,
+            size
+        );
+        let cells = size * size;
+        let mut tiles = vec![0; cells];
+        match pattern {
+            Pattern::Standard => return Self::goal_state(size),
+            Pattern::Snake => {
+                // Numbered along the rows, turning back at the end of each
+                for (tile, pos) in (1..cells).zip((0..cells).map(|i| {
+                    let (row, col) = (i / size, i % size);
+                    match row % 2 {
+                        0 => i,
+                        _ => row * size + size - 1 - col,
+                    }
+                })) {
+                    tiles[pos] = tile as Tile;
+                }
+            }
+            Pattern::Reverse => {
+                for (pos, tile) in tiles.iter_mut().enumerate().skip(1) {
+                    *tile = (cells - pos) as Tile;
+                }
+            }
+            Pattern::CenterBlank => {
+                let center = cells / 2;
+                for (tile, pos) in (1..cells).zip((0..cells).filter(|&pos| pos != center)) {
+                    tiles[pos] = tile as Tile;
+                }
+            }
+        }
+        Self::unlocked(tiles, size)
+    }
+
+    pub fn from_tiles(tiles: Vec<Tile>) -> Result<Self, BoardError> {
+        let size = (1..=MAX_SIZE)
+            .find(|size| size * size == tiles.len())
+            .ok_or(BoardError::NotSquare { found: tiles.len() })?;
+
+        Self::validated(tiles, size)
+    }
+
+    // Tiles in row-major order, one byte each when every tile number fits in
+    // a byte (boards up to 16x16, e.g. 9 bytes for a 3x3) and two big-endian
+    // bytes each for larger boards
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match Self::bytes_per_tile(self.size) {
+            1 => self.tiles.iter().map(|&tile| tile as u8).collect(),
+            _ => self
+                .tiles
+                .iter()
+                .flat_map(|tile| tile.to_be_bytes())
+                .collect(),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8], size: usize) -> Result<Self, BoardError> {
+        Self::check_size(size)?;
+        let width = Self::bytes_per_tile(size);
+        if bytes.len() != size * size * width {
+            return Err(BoardError::WrongTileCount {
+                expected: size * size,
+                found: bytes.len() / width,
+            });
+        }
+
+        let tiles = match width {
+            1 => bytes.iter().map(|&byte| byte as Tile).collect(),
+            _ => bytes
+                .chunks_exact(2)
+                .map(|pair| Tile::from_be_bytes([pair[0], pair[1]]))
+                .collect(),
+        };
+        Self::validated(tiles, size)
+    }
+
+    // The first size*size tiles from `iter`, such as a network buffer or a
+    // CSV reader. Whatever comes after them is left unread.
+    pub fn from_iter(iter: impl IntoIterator<Item = u8>, size: usize) -> Result<Self, BoardError> {
+        Self::check_size(size)?;
+        let cells = size * size;
+        let tiles: Vec<Tile> = iter.into_iter().take(cells).map(Tile::from).collect();
+        if tiles.len() != cells {
+            return Err(BoardError::WrongTileCount {
+                expected: cells,
+                found: tiles.len(),
+            });
+        }
+        Self::validated(tiles, size)
+    }
+
+    // The sizes from_tiles would find a square for: none of 0 cells, and
+    // none past MAX_SIZE
+    fn check_size(size: usize) -> Result<(), BoardError> {
+        match (1..=MAX_SIZE).contains(&size) {
+            true => Ok(()),
+            false => Err(BoardError::InvalidSize { size }),
+        }
+    }
+
+    fn bytes_per_tile(size: usize) -> usize {
+        match size * size <= 1 << u8::BITS {
+            true => 1,
+            false => 2,
+        }
+    }
+
+    // Checks that the tiles are exactly 0..size*size, each appearing once
+    fn validated(tiles: Vec<Tile>, size: usize) -> Result<Self, BoardError> {
+        let mut seen = vec![false; tiles.len()];
+        for &tile in &tiles {
+            let slot = seen
+                .get_mut(tile as usize)
+                .ok_or(BoardError::InvalidTile { tile })?;
+            if *slot {
+                return Err(BoardError::DuplicateTile { tile });
+            }
+            *slot = true;
+        }
+
+        Ok(Self::unlocked(tiles, size))
+    }
+
+    fn unlocked(tiles: Vec<Tile>, size: usize) -> Self {
+        let locked = vec![false; tiles.len()];
+        let blank_pos = tiles
+            .iter()
+            .position(|&tile| tile == 0)
+            .expect("every board has a blank");
+        Self {
+            tiles,
+            size,
+            locked,
+            blank_pos,
+            wraps: false,
+        }
+    }
+
+    // Keeps `tile` where it is: no move slides it and no rotation takes it
+    // along. The blank can't be locked. Returns whether the tile exists.
+    pub fn lock_tile(&mut self, tile: Tile) -> bool {
+        self.set_locked(tile, true)
+    }
+
+    pub fn unlock_tile(&mut self, tile: Tile) -> bool {
+        self.set_locked(tile, false)
+    }
+
+    fn set_locked(&mut self, tile: Tile, locked: bool) -> bool {
+        match self.position_of(tile).filter(|_| tile != 0) {
+            Some(pos) => {
+                self.locked[pos] = locked;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_locked(&self, pos: usize) -> bool {
+        self.locked.get(pos).copied().unwrap_or(false)
+    }
+
+    pub fn has_locked_tiles(&self) -> bool {
+        self.locked.contains(&true)
+    }
+
+    // Joins up the edges for manhattan_distance, or parts them again. Moves
+    // across them are apply_wraparound_move's.
+    pub fn set_wraparound(&mut self, wraps: bool) {
+        self.wraps = wraps;
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.wraps
+    }
+
+    // Back to the standard solved board. Locked tiles stay locked, now in
+    // their solved cells.
+    pub fn reset(&mut self) {
+        let locked = (0..self.tiles.len())
+            .filter(|&pos| self.locked[pos])
+            .map(|pos| self.tiles[pos])
+            .collect::<Vec<_>>();
+        let wraps = self.wraps;
+        *self = Self::goal_state(self.size);
+        self.wraps = wraps;
+        for tile in locked {
+            self.lock_tile(tile);
+        }
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // A random board that is solvable by construction, since it's reached by
+    // sliding tiles from the goal state
+    pub fn from_random(size: usize, rng: &mut impl Rng) -> Self {
+        let mut board = Self::goal_state(size);
+        board.scramble_from_position(rng, size * size * RANDOM_SCRAMBLE_STEPS_PER_TILE);
+        board
+    }
+
+    #[cfg(feature = "std-rng")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std-rng")))]
+    #[deprecated(since = "0.2.0", note = "use Board::from_random")]
+    pub fn shuffle(&mut self) {
+        *self = Self::from_random(self.size, &mut rand::thread_rng());
+    }
+
+    // Applies exactly `steps` effective random moves, never undoing the move
+    // just made, so short walks from the goal land at a predictable distance.
+    // Returns the moves, which from the goal are the scramble in notation.
+    pub fn scramble_from_position(&mut self, rng: &mut impl Rng, steps: usize) -> Vec<Direction> {
+        let mut moves = Vec::with_capacity(steps);
+        let mut previous_move: Option<Direction> = None;
+
+        for _ in 0..steps {
+            let direction = self
+                .random_valid_move(previous_move, rng)
+                .expect("every board larger than 1x1 has a move that isn't a reversal");
+            self.move_tile(direction);
+            moves.push(direction);
+            previous_move = Some(direction);
+        }
+        moves
+    }
+
+    // Directions that would actually slide a tile into the blank
+    pub fn valid_moves(&self) -> Vec<Direction> {
+        let blank = self.get_blank_position();
+        Direction::ALL
+            .into_iter()
+            .filter(|&direction| self.move_source(blank, direction).is_some())
+            .collect()
+    }
+
+    // The cell holding `tile`, or None for a tile past the largest
+    pub fn position_of(&self, tile: Tile) -> Option<usize> {
+        self.tiles.iter().position(|&t| t == tile)
+    }
+
+    // Like position_of, as the row and column
+    pub fn grid_position_of(&self, tile: Tile) -> Option<Position> {
+        self.position_of(tile)
+            .map(|index| Position::from_index(index, self.size))
+    }
+
+    // The move that slides `tile` into the blank, or None unless it's
+    // next to the blank
+    pub fn direction_for_tile(&self, tile: Tile) -> Option<Direction> {
+        if tile == 0 {
+            return None;
+        }
+        let pos = self.position_of(tile)?;
+        let blank = self.get_blank_position();
+        Direction::ALL
+            .into_iter()
+            .find(|&direction| self.move_source(blank, direction) == Some(pos))
+    }
+
+    // The tile `direction` would slide into the blank, the other way round
+    // from direction_for_tile
+    pub fn tile_for_direction(&self, direction: Direction) -> Option<Tile> {
+        self.move_source(self.blank_pos, direction)
+            .map(|pos| self.tiles[pos])
+    }
+
+    // A push of the line of tiles beyond the blank, as the single moves that
+    // make it: `direction` once for each tile between the blank and the far
+    // edge, or up to a locked tile. Empty when nothing can slide that way.
+    pub fn push_sequence(&self, direction: Direction) -> Vec<Direction> {
+        let mut board = self.clone();
+        let mut moves = Vec::new();
+        while board.move_tile(direction) {
+            moves.push(direction);
+        }
+        moves
+    }
+
+    // Every board one legal move away, paired with the move that reaches it
+    pub fn neighbor_states(&self) -> Vec<(Direction, Board)> {
+        self.valid_moves()
+            .into_iter()
+            .map(|direction| {
+                let mut next = self.clone();
+                next.move_tile(direction);
+                (direction, next)
+            })
+            .collect()
+    }
+
+    // Every board reachable from `start`, `start` included, by breadth-first
+    // search. Each board is kept, so this is only practical up to 3x3: a
+    // 4x4 has over ten trillion.
+    pub fn enumerate_reachable_states(start: &Board) -> HashSet<Board> {
+        let mut seen = HashSet::from([start.clone()]);
+        let mut frontier = VecDeque::from([start.clone()]);
+        while let Some(board) = frontier.pop_front() {
+            for (_, next) in board.neighbor_states() {
+                if seen.insert(next.clone()) {
+                    frontier.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    // A random valid move that doesn't undo `exclude`, the move just made
+    pub fn random_valid_move(
+        &self,
+        exclude: Option<Direction>,
+        rng: &mut impl Rng,
+    ) -> Option<Direction> {
+        let mut moves = self.valid_moves();
+        if let Some(undo) = exclude.map(Direction::opposite) {
+            moves.retain(|&direction| direction != undo);
+        }
+        moves.choose(rng).copied()
+    }
+
+    #[deprecated(since = "0.2.0", note = "use is_goal(&Board::goal_state(size))")]
+    pub fn is_solved(&self) -> bool {
+        self.is_goal(&Self::goal_state(self.size))
+    }
+
+    // Whether this board matches the given target arrangement
+    pub fn is_goal(&self, goal: &Board) -> bool {
+        self.tiles == goal.tiles && self.size == goal.size
+    }
+
+    // Sum over all tiles of how many rows and columns each is from its goal
+    // cell in the standard pattern, crossing the edges where the board wraps
+    // and that's shorter. For other goals use distance_from_board.
+    pub fn manhattan_distance(&self) -> u32 {
+        let size = self.size;
+
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile != 0)
+            .map(|(pos, &tile)| {
+                let goal = Self::goal_position_of(tile, size);
+                let pos = Position::from_index(pos, size);
+                match self.wraps {
+                    true => pos.toroidal_distance_to(&goal, size),
+                    false => pos.manhattan_distance_to(&goal),
+                }
+            })
+            .sum::<usize>() as u32
+    }
+
+    // Tiles that would have to leave their goal row or column to let the
+    // others in it pass: within each line, the fewest tiles to take out so
+    // the rest of the tiles that belong there are in order. Each costs at
+    // least two moves on top of manhattan_distance.
+    pub fn linear_conflicts(&self) -> u32 {
+        let size = self.size;
+        let goal = |tile: Tile| Self::goal_position_of(tile, size);
+        let mut conflicts = 0;
+        for line in 0..size {
+            // Goal columns of the tiles in this row that belong to it, then
+            // goal rows of the tiles in this column that belong to it
+            let row = (0..size)
+                .map(|col| self.tiles[line * size + col])
+                .filter(|&tile| tile != 0 && goal(tile).row() == line)
+                .map(|tile| goal(tile).col())
+                .collect::<Vec<_>>();
+            let col = (0..size)
+                .map(|row| self.tiles[row * size + line])
+                .filter(|&tile| tile != 0 && goal(tile).col() == line)
+                .map(|tile| goal(tile).row())
+                .collect::<Vec<_>>();
+            conflicts += out_of_order(&row) + out_of_order(&col);
+        }
+        conflicts
+    }
+
+    // Like manhattan_distance, but measured against any same-sized board
+    // instead of the goal. Still a lower bound on the moves between the two.
+    pub fn distance_from_board(&self, other: &Board) -> u32 {
+        assert_eq!(self.size, other.size, "boards must be the same size");
+        let size = self.size;
+        let mut target = vec![0; self.tiles.len()];
+        for (pos, &tile) in other.tiles.iter().enumerate() {
+            target[tile as usize] = pos;
+        }
+
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile != 0)
+            .map(|(pos, &tile)| {
+                let goal = target[tile as usize];
+                (pos / size).abs_diff(goal / size) + (pos % size).abs_diff(goal % size)
+            })
+            .sum::<usize>() as u32
+    }
+
+    // The weighted score shuffles use to hit a difficulty level
+    pub fn difficulty_score(&self) -> u32 {
+        difficulty::estimate(self, &DifficultyWeights::default())
+    }
+
+    // How hard this board is to solve, with the numbers behind the rating
+    pub fn difficulty_estimate(&self) -> DifficultyRating {
+        DifficultyRating::of(self)
+    }
+
+    // Counts pairs of tiles that appear in the wrong order relative to each
+    // other, ignoring the blank. On boards with an odd width (like 3x3) an even
+    // count means the board is solvable. Higher counts also roughly mean a
+    // more thoroughly shuffled board.
+    pub fn count_inversions(&self) -> usize {
+        let tiles = self
+            .tiles
+            .iter()
+            .filter(|&&tile| tile != 0)
+            .collect::<Vec<_>>();
+
+        tiles
+            .iter()
+            .enumerate()
+            .map(|(i, a)| tiles[i + 1..].iter().filter(|b| a > b).count())
+            .sum()
+    }
+
+    // Whether the goal is reachable: exactly half of all arrangements are.
+    // The searches need no separate filter for this. No move changes the
+    // answer, so every board they reach from a solvable start is solvable
+    // too, and skipping boards by inversion parity alone would even be wrong
+    // on even widths, where each vertical move flips that parity. Check the
+    // start with this instead.
+    pub fn is_solvable(&self) -> bool {
+        let inversions = self.count_inversions();
+
+        if self.size % 2 == 1 {
+            inversions.is_multiple_of(2)
+        } else {
+            // On even widths every vertical move flips the inversion parity, so
+            // the blank's row (counted from the bottom) has to be accounted for
+            let blank_row_from_bottom = self.size - self.get_blank_position() / self.size;
+            (inversions + blank_row_from_bottom) % 2 == 1
+        }
+    }
+
+    // is_solvable for the rotation variant. Turning a line of an odd width
+    // is an even permutation of its cells, blank included, so only boards
+    // an even permutation from the goal can be solved there; on even widths
+    // every board can.
+    pub fn is_rotation_solvable(&self) -> bool {
+        // The blank counts as the largest tile, ahead of every one after it
+        let blank_inversions = self.tiles.len() - 1 - self.blank_pos;
+        self.size.is_multiple_of(2)
+            || (self.count_inversions() + blank_inversions).is_multiple_of(2)
+    }
+
+    pub fn get_blank_position(&self) -> usize {
+        self.blank_pos
+    }
+
+    // Returns whether a tile actually moved
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self), ret)
+    )]
+    pub fn move_tile(&mut self, direction: Direction) -> bool {
+        let blank_pos = self.blank_pos;
+        debug_assert_eq!(self.tiles[blank_pos], 0, "blank_pos is out of step");
+
+        // The swap moves blank_pos to the tile's old cell
+        match self.move_source(blank_pos, direction) {
+            Some(tile_to_move_pos) => self.swap_tiles(blank_pos, tile_to_move_pos).is_ok(),
+            None => false,
+        }
+    }
+
+    // Where the tile that `direction` would slide into the blank comes from
+    fn move_source(&self, blank_pos: usize, direction: Direction) -> Option<usize> {
+        let size = self.size;
+        let Position(row, col) = Position::from_index(blank_pos, size);
+
+        // The tile comes from the side opposite the way it moves
+        let source = match direction {
+            Direction::Up => Position(row + 1, col),
+            Direction::Down => Position(row.checked_sub(1)?, col),
+            Direction::Left => Position(row, col + 1),
+            Direction::Right => Position(row, col.checked_sub(1)?),
+        };
+
+        // Cells off the board are simply not a legal move, and nor is sliding
+        // a locked tile
+        if source.row() >= size || source.col() >= size {
+            return None;
+        }
+        let pos = source.to_index(size);
+        (!self.locked[pos]).then_some(pos)
+    }
+
+    // The wraparound variant's move: as move_tile, but a blank on the edge
+    // takes its tile from the far side of the board, so every direction can
+    // be played unless the tile is locked
+    pub fn apply_wraparound_move(&mut self, direction: Direction) -> bool {
+        let blank_pos = self.blank_pos;
+        match self.wrapped_move_source(blank_pos, direction) {
+            Some(tile_to_move_pos) => self.swap_tiles(blank_pos, tile_to_move_pos).is_ok(),
+            None => false,
+        }
+    }
+
+    // valid_moves for the wraparound variant: all four, less any that would
+    // slide a locked tile
+    pub fn valid_wraparound_moves(&self) -> Vec<Direction> {
+        Direction::ALL
+            .into_iter()
+            .filter(|&direction| {
+                self.wrapped_move_source(self.blank_pos, direction)
+                    .is_some()
+            })
+            .collect()
+    }
+
+    // move_source with the rows and columns joined end to end
+    fn wrapped_move_source(&self, blank_pos: usize, direction: Direction) -> Option<usize> {
+        let size = self.size;
+        let Position(row, col) = Position::from_index(blank_pos, size);
+        let source = match direction {
+            Direction::Up => Position((row + 1) % size, col),
+            Direction::Down => Position((row + size - 1) % size, col),
+            Direction::Left => Position(row, (col + 1) % size),
+            Direction::Right => Position(row, (col + size - 1) % size),
+        };
+        let pos = source.to_index(size);
+        (!self.locked[pos]).then_some(pos)
+    }
+
+    // XOR of the Zobrist key of every (cell, tile) pair, for hashing states
+    // in searches
+    pub fn zobrist(&self) -> u64 {
+        self.tiles
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (cell, &tile)| hash ^ zobrist::key(cell, tile))
+    }
+
+    // The Zobrist hash after `direction`, updated from this board's `hash`
+    // by the two cells the move swaps, or None if the move isn't legal
+    pub fn zobrist_after_move(&self, hash: u64, direction: Direction) -> Option<u64> {
+        let blank_pos = self.get_blank_position();
+        let from = self.move_source(blank_pos, direction)?;
+        Some(zobrist::after_swap(hash, blank_pos, from, self.tiles[from]))
+    }
+
+    // Takes back `direction` by sliding the same tile back. Only meaningful
+    // right after that move actually moved a tile; then the board is exactly
+    // as it was before it.
+    pub fn undo_move(&mut self, direction: Direction) -> bool {
+        self.move_tile(direction.opposite())
+    }
+
+    // Applies each move in order and returns how many actually moved a tile
+    pub fn apply_moves(&mut self, moves: &[Direction]) -> usize {
+        moves
+            .iter()
+            .filter(|&&direction| self.move_tile(direction))
+            .count()
+    }
+
+    // The solved 3x3 board with a scramble like "DRU" applied to it
+    pub fn apply_scramble_sequence(sequence: &str) -> Result<Board, ParseError> {
+        Self::new().scrambled_by(sequence)
+    }
+
+    // Applies move notation strictly: every move has to actually move a tile
+    pub fn scrambled_by(mut self, sequence: &str) -> Result<Board, ParseError> {
+        for parsed in Direction::parse_indexed(sequence) {
+            let (index, direction) = parsed?;
+            if !self.move_tile(direction) {
+                return Err(ParseError::IllegalMove { direction, index });
+            }
+        }
+        Ok(self)
+    }
+
+    // As scrambled_by, but a failure comes with the index of the character
+    // it stopped at, counting characters rather than bytes
+    pub fn apply_move_sequence_checked(
+        initial: Board,
+        seq: &str,
+    ) -> Result<Board, (usize, MoveError)> {
+        initial.scrambled_by(seq).map_err(|err| {
+            let (index, error) = match err {
+                ParseError::InvalidChar { ch, index } => (index, MoveError::InvalidChar(ch)),
+                ParseError::IllegalMove { direction, index } => {
+                    (index, MoveError::IllegalMove(direction))
+                }
+            };
+            (seq[..index].chars().count(), error)
+        })
+    }
+
+    // The rotation variant's move: every tile in the row or column shifts one
+    // cell, the one pushed off the end wrapping round to the other. The blank
+    // rides along like any tile. Returns false for a row or column past the
+    // edge of the board or one holding a locked tile.
+    pub fn apply_rotation_move(&mut self, axis: RotationAxis, dir: RotationDir) -> bool {
+        let size = self.size;
+        let cells: Vec<usize> = match axis {
+            RotationAxis::Row(row) if row < size => (0..size).map(|col| row * size + col).collect(),
+            RotationAxis::Col(col) if col < size => (0..size).map(|row| row * size + col).collect(),
+            _ => return false,
+        };
+        if cells.iter().any(|&cell| self.locked[cell]) {
+            return false;
+        }
+        let mut line: Vec<Tile> = cells.iter().map(|&cell| self.tiles[cell]).collect();
+        match dir {
+            RotationDir::Forward => line.rotate_right(1),
+            RotationDir::Backward => line.rotate_left(1),
+        }
+        for (&cell, tile) in cells.iter().zip(line) {
+            self.tiles[cell] = tile;
+            if tile == 0 {
+                self.blank_pos = cell;
+            }
+        }
+        true
+    }
+
+    // The rotation that carries `tile` the way `direction` points: its row
+    // for left and right, its column for up and down. None for a tile past
+    // the largest.
+    pub fn rotation_through(&self, tile: Tile, direction: Direction) -> Option<Rotation> {
+        let Position(row, col) = self.grid_position_of(tile)?;
+        Some(match direction {
+            Direction::Right => Rotation::new(RotationAxis::Row(row), RotationDir::Forward),
+            Direction::Left => Rotation::new(RotationAxis::Row(row), RotationDir::Backward),
+            Direction::Down => Rotation::new(RotationAxis::Col(col), RotationDir::Forward),
+            Direction::Up => Rotation::new(RotationAxis::Col(col), RotationDir::Backward),
+        })
+    }
+
+    // Like scramble_from_position with `steps` rotations of any row or
+    // column free of locked tiles, never undoing the one just made, and
+    // carrying on past `steps` rather than stop on the board it started from
+    pub fn scramble_rotation(&mut self, rng: &mut impl Rng, steps: usize) -> Vec<Rotation> {
+        let size = self.size;
+        let lines = (0..size).flat_map(|i| [RotationAxis::Row(i), RotationAxis::Col(i)]);
+        let rotations: Vec<Rotation> = lines
+            .flat_map(|axis| {
+                [RotationDir::Forward, RotationDir::Backward].map(|dir| Rotation::new(axis, dir))
+            })
+            .filter(|&rotation| {
+                self.clone()
+                    .apply_rotation_move(rotation.axis, rotation.dir)
+            })
+            .collect();
+        let start = self.tiles.clone();
+        let mut moves: Vec<Rotation> = Vec::with_capacity(steps);
+        while moves.len() < steps || (self.tiles == start && size > 1) {
+            let undo = moves.last().map(|&last| last.inverse());
+            let choices: Vec<Rotation> = rotations
+                .iter()
+                .copied()
+                .filter(|&rotation| Some(rotation) != undo)
+                .collect();
+            let Some(&rotation) = choices.choose(rng) else {
+                break;
+            };
+            self.apply_rotation_move(rotation.axis, rotation.dir);
+            moves.push(rotation);
+        }
+        moves
+    }
+
+    // The magnetic variant's move: the tile slides into the blank as usual,
+    // and the next tile in line behind it is pulled one cell after it, unless
+    // it's locked or there isn't one. Returns whether the first tile moved,
+    // like move_tile.
+    pub fn apply_magnetic_move(&mut self, direction: Direction) -> bool {
+        if !self.move_tile(direction) {
+            return false;
+        }
+        self.move_tile(direction);
+        true
+    }
+
+    // The tile a magnetic move `direction` would pull along behind the one
+    // sliding into the blank
+    pub fn magnetic_pull(&self, direction: Direction) -> Option<Tile> {
+        let first = self.move_source(self.blank_pos, direction)?;
+        self.move_source(first, direction)
+            .map(|pos| self.tiles[pos])
+    }
+
+    // The gravity variant's settling: every tile with the blank below it
+    // drops into it, until none has. With a single blank that lifts the blank
+    // up its column, as far as the top row or the first locked tile, which
+    // stays where it is.
+    pub fn apply_gravity(&mut self) {
+        self.settle(None);
+    }
+
+    // The gravity variant's move: the tile slides into the blank as usual and
+    // the board settles, except that the tile just slid holds on where it
+    // landed, and so holds up everything above it. Without the hold a slide
+    // up would fall straight back, and the blank would end up in the top row
+    // after every move, leaving the rows below it out of reach. Returns
+    // whether the tile moved, like move_tile.
+    pub fn apply_gravity_move(&mut self, direction: Direction) -> bool {
+        let landed = self.blank_pos;
+        if !self.move_tile(direction) {
+            return false;
+        }
+        self.settle(Some(landed));
+        true
+    }
+
+    // Lifts the blank up its column until the cell above it is the top
+    // edge, a locked tile or `held`. Returns how many tiles fell.
+    fn settle(&mut self, held: Option<usize>) -> usize {
+        let mut falls = 0;
+        while self.blank_pos >= self.size {
+            let above = self.blank_pos - self.size;
+            if self.locked[above] || held == Some(above) {
+                break;
+            }
+            self.tiles.swap(above, self.blank_pos);
+            self.blank_pos = above;
+            falls += 1;
+        }
+        falls
+    }
+
+    // Like scramble_from_position with `steps` gravity moves, which can
+    // always be played back to the goal the same way. Returns the moves as
+    // plain slides, each fall a slide down, so the scramble replays like any
+    // other.
+    pub fn scramble_gravity(&mut self, rng: &mut impl Rng, steps: usize) -> Vec<Direction> {
+        self.scramble_walk(rng, steps, |board, direction| {
+            let landed = board.blank_pos;
+            board.move_tile(direction);
+            let falls = board.settle(Some(landed));
+            std::iter::once(direction)
+                .chain(std::iter::repeat_n(Direction::Down, falls))
+                .collect()
+        })
+    }
+
+    // Like scramble_gravity with magnetic moves, a pull being a second slide
+    // the same way
+    pub fn scramble_magnetic(&mut self, rng: &mut impl Rng, steps: usize) -> Vec<Direction> {
+        self.scramble_walk(rng, steps, |board, direction| {
+            board.move_tile(direction);
+            let slides = 1 + usize::from(board.move_tile(direction));
+            vec![direction; slides]
+        })
+    }
+
+    // `steps` random moves made by `play`, which gives back the slides each
+    // one took, and more if the walk comes back to where it started
+    fn scramble_walk(
+        &mut self,
+        rng: &mut impl Rng,
+        steps: usize,
+        mut play: impl FnMut(&mut Self, Direction) -> Vec<Direction>,
+    ) -> Vec<Direction> {
+        let start = self.tiles.clone();
+        let mut moves = Vec::with_capacity(steps);
+        let mut made = 0;
+        while made < steps || (self.tiles == start && self.tiles.len() > 1) {
+            let Some(&direction) = self.valid_moves().choose(rng) else {
+                break;
+            };
+            moves.extend(play(self, direction));
+            made += 1;
+        }
+        moves
+    }
+
+    // True when apply_gravity would leave the board as it is
+    pub fn is_gravity_stable(&self) -> bool {
+        self.blank_pos < self.size || self.locked[self.blank_pos - self.size]
+    }
+
+    // Low-level primitive: swaps any two in-bounds tiles without checking adjacency
+    pub(crate) fn swap_tiles(&mut self, pos_a: usize, pos_b: usize) -> Result<(), BoardError> {
+        let len = self.tiles.len();
+        for position in [pos_a, pos_b] {
+            if position >= len {
+                return Err(BoardError::PositionOutOfBounds { position, len });
+            }
+        }
+
+        self.tiles.swap(pos_a, pos_b);
+        self.locked.swap(pos_a, pos_b);
+        if self.blank_pos == pos_a {
+            self.blank_pos = pos_b;
+        } else if self.blank_pos == pos_b {
+            self.blank_pos = pos_a;
+        }
+        Ok(())
+    }
+}
+
+impl From<Board> for Vec<Tile> {
+    fn from(board: Board) -> Self {
+        board.tiles
+    }
+}
+
+impl TryFrom<Vec<Tile>> for Board {
+    type Error = BoardError;
+
+    fn try_from(tiles: Vec<Tile>) -> Result<Self, Self::Error> {
+        Self::from_tiles(tiles)
+    }
+}
+
+impl FromStr for Board {
+    type Err = BoardError;
+
+    // Accepts tiles separated by commas and/or whitespace, e.g. "1,2,3,4,5,6,7,8,0"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tiles = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<Tile>()
+                    .map_err(|_| BoardError::InvalidNumber {
+                        token: token.to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_tiles(tiles)
+    }
+}
+
+thread_local! {
+    // Why the last collect() on this thread fell back to the 3x3 goal
+    static COLLECT_ERROR: RefCell<Option<BoardError>> = const { RefCell::new(None) };
+}
+
+// The error behind the last collect() into a Board on this thread that
+// didn't make one, if it hasn't been taken already
+pub fn take_collect_error() -> Option<BoardError> {
+    COLLECT_ERROR.with(|error| error.borrow_mut().take())
+}
+
+// A 3x3 from the first 9 tiles, as Board::from_iter(iter, DEFAULT_SIZE).
+// collect() can't fail, so bad tiles give the solved 3x3 instead, with the
+// reason left for take_collect_error.
+impl FromIterator<u8> for Board {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let board = Board::from_iter(iter, DEFAULT_SIZE);
+        COLLECT_ERROR.with(|error| *error.borrow_mut() = board.as_ref().err().cloned());
+        board.unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = (self.tiles.len() - 1).to_string().len();
+
+        for row in self.tiles.chunks(self.size) {
+            let cells = row
+                .iter()
+                .map(|&tile| match tile {
+                    0 => format!("{:>width$}", "."),
+                    _ => format!("{:>width$}", tile),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Board {
+    // The board and the facts that tend to matter when a solver test goes
+    // wrong, as plain text that needs no terminal
+    pub fn debug_summary(&self) -> String {
+        let blank = self.blank_pos;
+        format!(
+            "{}blank at {} (row {}, column {})\nmanhattan distance: {}\nsolvable: {}\nsolved: {}\n",
+            self,
+            blank,
+            blank / self.size,
+            blank % self.size,
+            self.manhattan_distance(),
+            self.is_solvable(),
+            self.is_goal(&Self::goal_state(self.size))
+        )
+    }
+
+    // To stderr, which the test harness only shows for failing tests
+    pub fn print_debug(&self) {
+        eprint!("{}", self.debug_summary());
+    }
+}
+
+// assert_eq! for boards, printing both with print_debug before it panics
+#[macro_export]
+macro_rules! assert_eq_board {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if left != right {
+                    eprintln!("left board:");
+                    left.print_debug();
+                    eprintln!("right board:");
+                    right.print_debug();
+                    panic!("boards differ: {:?} != {:?}", left.tiles(), right.tiles());
+                }
+            }
+        }
+    };
+}
+
+// Where the tiles go when the puzzle is solved. Conventions differ between
+// puzzle traditions; Standard is the one the rest of the game assumes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pattern {
+    #[default]
+    Standard, // 1 to N in reading order, blank last
+    Snake,       // Like Standard, but every other row runs right to left
+    Reverse,     // Blank first, then N down to 1
+    CenterBlank, // Reading order around a blank in the middle
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 4] = [
+        Pattern::Standard,
+        Pattern::Snake,
+        Pattern::Reverse,
+        Pattern::CenterBlank,
+    ];
+
+    // Only odd boards have a middle cell
+    pub fn fits(self, size: usize) -> bool {
+        self != Pattern::CenterBlank || size % 2 == 1
+    }
+}
+
+// A row or a column by index from the top or left, for the rotation variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RotationAxis {
+    Row(usize),
+    Col(usize),
+}
+
+// Forward shifts a row right or a column down; Backward the other way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RotationDir {
+    Forward,
+    Backward,
+}
+
+// One move of the rotation variant, written as the row or column counted
+// from 1 and + for forward or - for backward, e.g. "R2+" or "C1-"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    pub axis: RotationAxis,
+    pub dir: RotationDir,
+}
+
+impl Rotation {
+    pub fn new(axis: RotationAxis, dir: RotationDir) -> Self {
+        Self { axis, dir }
+    }
+
+    // The same line turned back the other way
+    pub fn inverse(self) -> Self {
+        let dir = match self.dir {
+            RotationDir::Forward => RotationDir::Backward,
+            RotationDir::Backward => RotationDir::Forward,
+        };
+        Self { dir, ..self }
+    }
+
+    // Like Direction::sequence_to_spaced, e.g. "R2+ C1-"
+    pub fn sequence_to_spaced(rotations: &[Self]) -> String {
+        let names: Vec<String> = rotations.iter().map(Self::to_string).collect();
+        names.join(" ")
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (letter, index) = match self.axis {
+            RotationAxis::Row(row) => ('R', row),
+            RotationAxis::Col(col) => ('C', col),
+        };
+        let sign = match self.dir {
+            RotationDir::Forward => '+',
+            RotationDir::Backward => '-',
+        };
+        write!(f, "{}{}{}", letter, index + 1, sign)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// The fewest entries to remove from `targets` so the rest are in increasing
+// order: its length less its longest increasing run
+fn out_of_order(targets: &[usize]) -> u32 {
+    let mut longest = vec![1; targets.len()];
+    for i in 0..targets.len() {
+        for j in 0..i {
+            if targets[j] < targets[i] {
+                longest[i] = longest[i].max(longest[j] + 1);
+            }
+        }
+    }
+    (targets.len() - longest.iter().max().unwrap_or(&0)) as u32
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Up => 'U',
+            Direction::Down => 'D',
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+        }
+    }
+
+    // Parses move notation like "UULDR"; whitespace between moves is ignored
+    pub fn parse_sequence(s: &str) -> Result<Vec<Self>, ParseError> {
+        Self::parse_indexed(s)
+            .map(|parsed| parsed.map(|(_, direction)| direction))
+            .collect()
+    }
+
+    // Each move paired with its byte offset, for error reporting
+    fn parse_indexed(s: &str) -> impl Iterator<Item = Result<(usize, Self), ParseError>> + '_ {
+        s.char_indices()
+            .filter(|(_, c)| !c.is_whitespace())
+            .map(|(index, ch)| {
+                Self::from_char(ch)
+                    .map(|direction| (index, direction))
+                    .ok_or(ParseError::InvalidChar { ch, index })
+            })
+    }
+
+    pub fn sequence_to_string(moves: &[Self]) -> String {
+        moves.iter().map(|m| m.to_char()).collect()
+    }
+
+    // The way scrambles are passed around, e.g. "D R U L"
+    pub fn sequence_to_spaced(moves: &[Self]) -> String {
+        let letters: Vec<String> = moves.iter().map(|m| m.to_char().to_string()).collect();
+        letters.join(" ")
+    }
+
+    // The moves that take back `moves`: reversed, each replaced by its
+    // opposite. Applying a sequence of effective moves and then its inverse
+    // returns to the starting board.
+    pub fn invert_sequence(moves: &[Self]) -> Vec<Self> {
+        moves.iter().rev().map(|m| m.opposite()).collect()
+    }
+
+    // Drops every move that the next one takes straight back, as often as
+    // that leaves new such pairs. A sequence of effective moves ends on the
+    // same board either way.
+    pub fn cancel_sequence(moves: &[Self]) -> Vec<Self> {
+        let mut kept: Vec<Self> = Vec::with_capacity(moves.len());
+        for &direction in moves {
+            match kept.last() {
+                Some(&last) if last == direction.opposite() => {
+                    kept.pop();
+                }
+                _ => kept.push(direction),
+            }
+        }
+        kept
+    }
+}
+
+// A parsed move string such as a shared solution, "UULDDRRUL"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveSequence(Vec<Direction>);
+
+impl MoveSequence {
+    pub fn new(moves: Vec<Direction>) -> Self {
+        Self(moves)
+    }
+
+    pub fn moves(&self) -> &[Direction] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromStr for MoveSequence {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Direction::parse_sequence(s).map(Self)
+    }
+}
+
+impl fmt::Display for MoveSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Direction::sequence_to_string(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_new_board() {
+        let board = Board::new();
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
+    }
+
+    fn random_board(size: usize, seed: u64) -> Board {
+        Board::from_random(size, &mut StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    #[cfg(feature = "std-rng")]
+    #[allow(deprecated)]
+    fn test_shuffle_board() {
+        let mut board = Board::new();
+        let initial_board = board.clone();
+        board.shuffle();
+        assert_ne!(board, initial_board);
+        assert_eq!(board.size(), 3);
+    }
+
+    #[test]
+    fn test_valid_moves() {
+        assert_eq!(
+            Board::new().valid_moves(),
+            [Direction::Down, Direction::Right]
+        );
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(board.valid_moves(), Direction::ALL);
+    }
+
+    #[test]
+    fn test_neighbor_states() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let neighbors = board.neighbor_states();
+        assert_eq!(neighbors.len(), 4);
+        for (direction, next) in &neighbors {
+            let mut expected = board.clone();
+            assert!(expected.move_tile(*direction));
+            assert_eq!(next, &expected);
+        }
+
+        let (best, _) = neighbors
+            .iter()
+            .min_by_key(|(_, next)| next.manhattan_distance())
+            .unwrap();
+        assert_eq!(*best, Direction::Left);
+        assert_eq!(Board::new().neighbor_states().len(), 2);
+    }
+
+    #[test]
+    fn test_random_valid_move_is_valid() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for seed in 0..20 {
+            let board = random_board(4, seed);
+            for exclude in [None, Some(Direction::Up), Some(Direction::Left)] {
+                let direction = board.random_valid_move(exclude, &mut rng).unwrap();
+                assert!(board.valid_moves().contains(&direction));
+                assert_ne!(exclude.map(Direction::opposite), Some(direction));
+            }
+        }
+        // In a corner, excluding one of the two moves leaves only the other
+        let only = Board::new().random_valid_move(Some(Direction::Up), &mut rng);
+        assert_eq!(only, Some(Direction::Right));
+    }
+
+    #[test]
+    fn test_from_random_is_reproducible() {
+        assert_eq!(random_board(4, 7), random_board(4, 7));
+        assert_ne!(random_board(4, 7), random_board(4, 8));
+    }
+
+    #[test]
+    fn test_from_random_is_solvable() {
+        for seed in 0..50 {
+            let board = random_board(2 + seed as usize % 4, seed);
+            assert!(board.is_solvable());
+        }
+    }
+
+    #[test]
+    fn test_scramble_from_position_distance() {
+        // Shortest cycles on the 3x3 state graph have length 12, so a walk of up
+        // to 6 non-reversing moves is always a shortest path
+        for seed in 0..20 {
+            let mut board = Board::new();
+            board.scramble_from_position(&mut StdRng::seed_from_u64(seed), 5);
+            assert_eq!(crate::solver::bfs_solve(&board).unwrap().len(), 5);
+        }
+
+        let mut board = Board::new();
+        board.scramble_from_position(&mut StdRng::seed_from_u64(1), 0);
+        assert!(board.is_goal(&Board::new()));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_is_solved() {
+        let mut board = random_board(3, 1);
+        assert!(!board.is_solved());
+        board.tiles = vec![1, 2, 3, 4, 5, 6, 7, 8, 0];
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn test_is_goal() {
+        let goal: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut board = Board::new();
+        assert!(!board.is_goal(&goal));
+        board.apply_moves(&[Direction::Down, Direction::Right]);
+        assert!(board.is_goal(&goal));
+        assert!(!Board::goal_state(2).is_goal(&Board::goal_state(3)));
+    }
+
+    #[test]
+    fn test_get_blank_position() {
+        let board = Board::new();
+        assert_eq!(board.get_blank_position(), 8);
+    }
+
+    #[test]
+    fn test_move_tiles() {
+        let mut board = Board::new();
+        board.move_tile(Direction::Up);
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
+        board.move_tile(Direction::Left);
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
+        board.move_tile(Direction::Down);
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 0, 7, 8, 6]);
+        board.move_tile(Direction::Right);
+        assert_eq!(board.tiles, [1, 2, 3, 4, 0, 5, 7, 8, 6]);
+        board.move_tile(Direction::Right);
+        assert_eq!(board.tiles, [1, 2, 3, 0, 4, 5, 7, 8, 6]);
+        board.move_tile(Direction::Down);
+        assert_eq!(board.tiles, [0, 2, 3, 1, 4, 5, 7, 8, 6]);
+        board.move_tile(Direction::Up);
+        assert_eq!(board.tiles, [1, 2, 3, 0, 4, 5, 7, 8, 6]);
+    }
+
+    #[test]
+    fn test_apply_moves() {
+        let mut board = Board::new();
+        let moves = Direction::parse_sequence("UDLR").unwrap();
+        // Up and Left bump into the edge of the board
+        assert_eq!(board.apply_moves(&moves), 2);
+        assert_eq!(board.tiles, [1, 2, 3, 4, 0, 5, 7, 8, 6]);
+    }
+
+    #[test]
+    fn test_swap_tiles() {
+        let mut board = Board::new();
+        assert_eq!(board.swap_tiles(0, 8), Ok(()));
+        assert_eq!(board.tiles, [0, 2, 3, 4, 5, 6, 7, 8, 1]);
+        assert_eq!(
+            board.swap_tiles(2, 9),
+            Err(BoardError::PositionOutOfBounds {
+                position: 9,
+                len: 9
+            })
+        );
+        assert_eq!(board.tiles, [0, 2, 3, 4, 5, 6, 7, 8, 1]);
+        assert_eq!(board.get_blank_position(), 0);
+    }
+
+    #[test]
+    fn test_blank_position_tracked() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let mut board = Board::goal_state(5);
+        for _ in 0..200 {
+            let direction = Direction::ALL[rng.gen_range(0..4)];
+            board.move_tile(direction);
+            assert_eq!(board.tiles[board.get_blank_position()], 0);
+        }
+        board.apply_rotation_move(
+            RotationAxis::Row(board.get_blank_position() / 5),
+            RotationDir::Forward,
+        );
+        assert_eq!(board.tiles[board.get_blank_position()], 0);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        board.reset();
+        assert_eq!(board, Board::new());
+        assert_eq!(board.get_blank_position(), 8);
+
+        let mut board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        board.lock_tile(7);
+        board.reset();
+        assert!(board.is_goal(&Board::new()));
+        assert!(board.is_locked(6));
+        assert!(!board.is_locked(7));
+    }
+
+    #[test]
+    fn test_enumerate_reachable_states() {
+        let reachable = Board::enumerate_reachable_states(&Board::new());
+        assert_eq!(reachable.len(), 181_440);
+        assert!(reachable.iter().all(Board::is_solvable));
+
+        // Even widths too: the search stays in the solvable half
+        let reachable = Board::enumerate_reachable_states(&Board::goal_state(2));
+        assert_eq!(reachable.len(), 12);
+        let unsolvable: Board = "2 1 3 0".parse().unwrap();
+        assert!(!reachable.contains(&unsolvable));
+        assert_eq!(Board::enumerate_reachable_states(&unsolvable).len(), 12);
+    }
+
+    #[test]
+    fn test_linear_conflicts() {
+        assert_eq!(Board::new().linear_conflicts(), 0);
+        // 2 and 1 block each other in the top row
+        let board: Board = "2 1 3 4 5 6 7 8 0".parse().unwrap();
+        assert_eq!(board.linear_conflicts(), 1);
+        // Reversed, only one of 3, 2 and 1 can stay in the row
+        let board: Board = "3 2 1 4 5 6 7 8 0".parse().unwrap();
+        assert_eq!(board.linear_conflicts(), 2);
+        // Columns count too: 7 and 1 have swapped ends of the left one, so
+        // only one of them or 4 in between can stay
+        let board: Board = "7 2 3 4 5 6 1 8 0".parse().unwrap();
+        assert_eq!(board.linear_conflicts(), 2);
+    }
+
+    #[test]
+    fn test_position() {
+        let position = Position::from_index(7, 3);
+        assert_eq!((position.row(), position.col()), (2, 1));
+        assert_eq!(position.to_index(3), 7);
+        assert_eq!(Position(1, 3).to_index(4), 7);
+        assert_eq!(position.manhattan_distance_to(&Position(0, 2)), 3);
+        assert_eq!(position.manhattan_distance_to(&position), 0);
+
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(board.grid_position_of(6), Some(Position(2, 2)));
+        assert_eq!(board.grid_position_of(0), Some(Position(1, 1)));
+        assert_eq!(board.grid_position_of(9), None);
+    }
+
+    #[test]
+    fn test_goal_position_of() {
+        assert_eq!(Board::goal_position_of(1, 3), Position(0, 0));
+        assert_eq!(Board::goal_position_of(9, 4), Position(2, 0));
+        assert_eq!(Board::goal_position_of(0, 3), Position(2, 2));
+        // Agrees with the goal board itself
+        let goal = Board::goal_state(5);
+        for tile in 0..25 {
+            assert_eq!(
+                goal.grid_position_of(tile),
+                Some(Board::goal_position_of(tile, 5))
+            );
+        }
+    }
+
+    #[test]
+    fn test_manhattan_distance() {
+        assert_eq!(Board::new().manhattan_distance(), 0);
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(board.manhattan_distance(), 2);
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(board.manhattan_distance(), 21);
+    }
+
+    #[test]
+    fn test_distance_from_board() {
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(board.distance_from_board(&board), 0);
+        assert_eq!(
+            board.distance_from_board(&Board::new()),
+            board.manhattan_distance()
+        );
+
+        let other: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(other.distance_from_board(&Board::new()), 2);
+        assert_eq!(
+            board.distance_from_board(&other),
+            other.distance_from_board(&board)
+        );
+    }
+
+    #[test]
+    fn test_goal_state_for_pattern() {
+        let goal = |size, pattern| Board::goal_state_for_pattern(size, pattern).tiles;
+        assert_eq!(goal(3, Pattern::Standard), Board::goal_state(3).tiles);
+        assert_eq!(goal(3, Pattern::Snake), [1, 2, 3, 6, 5, 4, 7, 8, 0]);
+        assert_eq!(
+            goal(4, Pattern::Snake),
+            [1, 2, 3, 4, 8, 7, 6, 5, 9, 10, 11, 12, 0, 15, 14, 13]
+        );
+        assert_eq!(goal(3, Pattern::Reverse), [0, 8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(goal(2, Pattern::Reverse), [0, 3, 2, 1]);
+        assert_eq!(goal(3, Pattern::CenterBlank), [1, 2, 3, 4, 0, 5, 6, 7, 8]);
+        assert_eq!(goal(5, Pattern::CenterBlank)[12], 0);
+
+        assert!(!Pattern::CenterBlank.fits(4));
+        assert!(Pattern::ALL.iter().all(|pattern| pattern.fits(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "CenterBlank doesn't fit a 4x4 board")]
+    fn test_center_blank_needs_odd_size() {
+        Board::goal_state_for_pattern(4, Pattern::CenterBlank);
+    }
+
+    #[test]
+    fn test_distance_to_pattern_goal() {
+        let board = Board::goal_state(3);
+        for pattern in Pattern::ALL {
+            let goal = Board::goal_state_for_pattern(3, pattern);
+            assert!(goal.is_goal(&goal));
+            assert_eq!(goal.distance_from_board(&goal), 0);
+        }
+        // 4 and 6 swap ends of the middle row
+        let snake = Board::goal_state_for_pattern(3, Pattern::Snake);
+        assert_eq!(board.distance_from_board(&snake), 4);
+        let center = Board::goal_state_for_pattern(3, Pattern::CenterBlank);
+        // 5 to 8 each shift one cell along, 6 wrapping back a row
+        assert_eq!(board.distance_from_board(&center), 6);
+    }
+
+    #[test]
+    fn test_direction_for_tile() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(board.position_of(8), Some(7));
+        assert_eq!(board.position_of(9), None);
+        assert_eq!(board.direction_for_tile(2), Some(Direction::Down));
+        assert_eq!(board.direction_for_tile(8), Some(Direction::Up));
+        assert_eq!(board.direction_for_tile(4), Some(Direction::Right));
+        assert_eq!(board.direction_for_tile(5), Some(Direction::Left));
+        assert_eq!(board.direction_for_tile(1), None);
+        assert_eq!(board.direction_for_tile(0), None);
+        assert_eq!(board.direction_for_tile(42), None);
+
+        // Tiles on either side of a row break aren't neighbors
+        let board: Board = "1 2 3 0 4 5 6 7 8".parse().unwrap();
+        assert_eq!(board.direction_for_tile(3), None);
+        assert_eq!(board.tile_for_direction(Direction::Left), Some(4));
+        assert_eq!(board.tile_for_direction(Direction::Right), None);
+    }
+
+    #[test]
+    fn test_push_sequence() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        for direction in Direction::ALL {
+            // From the center there's one tile to each edge, the same as a move
+            assert_eq!(board.push_sequence(direction), [direction]);
+        }
+
+        let board = Board::goal_state(4);
+        assert_eq!(board.push_sequence(Direction::Right), [Direction::Right; 3]);
+        assert_eq!(board.push_sequence(Direction::Down), [Direction::Down; 3]);
+        assert!(board.push_sequence(Direction::Up).is_empty());
+        assert!(board.push_sequence(Direction::Left).is_empty());
+
+        let corner: Board = "0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15".parse().unwrap();
+        assert_eq!(corner.push_sequence(Direction::Left), [Direction::Left; 3]);
+        assert_eq!(corner.push_sequence(Direction::Up), [Direction::Up; 3]);
+
+        // A locked tile stops the push short
+        let mut locked = Board::goal_state(4);
+        locked.lock_tile(14);
+        assert_eq!(locked.push_sequence(Direction::Right), [Direction::Right]);
+    }
+
+    #[test]
+    fn test_locked_tiles() {
+        let mut board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert!(board.lock_tile(2));
+        assert!(board.is_locked(1));
+        assert!(board.has_locked_tiles());
+        assert!(!board.lock_tile(0));
+        assert!(!board.lock_tile(9));
+
+        assert_eq!(
+            board.valid_moves(),
+            [Direction::Up, Direction::Left, Direction::Right]
+        );
+        assert!(!board.move_tile(Direction::Down));
+        assert_eq!(board.direction_for_tile(2), None);
+        assert!(!board.apply_rotation_move(RotationAxis::Col(1), RotationDir::Forward));
+
+        // The lock goes with its tile through a low-level swap
+        board.swap_tiles(1, 0).unwrap();
+        assert!(board.is_locked(0) && !board.is_locked(1));
+
+        assert!(board.unlock_tile(2));
+        assert!(!board.has_locked_tiles());
+        assert!(board.move_tile(Direction::Up));
+    }
+
+    #[test]
+    fn test_rotate_row() {
+        let mut board = Board::new();
+        assert!(board.apply_rotation_move(RotationAxis::Row(0), RotationDir::Forward));
+        assert_eq!(board.tiles, [3, 1, 2, 4, 5, 6, 7, 8, 0]);
+        // The blank wraps like any other tile
+        assert!(board.apply_rotation_move(RotationAxis::Row(2), RotationDir::Backward));
+        assert_eq!(board.tiles, [3, 1, 2, 4, 5, 6, 8, 0, 7]);
+
+        assert!(board.apply_rotation_move(RotationAxis::Row(0), RotationDir::Backward));
+        assert!(board.apply_rotation_move(RotationAxis::Row(2), RotationDir::Forward));
+        assert!(board.is_goal(&Board::new()));
+    }
+
+    #[test]
+    fn test_rotate_col() {
+        let mut board = Board::new();
+        assert!(board.apply_rotation_move(RotationAxis::Col(1), RotationDir::Forward));
+        assert_eq!(board.tiles, [1, 8, 3, 4, 2, 6, 7, 5, 0]);
+        assert!(board.apply_rotation_move(RotationAxis::Col(2), RotationDir::Backward));
+        assert_eq!(board.tiles, [1, 8, 6, 4, 2, 0, 7, 5, 3]);
+
+        // A full turn of a column puts it back
+        let mut turned = Board::goal_state(4);
+        for _ in 0..4 {
+            assert!(turned.apply_rotation_move(RotationAxis::Col(3), RotationDir::Forward));
+        }
+        assert!(turned.is_goal(&Board::goal_state(4)));
+    }
+
+    #[test]
+    fn test_rotate_out_of_bounds() {
+        let mut board = Board::new();
+        assert!(!board.apply_rotation_move(RotationAxis::Row(3), RotationDir::Forward));
+        assert!(!board.apply_rotation_move(RotationAxis::Col(9), RotationDir::Backward));
+        assert!(board.is_goal(&Board::new()));
+    }
+
+    #[test]
+    fn test_apply_wraparound_move() {
+        // Inside the board it's an ordinary move
+        let mut board = Board::new();
+        let mut classic = Board::new();
+        assert!(board.apply_wraparound_move(Direction::Down));
+        assert!(classic.move_tile(Direction::Down));
+        assert_eq!(board, classic);
+
+        // From the bottom right corner, Up and Left reach round to the top
+        // row and the left column
+        let mut board = Board::new();
+        assert!(board.apply_wraparound_move(Direction::Up));
+        assert_eq!(board.tiles, [1, 2, 0, 4, 5, 6, 7, 8, 3]);
+        assert!(board.apply_wraparound_move(Direction::Down));
+        assert!(board.is_goal(&Board::new()));
+        assert!(board.apply_wraparound_move(Direction::Left));
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 0, 8, 7]);
+
+        // A locked tile across the edge stays put
+        let mut board = Board::new();
+        assert!(board.lock_tile(7));
+        assert!(!board.apply_wraparound_move(Direction::Left));
+        assert_eq!(board.valid_wraparound_moves().len(), 3);
+
+        // On two columns both ways round reach the same tile
+        let mut board = Board::goal_state(2);
+        assert!(board.apply_wraparound_move(Direction::Left));
+        assert_eq!(board.tiles, [1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn test_toroidal_distance() {
+        let mut board = Board::new();
+        board.set_wraparound(true);
+        assert_eq!(board.manhattan_distance(), 0);
+        // 3 is two columns from home inside the board, one across the edge
+        let mut board: Board = "3 1 2 4 5 6 7 8 0".parse().unwrap();
+        assert_eq!(board.manhattan_distance(), 4);
+        board.set_wraparound(true);
+        assert!(board.wraps());
+        assert_eq!(board.manhattan_distance(), 3);
+
+        let corner = Position(0, 0);
+        assert_eq!(corner.toroidal_distance_to(&Position(3, 3), 4), 2);
+        assert_eq!(corner.toroidal_distance_to(&Position(2, 2), 4), 4);
+    }
+
+    #[test]
+    fn test_apply_magnetic_move() {
+        // 8 slides into the corner and pulls 7 along behind it
+        let mut board = Board::new();
+        assert_eq!(board.magnetic_pull(Direction::Right), Some(7));
+        assert!(board.apply_magnetic_move(Direction::Right));
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 0, 7, 8]);
+
+        assert!(board.apply_magnetic_move(Direction::Down));
+        assert_eq!(board.tiles, [0, 2, 3, 1, 5, 6, 4, 7, 8]);
+        assert!(!board.apply_magnetic_move(Direction::Down));
+
+        // Nothing behind 3 at the edge, so it moves alone
+        let mut board = Board::goal_state(2);
+        assert_eq!(board.magnetic_pull(Direction::Right), None);
+        assert!(board.apply_magnetic_move(Direction::Right));
+        assert_eq!(board.tiles, [1, 2, 0, 3]);
+
+        // A locked tile stays put
+        let mut board = Board::new();
+        assert!(board.lock_tile(3));
+        assert_eq!(board.magnetic_pull(Direction::Down), None);
+        assert!(board.apply_magnetic_move(Direction::Down));
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 0, 7, 8, 6]);
+    }
+
+    #[test]
+    fn test_apply_gravity() {
+        // Already settled: the blank is in the top row
+        let mut board: Board = "1 0 3 4 2 6 7 5 8".parse().unwrap();
+        assert!(board.is_gravity_stable());
+        board.apply_gravity();
+        assert_eq!(board.tiles, [1, 0, 3, 4, 2, 6, 7, 5, 8]);
+
+        // The column above the blank falls one cell each
+        let mut board = Board::new();
+        assert!(!board.is_gravity_stable());
+        board.apply_gravity();
+        assert_eq!(board.tiles, [1, 2, 0, 4, 5, 3, 7, 8, 6]);
+        assert_eq!(board.get_blank_position(), 2);
+        assert!(board.is_gravity_stable());
+
+        // A locked tile holds up the tiles above it
+        let mut board = Board::new();
+        assert!(board.lock_tile(6));
+        assert!(board.is_gravity_stable());
+        assert!(board.unlock_tile(6));
+        assert!(board.lock_tile(3));
+        board.apply_gravity();
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 0, 7, 8, 6]);
+        assert!(board.is_gravity_stable());
+    }
+
+    #[test]
+    fn test_apply_gravity_move() {
+        // The slid tile holds on, so nothing falls into the blank below it
+        let mut board: Board = "1 2 0 4 5 3 7 8 6".parse().unwrap();
+        assert!(board.apply_gravity_move(Direction::Up));
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 0, 7, 8, 6]);
+
+        // Sideways the column above the blank falls
+        assert!(board.apply_gravity_move(Direction::Right));
+        assert_eq!(board.tiles, [1, 0, 3, 4, 2, 5, 7, 8, 6]);
+        assert_eq!(board.get_blank_position(), 1);
+
+        // And nothing moves when there's no tile to slide
+        assert!(!board.apply_gravity_move(Direction::Down));
+        assert_eq!(board.tiles, [1, 0, 3, 4, 2, 5, 7, 8, 6]);
+    }
+
+    #[test]
+    fn test_scramble_gravity_is_solvable() {
+        // Every board a gravity scramble reaches plays back to the goal
+        // with gravity moves
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..5 {
+            let mut board = Board::new();
+            let moves = board.scramble_gravity(&mut rng, 30);
+            assert!(moves.len() >= 30);
+            let goal = Board::new();
+            assert!(!board.is_goal(&goal));
+            let mut replayed = goal.clone();
+            assert!(moves.iter().all(|&direction| replayed.move_tile(direction)));
+            assert_eq!(replayed, board);
+
+            let mut seen = HashSet::from([board.tiles.clone()]);
+            let mut queue = VecDeque::from([board]);
+            let mut solved = false;
+            while let Some(board) = queue.pop_front() {
+                if board.tiles == goal.tiles {
+                    solved = true;
+                    break;
+                }
+                for direction in board.valid_moves() {
+                    let mut next = board.clone();
+                    next.apply_gravity_move(direction);
+                    if seen.insert(next.tiles.clone()) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+            assert!(solved);
+        }
+    }
+
+    #[test]
+    fn test_scramble_magnetic() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut board = Board::new();
+        let moves = board.scramble_magnetic(&mut rng, 20);
+        assert!(!board.is_goal(&Board::new()));
+        let mut replayed = Board::new();
+        assert!(moves.iter().all(|&direction| replayed.move_tile(direction)));
+        assert_eq!(replayed, board);
+    }
+
+    #[test]
+    fn test_rotation_through() {
+        let board = Board::new();
+        let rotation = board.rotation_through(6, Direction::Left).unwrap();
+        assert_eq!(
+            rotation,
+            Rotation::new(RotationAxis::Row(1), RotationDir::Backward)
+        );
+        assert_eq!(rotation.to_string(), "R2-");
+        assert_eq!(rotation.inverse().to_string(), "R2+");
+        let rotation = board.rotation_through(0, Direction::Down).unwrap();
+        assert_eq!(rotation.to_string(), "C3+");
+        assert_eq!(board.rotation_through(9, Direction::Up), None);
+    }
+
+    #[test]
+    fn test_is_rotation_solvable() {
+        assert!(Board::new().is_rotation_solvable());
+        // A slide is one swap with the blank, which rotations can't make up
+        let mut board = Board::new();
+        board.move_tile(Direction::Right);
+        assert!(board.is_solvable());
+        assert!(!board.is_rotation_solvable());
+        assert!(board.apply_rotation_move(RotationAxis::Col(0), RotationDir::Forward));
+        assert!(!board.is_rotation_solvable());
+        // Any board at all once the width is even
+        let board: Board = "2 1 3 0".parse().unwrap();
+        assert!(board.is_rotation_solvable());
+    }
+
+    #[test]
+    fn test_scramble_rotation() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut board = Board::new();
+        let rotations = board.scramble_rotation(&mut rng, 12);
+        assert!(rotations.len() >= 12);
+        assert!(!board.is_goal(&Board::new()));
+        assert!(board.is_rotation_solvable());
+        // Played back in reverse the rotations undo it
+        for rotation in rotations.iter().rev() {
+            let undo = rotation.inverse();
+            assert!(board.apply_rotation_move(undo.axis, undo.dir));
+        }
+        assert!(board.is_goal(&Board::new()));
+
+        // Lines with a locked tile stay out of it
+        let mut board = Board::new();
+        board.lock_tile(5);
+        let rotations = board.scramble_rotation(&mut rng, 20);
+        assert!(rotations.iter().all(|rotation| {
+            !matches!(rotation.axis, RotationAxis::Row(1) | RotationAxis::Col(1))
+        }));
+        assert_eq!(board.position_of(5), Some(4));
+    }
+
+    #[test]
+    fn test_count_inversions() {
+        assert_eq!(Board::new().count_inversions(), 0);
+        let board: Board = "1 2 3 4 5 6 8 7 0".parse().unwrap();
+        assert_eq!(board.count_inversions(), 1);
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(board.count_inversions(), 24);
+        let board: Board = "0 8 7 6 5 4 3 2 1".parse().unwrap();
+        assert_eq!(board.count_inversions(), 28);
+    }
+
+    #[test]
+    fn test_is_solvable() {
+        assert!(Board::goal_state(4).is_solvable());
+        assert!(random_board(4, 2).is_solvable());
+        assert!(!"1 2 3 4 5 6 8 7 0".parse::<Board>().unwrap().is_solvable());
+        let loyd: Board = "1 2 3 4 5 6 7 8 9 10 11 12 13 15 14 0".parse().unwrap();
+        assert!(!loyd.is_solvable());
+    }
+
+    #[test]
+    fn test_parse_board() {
+        let board: Board = "1,2,3 4,5,6 7,0,8".parse().unwrap();
+        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 0, 8]);
+        assert_eq!(board.size(), 3);
+        assert_eq!(
+            "1 2 3 4 5".parse::<Board>(),
+            Err(BoardError::NotSquare { found: 5 })
+        );
+        assert_eq!(
+            "1 2 3 3".parse::<Board>(),
+            Err(BoardError::DuplicateTile { tile: 3 })
+        );
+        assert_eq!(
+            "1 2 3 4".parse::<Board>(),
+            Err(BoardError::InvalidTile { tile: 4 })
+        );
+        assert_eq!(
+            "1 2 x 0".parse::<Board>(),
+            Err(BoardError::InvalidNumber {
+                token: "x".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let board = random_board(4, 3);
+        let bytes = board.to_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(Board::from_bytes(&bytes, 4), Ok(board));
+
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(Board::from_bytes(&board.to_bytes(), 3), Ok(board));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let bytes = [1, 2, 3, 4, 0, 5, 7, 8, 6];
+        assert_eq!(
+            Board::from_iter(bytes.iter().copied(), 3),
+            Ok(board.clone())
+        );
+        assert_eq!(
+            Board::from_iter((1..=15).chain([0]), 4),
+            Ok(Board::goal_state(4))
+        );
+        // Only the board's worth is taken from a longer stream
+        assert_eq!(
+            Board::from_iter(bytes.into_iter().chain(9..), 3),
+            Ok(board.clone())
+        );
+
+        assert_eq!(
+            Board::from_iter(1..4, 2),
+            Err(BoardError::WrongTileCount {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Board::from_iter([1, 2, 3, 3], 2),
+            Err(BoardError::DuplicateTile { tile: 3 })
+        );
+
+        assert_eq!(
+            Board::from_iter(std::iter::empty(), 0),
+            Err(BoardError::InvalidSize { size: 0 })
+        );
+        assert_eq!(
+            Board::from_iter(0.., MAX_SIZE + 1),
+            Err(BoardError::InvalidSize { size: MAX_SIZE + 1 })
+        );
+
+        let collected: Board = (1..9).chain(std::iter::once(0)).collect();
+        assert_eq!(collected, Board::goal_state(3));
+        assert_eq!(take_collect_error(), None);
+        assert_eq!(bytes.into_iter().collect::<Board>(), board);
+    }
+
+    #[test]
+    fn test_collect_invalid_board() {
+        let collected: Board = [1, 2, 3].into_iter().collect();
+        assert_eq!(collected, Board::new());
+        assert_eq!(
+            take_collect_error(),
+            Some(BoardError::WrongTileCount {
+                expected: 9,
+                found: 3
+            })
+        );
+        assert_eq!(take_collect_error(), None);
+
+        // A good collect clears what a bad one left
+        let _: Board = [1, 1, 2, 3, 4, 5, 6, 7, 8].into_iter().collect();
+        let _: Board = (0..9).collect();
+        assert_eq!(take_collect_error(), None);
+    }
+
+    #[test]
+    fn test_from_bytes_validation() {
+        let bytes = Board::new().to_bytes();
+        assert_eq!(
+            Board::from_bytes(&bytes, 4),
+            Err(BoardError::WrongTileCount {
+                expected: 16,
+                found: 9
+            })
+        );
+        assert_eq!(
+            Board::from_bytes(&[0, 1, 2, 2], 2),
+            Err(BoardError::DuplicateTile { tile: 2 })
+        );
+        assert_eq!(
+            Board::from_bytes(&[0, 1, 2, 9], 2),
+            Err(BoardError::InvalidTile { tile: 9 })
+        );
+        assert_eq!(
+            Board::from_bytes(&[], 0),
+            Err(BoardError::InvalidSize { size: 0 })
+        );
+        assert_eq!(
+            Board::from_bytes(&[0], usize::MAX),
+            Err(BoardError::InvalidSize { size: usize::MAX })
+        );
+    }
+
+    #[test]
+    fn test_boards_past_16x16() {
+        for size in [16, 20] {
+            let mut board = random_board(size, 5);
+            assert!(board.is_solvable());
+            assert_eq!(board.tiles().len(), size * size);
+            assert_eq!(
+                *board.tiles().iter().max().unwrap() as usize,
+                size * size - 1
+            );
+
+            let bytes = board.to_bytes();
+            assert_eq!(bytes.len(), size * size * if size == 16 { 1 } else { 2 });
+            assert_eq!(Board::from_bytes(&bytes, size).unwrap(), board);
+
+            let json = serde_json::to_string(&board).unwrap();
+            assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), board);
+
+            let moves = board.valid_moves();
+            assert!(board.move_tile(moves[0]));
+            assert!(board.undo_move(moves[0]));
+        }
+
+        let widest = Board::goal_state(20).to_string();
+        assert!(widest.ends_with(
+            "381 382 383 384 385 386 387 388 389 390 391 392 393 394 395 396 397 398 399   .\n"
+        ));
+        assert_eq!(Board::goal_state(MAX_SIZE).tiles().len(), 1024);
+    }
+
+    #[test]
+    fn test_display_board() {
+        let board = Board::goal_state(4);
+        assert_eq!(
+            board.to_string(),
+            " 1  2  3  4\n 5  6  7  8\n 9 10 11 12\n13 14 15  .\n"
+        );
+    }
+
+    #[test]
+    fn test_debug_summary() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(
+            board.debug_summary(),
+            "1 2 3\n4 . 5\n7 8 6\nblank at 4 (row 1, column 1)\n\
+             manhattan distance: 2\nsolvable: true\nsolved: false\n"
+        );
+        assert_eq_board!(board, board.clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "boards differ: [1, 2, 3, 0] != [1, 2, 0, 3]")]
+    fn test_assert_eq_board() {
+        assert_eq_board!(Board::goal_state(2), "1 2 0 3".parse::<Board>().unwrap());
+    }
+
+    #[test]
+    fn test_parse_move_sequence() {
+        assert_eq!(
+            Direction::parse_sequence("UD l r"),
+            Ok(vec![
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right
+            ])
+        );
+        assert_eq!(
+            Direction::parse_sequence("UUX"),
+            Err(ParseError::InvalidChar { ch: 'X', index: 2 })
+        );
+    }
+
+    #[test]
+    fn test_move_sequence_round_trip() {
+        let sequence: MoveSequence = "uu ldd".parse().unwrap();
+        assert_eq!(sequence.len(), 5);
+        assert_eq!(sequence.moves()[2], Direction::Left);
+        assert_eq!(sequence.to_string(), "UULDD");
+        assert!("UUQ".parse::<MoveSequence>().is_err());
+    }
+
+    #[test]
+    fn test_undo_move() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut board = random_board(4, 2);
+        for _ in 0..100 {
+            let before = board.clone();
+            let direction = board.random_valid_move(None, &mut rng).unwrap();
+            assert!(board.move_tile(direction));
+            assert!(board.undo_move(direction));
+            assert_eq!(board, before);
+            board.move_tile(direction);
+        }
+
+        // A move that did nothing has nothing to take back
+        let mut board = Board::new();
+        assert!(!board.move_tile(Direction::Up));
+        assert!(board.undo_move(Direction::Up));
+        assert_ne!(board, Board::new());
+    }
+
+    #[test]
+    fn test_invert_sequence() {
+        assert_eq!(
+            Direction::invert_sequence(&[Direction::Up, Direction::Left]),
+            [Direction::Right, Direction::Down]
+        );
+        assert_eq!(Direction::invert_sequence(&[]), []);
+
+        let mut rng = StdRng::seed_from_u64(9);
+        for size in [2, 3, 5] {
+            let start = random_board(size, size as u64);
+            let mut board = start.clone();
+            let mut moves = Vec::new();
+            for _ in 0..40 {
+                let direction = board.random_valid_move(moves.last().copied(), &mut rng);
+                moves.push(direction.unwrap());
+                board.move_tile(*moves.last().unwrap());
+            }
+
+            let inverse = Direction::invert_sequence(&moves);
+            assert_eq!(board.apply_moves(&inverse), inverse.len());
+            assert_eq!(board, start);
+            assert_eq!(Direction::invert_sequence(&inverse), moves);
+        }
+    }
+
+    #[test]
+    fn test_cancel_sequence() {
+        let moves = Direction::parse_sequence("DURLLUDR").unwrap();
+        assert_eq!(Direction::cancel_sequence(&moves), []);
+        // Each pair taken out can leave a new one behind
+        let moves = Direction::parse_sequence("DLRRUDL").unwrap();
+        assert_eq!(Direction::cancel_sequence(&moves), [Direction::Down]);
+        let moves = Direction::parse_sequence("DRUL").unwrap();
+        assert_eq!(Direction::cancel_sequence(&moves), moves);
+    }
+
+    #[test]
+    fn test_zobrist_incremental_matches_full() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for size in [2, 3, 4, 7] {
+            let mut board = random_board(size, 3);
+            let mut hash = board.zobrist();
+            for _ in 0..200 {
+                let direction = Direction::ALL[rng.gen_range(0..4)];
+                match board.zobrist_after_move(hash, direction) {
+                    Some(next) => {
+                        assert!(board.move_tile(direction));
+                        hash = next;
+                    }
+                    None => assert!(!board.clone().move_tile(direction)),
+                }
+                assert_eq!(hash, board.zobrist());
+            }
+        }
+
+        assert_eq!(Board::new().zobrist(), Board::goal_state(3).zobrist());
+        assert_ne!(Board::new().zobrist(), random_board(3, 1).zobrist());
+    }
+
+    #[test]
+    fn test_direction_serde() {
+        for (direction, json) in
+            Direction::ALL
+                .into_iter()
+                .zip([r#""up""#, r#""down""#, r#""left""#, r#""right""#])
+        {
+            assert_eq!(serde_json::to_string(&direction).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Direction>(json).unwrap(), direction);
+        }
+
+        let err = serde_json::from_str::<Direction>(r#""north""#).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unknown variant `north`, expected one of `up`, `down`, `left`, `right`"));
+    }
+
+    #[test]
+    fn test_apply_scramble_sequence() {
+        let board = Board::apply_scramble_sequence("DRU").unwrap();
+        assert_eq!(board.tiles, [1, 2, 3, 4, 8, 5, 7, 0, 6]);
+        assert_eq!(
+            Board::apply_scramble_sequence("D?"),
+            Err(ParseError::InvalidChar { ch: '?', index: 1 })
+        );
+        assert_eq!(
+            Board::apply_scramble_sequence("D U U"),
+            Err(ParseError::IllegalMove {
+                direction: Direction::Up,
+                index: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_move_sequence_checked() {
+        let board = Board::apply_move_sequence_checked(Board::new(), "DRU").unwrap();
+        assert_eq!(board.tiles, [1, 2, 3, 4, 8, 5, 7, 0, 6]);
+        assert_eq!(
+            Board::apply_move_sequence_checked(Board::new(), "DRUX"),
+            Err((3, MoveError::InvalidChar('X')))
+        );
+        // The third Down has nothing above the blank to bring down
+        assert_eq!(
+            Board::apply_move_sequence_checked(Board::new(), "DDD"),
+            Err((2, MoveError::IllegalMove(Direction::Down)))
+        );
+        // Characters, not bytes: the ideographic space is skipped like any
+        // whitespace, but takes three bytes
+        assert_eq!(
+            Board::apply_move_sequence_checked(Board::new(), "D\u{3000}X"),
+            Err((2, MoveError::InvalidChar('X')))
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use crate::board::Board;
+
+// Named starting positions worth trying, usable with `--state`. Kept as
+// puzzle text, as puzzle_text reads and writes it.
+pub struct CatalogEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub puzzle: &'static str,
+}
+
+impl CatalogEntry {
+    pub fn board(&self) -> Board {
+        Board::from_puzzle_text(self.puzzle).expect("catalog puzzles are valid")
+    }
+}
+
+pub const CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        name: "warmup",
+        description: "Two moves from solved",
+        puzzle: "puzzle 3x3\n1 2 3\n4 5 6\n. 7 8\n",
+    },
+    CatalogEntry {
+        name: "spiral",
+        description: "A gentle 3x3 that circles the center",
+        puzzle: "puzzle 3x3\n4 1 3\n7 2 6\n. 5 8\n",
+    },
+    CatalogEntry {
+        name: "hardest-a",
+        description: "One of the two hardest 3x3 positions (31 moves)",
+        puzzle: "puzzle 3x3\n8 6 7\n2 5 4\n3 . 1\n",
+    },
+    CatalogEntry {
+        name: "hardest-b",
+        description: "The other hardest 3x3 position (31 moves)",
+        puzzle: "puzzle 3x3\n6 4 7\n8 5 .\n3 2 1\n",
+    },
+    CatalogEntry {
+        name: "loyd",
+        description: "Sam Loyd's famous 15-puzzle with 14 and 15 swapped (unsolvable)",
+        puzzle: "puzzle 4x4\n 1  2  3  4\n 5  6  7  8\n 9 10 11 12\n13 15 14  .\n",
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|entry| entry.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{puzzle_text::Puzzle, solver::bfs_solve};
+
+    #[test]
+    fn test_catalog_puzzles_parse() {
+        for entry in CATALOG {
+            let puzzle = Puzzle::parse(entry.puzzle);
+            assert!(puzzle.is_ok(), "{}: {:?}", entry.name, puzzle);
+            // Written the way the format writes them
+            assert_eq!(puzzle.unwrap().to_string(), entry.puzzle, "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn test_catalog_warmup_distance() {
+        let board = find("warmup").unwrap().board();
+        assert_eq!(bfs_solve(&board).map(|moves| moves.len()), Some(2));
+    }
+}
@@ -0,0 +1,755 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use log::LevelFilter;
+use std::path::PathBuf;
+#[cfg(feature = "image")]
+use tile_game_rs::photo::ImageFit;
+use tile_game_rs::{
+    board::{Board, MoveSequence, DEFAULT_SIZE, MAX_SIZE, MIN_SIZE},
+    config::{KeyBindings, Sound, Timings, MAX_SPEED, MIN_SPEED},
+    difficulty::DifficultyLevel,
+    duration::TimeStyle,
+    game::GameMode,
+    i18n::Lang,
+    solver::{AStarSolver, Heuristic, IdaStarSolver, Solver},
+    theme::Theme,
+};
+
+use crate::error::EXIT_CODES_HELP;
+
+// Deeper trees on a big board are far more than Graphviz can lay out
+const MAX_DOT_DEPTH: u8 = 12;
+// 127 cells, still two-digit tiles
+const MAX_HEX_RADIUS: u8 = 6;
+// 124 tiles, already an afternoon's work
+const MAX_CUBE_SIZE: u8 = 5;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "tile-game",
+    version,
+    about = "A sliding tile puzzle for the terminal"
+)]
+#[command(args_conflicts_with_subcommands = true, after_help = EXIT_CODES_HELP)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Write a debug log to this file
+    #[arg(long, value_name = "FILE", global = true)]
+    pub log_file: Option<PathBuf>,
+
+    /// How much to log: off, error, warn, info, debug or trace (default: RUST_LOG, then info)
+    #[arg(long, value_name = "LEVEL", global = true, requires = "log_file")]
+    pub log_level: Option<LevelFilter>,
+
+    /// Load or build the table of every 3x3 board's distance from solved first, so 3x3 hints
+    /// and solves are instant (cached in ~/.cache/tilegame)
+    #[arg(long, global = true)]
+    pub precompute: bool,
+
+    /// Language for the game's text: en or es (default: from LANG, then en)
+    #[arg(long, value_name = "LANG", global = true)]
+    pub lang: Option<Lang>,
+
+    // Running without a subcommand is the same as `tile-game play`
+    #[command(flatten)]
+    pub play: PlayArgs,
+}
+
+// Parsed once at startup, so the size of the play variant doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Play a puzzle in the terminal (the default)
+    Play(PlayArgs),
+    /// Print the best times for each board size and mode
+    Leaderboard(LeaderboardArgs),
+    /// Summarize past games: the leaderboard's solves by size and mode, and any saved results
+    Stats(StatsArgs),
+    /// Print the shortest solution for a board
+    Solve(SolveArgs),
+    /// Play today's puzzle, the same for everyone on a given date
+    Daily(DailyArgs),
+    /// Apply a move sequence to a board and print the result
+    Replay(ReplayArgs),
+    /// Print a board saved with play --snapshots as puzzle text
+    Export(ExportArgs),
+    /// List named starting positions
+    Catalog,
+    /// Solve reproducible scrambles with every solver heuristic and compare them
+    BenchHeuristics(BenchArgs),
+    /// Manage the pattern databases that let solve handle 4x4 boards
+    #[command(subcommand)]
+    Pdb(PdbCommand),
+    /// Show, check or find the settings
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print every setting as it applies: the defaults, then the settings file, then the
+    /// TILEGAME_ variables
+    Show,
+    /// Check the settings file and the TILEGAME_ variables, listing every problem
+    Validate,
+    /// Print where the settings file is read from
+    Path,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PdbCommand {
+    /// Build the 4x4 pattern databases into ~/.cache/tilegame (under a minute in a release
+    /// build, and about 120 MB of memory)
+    Build,
+}
+
+#[derive(Debug, Args)]
+pub struct PlayArgs {
+    /// Board width and height, from 2 to 32
+    #[arg(long, default_value_t = DEFAULT_SIZE as u8, value_parser = clap::value_parser!(u8).range(MIN_SIZE as i64..=MAX_SIZE as i64))]
+    pub size: u8,
+
+    /// Seed for a reproducible shuffle
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// How hard the shuffled board should be to solve
+    #[arg(long, value_enum, default_value_t = Difficulty::Medium)]
+    pub difficulty: Difficulty,
+
+    /// Tile colors
+    #[arg(long, value_enum, default_value_t = Theme::Classic)]
+    pub theme: Theme,
+
+    /// Rules to play by
+    #[arg(long, value_enum, default_value_t = Mode::Classic)]
+    pub mode: Mode,
+
+    /// Player 2's keys for up, down, left and right in two-player mode
+    #[arg(long, value_name = "KEYS", default_value = "wsad")]
+    pub player2_keys: KeyBindings,
+
+    /// Start from these tiles instead of a shuffle, e.g. "1,2,3,4,5,6,7,0,8" (0 is the blank)
+    #[arg(long, conflicts_with_all = ["seed", "difficulty", "size"])]
+    pub state: Option<Board>,
+
+    /// Start from the solved board with these moves applied, e.g. "DRU"
+    #[arg(long, value_name = "MOVES", conflicts_with_all = ["state", "seed", "difficulty"])]
+    pub scramble: Option<String>,
+
+    /// Start from the solved board (debug; never recorded in stats)
+    #[arg(long, conflicts_with_all = ["state", "seed", "difficulty", "moves_from_solved", "scramble"])]
+    pub no_shuffle: bool,
+
+    /// Skip the terminal UI: read moves (U, D, L, R) from stdin until EOF and print the final board
+    #[arg(long)]
+    pub pipe: bool,
+
+    /// Start exactly this many random moves from solved (debug; never recorded in stats)
+    #[arg(long, value_name = "MOVES", conflicts_with_all = ["state", "difficulty", "scramble"])]
+    pub moves_from_solved: Option<usize>,
+
+    /// Locked-tile challenge: lock this many tiles in their solved places and shuffle around them
+    #[arg(long, value_name = "COUNT", conflicts_with_all = [
+        "state", "scramble", "no_shuffle", "moves_from_solved",
+    ])]
+    pub locked: Option<usize>,
+
+    /// Play on a hexagonal board this many rings out from the middle cell, sliding tiles six ways
+    #[arg(long, value_name = "RADIUS", conflicts_with_all = [
+        "state", "scramble", "no_shuffle", "moves_from_solved", "locked", "size",
+        "difficulty", "replay",
+    ], value_parser = clap::value_parser!(u8).range(1..=MAX_HEX_RADIUS as i64))]
+    pub hex: Option<u8>,
+
+    /// Play on a cube this many tiles a side, one layer at a time; tiles also slide between layers
+    #[arg(long, value_name = "SIZE", conflicts_with_all = [
+        "state", "scramble", "no_shuffle", "moves_from_solved", "locked", "size",
+        "difficulty", "replay", "hex", "pipe",
+    ], value_parser = clap::value_parser!(u8).range(2..=MAX_CUBE_SIZE as i64))]
+    pub cube: Option<u8>,
+
+    /// Print this board, e.g. "1 2 3 4 5 6 7 8 0", and exit; add --json for a summary
+    #[arg(long, value_name = "TILES", conflicts_with_all = [
+        "state", "scramble", "seed", "difficulty", "size", "no_shuffle",
+        "moves_from_solved", "pipe", "replay",
+    ])]
+    pub print_board: Option<Board>,
+
+    /// Print the tree of boards up to this many moves from the start as Graphviz DOT, and exit
+    #[arg(long, value_name = "DEPTH", conflicts_with_all = ["pipe", "replay", "print_board"],
+        value_parser = clap::value_parser!(u8).range(0..=MAX_DOT_DEPTH as i64))]
+    pub dot: Option<u8>,
+
+    /// Play these moves back on the starting board instead of taking input, e.g. "UULDDRRUL"
+    #[arg(long, value_name = "MOVES", conflicts_with = "pipe")]
+    pub replay: Option<MoveSequence>,
+
+    /// Pause between replayed moves, in milliseconds (default 300)
+    #[arg(long, value_name = "MS", requires = "replay")]
+    pub delay_ms: Option<u64>,
+
+    /// Make animations and pauses faster or slower, from 0.5 to 4.0
+    #[arg(long, value_name = "FACTOR", default_value_t = 1.0, value_parser = parse_speed)]
+    pub speed: f64,
+
+    /// Skip animations and pauses, like the one after a win
+    #[arg(long)]
+    pub reduced_motion: bool,
+
+    /// Draw the tiles as emoji (colored squares, or the settings' emoji_labels)
+    #[arg(long)]
+    pub emoji: bool,
+
+    /// Before play, show the solved board shuffling itself into the start; any key skips it
+    #[arg(long)]
+    pub shuffle_intro: bool,
+
+    /// Ring the terminal bell: any of move, invalid and win, comma-separated, or all or off
+    /// (bare --sound means invalid,win)
+    #[arg(long, value_name = "CUES", default_value = "off", num_args = 0..=1,
+        default_missing_value = "invalid,win")]
+    pub sound: Sound,
+
+    /// Most moves one burst of a held arrow key can make before the screen catches up,
+    /// or 0 to let every key repeat through
+    #[arg(long, value_name = "MOVES", default_value_t = 2)]
+    pub key_repeat: u32,
+
+    /// Pause the clock after this many seconds without a key, or 0 to keep it running
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    pub idle_pause: u64,
+
+    /// How to show the play time
+    #[arg(long, value_enum, default_value_t = TimeStyle::Compact)]
+    pub time_style: TimeStyle,
+
+    /// Play a photo puzzle: cut this PNG or JPEG into the tiles and put the picture back together
+    #[cfg(feature = "image")]
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["pipe", "print_board"])]
+    pub image: Option<PathBuf>,
+
+    /// How a photo that isn't square is fitted to the board
+    #[cfg(feature = "image")]
+    #[arg(long, value_enum, default_value_t = ImageFit::Whole, requires = "image")]
+    pub image_fit: ImageFit,
+
+    /// Save board snapshots every 10 moves and on a win to this JSON file
+    #[arg(long, value_name = "FILE")]
+    pub snapshots: Option<PathBuf>,
+
+    /// Keep the leaderboard in this file, e.g. on a shared drive [default: leaderboard.jsonl
+    /// beside the settings file]
+    #[arg(long, value_name = "FILE")]
+    pub leaderboard: Option<PathBuf>,
+
+    /// Race a won game saved with --snapshots, shown as a ghost beside the moves count
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["pipe", "print_board"])]
+    pub ghost: Option<PathBuf>,
+
+    /// Append each move, hint, win and quit to this file as a line of JSON
+    #[arg(long, value_name = "FILE")]
+    pub event_log: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub json: JsonArgs,
+}
+
+impl PlayArgs {
+    pub fn timings(&self) -> Result<Timings, String> {
+        let mut timings = Timings::default();
+        if let Some(delay_ms) = self.delay_ms {
+            timings.autosolve_step_ms = delay_ms;
+        }
+        timings.validate()?;
+
+        let timings = timings.with_speed(self.speed)?;
+        Ok(match self.reduced_motion {
+            true => timings.reduced_motion(),
+            false => timings,
+        })
+    }
+
+    // TILEGAME_SIZE and TILEGAME_SEED stand in for --size and --seed when
+    // they're left out. They aren't settings, so Config::from_env can't hold
+    // them; and as environment in clap's sense they'd clash with --state.
+    // A bad value is skipped, like a bad setting, and returned for logging.
+    pub fn apply_env(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(value) = lookup("TILEGAME_SIZE").filter(|_| self.size == DEFAULT_SIZE as u8) {
+            match value.trim().parse::<u8>() {
+                Ok(size) if (MIN_SIZE..=MAX_SIZE).contains(&(size as usize)) => self.size = size,
+                _ => problems.push(format!(
+                    "TILEGAME_SIZE: '{}' isn't a size from {} to {}",
+                    value, MIN_SIZE, MAX_SIZE
+                )),
+            }
+        }
+        if let Some(value) = lookup("TILEGAME_SEED").filter(|_| self.seed.is_none()) {
+            match value.trim().parse() {
+                Ok(seed) => self.seed = Some(seed),
+                Err(_) => problems.push(format!("TILEGAME_SEED: '{}' isn't a seed", value)),
+            }
+        }
+        problems
+    }
+}
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|_| format!("'{}' isn't a number", s))?;
+    match (MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        true => Ok(speed),
+        false => Err(format!("must be from {} to {}", MIN_SPEED, MAX_SPEED)),
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct JsonArgs {
+    /// Print a JSON summary of the result on exit
+    #[arg(long)]
+    pub json: bool,
+
+    /// Write the JSON summary to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub json_out: Option<PathBuf>,
+}
+
+impl JsonArgs {
+    pub fn enabled(&self) -> bool {
+        self.json || self.json_out.is_some()
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    /// The board to solve, e.g. "1,2,3,4,5,6,7,0,8"
+    #[arg(long, required_unless_present = "puzzle", conflicts_with = "puzzle")]
+    pub state: Option<Board>,
+
+    /// Read the board from a puzzle text file instead, or "-" for stdin
+    #[arg(long, value_name = "FILE")]
+    pub puzzle: Option<PathBuf>,
+
+    /// How to print the solution: the moves, or the puzzle as text with a SOLUTION line
+    #[arg(long, value_enum, default_value_t = SolveFormat::Moves, conflicts_with_all = ["json", "json_out", "interactive"])]
+    pub format: SolveFormat,
+
+    /// Also rate how hard the board is, with the numbers behind the rating
+    #[arg(long, conflicts_with_all = ["json", "json_out"])]
+    pub analyze: bool,
+
+    /// Also print the scramble that leads to the board, and its inverse as
+    /// moves to follow by hand
+    #[arg(long, conflicts_with_all = ["json", "json_out"])]
+    pub print_inverse: bool,
+
+    /// Step through the solution on the board instead of printing it
+    #[arg(long, conflicts_with_all = ["json", "json_out"])]
+    pub interactive: bool,
+
+    /// Show on stderr how a long search is going: nodes expanded, the bound and the time so far
+    #[arg(long)]
+    pub progress: bool,
+
+    /// Give up after this many seconds, exiting with 2
+    #[arg(long, value_name = "SECONDS")]
+    pub time_limit: Option<u64>,
+
+    /// Search with this, guided by Manhattan distance, instead of the tables
+    /// and pattern databases
+    #[arg(long, value_enum)]
+    pub solver: Option<SolverKind>,
+
+    #[command(flatten)]
+    pub json: JsonArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SolverKind {
+    /// Iterative deepening A*: little memory, but boards are searched again on every pass
+    #[value(name = "ida*", alias = "ida-star")]
+    IdaStar,
+    /// A*: each board searched once, but every board seen is kept
+    #[value(name = "a*", alias = "a-star")]
+    AStar,
+}
+
+impl SolverKind {
+    pub fn with(self, heuristic: Heuristic) -> Box<dyn Solver> {
+        match self {
+            SolverKind::IdaStar => Box::new(IdaStarSolver { heuristic }),
+            SolverKind::AStar => Box::new(AStarSolver { heuristic }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SolveFormat {
+    Moves,
+    Text,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// A file written by play --snapshots
+    pub snapshots: PathBuf,
+
+    /// The board as it was after this many moves (default: the last snapshot)
+    #[arg(long, value_name = "MOVES")]
+    pub at_move: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct LeaderboardArgs {
+    /// Only this board size
+    #[arg(long)]
+    pub size: Option<u8>,
+
+    /// How many places to list for each size and mode
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// The leaderboard file, as for play --leaderboard
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// The leaderboard file, as for play --leaderboard
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// A result saved by play --json-out, to summarize as well; repeat for more
+    #[arg(long, value_name = "FILE")]
+    pub results: Vec<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct DailyArgs {
+    /// Board width and height, from 2 to 32
+    #[arg(long, default_value_t = DEFAULT_SIZE as u8, value_parser = clap::value_parser!(u8).range(MIN_SIZE as i64..=MAX_SIZE as i64))]
+    pub size: u8,
+
+    /// Tile colors
+    #[arg(long, value_enum, default_value_t = Theme::Classic)]
+    pub theme: Theme,
+}
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// The starting board, e.g. "1,2,3,4,5,6,7,0,8"
+    #[arg(long)]
+    pub state: Board,
+
+    /// Moves to apply, e.g. "ULDR" (whitespace is ignored)
+    pub moves: String,
+}
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// Board width and height, from 2 to 32
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(MIN_SIZE as i64..=MAX_SIZE as i64))]
+    pub size: u8,
+
+    /// How many scrambles to solve
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: u32,
+
+    /// Seed for the scrambles
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Random moves from solved for each scramble
+    #[arg(long, default_value_t = 30)]
+    pub moves: usize,
+
+    /// The search every heuristic guides
+    #[arg(long, value_enum, default_value_t = SolverKind::IdaStar)]
+    pub solver: SolverKind,
+
+    #[command(flatten)]
+    pub json: JsonArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Difficulty {
+    Easy,
+    #[value(alias = "normal")]
+    Medium,
+    Hard,
+}
+
+impl From<Difficulty> for DifficultyLevel {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => DifficultyLevel::Easy,
+            Difficulty::Medium => DifficultyLevel::Medium,
+            Difficulty::Hard => DifficultyLevel::Hard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    /// Slide tiles into the blank until they're in order
+    Classic,
+    /// Two players on one keyboard take turns: player 1 on the arrows, player 2 on --player2-keys
+    TwoPlayer,
+    /// Split screen race on two copies of the board: player 1 on the arrows, player 2 on --player2-keys
+    Versus,
+    /// Tiles fall into the blank after every slide, all but the one just slid
+    Gravity,
+    /// Every slide pulls the next tile in line along behind it
+    Magnetic,
+    /// The edges join up, so tiles slide across them to the far side
+    Wraparound,
+    /// Rows and columns turn instead of tiles sliding: type a tile's number, then arrows turn its row or column
+    Rotation,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Classic => "classic",
+            Mode::TwoPlayer => "two-player",
+            Mode::Versus => "versus",
+            Mode::Gravity => "gravity",
+            Mode::Magnetic => "magnetic",
+            Mode::Wraparound => "wraparound",
+            Mode::Rotation => "rotation",
+        }
+    }
+}
+
+impl From<Mode> for GameMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            // Each versus player plays an ordinary game on their own board
+            Mode::Classic | Mode::Versus => GameMode::Classic,
+            Mode::TwoPlayer => GameMode::two_player(),
+            Mode::Gravity => GameMode::Gravity,
+            Mode::Magnetic => GameMode::Magnetic,
+            Mode::Wraparound => GameMode::Wraparound,
+            Mode::Rotation => GameMode::Rotation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_bare_invocation_plays() {
+        let cli = Cli::try_parse_from(["tile-game"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.play.size, 3);
+        assert_eq!(cli.play.difficulty, Difficulty::Medium);
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let lookup = |name: &str| match name {
+            "TILEGAME_SIZE" => Some("5".to_string()),
+            "TILEGAME_SEED" => Some("seven".to_string()),
+            _ => None,
+        };
+        let mut args = Cli::try_parse_from(["tile-game"]).unwrap().play;
+        assert_eq!(
+            args.apply_env(lookup),
+            ["TILEGAME_SEED: 'seven' isn't a seed"]
+        );
+        assert_eq!((args.size, args.seed), (5, None));
+
+        // The flags win
+        let cli = Cli::try_parse_from(["tile-game", "--size", "4", "--seed", "9"]).unwrap();
+        let mut args = cli.play;
+        args.apply_env(|_| Some("6".to_string()));
+        assert_eq!((args.size, args.seed), (4, Some(9)));
+
+        let mut args = Cli::try_parse_from(["tile-game"]).unwrap().play;
+        args.apply_env(|_| Some("40".to_string()));
+        assert_eq!((args.size, args.seed), (3, Some(40)));
+    }
+
+    #[test]
+    fn test_stats_and_config() {
+        let cli = Cli::try_parse_from(["tile-game", "stats", "--results", "a", "--results", "b"])
+            .unwrap();
+        match cli.command {
+            Some(Command::Stats(args)) => {
+                assert_eq!(args.file, None);
+                assert_eq!(args.results, [PathBuf::from("a"), PathBuf::from("b")]);
+            }
+            _ => panic!("expected stats"),
+        }
+        let cli = Cli::try_parse_from(["tile-game", "config", "path"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Config(ConfigCommand::Path))
+        ));
+        assert!(Cli::try_parse_from(["tile-game", "config"]).is_err());
+    }
+
+    #[test]
+    fn test_size_range() {
+        assert!(Cli::try_parse_from(["tile-game", "--size", "4"]).is_ok());
+        assert!(Cli::try_parse_from(["tile-game", "--size", "1"]).is_err());
+        assert!(Cli::try_parse_from(["tile-game", "play", "--size", "20"]).is_ok());
+        assert!(Cli::try_parse_from(["tile-game", "play", "--size", "33"]).is_err());
+    }
+
+    #[test]
+    fn test_state_conflicts_with_seed() {
+        let result = Cli::try_parse_from(["tile-game", "--state", "1,2,3,0", "--seed", "4"]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+    }
+
+    #[test]
+    fn test_debug_start_flags() {
+        let cli =
+            Cli::try_parse_from(["tile-game", "--moves-from-solved", "5", "--seed", "1"]).unwrap();
+        assert_eq!(cli.play.moves_from_solved, Some(5));
+        assert!(Cli::try_parse_from(["tile-game", "--no-shuffle", "--seed", "1"]).is_err());
+        assert!(
+            Cli::try_parse_from(["tile-game", "--no-shuffle", "--difficulty", "easy"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_locked_challenge_shuffles() {
+        let cli = Cli::try_parse_from(["tile-game", "--locked", "2", "--seed", "1"]).unwrap();
+        assert_eq!(cli.play.locked, Some(2));
+        assert!(Cli::try_parse_from(["tile-game", "--locked", "2", "--no-shuffle"]).is_err());
+    }
+
+    #[test]
+    fn test_log_flags() {
+        let cli = Cli::try_parse_from([
+            "tile-game",
+            "solve",
+            "--state",
+            "1,2,3,0",
+            "--log-file",
+            "game.log",
+            "--log-level",
+            "trace",
+        ])
+        .unwrap();
+        assert_eq!(cli.log_level, Some(LevelFilter::Trace));
+        assert!(Cli::try_parse_from(["tile-game", "--log-level", "debug"]).is_err());
+    }
+
+    #[test]
+    fn test_two_player_keys() {
+        let cli = Cli::try_parse_from([
+            "tile-game",
+            "--mode",
+            "two-player",
+            "--player2-keys",
+            "ikjl",
+        ])
+        .unwrap();
+        assert_eq!(cli.play.mode, Mode::TwoPlayer);
+        assert_eq!(cli.play.player2_keys.up, 'i');
+        assert!(Cli::try_parse_from(["tile-game", "--player2-keys", "wsaq"]).is_err());
+    }
+
+    #[test]
+    fn test_replay_flags() {
+        let cli = Cli::try_parse_from(["tile-game", "--replay", "LU", "--delay-ms", "50"]).unwrap();
+        assert_eq!(cli.play.timings().unwrap().autosolve_step_ms, 50);
+        assert_eq!(cli.play.replay.unwrap().to_string(), "LU");
+        assert!(Cli::try_parse_from(["tile-game", "--replay", "LX"]).is_err());
+        assert!(Cli::try_parse_from(["tile-game", "--delay-ms", "50"]).is_err());
+    }
+
+    #[test]
+    fn test_bench_heuristics_flags() {
+        let cli = Cli::try_parse_from([
+            "tile-game",
+            "bench-heuristics",
+            "--size",
+            "3",
+            "--seed",
+            "7",
+            "--json",
+        ])
+        .unwrap();
+        let Some(Command::BenchHeuristics(args)) = cli.command else {
+            panic!("expected bench-heuristics");
+        };
+        assert_eq!((args.size, args.count, args.seed), (3, 50, 7));
+        assert_eq!(args.solver, SolverKind::IdaStar);
+        assert!(args.json.json);
+        let cli = Cli::try_parse_from(["tile-game", "bench-heuristics", "--solver", "a-star"]);
+        let Some(Command::BenchHeuristics(args)) = cli.unwrap().command else {
+            panic!("expected bench-heuristics");
+        };
+        assert_eq!(args.solver, SolverKind::AStar);
+        assert_eq!(args.solver.with(Heuristic::Manhattan).name(), "a*");
+        let cli = Cli::try_parse_from([
+            "tile-game",
+            "solve",
+            "--state",
+            "1,2,0,3",
+            "--solver",
+            "ida*",
+        ]);
+        let Some(Command::Solve(args)) = cli.unwrap().command else {
+            panic!("expected solve");
+        };
+        assert_eq!(args.solver, Some(SolverKind::IdaStar));
+        assert!(Cli::try_parse_from(["tile-game", "bench-heuristics", "--count", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_timing_flags() {
+        let cli = Cli::try_parse_from(["tile-game", "--speed", "2"]).unwrap();
+        let timings = cli.play.timings().unwrap();
+        assert_eq!(timings.celebration_ms, 1_000);
+        assert_eq!(timings.tick_ms, Timings::default().tick_ms);
+
+        let cli = Cli::try_parse_from(["tile-game", "--reduced-motion", "--speed", "0.5"]).unwrap();
+        assert_eq!(cli.play.timings().unwrap().celebration_ms, 0);
+
+        assert!(Cli::try_parse_from(["tile-game", "--speed", "5"]).is_err());
+        assert!(Cli::try_parse_from(["tile-game", "--speed", "fast"]).is_err());
+        let cli =
+            Cli::try_parse_from(["tile-game", "--replay", "L", "--delay-ms", "60000"]).unwrap();
+        assert!(cli.play.timings().is_err());
+
+        assert_eq!(cli.play.time_style, TimeStyle::Compact);
+        let cli = Cli::try_parse_from(["tile-game", "--time-style", "precise"]).unwrap();
+        assert_eq!(cli.play.time_style, TimeStyle::Precise);
+        assert!(cli.play.sound.is_off());
+
+        let cli = Cli::try_parse_from(["tile-game", "--sound"]).unwrap();
+        assert_eq!(cli.play.sound, "invalid,win".parse().unwrap());
+        let cli = Cli::try_parse_from(["tile-game", "--sound", "move"]).unwrap();
+        assert!(cli.play.sound.moves && !cli.play.sound.win);
+    }
+
+    #[test]
+    fn test_lang_flag() {
+        let cli = Cli::try_parse_from(["tile-game", "catalog", "--lang", "es"]).unwrap();
+        assert_eq!(cli.lang, Some(Lang::Es));
+        assert!(Cli::try_parse_from(["tile-game", "--lang", "xx"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_state_rejected() {
+        assert!(Cli::try_parse_from(["tile-game", "solve", "--state", "1,1,2,0"]).is_err());
+    }
+}
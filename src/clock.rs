@@ -0,0 +1,117 @@
+use std::{fmt, rc::Rc, time::Duration};
+
+// Where a timed session reads the time: how long since some fixed point, by
+// whatever clock the caller has. The core never reads one itself, as there's
+// none on wasm. Shared so that checkpoints cloned from a session keep the
+// same clock; two sources are equal when they're the same one.
+#[derive(Clone)]
+pub struct TimeSource(Rc<dyn Fn() -> Duration>);
+
+impl TimeSource {
+    pub fn new(now: impl Fn() -> Duration + 'static) -> Self {
+        Self(Rc::new(now))
+    }
+
+    pub fn now(&self) -> Duration {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for TimeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TimeSource")
+    }
+}
+
+impl PartialEq for TimeSource {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TimeSource {}
+
+// Play time that can stand still: the time since the clock started, less any
+// spent paused, a pause still going on included. Once stopped it reads the
+// same for good. Times are a TimeSource's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    started: Duration,
+    paused: Duration, // Pauses that have ended
+    paused_since: Option<Duration>,
+    stopped: Option<Duration>,
+}
+
+impl Clock {
+    pub fn start_at(now: Duration) -> Self {
+        Self {
+            started: now,
+            paused: Duration::ZERO,
+            paused_since: None,
+            stopped: None,
+        }
+    }
+
+    pub fn elapsed_at(&self, now: Duration) -> Duration {
+        let now = self.stopped.map_or(now, |stopped| stopped.min(now));
+        now.saturating_sub(self.started)
+            .saturating_sub(self.paused_at(now))
+    }
+
+    pub fn paused_at(&self, now: Duration) -> Duration {
+        let now = self.stopped.map_or(now, |stopped| stopped.min(now));
+        let ongoing = self
+            .paused_since
+            .map_or(Duration::ZERO, |since| now.saturating_sub(since));
+        self.paused + ongoing
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    // Pausing a paused clock, or resuming a running one, does nothing
+    pub fn pause_at(&mut self, now: Duration) {
+        self.paused_since.get_or_insert(now);
+    }
+
+    pub fn resume_at(&mut self, now: Duration) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused += now.saturating_sub(since);
+        }
+    }
+
+    pub fn stop_at(&mut self, now: Duration) {
+        self.stopped.get_or_insert(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock() {
+        let start = Duration::from_secs(100);
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut clock = Clock::start_at(start);
+        assert_eq!(clock.elapsed_at(at(5)), Duration::from_secs(5));
+
+        clock.pause_at(at(5));
+        clock.pause_at(at(7));
+        assert!(clock.is_paused());
+        // Standing still while paused
+        assert_eq!(clock.elapsed_at(at(9)), Duration::from_secs(5));
+        assert_eq!(clock.paused_at(at(9)), Duration::from_secs(4));
+
+        clock.resume_at(at(10));
+        clock.resume_at(at(11));
+        assert!(!clock.is_paused());
+        assert_eq!(clock.elapsed_at(at(12)), Duration::from_secs(7));
+        assert_eq!(clock.paused_at(at(12)), Duration::from_secs(5));
+
+        clock.stop_at(at(14));
+        clock.stop_at(at(20));
+        assert_eq!(clock.elapsed_at(at(30)), Duration::from_secs(9));
+    }
+}
@@ -0,0 +1,784 @@
+use crate::{board::Direction, duration::TimeStyle, i18n::Lang, settings, theme::Theme};
+use std::{env, fmt, path::PathBuf, str::FromStr, time::Duration};
+
+// Keys that are already bound to game commands. Digits are too: they move a
+// tile by its number.
+const RESERVED_KEYS: [char; 13] = [
+    'c', 'f', 'h', 'm', 'n', 'p', 'q', 't', 'v', 'L', 'N', 'R', 'T',
+];
+
+// Longest allowed tick and animation; anything past these is a typo
+const MAX_TICK_MS: u64 = 1_000;
+const MAX_ANIMATION_MS: u64 = 10_000;
+
+// Range of the multiplier Timings::with_speed accepts
+pub const MIN_SPEED: f64 = 0.5;
+pub const MAX_SPEED: f64 = 4.0;
+
+// Settings that shape how the game looks and behaves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub theme: Theme,
+    pub player2_keys: KeyBindings,
+    pub timings: Timings,
+    pub lang: Lang,
+    pub time_style: TimeStyle,
+    pub sound: Sound,
+    pub key_repeat: u32, // Moves a burst of one held key can make, 0 for no limit
+    pub idle_pause_secs: u64, // Without a key this long the clock pauses, 0 never
+    pub leaderboard: Option<PathBuf>, // For the 'L' screen; None has no file to show
+    pub assist: bool,    // Arrows beside the board for the ways a tile can slide
+    pub tile_labels: Option<Vec<String>>, // Shown in place of tile numbers 1 upwards
+    pub emoji_tiles: bool, // Emoji in place of the numbers, or of any labels
+    pub emoji_labels: Vec<String>, // The emoji for tiles 1 upwards
+    pub shuffle_intro: bool, // The scramble played out from solved before play
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Classic,
+            player2_keys: KeyBindings::default(),
+            timings: Timings::default(),
+            lang: Lang::default(),
+            time_style: TimeStyle::default(),
+            sound: Sound::default(),
+            key_repeat: 2,
+            idle_pause_secs: 60,
+            leaderboard: None,
+            assist: false,
+            tile_labels: None,
+            emoji_tiles: false,
+            emoji_labels: DEFAULT_EMOJI.map(String::from).to_vec(),
+            shuffle_intro: false,
+        }
+    }
+}
+
+// `override_` where it differs from the default, otherwise `base`
+fn layer<T: PartialEq>(base: T, override_: T, default: T) -> T {
+    match override_ == default {
+        true => base,
+        false => override_,
+    }
+}
+
+impl Config {
+    pub fn idle_pause(&self) -> Option<Duration> {
+        (self.idle_pause_secs > 0).then(|| Duration::from_secs(self.idle_pause_secs))
+    }
+
+    // One layer of settings over another, such as the command line's over
+    // the settings file's: every field `override_` changed from the default
+    // wins, and the rest come from `base`. A layer can't set a field back to
+    // its default over a lower one that changed it.
+    pub fn merge(base: Config, override_: Config) -> Config {
+        let Config {
+            theme,
+            player2_keys,
+            timings,
+            lang,
+            time_style,
+            sound,
+            key_repeat,
+            idle_pause_secs,
+            leaderboard,
+            assist,
+            tile_labels,
+            emoji_tiles,
+            emoji_labels,
+            shuffle_intro,
+        } = override_;
+        let default = Config::default();
+        Config {
+            theme: layer(base.theme, theme, default.theme),
+            player2_keys: layer(base.player2_keys, player2_keys, default.player2_keys),
+            timings: Timings::merge(base.timings, timings),
+            lang: layer(base.lang, lang, default.lang),
+            time_style: layer(base.time_style, time_style, default.time_style),
+            sound: layer(base.sound, sound, default.sound),
+            key_repeat: layer(base.key_repeat, key_repeat, default.key_repeat),
+            idle_pause_secs: layer(
+                base.idle_pause_secs,
+                idle_pause_secs,
+                default.idle_pause_secs,
+            ),
+            leaderboard: layer(base.leaderboard, leaderboard, default.leaderboard),
+            assist: layer(base.assist, assist, default.assist),
+            tile_labels: layer(base.tile_labels, tile_labels, default.tile_labels),
+            emoji_tiles: layer(base.emoji_tiles, emoji_tiles, default.emoji_tiles),
+            emoji_labels: layer(base.emoji_labels, emoji_labels, default.emoji_labels),
+            shuffle_intro: layer(base.shuffle_intro, shuffle_intro, default.shuffle_intro),
+        }
+    }
+
+    // The settings file's keys from TILEGAME_ variables, such as
+    // TILEGAME_THEME=ocean; bad values are logged and left at the default
+    pub fn from_env() -> Config {
+        let mut config = Config::default();
+        for problem in settings::apply_env(&mut config, |name| env::var(name).ok()) {
+            log::warn!("{}", problem);
+        }
+        config
+    }
+
+    // Every rule a config has to keep, all checked, so everything wrong can
+    // be shown at once
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = self.timings.errors();
+        errors.extend(self.player2_keys.errors());
+        for (key, labels) in [
+            (
+                "tile_labels",
+                self.tile_labels.as_deref().unwrap_or_default(),
+            ),
+            ("emoji_labels", &self.emoji_labels[..]),
+        ] {
+            errors.extend(
+                labels
+                    .iter()
+                    .filter(|label| !label_fits(label))
+                    .map(|label| ConfigError::LabelLength {
+                        key,
+                        label: label.clone(),
+                    }),
+            );
+        }
+        if self.emoji_labels.is_empty() {
+            errors.push(ConfigError::NoEmoji);
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    // The labels have to cover every tile of the board, no more and no fewer.
+    // Emoji only need enough; the rest of the set goes unused.
+    pub fn check_tile_labels(&self, tiles: usize) -> Result<(), String> {
+        if self.emoji_tiles && self.emoji_labels.len() < tiles {
+            return Err(format!(
+                "--emoji has {} emoji but the board has {} tiles",
+                self.emoji_labels.len(),
+                tiles
+            ));
+        }
+        match &self.tile_labels {
+            Some(labels) if labels.len() != tiles => Err(format!(
+                "tile_labels has {} labels but the board has {} tiles",
+                labels.len(),
+                tiles
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// The emoji --emoji draws unless the settings give others: colored squares,
+// then circles and hearts, enough for a 5x5 board
+pub const DEFAULT_EMOJI: [&str; 24] = [
+    "🟦", "🟩", "🟨", "🟧", "🟥", "🟪", "🟫", "⬛", "🔵", "🟢", "🟡", "🟠", "🔴", "🟣", "🟤", "⚫",
+    "💙", "💚", "💛", "🧡", "💜", "🤎", "🖤", "🤍",
+];
+
+// A broken rule found by Config::validate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    OutOfRange {
+        key: &'static str,
+        value: u64,
+        min: u64,
+        max: u64,
+    },
+    ReservedKey(char),  // A move key that's also a command
+    DuplicateKey(char), // One key for two moves
+    LabelLength {
+        key: &'static str,
+        label: String,
+    },
+    NoEmoji, // An empty emoji_labels, leaving --emoji nothing to draw
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::OutOfRange {
+                key,
+                value,
+                min: 0,
+                max,
+            } => write!(f, "{} must be at most {}, not {}", key, max, value),
+            ConfigError::OutOfRange {
+                key,
+                value,
+                min,
+                max,
+            } => write!(f, "{} must be from {} to {}, not {}", key, min, max, value),
+            ConfigError::ReservedKey(key) => {
+                write!(f, "'{}' is already used by another command", key)
+            }
+            ConfigError::DuplicateKey(key) => write!(f, "'{}' is bound more than once", key),
+            ConfigError::LabelLength { key, label } => {
+                write!(f, "{}: '{}' isn't 1 to 3 characters", key, label)
+            }
+            ConfigError::NoEmoji => write!(f, "emoji_labels needs at least one emoji"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn label_fits(label: &str) -> bool {
+    (1..=3).contains(&label.chars().count())
+}
+
+// Tile labels as the settings file has them, a JSON list such as
+// ["A", "B", "C"], each of 1 to 3 characters
+pub fn parse_tile_labels(s: &str) -> Result<Vec<String>, String> {
+    let labels: Vec<String> = serde_json::from_str(s)
+        .map_err(|_| format!("'{}' isn't a list of labels like [\"A\", \"B\"]", s))?;
+    match labels.iter().find(|label| !label_fits(label)) {
+        Some(label) => Err(format!("'{}' isn't 1 to 3 characters", label)),
+        None => Ok(labels),
+    }
+}
+
+// How long things take on screen, in milliseconds. The tick is how often the
+// event loop wakes up without input; the rest are animations, which --speed
+// scales and reduced motion turns off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    pub tick_ms: u64,
+    pub slide_ms: u64,          // A tile sliding into the blank
+    pub autosolve_step_ms: u64, // Between moves when a sequence plays itself
+    pub celebration_ms: u64,    // How long the solved board stays up
+    pub shuffle_step_ms: u64,   // Between moves of the shuffle intro
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            tick_ms: 100,
+            slide_ms: 80,
+            autosolve_step_ms: 300,
+            celebration_ms: 2_000,
+            shuffle_step_ms: 8,
+        }
+    }
+}
+
+impl Timings {
+    // Config::merge, a timing at a time
+    pub fn merge(base: Timings, override_: Timings) -> Timings {
+        let default = Timings::default();
+        Timings {
+            tick_ms: layer(base.tick_ms, override_.tick_ms, default.tick_ms),
+            slide_ms: layer(base.slide_ms, override_.slide_ms, default.slide_ms),
+            autosolve_step_ms: layer(
+                base.autosolve_step_ms,
+                override_.autosolve_step_ms,
+                default.autosolve_step_ms,
+            ),
+            celebration_ms: layer(
+                base.celebration_ms,
+                override_.celebration_ms,
+                default.celebration_ms,
+            ),
+            shuffle_step_ms: layer(
+                base.shuffle_step_ms,
+                override_.shuffle_step_ms,
+                default.shuffle_step_ms,
+            ),
+        }
+    }
+
+    // The first problem only, as settings reset all the timings on any
+    pub fn validate(&self) -> Result<(), String> {
+        match self.errors().first() {
+            Some(err) => Err(err.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    fn errors(&self) -> Vec<ConfigError> {
+        let ranges = [
+            ("tick_ms", self.tick_ms, 1, MAX_TICK_MS),
+            ("slide_ms", self.slide_ms, 0, MAX_ANIMATION_MS),
+            (
+                "autosolve_step_ms",
+                self.autosolve_step_ms,
+                0,
+                MAX_ANIMATION_MS,
+            ),
+            ("celebration_ms", self.celebration_ms, 0, MAX_ANIMATION_MS),
+            ("shuffle_step_ms", self.shuffle_step_ms, 0, MAX_ANIMATION_MS),
+        ];
+        ranges
+            .into_iter()
+            .filter(|&(_, value, min, max)| !(min..=max).contains(&value))
+            .map(|(key, value, min, max)| ConfigError::OutOfRange {
+                key,
+                value,
+                min,
+                max,
+            })
+            .collect()
+    }
+
+    // Every animation runs `speed` times as fast; the tick is left alone
+    pub fn with_speed(self, speed: f64) -> Result<Self, String> {
+        if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+            return Err(format!(
+                "speed must be from {} to {}, not {}",
+                MIN_SPEED, MAX_SPEED, speed
+            ));
+        }
+        let scale = |ms: u64| (ms as f64 / speed).round() as u64;
+        Ok(Self {
+            slide_ms: scale(self.slide_ms),
+            autosolve_step_ms: scale(self.autosolve_step_ms),
+            celebration_ms: scale(self.celebration_ms),
+            shuffle_step_ms: scale(self.shuffle_step_ms),
+            ..self
+        })
+    }
+
+    pub fn reduced_motion(self) -> Self {
+        Self {
+            slide_ms: 0,
+            autosolve_step_ms: 0,
+            celebration_ms: 0,
+            shuffle_step_ms: 0,
+            ..self
+        }
+    }
+
+    pub fn tick(&self) -> Duration {
+        Duration::from_millis(self.tick_ms)
+    }
+
+    pub fn slide(&self) -> Duration {
+        Duration::from_millis(self.slide_ms)
+    }
+
+    pub fn autosolve_step(&self) -> Duration {
+        Duration::from_millis(self.autosolve_step_ms)
+    }
+
+    pub fn celebration(&self) -> Duration {
+        Duration::from_millis(self.celebration_ms)
+    }
+
+    pub fn shuffle_step(&self) -> Duration {
+        Duration::from_millis(self.shuffle_step_ms)
+    }
+}
+
+// Feedback played through the terminal bell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    Move,
+    Invalid, // A key that didn't move a tile
+    Win,
+}
+
+impl Cue {
+    pub const ALL: [Cue; 3] = [Cue::Move, Cue::Invalid, Cue::Win];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Cue::Move => "move",
+            Cue::Invalid => "invalid",
+            Cue::Win => "win",
+        }
+    }
+}
+
+// Which cues play, all off by default. Written as a comma-separated list of
+// cue names, or "all" or "off".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sound {
+    pub moves: bool,
+    pub invalid: bool,
+    pub win: bool,
+}
+
+impl Sound {
+    pub fn plays(&self, cue: Cue) -> bool {
+        match cue {
+            Cue::Move => self.moves,
+            Cue::Invalid => self.invalid,
+            Cue::Win => self.win,
+        }
+    }
+
+    pub fn is_off(&self) -> bool {
+        !Cue::ALL.into_iter().any(|cue| self.plays(cue))
+    }
+}
+
+impl fmt::Display for Sound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cues: Vec<_> = Cue::ALL
+            .into_iter()
+            .filter(|&cue| self.plays(cue))
+            .map(Cue::name)
+            .collect();
+        match cues.len() {
+            0 => f.write_str("off"),
+            n if n == Cue::ALL.len() => f.write_str("all"),
+            _ => f.write_str(&cues.join(",")),
+        }
+    }
+}
+
+impl FromStr for Sound {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut sound = Sound::default();
+        for name in s.split(',').map(str::trim) {
+            match name {
+                "off" => {}
+                "all" => {
+                    sound = Sound {
+                        moves: true,
+                        invalid: true,
+                        win: true,
+                    }
+                }
+                "move" => sound.moves = true,
+                "invalid" => sound.invalid = true,
+                "win" => sound.win = true,
+                _ => {
+                    return Err(format!(
+                        "unknown cue '{}', expected move, invalid, win, all or off",
+                        name
+                    ))
+                }
+            }
+        }
+        Ok(sound)
+    }
+}
+
+// Character keys for the four moves, written as "up down left right" in one
+// string, e.g. "wsad"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBindings {
+    pub up: char,
+    pub down: char,
+    pub left: char,
+    pub right: char,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            up: 'w',
+            down: 's',
+            left: 'a',
+            right: 'd',
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn direction_for(&self, key: char) -> Option<Direction> {
+        match key {
+            _ if key == self.up => Some(Direction::Up),
+            _ if key == self.down => Some(Direction::Down),
+            _ if key == self.left => Some(Direction::Left),
+            _ if key == self.right => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    // Digits are reserved too, as they move a tile by its number
+    fn errors(&self) -> Vec<ConfigError> {
+        let keys = [self.up, self.down, self.left, self.right];
+        let mut errors = Vec::new();
+        for (i, &key) in keys.iter().enumerate() {
+            let error = match key {
+                _ if RESERVED_KEYS.contains(&key) || key.is_ascii_digit() => {
+                    ConfigError::ReservedKey(key)
+                }
+                _ if keys[..i].contains(&key) => ConfigError::DuplicateKey(key),
+                _ => continue,
+            };
+            if !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+        errors
+    }
+}
+
+impl fmt::Display for KeyBindings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}{}", self.up, self.down, self.left, self.right)
+    }
+}
+
+impl FromStr for KeyBindings {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let keys = s.chars().collect::<Vec<_>>();
+        let [up, down, left, right] = keys[..] else {
+            return Err(format!(
+                "expected 4 keys for up, down, left and right, found {}",
+                keys.len()
+            ));
+        };
+
+        let bindings = Self {
+            up,
+            down,
+            left,
+            right,
+        };
+        match bindings.errors().first() {
+            Some(err) => Err(err.to_string()),
+            None => Ok(bindings),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_bindings() {
+        let keys: KeyBindings = "ikjl".parse().unwrap();
+        assert_eq!(keys.direction_for('i'), Some(Direction::Up));
+        assert_eq!(keys.direction_for('l'), Some(Direction::Right));
+        assert_eq!(keys.direction_for('w'), None);
+        assert_eq!(keys.to_string(), "ikjl");
+    }
+
+    #[test]
+    fn test_timings_validation() {
+        assert_eq!(Timings::default().validate(), Ok(()));
+        let zero_tick = Timings {
+            tick_ms: 0,
+            ..Timings::default()
+        };
+        assert!(zero_tick.validate().is_err());
+        let slow_win = Timings {
+            celebration_ms: 60_000,
+            ..Timings::default()
+        };
+        assert!(slow_win.validate().unwrap_err().contains("celebration_ms"));
+    }
+
+    #[test]
+    fn test_timings_speed_and_reduced_motion() {
+        let fast = Timings::default().with_speed(2.0).unwrap();
+        assert_eq!(fast.tick_ms, 100);
+        assert_eq!(fast.autosolve_step_ms, 150);
+        assert_eq!(fast.celebration_ms, 1_000);
+        assert_eq!(Timings::default().with_speed(0.5).unwrap().slide_ms, 160);
+        assert!(Timings::default().with_speed(8.0).is_err());
+        assert!(Timings::default().with_speed(0.0).is_err());
+
+        let still = Timings::default().reduced_motion();
+        assert_eq!(still.tick_ms, 100);
+        assert_eq!(
+            (
+                still.slide_ms,
+                still.autosolve_step_ms,
+                still.celebration_ms,
+                still.shuffle_step_ms
+            ),
+            (0, 0, 0, 0)
+        );
+        assert_eq!(still.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_sound() {
+        assert!(Sound::default().is_off());
+        assert!("off".parse::<Sound>().unwrap().is_off());
+
+        let sound: Sound = "invalid, win".parse().unwrap();
+        assert!(!sound.plays(Cue::Move));
+        assert!(sound.plays(Cue::Invalid) && sound.plays(Cue::Win));
+        assert!(Cue::ALL
+            .into_iter()
+            .all(|cue| "all".parse::<Sound>().unwrap().plays(cue)));
+        assert!("beep".parse::<Sound>().is_err());
+        for text in ["off", "invalid,win", "all"] {
+            assert_eq!(text.parse::<Sound>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_reject_bad_key_bindings() {
+        assert!("wsa".parse::<KeyBindings>().is_err());
+        assert!("wwad".parse::<KeyBindings>().is_err());
+        assert!("wsaq".parse::<KeyBindings>().is_err());
+        assert!("8246".parse::<KeyBindings>().is_err());
+    }
+
+    #[test]
+    fn test_parse_tile_labels() {
+        assert_eq!(
+            parse_tile_labels(r#"["A", "Bee", "🐶"]"#),
+            Ok(vec!["A".to_string(), "Bee".to_string(), "🐶".to_string()])
+        );
+        assert!(parse_tile_labels(r#"["A", ""]"#).is_err());
+        assert!(parse_tile_labels(r#"["Four"]"#).is_err());
+        assert!(parse_tile_labels("A B C").is_err());
+
+        let config = Config {
+            tile_labels: Some(vec!["A".to_string(); 8]),
+            ..Config::default()
+        };
+        assert_eq!(config.check_tile_labels(8), Ok(()));
+        assert!(config.check_tile_labels(15).is_err());
+        assert_eq!(Config::default().check_tile_labels(15), Ok(()));
+    }
+
+    #[test]
+    fn test_merge() {
+        let defaults = Config::default();
+        let file = Config {
+            theme: Theme::Ocean,
+            key_repeat: 5,
+            assist: true,
+            timings: Timings {
+                tick_ms: 50,
+                slide_ms: 120,
+                ..Timings::default()
+            },
+            ..Config::default()
+        };
+        let env = Config {
+            theme: Theme::Mono,
+            key_repeat: 7,
+            ..Config::default()
+        };
+        let cli = Config {
+            key_repeat: 0,
+            emoji_tiles: true,
+            timings: Timings {
+                slide_ms: 40,
+                ..Timings::default()
+            },
+            ..Config::default()
+        };
+        let config = Config::merge(
+            Config::merge(defaults.clone(), file),
+            Config::merge(env, cli),
+        );
+        // The command line over the environment over the file
+        assert_eq!(config.key_repeat, 0);
+        assert_eq!(config.theme, Theme::Mono);
+        assert!(config.assist && config.emoji_tiles);
+        assert_eq!(config.timings.tick_ms, 50);
+        assert_eq!(config.timings.slide_ms, 40);
+        assert_eq!(config.idle_pause_secs, defaults.idle_pause_secs);
+
+        // Nothing changed is nothing lost
+        assert_eq!(Config::merge(config.clone(), Config::default()), config);
+        assert_eq!(Config::merge(Config::default(), config.clone()), config);
+    }
+
+    #[test]
+    fn test_validate_default() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_timings() {
+        let config = Config {
+            timings: Timings {
+                tick_ms: 0,
+                slide_ms: 20_000,
+                ..Timings::default()
+            },
+            ..Config::default()
+        };
+        let errors = config.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                ConfigError::OutOfRange {
+                    key: "tick_ms",
+                    value: 0,
+                    min: 1,
+                    max: MAX_TICK_MS
+                },
+                ConfigError::OutOfRange {
+                    key: "slide_ms",
+                    value: 20_000,
+                    min: 0,
+                    max: MAX_ANIMATION_MS
+                },
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "tick_ms must be from 1 to 1000, not 0"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "slide_ms must be at most 10000, not 20000"
+        );
+    }
+
+    #[test]
+    fn test_validate_key_bindings() {
+        // Built directly, so nothing parsed them on the way in
+        let config = Config {
+            player2_keys: KeyBindings {
+                up: 'q',
+                down: 'x',
+                left: 'x',
+                right: '7',
+            },
+            ..Config::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(vec![
+                ConfigError::ReservedKey('q'),
+                ConfigError::DuplicateKey('x'),
+                ConfigError::ReservedKey('7'),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_labels() {
+        let config = Config {
+            tile_labels: Some(vec!["A".to_string(), "Four".to_string()]),
+            emoji_labels: vec![String::new()],
+            ..Config::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(vec![
+                ConfigError::LabelLength {
+                    key: "tile_labels",
+                    label: "Four".to_string()
+                },
+                ConfigError::LabelLength {
+                    key: "emoji_labels",
+                    label: String::new()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_no_emoji() {
+        let config = Config {
+            emoji_labels: Vec::new(),
+            ..Config::default()
+        };
+        assert_eq!(config.validate(), Err(vec![ConfigError::NoEmoji]));
+    }
+}
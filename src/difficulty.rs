@@ -0,0 +1,422 @@
+use crate::{
+    board::{Board, Direction, Tile},
+    distance_table, solver,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
+
+// The depth search gives up after this many levels or expanded boards,
+// whichever comes first, so the estimate stays cheap on large boards
+const DEPTH_SEARCH_MAX_LEVELS: u32 = 20;
+const DEPTH_SEARCH_MAX_NODES: usize = 2_000;
+
+// Without the optimal length a rating goes by the lower bound scaled by this
+// fraction, as that usually falls about a quarter short on 3x3 boards. See
+// test_rating_calibration.
+const LOWER_BOUND_SCALE: (u32, u32) = (4, 3);
+
+// Shuffles that don't land in the requested band are retried this many times
+const MAX_SHUFFLE_ATTEMPTS: usize = 20;
+
+// How much each metric contributes to the difficulty estimate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyWeights {
+    pub manhattan: u32,
+    pub inversions: u32,
+    pub depth: u32,
+}
+
+impl Default for DifficultyWeights {
+    fn default() -> Self {
+        Self {
+            manhattan: 1,
+            inversions: 1,
+            depth: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyLevel {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl DifficultyLevel {
+    pub fn from_estimate(estimate: u32) -> Self {
+        match estimate {
+            0..=19 => DifficultyLevel::Easy,
+            20..=50 => DifficultyLevel::Medium,
+            _ => DifficultyLevel::Hard,
+        }
+    }
+
+    pub fn shuffle_moves(self) -> usize {
+        match self {
+            DifficultyLevel::Easy => 8,
+            DifficultyLevel::Medium => 100,
+            DifficultyLevel::Hard => 400,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DifficultyLevel::Easy => "Easy",
+            DifficultyLevel::Medium => "Medium",
+            DifficultyLevel::Hard => "Hard",
+        }
+    }
+}
+
+// A rough feel for a board, from how many moves it takes per tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Brutal,
+}
+
+impl Rating {
+    pub const ALL: [Rating; 5] = [
+        Rating::Trivial,
+        Rating::Easy,
+        Rating::Medium,
+        Rating::Hard,
+        Rating::Brutal,
+    ];
+
+    // Bands in quarter moves per tile. On a 3x3 that's Trivial up to 3
+    // moves, Easy to 11, Medium to 19, Hard to 25 and Brutal from 26, the
+    // last few moves short of the 31 the hardest boards take.
+    pub fn for_moves(moves: u32, size: usize) -> Self {
+        let tiles = (size * size - 1).max(1) as u32;
+        match moves * 4 / tiles {
+            0..=1 => Rating::Trivial,
+            2..=5 => Rating::Easy,
+            6..=9 => Rating::Medium,
+            10..=12 => Rating::Hard,
+            _ => Rating::Brutal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rating::Trivial => "Trivial",
+            Rating::Easy => "Easy",
+            Rating::Medium => "Medium",
+            Rating::Hard => "Hard",
+            Rating::Brutal => "Brutal",
+        }
+    }
+}
+
+// The signals behind a Rating, all cheap enough to work out every frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifficultyRating {
+    pub rating: Rating,
+    pub manhattan: u32,
+    pub linear_conflicts: u32,
+    pub inversions: u32,
+    // The fewest moves the signals above allow, never more than the real
+    // number
+    pub lower_bound: u32,
+    // The real number, on boards where that's known for free
+    pub optimal: Option<u32>,
+}
+
+impl DifficultyRating {
+    pub fn of(board: &Board) -> Self {
+        Self::from_signals(board, known_optimal(board))
+    }
+
+    // Rated again by a solution length found some other way
+    pub fn with_optimal(self, moves: u32, size: usize) -> Self {
+        Self {
+            rating: Rating::for_moves(moves, size),
+            optimal: Some(moves),
+            ..self
+        }
+    }
+
+    // The optimal length when known, otherwise the lower bound
+    pub fn moves(&self) -> u32 {
+        self.optimal.unwrap_or(self.lower_bound)
+    }
+
+    fn from_signals(board: &Board, optimal: Option<u32>) -> Self {
+        let manhattan = board.manhattan_distance();
+        let linear_conflicts = board.linear_conflicts();
+        let inversions = board.count_inversions() as u32;
+        // Each conflict costs two moves past the Manhattan distance. Sliding
+        // a tile sideways keeps the reading order, while sliding it up or
+        // down passes it over size - 1 tiles and so fixes at most that many
+        // inversions.
+        let lower_bound =
+            (manhattan + 2 * linear_conflicts).max(inversions.div_ceil(board.size() as u32 - 1));
+        let expected = lower_bound * LOWER_BOUND_SCALE.0 / LOWER_BOUND_SCALE.1;
+        let rating = Rating::for_moves(optimal.unwrap_or(expected), board.size());
+        Self {
+            rating,
+            manhattan,
+            linear_conflicts,
+            inversions,
+            lower_bound,
+            optimal,
+        }
+    }
+}
+
+// A 2x2 has only twelve boards to search, and a 3x3 is a lookup once the
+// distance table is built; the rating never builds it. Neither knows about
+// locked tiles, so a board with any goes by its lower bound.
+fn known_optimal(board: &Board) -> Option<u32> {
+    if board.has_locked_tiles() {
+        return None;
+    }
+    match board.size() {
+        2 => solver::solve(board).ok().map(|moves| moves.len() as u32),
+        distance_table::TABLE_SIZE if distance_table::is_ready() => {
+            board.optimal_moves_to_solve().map(u32::from)
+        }
+        _ => None,
+    }
+}
+
+// Weighted sum of the Manhattan distance, the inversion count and a rough
+// lower bound on the solution length from a small breadth-first search
+pub fn estimate(board: &Board, weights: &DifficultyWeights) -> u32 {
+    weights.manhattan * board.manhattan_distance()
+        + weights.inversions * board.count_inversions() as u32
+        + weights.depth * depth_estimate(board)
+}
+
+// Depth of the goal if the bounded search finds it, otherwise the deepest
+// level that was fully explored
+fn depth_estimate(board: &Board) -> u32 {
+    let mut visited = HashSet::from([board.zobrist()]);
+    let mut frontier = vec![(board.clone(), board.zobrist())];
+    let goal = Board::goal_state(board.size());
+
+    for depth in 0..DEPTH_SEARCH_MAX_LEVELS {
+        if frontier.iter().any(|(board, _)| board.is_goal(&goal)) {
+            return depth;
+        }
+        if visited.len() > DEPTH_SEARCH_MAX_NODES {
+            return depth;
+        }
+
+        let mut next_frontier = Vec::new();
+        for (current, hash) in &frontier {
+            for (direction, next) in current.neighbor_states() {
+                let next_hash = current
+                    .zobrist_after_move(*hash, direction)
+                    .expect("neighbor_states only yields legal moves");
+                if visited.insert(next_hash) {
+                    next_frontier.push((next, next_hash));
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    DEPTH_SEARCH_MAX_LEVELS
+}
+
+// Shuffles a board of the given size until its estimate falls in the requested
+// band. Tiny boards can't reach the harder bands, so after a bounded number of
+// attempts the last shuffle is used as-is.
+pub fn shuffle_for(level: DifficultyLevel, size: usize, rng: &mut impl Rng) -> Board {
+    shuffle_with_scramble(level, size, rng).0
+}
+
+// Like shuffle_for, along with the moves that scramble the goal into it
+pub fn shuffle_with_scramble(
+    level: DifficultyLevel,
+    size: usize,
+    rng: &mut impl Rng,
+) -> (Board, Vec<Direction>) {
+    let mut board = Board::goal_state(size);
+    let mut scramble = Vec::new();
+
+    for _ in 0..MAX_SHUFFLE_ATTEMPTS {
+        board = Board::goal_state(size);
+        scramble = board.scramble_from_position(rng, level.shuffle_moves());
+        if DifficultyLevel::from_estimate(board.difficulty_score()) == level {
+            break;
+        }
+    }
+
+    (board, scramble)
+}
+
+// The locked-tile challenge: `locks` random tiles held in their solved
+// cells, and the rest shuffled around them. The shuffle is a walk from the
+// goal, so it can always be undone, even where the locks wall off part of
+// the board. The two tiles beside the blank's goal cell are never locked,
+// so the blank always has somewhere to go.
+pub fn locked_challenge(
+    level: DifficultyLevel,
+    size: usize,
+    locks: usize,
+    rng: &mut impl Rng,
+) -> Board {
+    let mut board = Board::goal_state(size);
+    let cells = size * size;
+    let beside_blank = [cells - 2, cells - 1 - size];
+    let mut candidates: Vec<Tile> = (0..cells - 1)
+        .filter(|pos| !beside_blank.contains(pos))
+        .map(|pos| pos as Tile + 1)
+        .collect();
+    candidates.shuffle(rng);
+    for &tile in candidates.iter().take(locks) {
+        board.lock_tile(tile);
+    }
+
+    // A dead end walled off by locks leaves only the way back
+    let mut previous = None;
+    for _ in 0..level.shuffle_moves() {
+        let Some(direction) = board
+            .random_valid_move(previous, rng)
+            .or_else(|| board.random_valid_move(None, rng))
+        else {
+            break;
+        };
+        board.move_tile(direction);
+        previous = Some(direction);
+    }
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_solved_board_estimate() {
+        assert_eq!(Board::new().difficulty_score(), 0);
+    }
+
+    #[test]
+    fn test_scrambled_board_estimate() {
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert!(board.difficulty_score() > 50);
+
+        let board = Board::from_random(4, &mut StdRng::seed_from_u64(3));
+        assert!(board.difficulty_score() > 0);
+    }
+
+    #[test]
+    fn test_weights() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let manhattan_only = DifficultyWeights {
+            manhattan: 1,
+            inversions: 0,
+            depth: 0,
+        };
+        assert_eq!(estimate(&board, &manhattan_only), 2);
+        assert_eq!(board.difficulty_score(), 2 + 2 + 2);
+    }
+
+    #[test]
+    fn test_rating() {
+        let rating = Board::goal_state(4).difficulty_estimate();
+        assert_eq!(rating.rating, Rating::Trivial);
+        assert_eq!((rating.lower_bound, rating.optimal), (0, None));
+
+        // 4 and 8 have swapped places, which costs a lot of inversions but
+        // no conflicts
+        let board: Board = "1 2 3 8 5 6 7 4 0".parse().unwrap();
+        let rating = DifficultyRating::from_signals(&board, None);
+        assert_eq!((rating.manhattan, rating.linear_conflicts), (4, 0));
+        assert_eq!(rating.inversions, 7);
+        assert_eq!(rating.lower_bound, 4);
+
+        let rating = rating.with_optimal(22, 3);
+        assert_eq!((rating.rating, rating.moves()), (Rating::Hard, 22));
+        assert_eq!(Board::goal_state(2).difficulty_estimate().optimal, Some(0));
+        assert_eq!(Rating::for_moves(80, 4), Rating::Brutal);
+    }
+
+    // Over a corpus of walks of every length, the rating taken from the
+    // cheap signals alone should track the real optimal length
+    #[test]
+    fn test_rating_calibration() {
+        let mut rng = StdRng::seed_from_u64(2024);
+        let mut by_rating = vec![Vec::new(); Rating::ALL.len()];
+        let mut close = 0;
+        for steps in 0..400 {
+            let mut board = Board::goal_state(3);
+            board.scramble_from_position(&mut rng, steps % 60);
+            let optimal = board.optimal_moves_to_solve().unwrap() as u32;
+            let rating = DifficultyRating::from_signals(&board, None);
+            assert!(rating.lower_bound <= optimal, "{:?}", board);
+            let exact = Rating::for_moves(optimal, 3);
+            if (rating.rating as usize).abs_diff(exact as usize) <= 1 {
+                close += 1;
+            }
+            by_rating[rating.rating as usize].push(optimal);
+        }
+        assert!(close >= 380, "only {} of 400 within a band", close);
+
+        // Every band turns up, each harder on average than the one before
+        let means = by_rating
+            .iter()
+            .map(|optimal| optimal.iter().sum::<u32>() as f64 / optimal.len() as f64)
+            .collect::<Vec<_>>();
+        assert!(means.iter().all(|mean| mean.is_finite()));
+        assert!(
+            means.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            means
+        );
+    }
+
+    #[test]
+    fn test_from_estimate() {
+        assert_eq!(DifficultyLevel::from_estimate(0), DifficultyLevel::Easy);
+        assert_eq!(DifficultyLevel::from_estimate(20), DifficultyLevel::Medium);
+        assert_eq!(DifficultyLevel::from_estimate(50), DifficultyLevel::Medium);
+        assert_eq!(DifficultyLevel::from_estimate(51), DifficultyLevel::Hard);
+    }
+
+    #[test]
+    fn test_shuffle_for_easy() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let board = shuffle_for(DifficultyLevel::Easy, 3, &mut rng);
+        assert!(board.difficulty_score() < 20);
+
+        let (board, scramble) =
+            shuffle_with_scramble(DifficultyLevel::Easy, 3, &mut StdRng::seed_from_u64(11));
+        assert_eq!(
+            board,
+            shuffle_for(DifficultyLevel::Easy, 3, &mut StdRng::seed_from_u64(11))
+        );
+        let mut replayed = Board::goal_state(3);
+        assert_eq!(replayed.apply_moves(&scramble), scramble.len());
+        assert_eq!(replayed, board);
+    }
+
+    #[test]
+    fn test_locked_challenge() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let board = locked_challenge(DifficultyLevel::Medium, 3, 2, &mut rng);
+        let locked = (0..9)
+            .filter(|&pos| board.is_locked(pos))
+            .collect::<Vec<_>>();
+        assert_eq!(locked.len(), 2);
+        assert!(!board.is_locked(5) && !board.is_locked(7));
+        // Locked tiles never left their solved cells
+        for pos in locked {
+            assert_eq!(board.tiles()[pos] as usize, pos + 1);
+        }
+
+        let moves = crate::solver::bfs_solve(&board).unwrap();
+        let mut solved = board.clone();
+        assert_eq!(solved.apply_moves(&moves), moves.len());
+        assert!(solved.is_goal(&Board::goal_state(3)));
+    }
+}
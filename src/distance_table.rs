@@ -0,0 +1,154 @@
+use crate::{
+    board::{Board, Direction},
+    fixed_board::FixedBoard3,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::OnceLock,
+};
+
+// The only size small enough to tabulate: a 3x3 has 9!/2 solvable boards,
+// while a 4x4 has over ten trillion
+pub const TABLE_SIZE: usize = 3;
+pub const SOLVABLE_3X3: usize = 181_440;
+
+// Start of a saved table, bumped whenever the layout changes
+const MAGIC: &[u8; 5] = b"TGDT1";
+const RECORD_LEN: usize = TABLE_SIZE * TABLE_SIZE + 1;
+
+static TABLE: OnceLock<HashMap<Board, u8>> = OnceLock::new();
+
+// Every solvable 3x3 board with its distance from solved, by a breadth-first
+// search outward from the goal. Moves are reversible, so the distance from
+// the goal to a board is also the distance back.
+pub fn compute() -> HashMap<Board, u8> {
+    let goal = FixedBoard3::goal();
+    let mut distances = HashMap::with_capacity(SOLVABLE_3X3);
+    distances.insert(goal, 0u8);
+    let mut frontier = VecDeque::from([goal]);
+
+    while let Some(board) = frontier.pop_front() {
+        let distance = distances[&board] + 1;
+        for (_, next) in board.successors() {
+            distances.entry(next).or_insert_with(|| {
+                frontier.push_back(next);
+                distance
+            });
+        }
+    }
+
+    distances
+        .into_iter()
+        .map(|(board, distance)| (Board::from(board), distance))
+        .collect()
+}
+
+// Makes `table` the one lookups use. Returns false if a table was already in
+// place, whether installed or computed, in which case `table` is dropped.
+pub fn install(table: HashMap<Board, u8>) -> bool {
+    TABLE.set(table).is_ok()
+}
+
+// Whether lookups are free yet. The solver only uses the table once it is,
+// so nothing pays for the search without asking for it.
+pub fn is_ready() -> bool {
+    TABLE.get().is_some()
+}
+
+// The magic bytes, then each board as Board::to_bytes followed by its distance
+pub fn to_bytes(table: &HashMap<Board, u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(MAGIC.len() + table.len() * RECORD_LEN);
+    bytes.extend_from_slice(MAGIC);
+    for (board, &distance) in table {
+        bytes.extend(board.to_bytes());
+        bytes.push(distance);
+    }
+    bytes
+}
+
+// None for anything that isn't a complete table, such as a file written by
+// another version or cut short
+pub fn from_bytes(bytes: &[u8]) -> Option<HashMap<Board, u8>> {
+    let records = bytes.strip_prefix(MAGIC)?;
+    if records.len() != SOLVABLE_3X3 * RECORD_LEN {
+        return None;
+    }
+
+    let mut table = HashMap::with_capacity(SOLVABLE_3X3);
+    for record in records.chunks_exact(RECORD_LEN) {
+        let (tiles, distance) = record.split_at(RECORD_LEN - 1);
+        let board = Board::from_bytes(tiles, TABLE_SIZE).ok()?;
+        table.insert(board, distance[0]);
+    }
+    (table.len() == SOLVABLE_3X3).then_some(table)
+}
+
+impl Board {
+    // The table of every solvable 3x3 board's distance from solved, computed
+    // the first time it's needed unless one was installed before
+    pub fn solved_distance_lookup() -> &'static HashMap<Board, u8> {
+        TABLE.get_or_init(|| {
+            log::debug!("computing the 3x3 distance table");
+            compute()
+        })
+    }
+
+    // The fewest moves that solve this board, or None for a board that isn't
+    // a solvable 3x3
+    pub fn optimal_moves_to_solve(&self) -> Option<u8> {
+        if self.size() != TABLE_SIZE {
+            return None;
+        }
+        Self::solved_distance_lookup().get(self).copied()
+    }
+}
+
+// A shortest solution read off the table, one step closer each move
+pub(crate) fn solve(board: &Board) -> Option<Vec<Direction>> {
+    let mut board = board.clone();
+    let mut distance = board.optimal_moves_to_solve()?;
+    let mut moves = Vec::with_capacity(distance as usize);
+    while distance > 0 {
+        let (direction, next) = board
+            .neighbor_states()
+            .into_iter()
+            .find(|(_, next)| next.optimal_moves_to_solve() == Some(distance - 1))?;
+        moves.push(direction);
+        board = next;
+        distance -= 1;
+    }
+    Some(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    #[test]
+    fn test_table() {
+        let table = Board::solved_distance_lookup();
+        assert!(is_ready());
+        assert_eq!(table.len(), SOLVABLE_3X3);
+        // The hardest 3x3 boards take 31 moves
+        assert_eq!(table.values().max(), Some(&31));
+
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(board.optimal_moves_to_solve(), Some(31));
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(board.optimal_moves_to_solve(), Some(2));
+        assert_eq!(solve(&board), solver::bfs_solve(&board));
+        assert_eq!(Board::goal_state(3).optimal_moves_to_solve(), Some(0));
+
+        let unsolvable: Board = "2 1 3 4 5 6 7 8 0".parse().unwrap();
+        assert_eq!(unsolvable.optimal_moves_to_solve(), None);
+        assert_eq!(Board::goal_state(4).optimal_moves_to_solve(), None);
+
+        let bytes = to_bytes(table);
+        assert_eq!(from_bytes(&bytes).as_ref(), Some(table));
+        assert_eq!(from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(from_bytes(b"TGDT0"), None);
+        // Already in place, so a second table is turned away
+        assert!(!install(HashMap::new()));
+    }
+}
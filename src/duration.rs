@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+// How a play time is written out. Every style truncates rather than rounds,
+// so the time shown never runs ahead of the clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "tui", derive(clap::ValueEnum))]
+pub enum TimeStyle {
+    // "2:13", with hours only once there are any: "1:02:13"
+    #[default]
+    Compact,
+    // Compact plus tenths of a second: "2:13.4"
+    Precise,
+    // Always with hours: "0:02:13"
+    Clock,
+    // Whole seconds: "133s"
+    Seconds,
+}
+
+pub fn format_duration(d: Duration, style: TimeStyle) -> String {
+    let secs = d.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let tenths = d.subsec_millis() / 100;
+
+    match style {
+        TimeStyle::Compact | TimeStyle::Precise => {
+            let mut time = match hours {
+                0 => format!("{}:{:02}", minutes, seconds),
+                _ => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+            };
+            if style == TimeStyle::Precise {
+                time += &format!(".{}", tenths);
+            }
+            time
+        }
+        TimeStyle::Clock => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+        TimeStyle::Seconds => format!("{}s", secs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all(d: Duration) -> [String; 4] {
+        [
+            TimeStyle::Compact,
+            TimeStyle::Precise,
+            TimeStyle::Clock,
+            TimeStyle::Seconds,
+        ]
+        .map(|style| format_duration(d, style))
+    }
+
+    #[test]
+    fn test_zero() {
+        assert_eq!(all(Duration::ZERO), ["0:00", "0:00.0", "0:00:00", "0s"]);
+    }
+
+    #[test]
+    fn test_just_under_a_minute() {
+        assert_eq!(
+            all(Duration::from_millis(59_950)),
+            ["0:59", "0:59.9", "0:00:59", "59s"]
+        );
+    }
+
+    #[test]
+    fn test_hours_appear_when_needed() {
+        assert_eq!(
+            all(Duration::from_secs(3600)),
+            ["1:00:00", "1:00:00.0", "1:00:00", "3600s"]
+        );
+        assert_eq!(
+            all(Duration::from_millis(3_599_999)),
+            ["59:59", "59:59.9", "0:59:59", "3599s"]
+        );
+        assert_eq!(
+            all(Duration::from_millis(133_400)),
+            ["2:13", "2:13.4", "0:02:13", "133s"]
+        );
+    }
+}
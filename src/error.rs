@@ -0,0 +1,220 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+use tile_game_rs::{
+    board::{Board, BoardError, ParseError},
+    puzzle_text::PuzzleTextError,
+    solver::SolveError,
+};
+
+// Process exit codes, also listed in --help
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_PANIC: i32 = 1;
+pub const EXIT_NOT_SOLVED: i32 = 2;
+// Not 2: that already meant a game or search that stopped short, and
+// scripts should be able to tell that apart from a board no search solves
+pub const EXIT_UNSOLVABLE: i32 = 3;
+pub const EXIT_INVALID_INPUT: i32 = 4;
+pub const EXIT_IO: i32 = 5;
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+    0  Solved, or quit from interactive play
+    1  Unexpected internal error
+    2  Finished without solving (pipe, replay, solve --time-limit)
+    3  The board can't be solved
+    4  Invalid arguments, board state or moves
+    5  I/O or terminal error
+  130  Interrupted by SIGINT outside interactive play, or twice during it";
+
+// How a command finished when nothing went wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Solved,
+    Quit,
+    NotSolved,
+    Done, // Commands that don't involve playing, like catalog
+}
+
+impl Outcome {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Solved | Outcome::Quit | Outcome::Done => EXIT_OK,
+            Outcome::NotSolved => EXIT_NOT_SOLVED,
+        }
+    }
+}
+
+// Everything that can stop a command, each with enough context for a
+// one-line message. Library errors convert into the matching variant.
+#[derive(Debug)]
+pub enum GameError {
+    Io(io::Error), // Terminal, stdin or stdout
+    InvalidBoard(BoardError),
+    Unsolvable(Board),
+    Parse(ParseError),
+    Config(String), // Options that can't be used together or in this environment
+    Save {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Read {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Puzzle {
+        path: PathBuf,
+        source: PuzzleTextError,
+    },
+    Solver(SolveError),
+    #[cfg(feature = "image")]
+    Image {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+}
+
+impl GameError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GameError::Io(_) | GameError::Save { .. } | GameError::Read { .. } => EXIT_IO,
+            GameError::InvalidBoard(_)
+            | GameError::Parse(_)
+            | GameError::Config(_)
+            | GameError::Puzzle { .. } => EXIT_INVALID_INPUT,
+            GameError::Unsolvable(_) | GameError::Solver(SolveError::Unsolvable) => EXIT_UNSOLVABLE,
+            GameError::Solver(SolveError::TooLarge { .. } | SolveError::NoPatternDb) => {
+                EXIT_INVALID_INPUT
+            }
+            GameError::Solver(SolveError::Cancelled) => EXIT_INTERRUPTED,
+            // Gave up before finishing, on a limit
+            GameError::Solver(SolveError::TimedOut | SolveError::OutOfMemory) => EXIT_NOT_SOLVED,
+            #[cfg(feature = "image")]
+            GameError::Image { source, .. } => match source {
+                image::ImageError::IoError(_) => EXIT_IO,
+                _ => EXIT_INVALID_INPUT,
+            },
+        }
+    }
+
+    pub fn save(path: &Path, source: io::Error) -> Self {
+        GameError::Save {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Io(err) => write!(f, "terminal I/O failed: {}", err),
+            GameError::InvalidBoard(err) => write!(f, "invalid board: {}", err),
+            GameError::Unsolvable(board) => {
+                let tiles = board
+                    .tiles()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                write!(f, "the board {} cannot be solved", tiles.join(","))
+            }
+            GameError::Parse(err) => write!(f, "invalid moves: {}", err),
+            GameError::Config(message) => write!(f, "{}", message),
+            GameError::Save { path, source } => {
+                write!(f, "couldn't write {}: {}", path.display(), source)
+            }
+            GameError::Read { path, source } => {
+                write!(f, "couldn't read {}: {}", path.display(), source)
+            }
+            GameError::Puzzle { path, source } => write!(f, "{}: {}", path.display(), source),
+            GameError::Solver(err) => write!(f, "{}", err),
+            #[cfg(feature = "image")]
+            GameError::Image { path, source } => {
+                write!(f, "couldn't use {} as a puzzle: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameError::Io(err)
+            | GameError::Save { source: err, .. }
+            | GameError::Read { source: err, .. } => Some(err),
+            GameError::Puzzle { source, .. } => Some(source),
+            GameError::InvalidBoard(err) => Some(err),
+            GameError::Parse(err) => Some(err),
+            GameError::Solver(err) => Some(err),
+            #[cfg(feature = "image")]
+            GameError::Image { source, .. } => Some(source),
+            GameError::Unsolvable(_) | GameError::Config(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for GameError {
+    fn from(err: io::Error) -> Self {
+        GameError::Io(err)
+    }
+}
+
+impl From<BoardError> for GameError {
+    fn from(err: BoardError) -> Self {
+        GameError::InvalidBoard(err)
+    }
+}
+
+impl From<ParseError> for GameError {
+    fn from(err: ParseError) -> Self {
+        GameError::Parse(err)
+    }
+}
+
+impl From<SolveError> for GameError {
+    fn from(err: SolveError) -> Self {
+        GameError::Solver(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tile_game_rs::puzzle_text::Puzzle;
+
+    #[test]
+    fn test_messages_and_codes() {
+        let board: Board = "2 1 3 0".parse().unwrap();
+        let err = GameError::Unsolvable(board);
+        assert_eq!(err.to_string(), "the board 2,1,3,0 cannot be solved");
+        assert_eq!(err.exit_code(), EXIT_UNSOLVABLE);
+
+        let err = GameError::save(
+            Path::new("out.json"),
+            io::Error::from(io::ErrorKind::PermissionDenied),
+        );
+        assert_eq!(
+            err.to_string(),
+            "couldn't write out.json: permission denied"
+        );
+        assert_eq!(err.exit_code(), EXIT_IO);
+
+        let source = "puzzle 2x2\n1 2\n2 0\n".parse::<Puzzle>().unwrap_err();
+        let err = GameError::Puzzle {
+            path: PathBuf::from("board.txt"),
+            source,
+        };
+        assert_eq!(
+            err.to_string(),
+            "board.txt: line 3, column 1: tile 2 appears more than once"
+        );
+        assert_eq!(err.exit_code(), EXIT_INVALID_INPUT);
+
+        let err = GameError::from(SolveError::TooLarge { size: 4 });
+        assert_eq!(err.exit_code(), EXIT_INVALID_INPUT);
+        let err = GameError::from(SolveError::TimedOut);
+        assert_eq!(err.exit_code(), EXIT_NOT_SOLVED);
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(feature = "tui")]
+use crate::{board::Board, snapshot};
+use crate::{board::Direction, game::GameSession};
+#[cfg(feature = "tui")]
+use serde::Serialize;
+use std::{cell::RefCell, fmt, rc::Rc};
+#[cfg(feature = "tui")]
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+// Something that happened during a game, reported to the session's handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    // Only moves that actually moved a tile
+    Move(Direction),
+    Hint(Direction),
+    Win,
+    // The game ended without being solved
+    Quit,
+    // Given up for a new game without leaving, like a change of size
+    Abandon,
+}
+
+impl GameEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameEvent::Move(_) => "move",
+            GameEvent::Hint(_) => "hint",
+            GameEvent::Win => "win",
+            GameEvent::Quit => "quit",
+            GameEvent::Abandon => "abandon",
+        }
+    }
+}
+
+// Called after the event has already changed the session
+pub trait GameEventHandler {
+    fn handle(&mut self, event: GameEvent, session: &GameSession);
+}
+
+// Shared so a checkpointed or cloned session reports to the same handler
+#[derive(Clone)]
+pub struct EventHandle(Rc<RefCell<dyn GameEventHandler>>);
+
+impl EventHandle {
+    pub fn new(handler: impl GameEventHandler + 'static) -> Self {
+        Self(Rc::new(RefCell::new(handler)))
+    }
+
+    pub fn handle(&self, event: GameEvent, session: &GameSession) {
+        self.0.borrow_mut().handle(event, session)
+    }
+}
+
+impl fmt::Debug for EventHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventHandle")
+    }
+}
+
+impl PartialEq for EventHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for EventHandle {}
+
+// One line of a --event-log file
+#[cfg(feature = "tui")]
+#[derive(Debug, Serialize)]
+struct EventRecord<'a> {
+    timestamp: String,
+    event_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    direction: Option<Direction>,
+    board_state: &'a Board,
+    move_count: u32,
+}
+
+// Appends every event as a line of JSON. Logging is best effort: a failed
+// write is dropped rather than interrupting the game. Only built for the
+// terminal game, as it writes files and reads the system clock.
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub struct JsonlLogger {
+    file: BufWriter<File>,
+}
+
+#[cfg(feature = "tui")]
+impl JsonlLogger {
+    pub fn new(file: File) -> Self {
+        Self {
+            file: BufWriter::new(file),
+        }
+    }
+
+    pub fn append(path: &Path) -> io::Result<Self> {
+        let file = File::options().create(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+#[cfg(feature = "tui")]
+impl GameEventHandler for JsonlLogger {
+    fn handle(&mut self, event: GameEvent, session: &GameSession) {
+        let direction = match event {
+            GameEvent::Move(direction) | GameEvent::Hint(direction) => Some(direction),
+            GameEvent::Win | GameEvent::Quit | GameEvent::Abandon => None,
+        };
+        let record = EventRecord {
+            timestamp: snapshot::utc_timestamp(SystemTime::now()),
+            event_type: event.name(),
+            direction,
+            board_state: &session.board,
+            move_count: session.move_count,
+        };
+        if let Ok(json) = serde_json::to_string(&record) {
+            let _ = writeln!(self.file, "{}", json);
+            // Flushed every line so the log survives a crash
+            let _ = self.file.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Board, game::StartKind};
+
+    #[derive(Default)]
+    struct Recorder(Vec<&'static str>);
+
+    impl GameEventHandler for Rc<RefCell<Recorder>> {
+        fn handle(&mut self, event: GameEvent, _: &GameSession) {
+            self.borrow_mut().0.push(event.name());
+        }
+    }
+
+    #[test]
+    fn test_session_reports_moves_and_win() {
+        let recorder = Rc::new(RefCell::new(Recorder::default()));
+        let board: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+        let mut session = GameSession::new(board, StartKind::Custom, None)
+            .with_events(EventHandle::new(recorder.clone()));
+
+        // Nothing is below the blank, so the Up isn't reported
+        session.apply_moves(&Direction::parse_sequence("UL").unwrap());
+        assert!(session.is_won());
+        assert_eq!(recorder.borrow().0, ["move", "win"]);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    board::{Board, BoardError, Direction, Position, Tile},
+    zobrist,
+};
+
+// A board whose N tiles live inline in an array, so cloning and hashing it
+// never touches the heap. The solvers use it for the small sizes they can
+// actually search; everything else keeps using the dynamic Board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedBoard<const N: usize> {
+    tiles: [u8; N],
+    blank: u8,
+}
+
+pub type FixedBoard2 = FixedBoard<4>;
+pub type FixedBoard3 = FixedBoard<9>;
+pub type FixedBoard4 = FixedBoard<16>;
+
+impl<const N: usize> FixedBoard<N> {
+    pub const WIDTH: usize = N.isqrt();
+
+    pub fn goal() -> Self {
+        let mut tiles = [0; N];
+        for (pos, tile) in tiles.iter_mut().enumerate().take(N - 1) {
+            *tile = pos as u8 + 1;
+        }
+        FixedBoard {
+            tiles,
+            blank: N as u8 - 1,
+        }
+    }
+
+    pub fn tiles(&self) -> &[u8; N] {
+        &self.tiles
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::goal()
+    }
+
+    // Same rules as Board::move_tile: the direction is the way the tile moves
+    pub fn move_tile(&mut self, direction: Direction) -> bool {
+        match self.move_source(direction) {
+            Some(from) => {
+                self.tiles.swap(self.blank as usize, from);
+                self.blank = from as u8;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn move_source(&self, direction: Direction) -> Option<usize> {
+        let width = Self::WIDTH;
+        let blank = self.blank as usize;
+
+        match direction {
+            Direction::Up => Some(blank + width).filter(|&pos| pos < N),
+            Direction::Down => blank.checked_sub(width),
+            Direction::Left => Some(blank + 1).filter(|pos| pos % width != 0),
+            Direction::Right => blank.checked_sub(1).filter(|pos| pos % width != width - 1),
+        }
+    }
+
+    // Same keys as Board::zobrist, so equal boards hash equally in either form
+    pub fn zobrist(&self) -> u64 {
+        self.tiles
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (cell, &tile)| {
+                hash ^ zobrist::key(cell, tile as Tile)
+            })
+    }
+
+    pub fn zobrist_after_move(&self, hash: u64, direction: Direction) -> Option<u64> {
+        let from = self.move_source(direction)?;
+        let tile = self.tiles[from] as Tile;
+        Some(zobrist::after_swap(hash, self.blank as usize, from, tile))
+    }
+
+    // Every board one move away, in Direction::ALL order
+    pub fn successors(&self) -> impl Iterator<Item = (Direction, Self)> + '_ {
+        Direction::ALL.into_iter().filter_map(|direction| {
+            let mut next = *self;
+            next.move_tile(direction).then_some((direction, next))
+        })
+    }
+
+    pub fn manhattan_distance(&self) -> u32 {
+        let width = Self::WIDTH;
+
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile != 0)
+            .map(|(pos, &tile)| {
+                let goal = Board::goal_position_of(Tile::from(tile), width);
+                Position::from_index(pos, width).manhattan_distance_to(&goal)
+            })
+            .sum::<usize>() as u32
+    }
+}
+
+impl<const N: usize> TryFrom<&Board> for FixedBoard<N> {
+    type Error = BoardError;
+
+    fn try_from(board: &Board) -> Result<Self, Self::Error> {
+        if board.tiles().len() != N {
+            return Err(BoardError::WrongTileCount {
+                expected: N,
+                found: board.tiles().len(),
+            });
+        }
+
+        let mut tiles = [0; N];
+        for (slot, &tile) in tiles.iter_mut().zip(board.tiles()) {
+            *slot = tile as u8;
+        }
+        Ok(FixedBoard {
+            tiles,
+            blank: board.get_blank_position() as u8,
+        })
+    }
+}
+
+impl<const N: usize> From<FixedBoard<N>> for Board {
+    fn from(board: FixedBoard<N>) -> Self {
+        let tiles = board.tiles.iter().map(|&tile| tile as Tile).collect();
+        Board::from_tiles(tiles).expect("a FixedBoard always holds a valid board")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_goal_matches_board() {
+        assert_eq!(FixedBoard3::WIDTH, 3);
+        assert_eq!(FixedBoard4::WIDTH, 4);
+        assert_eq!(Board::from(FixedBoard3::goal()), Board::goal_state(3));
+        assert_eq!(Board::from(FixedBoard4::goal()), Board::goal_state(4));
+        assert!(FixedBoard2::goal().is_solved());
+    }
+
+    #[test]
+    fn test_moves_match_board() {
+        let mut board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        let mut fixed = FixedBoard3::try_from(&board).unwrap();
+        assert_eq!(fixed.manhattan_distance(), board.manhattan_distance());
+
+        for direction in "UDLRRUULLDDRUL".chars().filter_map(Direction::from_char) {
+            assert_eq!(fixed.move_tile(direction), board.move_tile(direction));
+            assert_eq!(Board::from(fixed), board);
+            assert_eq!(fixed.manhattan_distance(), board.manhattan_distance());
+        }
+    }
+
+    #[test]
+    fn test_zobrist_matches_board() {
+        let mut board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        let mut fixed = FixedBoard3::try_from(&board).unwrap();
+        let mut hash = fixed.zobrist();
+        assert_eq!(hash, board.zobrist());
+
+        for direction in "UDLRRUULLDDRUL".chars().filter_map(Direction::from_char) {
+            assert_eq!(
+                fixed.zobrist_after_move(hash, direction),
+                board.zobrist_after_move(hash, direction)
+            );
+            if let Some(next) = fixed.zobrist_after_move(hash, direction) {
+                hash = next;
+            }
+            fixed.move_tile(direction);
+            board.move_tile(direction);
+            assert_eq!(hash, fixed.zobrist());
+        }
+    }
+
+    #[test]
+    fn test_successors() {
+        let corner = FixedBoard3::goal();
+        let moves: Vec<_> = corner
+            .successors()
+            .map(|(direction, _)| direction)
+            .collect();
+        assert_eq!(moves, [Direction::Down, Direction::Right]);
+
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(
+            FixedBoard3::try_from(&board).unwrap().successors().count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_wrong_size_rejected() {
+        assert_eq!(
+            FixedBoard4::try_from(&Board::new()),
+            Err(BoardError::WrongTileCount {
+                expected: 16,
+                found: 9
+            })
+        );
+    }
+}
@@ -0,0 +1,1153 @@
+use crate::{
+    board::{Board, Direction, Rotation, RotationAxis, RotationDir, Tile},
+    clock::{Clock, TimeSource},
+    difficulty::{self, DifficultyLevel, DifficultyRating},
+    events::{EventHandle, GameEvent},
+    heatmap::Heatmap,
+    renderer::RendererHandle,
+    snapshot::{self, BoardSnapshot, Ghost},
+    solver,
+    stats::MoveRate,
+};
+use rand::Rng;
+use std::{
+    cell::OnceCell,
+    fmt, io,
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const STUCK_MOVES: u32 = 10;
+
+// A recording session keeps a snapshot every this many moves, and one on the win
+const SNAPSHOT_EVERY_MOVES: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartKind {
+    // A regular shuffle, random or seeded
+    Shuffled,
+    // Tiles supplied by the player with --state
+    Custom,
+    // --no-shuffle or --moves-from-solved, for development and screenshots
+    Debug,
+}
+
+// Why a session can't take its moves back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewindError {
+    // The move list doesn't undo this mode: a magnetic pull takes two
+    // entries, gravity's falls and rotations aren't slides of the blank, and
+    // two-player turns and per-player counts aren't kept per move
+    Unsupported(GameMode),
+}
+
+impl fmt::Display for RewindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewindError::Unsupported(mode) => {
+                write!(f, "moves can't be taken back in {:?} mode", mode)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RewindError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Classic,
+    // Two people share the keyboard and take turns, player 1 first
+    TwoPlayer { current_player: u8 },
+    // Whole rows and columns rotate instead of tiles sliding into the blank
+    Rotation,
+    // After every slide the tiles fall into the blank, except that the tile
+    // just slid holds on
+    Gravity,
+    // Every slide pulls the next tile in line along behind it
+    Magnetic,
+    // The edges of the board join up, so a tile can slide across them
+    Wraparound,
+}
+
+impl GameMode {
+    pub fn two_player() -> Self {
+        GameMode::TwoPlayer { current_player: 1 }
+    }
+
+    // Like difficulty::shuffle_with_scramble, with a scramble made of this
+    // mode's moves, so it can be played back to the goal
+    pub fn shuffle(
+        self,
+        level: DifficultyLevel,
+        size: usize,
+        rng: &mut impl Rng,
+    ) -> (Board, Vec<Direction>) {
+        match self {
+            // A walk of the mode's own moves, which can be played back. Few
+            // boards can be reached once every slide pulls, so a classic
+            // shuffle would rarely be one of them.
+            GameMode::Gravity | GameMode::Magnetic => {
+                let mut board = Board::goal_state(size);
+                let steps = level.shuffle_moves();
+                let scramble = match self {
+                    GameMode::Gravity => board.scramble_gravity(rng, steps),
+                    _ => board.scramble_magnetic(rng, steps),
+                };
+                (board, scramble)
+            }
+            _ => difficulty::shuffle_with_scramble(level, size, rng),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameSession {
+    pub board: Board,
+    pub initial_board: Board,
+    pub goal: Board,           // The arrangement that wins the game
+    pub moves: Vec<Direction>, // Only moves that actually moved a tile
+    pub move_count: u32,
+    pub hints_used: u32,
+    pub start: StartKind,
+    pub seed: Option<u64>,
+    pub scramble: Option<Vec<Direction>>, // Moves from the goal to initial_board, when known
+    pub mode: GameMode,
+    // Rotation mode's moves and scramble, which aren't slides for `moves`
+    // and `scramble`, and the tile whose row or column the arrows turn: the
+    // blank until another is picked
+    pub rotations: Vec<Rotation>,
+    pub rotation_scramble: Option<Vec<Rotation>>,
+    pub selected: Tile,
+    pub player_moves: [u32; 2], // Effective moves by each player in two-player mode
+    pub message: Option<String>, // Shown in the status area on the next render
+    pub key_help: Option<String>, // Instead of the usual keys, for screens with others
+    pub highlight: Option<Tile>, // Picked out on the board on the next render
+    pub pulled: Option<Tile>,   // What the last magnetic move pulled along
+    pub renderer: Option<RendererHandle>,
+    pub events: Option<EventHandle>,
+    pub snapshots: Vec<BoardSnapshot>,
+    pub ghost: Option<Rc<Ghost>>, // A past run raced on the same clock
+    pub move_rate: MoveRate,      // Only timed sessions record into it
+    pub show_stats: bool,         // Set by 'i' in the terminal UI
+    pub assist: bool,             // Set by 'g' in the terminal UI
+    pub hide_numbers: bool,       // Set by 'N', for the picture theme
+    pub attempt: u32,             // 1, then one more each time the puzzle is reset
+    par: OnceCell<Option<u32>>,
+    moves_since_progress: u32,
+    // Set by with_clock; sessions without it never read the time
+    clock: Option<(Clock, TimeSource)>,
+    clock_held: bool, // Until the first move, by hold_clock
+    // Set by with_snapshots: the wall-clock time since the Unix epoch, to
+    // stamp the snapshots with
+    recording: Option<TimeSource>,
+}
+
+impl GameSession {
+    pub fn new(board: Board, start: StartKind, seed: Option<u64>) -> Self {
+        Self {
+            initial_board: board.clone(),
+            goal: Board::goal_state(board.size()),
+            board,
+            moves: Vec::new(),
+            move_count: 0,
+            hints_used: 0,
+            start,
+            seed,
+            scramble: None,
+            mode: GameMode::Classic,
+            rotations: Vec::new(),
+            rotation_scramble: None,
+            selected: 0,
+            player_moves: [0; 2],
+            message: None,
+            key_help: None,
+            highlight: None,
+            pulled: None,
+            renderer: None,
+            events: None,
+            snapshots: Vec::new(),
+            ghost: None,
+            move_rate: MoveRate::default(),
+            show_stats: false,
+            assist: false,
+            hide_numbers: false,
+            attempt: 1,
+            par: OnceCell::new(),
+            moves_since_progress: 0,
+            clock: None,
+            clock_held: false,
+            recording: None,
+        }
+    }
+
+    pub fn with_scramble(mut self, scramble: Vec<Direction>) -> Self {
+        self.scramble = Some(scramble);
+        self
+    }
+
+    pub fn with_rotation_scramble(mut self, scramble: Vec<Rotation>) -> Self {
+        self.rotation_scramble = Some(scramble);
+        self
+    }
+
+    // The scramble for the starting board. One that wasn't recorded when the
+    // game was set up is worked out as the inverse of a solution, which only
+    // the boards the solver takes can have. Rotation mode's scramble is
+    // rotation_scramble, since the solver's slides aren't moves there.
+    pub fn find_scramble(&mut self) -> Option<&[Direction]> {
+        if self.mode == GameMode::Rotation {
+            return None;
+        }
+        if self.scramble.is_none() {
+            let solution = solver::solve(&self.initial_board).ok()?;
+            self.scramble = Some(Direction::invert_sequence(&solution));
+        }
+        self.scramble.as_deref()
+    }
+
+    // The way back to the goal by taking back every move made and then the
+    // scramble, less the moves that cancel out. A move off this path just
+    // puts its own opposite at the front. None when the scramble can't be
+    // found, or in rotation and gravity mode, where the rotations and falls
+    // aren't slides to take back, and in magnetic mode, where following it would
+    // pull other tiles along.
+    pub fn path_back(&mut self) -> Option<Vec<Direction>> {
+        if matches!(
+            self.mode,
+            GameMode::Rotation | GameMode::Gravity | GameMode::Magnetic
+        ) {
+            return None;
+        }
+        let mut path = Direction::invert_sequence(&self.moves);
+        path.extend(Direction::invert_sequence(self.find_scramble()?));
+        Some(Direction::cancel_sequence(&path))
+    }
+
+    pub fn with_mode(mut self, mode: GameMode) -> Self {
+        self.mode = mode;
+        let wraps = mode == GameMode::Wraparound;
+        self.board.set_wraparound(wraps);
+        self.initial_board.set_wraparound(wraps);
+        self
+    }
+
+    // Whose turn it is, or None outside two-player mode
+    pub fn current_player(&self) -> Option<u8> {
+        match self.mode {
+            GameMode::Classic
+            | GameMode::Rotation
+            | GameMode::Gravity
+            | GameMode::Magnetic
+            | GameMode::Wraparound => None,
+            GameMode::TwoPlayer { current_player } => Some(current_player),
+        }
+    }
+
+    // Times the game from now by `time`, starting over if it was timed
+    pub fn with_clock(mut self, time: TimeSource) -> Self {
+        self.clock = Some((Clock::start_at(time.now()), time));
+        self
+    }
+
+    // Races `ghost`, which only moves once with_clock times the game
+    pub fn with_ghost(mut self, ghost: Ghost) -> Self {
+        self.ghost = Some(Rc::new(ghost));
+        self
+    }
+
+    // Records snapshots as the game is played, stamped with the date by
+    // `wall` and timed by with_clock if it's given one
+    pub fn with_snapshots(mut self, wall: TimeSource) -> Self {
+        self.recording = Some(wall);
+        self
+    }
+
+    // Time since with_clock less any spent paused, or None for a session
+    // that isn't timed
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.clock
+            .as_ref()
+            .map(|(clock, time)| clock.elapsed_at(time.now()))
+    }
+
+    // How long the clock has stood still, for the result to own up to
+    pub fn paused_time(&self) -> Duration {
+        self.clock
+            .as_ref()
+            .map_or(Duration::ZERO, |(clock, time)| clock.paused_at(time.now()))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.clock
+            .as_ref()
+            .is_some_and(|(clock, _)| clock.is_paused())
+    }
+
+    // Both do nothing to a session that isn't timed
+    pub fn pause_clock(&mut self) {
+        if let Some((clock, time)) = &mut self.clock {
+            clock.pause_at(time.now());
+        }
+    }
+
+    pub fn resume_clock(&mut self) {
+        if let Some((clock, time)) = &mut self.clock {
+            clock.resume_at(time.now());
+        }
+    }
+
+    // Stands the clock at 0 until the first move, which starts it over, so
+    // the time before that isn't counted as play or as a pause
+    pub fn hold_clock(&mut self) {
+        if let Some((clock, time)) = &mut self.clock {
+            let now = time.now();
+            *clock = Clock::start_at(now);
+            clock.pause_at(now);
+            self.clock_held = true;
+        }
+    }
+
+    fn release_clock(&mut self) {
+        if std::mem::take(&mut self.clock_held) {
+            if let Some((clock, time)) = &mut self.clock {
+                *clock = Clock::start_at(time.now());
+            }
+        }
+    }
+
+    // A won game's time stays what it was at the winning move
+    fn stop_clock(&mut self) {
+        if let Some((clock, time)) = &mut self.clock {
+            clock.stop_at(time.now());
+        }
+    }
+
+    // The fewest moves that solve the starting board, where that's cheap to
+    // know; worked out on first use and kept for the game
+    pub fn par(&self) -> Option<u32> {
+        *self.par.get_or_init(|| {
+            let standard = self.goal == Board::goal_state(self.goal.size());
+            standard
+                .then(|| DifficultyRating::of(&self.initial_board).optimal)
+                .flatten()
+        })
+    }
+
+    // The current state, timed from with_clock (or 0 without it) and taken
+    // at `timestamp`
+    pub fn snapshot(&self, timestamp: SystemTime) -> BoardSnapshot {
+        BoardSnapshot {
+            board: self.board.clone(),
+            move_count: self.move_count,
+            elapsed_ms: self
+                .elapsed()
+                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+            hints_used: self.hints_used,
+            timestamp_utc: snapshot::utc_timestamp(timestamp),
+        }
+    }
+
+    // A fresh game on `board` that draws, reports and records like this one:
+    // same renderer, event handler and mode, with the counters, turn,
+    // snapshots and clock starting over
+    pub fn restart(&self, board: Board, seed: Option<u64>) -> GameSession {
+        let mode = match self.mode {
+            GameMode::TwoPlayer { .. } => GameMode::two_player(),
+            mode => mode,
+        };
+        let mut next = GameSession::new(board, StartKind::Shuffled, seed).with_mode(mode);
+        next.renderer = self.renderer.clone();
+        next.events = self.events.clone();
+        next.recording = self.recording.clone();
+        next.show_stats = self.show_stats;
+        next.assist = self.assist;
+        next.hide_numbers = self.hide_numbers;
+        // A ghost from a game on another size has nothing to race
+        next.ghost = self
+            .ghost
+            .clone()
+            .filter(|ghost| ghost.finish().board.size() == next.board.size());
+        if let Some((_, time)) = &self.clock {
+            next = next.with_clock(time.clone());
+        }
+        next
+    }
+
+    // Replaces this game with a freshly shuffled one like restart does. The
+    // shuffle happens before anything is touched, so if it fails the old
+    // game is left as it was. `seed` is the one `rng` was made from, if any,
+    // so the new game can be played again.
+    pub fn new_game(
+        &mut self,
+        level: DifficultyLevel,
+        size: usize,
+        rng: &mut impl Rng,
+        seed: Option<u64>,
+    ) {
+        if self.mode == GameMode::Rotation {
+            let mut board = Board::goal_state(size);
+            let scramble = board.scramble_rotation(rng, level.shuffle_moves());
+            *self = self.restart(board, seed).with_rotation_scramble(scramble);
+            return;
+        }
+        let (board, scramble) = self.mode.shuffle(level, size, rng);
+        *self = self.restart(board, seed).with_scramble(scramble);
+    }
+
+    // The same puzzle from its first move again: the starting board, goal,
+    // start kind and scramble are kept, the moves and clock start over, and
+    // it counts as the next attempt
+    pub fn reset(&mut self) {
+        let mut next = self.restart(self.initial_board.clone(), self.seed);
+        next.goal = self.goal.clone();
+        next.start = self.start;
+        next.scramble = self.scramble.take();
+        next.rotation_scramble = self.rotation_scramble.take();
+        next.attempt = self.attempt + 1;
+        *self = next;
+    }
+
+    pub fn with_renderer(mut self, renderer: RendererHandle) -> Self {
+        self.renderer = Some(renderer);
+        self
+    }
+
+    pub fn with_events(mut self, events: EventHandle) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    // Moves and wins are reported by apply_move; the game loops report the rest
+    pub fn emit(&self, event: GameEvent) {
+        if let Some(events) = &self.events {
+            events.handle(event, self);
+        }
+    }
+
+    // Draws the current board; sessions without a renderer are headless
+    pub fn render(&self) -> io::Result<()> {
+        match &self.renderer {
+            Some(renderer) => renderer.render(&self.board, self),
+            None => Ok(()),
+        }
+    }
+
+    // Returns whether a tile actually moved. In two-player mode the turn only
+    // passes on a move that counts. In rotation mode the selected tile's row
+    // or column turns the way `direction` points instead, in gravity mode the
+    // board settles after the slide, in magnetic mode the slide pulls another
+    // tile along, and in wraparound mode a tile can come from across the
+    // edge.
+    pub fn apply_move(&mut self, direction: Direction) -> bool {
+        self.slide(direction, true)
+    }
+
+    // Pushes the whole line of tiles beyond the blank one cell `direction`,
+    // as the single moves Board::push_sequence gives, so the history, counter
+    // and events match moving each tile by hand. Returns how many tiles
+    // moved; in two-player mode the push is one turn.
+    pub fn push(&mut self, direction: Direction) -> usize {
+        let moves = match self.mode {
+            GameMode::Rotation => Vec::new(),
+            _ => self.board.push_sequence(direction),
+        };
+        for (i, &direction) in moves.iter().enumerate() {
+            self.slide(direction, i + 1 == moves.len());
+        }
+        moves.len()
+    }
+
+    fn slide(&mut self, direction: Direction, pass_turn: bool) -> bool {
+        if self.mode == GameMode::Rotation {
+            return match self.board.rotation_through(self.selected, direction) {
+                Some(rotation) => self.apply_rotation_move(rotation.axis, rotation.dir),
+                None => false,
+            };
+        }
+        // Across the edges where they join up
+        let distance = self.board.manhattan_distance();
+        let pulled = match self.mode {
+            GameMode::Magnetic => self.board.magnetic_pull(direction),
+            _ => None,
+        };
+        let moved = match self.mode {
+            GameMode::Magnetic => self.board.apply_magnetic_move(direction),
+            GameMode::Wraparound => self.board.apply_wraparound_move(direction),
+            GameMode::Gravity => self.board.apply_gravity_move(direction),
+            _ => self.board.move_tile(direction),
+        };
+        self.pulled = pulled;
+        if moved {
+            self.moves.push(direction);
+            // The pull is a slide of its own, so `moves` still replays
+            if pulled.is_some() {
+                self.moves.push(direction);
+            }
+            self.move_count += 1;
+            self.release_clock();
+            self.record_time();
+            self.moves_since_progress = match self.board.manhattan_distance() < distance {
+                true => 0,
+                false => self.moves_since_progress + 1,
+            };
+            if let GameMode::TwoPlayer { current_player } = &mut self.mode {
+                self.player_moves[*current_player as usize - 1] += 1;
+                if pass_turn {
+                    *current_player = 3 - *current_player;
+                }
+            }
+            log::debug!("move {:?} applied, {} moves", direction, self.move_count);
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                moves = %self.move_count,
+                distance = %self.board.manhattan_distance(),
+                "move applied"
+            );
+            self.record_snapshot();
+            self.emit(GameEvent::Move(direction));
+            if self.is_won() {
+                self.stop_clock();
+                self.emit(GameEvent::Win);
+            }
+        } else {
+            log::debug!("move {:?} ignored, no tile to slide", direction);
+        }
+        moved
+    }
+
+    // Every SNAPSHOT_EVERY_MOVES moves and on the win, when recording
+    fn record_snapshot(&mut self) {
+        let Some(wall) = &self.recording else {
+            return;
+        };
+        if self.move_count.is_multiple_of(SNAPSHOT_EVERY_MOVES) || self.is_won() {
+            let snapshot = self.snapshot(UNIX_EPOCH + wall.now());
+            self.snapshots.push(snapshot);
+        }
+    }
+
+    fn record_time(&mut self) {
+        if let Some(elapsed) = self.elapsed() {
+            self.move_rate.record(elapsed);
+        }
+    }
+
+    // The rotation mode move, false outside that mode or off the board.
+    // Rotations count as moves and are kept in `rotations`, as slides are in
+    // `moves`.
+    pub fn apply_rotation_move(&mut self, axis: RotationAxis, dir: RotationDir) -> bool {
+        if self.mode != GameMode::Rotation || !self.board.apply_rotation_move(axis, dir) {
+            return false;
+        }
+        self.rotations.push(Rotation::new(axis, dir));
+        self.move_count += 1;
+        self.release_clock();
+        self.record_time();
+        log::debug!("rotated {:?} {:?}, {} moves", axis, dir, self.move_count);
+        self.record_snapshot();
+        if self.is_won() {
+            self.stop_clock();
+            self.emit(GameEvent::Win);
+        }
+        true
+    }
+
+    // Where this game's moves went, from the start up to now
+    pub fn heatmap(&self) -> Heatmap {
+        match self.mode {
+            GameMode::Rotation => Heatmap::replay_rotations(&self.initial_board, &self.rotations),
+            mode => Heatmap::replay(&self.initial_board, &self.moves, mode),
+        }
+    }
+
+    pub fn apply_moves(&mut self, moves: &[Direction]) -> usize {
+        moves
+            .iter()
+            .filter(|&&direction| self.apply_move(direction))
+            .count()
+    }
+
+    // Takes back every move after the first `index`, as if they'd never been
+    // made, along with the snapshots taken since. The clock carries on. Only
+    // classic and wraparound modes, where each entry in `moves` is one whole
+    // move; anything else is refused and the session left as it was.
+    pub fn rewind(&mut self, index: usize) -> Result<(), RewindError> {
+        let wrap = match self.mode {
+            GameMode::Classic => false,
+            GameMode::Wraparound => true,
+            mode => return Err(RewindError::Unsupported(mode)),
+        };
+        while self.moves.len() > index {
+            let Some(direction) = self.moves.pop() else {
+                break;
+            };
+            // A wrapped slide comes back by wrapping the other way
+            if wrap {
+                self.board.apply_wraparound_move(direction.opposite());
+            } else {
+                self.board.undo_move(direction);
+            }
+            self.move_count -= 1;
+        }
+        self.moves_since_progress = 0;
+        let move_count = self.move_count;
+        self.snapshots
+            .retain(|snapshot| snapshot.move_count <= move_count);
+        log::debug!("rewound to move {}", move_count);
+        Ok(())
+    }
+
+    // Consecutive moves, up to now, that didn't bring the board closer to
+    // solved by Manhattan distance
+    pub fn steps_since_last_progress(&self) -> u32 {
+        self.moves_since_progress
+    }
+
+    // The player seems stuck once this many moves go by without progress
+    pub fn is_stuck(&self) -> bool {
+        self.moves_since_progress > STUCK_MOVES
+    }
+
+    // A board that starts out solved only counts once it's been moved and put back
+    pub fn is_won(&self) -> bool {
+        self.move_count > 0 && self.board.is_goal(&self.goal)
+    }
+
+    // A full copy of the game to return to later with a restore
+    pub fn checkpoint(&self) -> GameSession {
+        self.clone()
+    }
+
+    // Debug starts are never recorded in stats or records
+    pub fn records_stats(&self) -> bool {
+        self.start != StartKind::Debug
+    }
+
+    // A win fit for the leaderboard: unaided, and from a start that counts
+    pub fn leaderboard_eligible(&self) -> bool {
+        self.is_won() && self.hints_used == 0 && self.records_stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::cell::Cell;
+
+    // A clock the test moves on by hand, from 0
+    fn manual_time() -> (TimeSource, Rc<Cell<Duration>>) {
+        let now = Rc::new(Cell::new(Duration::ZERO));
+        let time = TimeSource::new({
+            let now = now.clone();
+            move || now.get()
+        });
+        (time, now)
+    }
+
+    #[test]
+    fn test_solved_start_is_not_won() {
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None);
+        assert!(!session.is_won());
+        assert!(!session.records_stats());
+
+        assert!(!session.apply_move(Direction::Up));
+        assert!(!session.is_won());
+
+        assert!(session.apply_move(Direction::Down));
+        assert!(session.apply_move(Direction::Up));
+        assert_eq!(session.move_count, 2);
+        assert_eq!(session.moves, [Direction::Down, Direction::Up]);
+        assert!(session.is_won());
+        assert!(!session.leaderboard_eligible());
+    }
+
+    #[test]
+    fn test_leaderboard_eligible() {
+        let start: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+        for (kind, eligible) in [
+            (StartKind::Shuffled, true),
+            (StartKind::Custom, true),
+            (StartKind::Debug, false),
+        ] {
+            let mut session = GameSession::new(start.clone(), kind, None);
+            assert!(!session.leaderboard_eligible());
+            session.apply_move(Direction::Left);
+            assert!(session.is_won());
+            assert_eq!(session.leaderboard_eligible(), eligible, "{:?}", kind);
+        }
+
+        let mut session = GameSession::new(start, StartKind::Shuffled, None);
+        session.hints_used = 1;
+        session.apply_move(Direction::Left);
+        assert!(!session.leaderboard_eligible());
+    }
+
+    #[test]
+    fn test_snapshots_every_ten_moves_and_on_win() {
+        let mut board = Board::goal_state(5);
+        board.apply_moves(&Direction::parse_sequence("DDDDRRRR").unwrap());
+        let mut plain = GameSession::new(board.clone(), StartKind::Custom, None);
+        let y2k = TimeSource::new(|| Duration::from_secs(946_684_800));
+        let mut session = GameSession::new(board, StartKind::Custom, None).with_snapshots(y2k);
+
+        // Twelve moves out and back, then the eight that solve it
+        let moves = Direction::parse_sequence("UDUDUDUDUDUD LLLLUUUU").unwrap();
+        assert_eq!(session.apply_moves(&moves), 20);
+        assert_eq!(plain.apply_moves(&moves), 20);
+        assert!(session.is_won());
+
+        let counts: Vec<_> = session.snapshots.iter().map(|s| s.move_count).collect();
+        assert_eq!(counts, [10, 20]);
+        assert!(session.snapshots[1].board.is_goal(&session.goal));
+        assert!(plain.snapshots.is_empty());
+        assert_eq!(plain.snapshot(UNIX_EPOCH).elapsed_ms, 0);
+        // Stamped by the session's wall clock, at 2000-01-01
+        assert_eq!(session.snapshots[0].timestamp_utc, "2000-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_two_player_alternates() {
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None)
+            .with_mode(GameMode::two_player());
+        assert_eq!(session.current_player(), Some(1));
+
+        session.apply_move(Direction::Down);
+        assert_eq!(session.current_player(), Some(2));
+        session.apply_move(Direction::Down);
+        assert_eq!(session.current_player(), Some(1));
+        // A move that goes nowhere doesn't use up the turn
+        assert!(!session.apply_move(Direction::Down));
+        assert_eq!(session.current_player(), Some(1));
+        session.apply_move(Direction::Right);
+
+        assert_eq!(session.current_player(), Some(2));
+        assert_eq!(session.player_moves, [2, 1]);
+        assert_eq!(session.move_count, 3);
+    }
+
+    #[test]
+    fn test_steps_since_last_progress() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut session = GameSession::new(board, StartKind::Custom, None);
+        assert_eq!(session.steps_since_last_progress(), 0);
+
+        // Down moves the 2 away from home, Up brings it back
+        session.apply_move(Direction::Down);
+        assert_eq!(session.steps_since_last_progress(), 1);
+        session.apply_move(Direction::Up);
+        assert_eq!(session.steps_since_last_progress(), 0);
+
+        // Every one of these pushes a tile further from home
+        let mut session = GameSession::new(Board::goal_state(5), StartKind::Debug, None);
+        session.apply_moves(&Direction::parse_sequence("DDDDRRRR").unwrap());
+        assert_eq!(session.steps_since_last_progress(), 8);
+
+        // A move that goes nowhere doesn't count either way
+        assert!(!session.apply_move(Direction::Right));
+        assert_eq!(session.steps_since_last_progress(), 8);
+
+        session.apply_moves(&Direction::parse_sequence("UL").unwrap());
+        assert_eq!(session.steps_since_last_progress(), 10);
+        assert!(!session.is_stuck());
+        session.apply_move(Direction::Left);
+        assert!(session.is_stuck());
+
+        session.apply_move(Direction::Right);
+        assert_eq!(session.steps_since_last_progress(), 0);
+    }
+
+    #[test]
+    fn test_find_scramble() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut session = GameSession::new(board.clone(), StartKind::Custom, None);
+        assert_eq!(
+            session.find_scramble(),
+            Some(&[Direction::Down, Direction::Right][..])
+        );
+        let mut replayed = Board::new();
+        replayed.apply_moves(session.scramble.as_ref().unwrap());
+        assert_eq!(replayed, board);
+
+        let recorded = GameSession::new(board, StartKind::Shuffled, None)
+            .with_scramble(vec![Direction::Down, Direction::Right]);
+        assert_eq!(
+            recorded
+                .scramble
+                .as_deref()
+                .map(Direction::sequence_to_spaced),
+            Some("D R".to_string())
+        );
+
+        let mut big = GameSession::new(Board::goal_state(4), StartKind::Custom, None);
+        assert_eq!(big.find_scramble(), None);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut session = GameSession::new(Board::goal_state(4), StartKind::Debug, None)
+            .with_mode(GameMode::two_player());
+        assert_eq!(session.push(Direction::Right), 3);
+        assert_eq!(session.move_count, 3);
+        assert_eq!(session.moves, [Direction::Right; 3]);
+        assert_eq!(session.player_moves, [3, 0]);
+        assert_eq!(session.current_player(), Some(2));
+
+        assert_eq!(session.push(Direction::Right), 0);
+        assert_eq!(session.current_player(), Some(2));
+
+        // A one-tile push is just a move
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut pushed = GameSession::new(board, StartKind::Debug, None);
+        let mut moved = pushed.clone();
+        assert_eq!(pushed.push(Direction::Left), 1);
+        assert!(moved.apply_move(Direction::Left));
+        assert_eq!(pushed, moved);
+    }
+
+    #[test]
+    fn test_rotation_mode() {
+        let mut session =
+            GameSession::new(Board::new(), StartKind::Debug, None).with_mode(GameMode::Rotation);
+        assert!(session.apply_rotation_move(RotationAxis::Row(1), RotationDir::Forward));
+        assert!(!session.is_won());
+        assert!(session.apply_rotation_move(RotationAxis::Row(1), RotationDir::Backward));
+        assert_eq!(session.move_count, 2);
+        assert!(session.moves.is_empty());
+        assert_eq!(Rotation::sequence_to_spaced(&session.rotations), "R2+ R2-");
+        assert!(session.is_won());
+        assert_eq!(session.heatmap().tile_moves(), [0, 0, 0, 2, 2, 2, 0, 0]);
+
+        // The arrows turn the blank's row or column, or the picked tile's
+        assert!(session.apply_move(Direction::Down));
+        assert_eq!(session.board.tiles(), [1, 2, 0, 4, 5, 3, 7, 8, 6]);
+        session.selected = 4;
+        assert!(session.apply_move(Direction::Left));
+        assert_eq!(session.board.tiles(), [1, 2, 0, 5, 3, 4, 7, 8, 6]);
+        assert_eq!(
+            Rotation::sequence_to_spaced(&session.rotations[2..]),
+            "C3+ R2-"
+        );
+        assert_eq!(session.find_scramble(), None);
+
+        // A new game is a rotation scramble
+        let mut rng = StdRng::seed_from_u64(3);
+        session.new_game(DifficultyLevel::Easy, 3, &mut rng, None);
+        assert!(session.rotations.is_empty());
+        let mut board = session.initial_board.clone();
+        for rotation in session.rotation_scramble.as_ref().unwrap().iter().rev() {
+            let undo = rotation.inverse();
+            board.apply_rotation_move(undo.axis, undo.dir);
+        }
+        assert!(board.is_goal(&session.goal));
+
+        let mut classic = GameSession::new(Board::new(), StartKind::Debug, None);
+        assert!(!classic.apply_rotation_move(RotationAxis::Row(1), RotationDir::Forward));
+    }
+
+    #[test]
+    fn test_rewind() {
+        let (time, _) = manual_time();
+        let mut session =
+            GameSession::new(Board::new(), StartKind::Debug, None).with_snapshots(time);
+        let moves = Direction::parse_sequence("DRULDRULDRU").unwrap();
+        session.apply_moves(&moves[..2]);
+        let midway = session.board.clone();
+        session.apply_moves(&moves[2..]);
+        assert_eq!(session.snapshots.len(), 1);
+
+        session.rewind(2).unwrap();
+        assert_eq!(session.board, midway);
+        assert_eq!(session.moves, &moves[..2]);
+        assert_eq!(session.move_count, 2);
+        assert!(session.snapshots.is_empty());
+
+        // Past the end there's nothing to take back
+        session.rewind(5).unwrap();
+        assert_eq!(session.move_count, 2);
+        session.rewind(0).unwrap();
+        assert_eq!(session.board, session.initial_board);
+    }
+
+    #[test]
+    fn test_rewind_wraparound() {
+        let mut session =
+            GameSession::new(Board::new(), StartKind::Debug, None).with_mode(GameMode::Wraparound);
+        // With the blank in a corner, Up and Left only move by wrapping round
+        // the edge
+        assert!(session.apply_move(Direction::Up));
+        let midway = session.board.clone();
+        assert!(session.apply_move(Direction::Left));
+        assert!(session.apply_move(Direction::Down));
+        assert!(session.apply_move(Direction::Left));
+
+        session.rewind(1).unwrap();
+        assert_eq!(session.board, midway);
+        assert_eq!(session.move_count, 1);
+        session.rewind(0).unwrap();
+        assert_eq!(session.board, session.initial_board);
+    }
+
+    #[test]
+    fn test_rewind_refused() {
+        for mode in [
+            GameMode::TwoPlayer { current_player: 1 },
+            GameMode::Rotation,
+            GameMode::Gravity,
+            GameMode::Magnetic,
+        ] {
+            let mut session =
+                GameSession::new(Board::new(), StartKind::Debug, None).with_mode(mode);
+            session.apply_moves(&Direction::parse_sequence("RD").unwrap());
+            let (board, moves, move_count) = (
+                session.board.clone(),
+                session.moves.clone(),
+                session.move_count,
+            );
+
+            assert_eq!(
+                session.rewind(0),
+                Err(RewindError::Unsupported(session.mode))
+            );
+            assert_eq!(session.board, board);
+            assert_eq!(session.moves, moves);
+            assert_eq!(session.move_count, move_count);
+        }
+    }
+
+    #[test]
+    fn test_magnetic_mode() {
+        let mut session =
+            GameSession::new(Board::new(), StartKind::Debug, None).with_mode(GameMode::Magnetic);
+        assert!(session.apply_move(Direction::Right));
+        assert_eq!(session.pulled, Some(7));
+        assert_eq!(session.board.tiles(), [1, 2, 3, 4, 5, 6, 0, 7, 8]);
+        // One move made, two slides to replay it
+        assert_eq!(session.move_count, 1);
+        assert_eq!(session.moves, [Direction::Right, Direction::Right]);
+        assert_eq!(session.path_back(), None);
+
+        // A move that can't be made pulls nothing
+        assert!(!session.apply_move(Direction::Right));
+        assert_eq!(session.pulled, None);
+        assert!(session.apply_move(Direction::Left));
+        assert_eq!(session.pulled, Some(8));
+        assert!(session.is_won());
+    }
+
+    #[test]
+    fn test_wraparound_mode() {
+        let mut session =
+            GameSession::new(Board::new(), StartKind::Debug, None).with_mode(GameMode::Wraparound);
+        assert!(session.apply_move(Direction::Up));
+        assert_eq!(session.board.tiles(), [1, 2, 0, 4, 5, 6, 7, 8, 3]);
+        // Back the way it came, round the edge again
+        assert_eq!(session.path_back(), Some(vec![Direction::Down]));
+        assert!(session.apply_move(Direction::Down));
+        assert!(session.is_won());
+
+        // 7 was already one step from home round the edge, so moving it
+        // next to its goal inside the board is no progress
+        let start: Board = "1 2 3 4 5 6 8 0 7".parse().unwrap();
+        let mut session =
+            GameSession::new(start, StartKind::Custom, None).with_mode(GameMode::Wraparound);
+        assert!(session.apply_move(Direction::Left));
+        assert_eq!(session.steps_since_last_progress(), 1);
+    }
+
+    #[test]
+    fn test_gravity_mode() {
+        let start: Board = "1 2 0 4 5 3 7 8 6".parse().unwrap();
+        let mut session =
+            GameSession::new(start, StartKind::Custom, None).with_mode(GameMode::Gravity);
+        // 3 holds on where it lands, so the slide counts
+        assert!(session.apply_move(Direction::Up));
+        assert_eq!(session.board.tiles(), [1, 2, 3, 4, 5, 0, 7, 8, 6]);
+        assert!(session.apply_move(Direction::Right));
+        assert_eq!(session.board.tiles(), [1, 0, 3, 4, 2, 5, 7, 8, 6]);
+        assert_eq!(session.move_count, 2);
+        assert_eq!(session.moves, [Direction::Up, Direction::Right]);
+        assert_eq!(session.path_back(), None);
+
+        // A wall the player can't move against isn't recorded
+        assert!(!session.apply_move(Direction::Down));
+        assert_eq!(session.move_count, 2);
+        assert_eq!(session.moves.len(), 2);
+
+        // The last tile slides up into place and holds there
+        let start: Board = "1 2 3 4 5 0 7 8 6".parse().unwrap();
+        let mut session =
+            GameSession::new(start, StartKind::Custom, None).with_mode(GameMode::Gravity);
+        assert!(session.apply_move(Direction::Up));
+        assert!(session.is_won());
+
+        // A new game is a gravity scramble, which is never the goal
+        let mut rng = StdRng::seed_from_u64(3);
+        session.new_game(DifficultyLevel::Medium, 3, &mut rng, None);
+        assert!(!session.board.is_goal(&session.goal));
+        assert!(session.board.is_solvable());
+    }
+
+    #[test]
+    fn test_restart() {
+        let (time, _) = manual_time();
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None)
+            .with_mode(GameMode::two_player())
+            .with_snapshots(time.clone())
+            .with_clock(time);
+        session.apply_moves(&Direction::parse_sequence("DRU").unwrap());
+        session.hints_used = 1;
+
+        let next = session.restart(Board::goal_state(4), Some(9));
+        assert_eq!((next.move_count, next.hints_used), (0, 0));
+        assert_eq!(next.current_player(), Some(1));
+        assert_eq!(next.player_moves, [0, 0]);
+        assert_eq!(next.board.size(), 4);
+        assert_eq!(next.goal, Board::goal_state(4));
+        assert_eq!(next.start, StartKind::Shuffled);
+        assert!(next.elapsed().is_some());
+        assert!(next.recording.is_some());
+    }
+
+    #[test]
+    fn test_par_and_move_rate() {
+        let board: Board = "1 2 0 3".parse().unwrap();
+        let mut session = GameSession::new(board.clone(), StartKind::Custom, None);
+        assert_eq!(session.par(), Some(1));
+        session.apply_move(Direction::Left);
+        assert_eq!(session.move_rate, MoveRate::default());
+
+        let (time, _) = manual_time();
+        let mut timed = GameSession::new(board, StartKind::Custom, None).with_clock(time);
+        timed.apply_move(Direction::Left);
+        assert_ne!(timed.move_rate, MoveRate::default());
+        // Unknown past the sizes with a cheap optimum
+        let large = GameSession::new(Board::goal_state(5), StartKind::Debug, None);
+        assert_eq!(large.par(), None);
+    }
+
+    #[test]
+    fn test_pause_clock() {
+        let mut untimed = GameSession::new(Board::new(), StartKind::Debug, None);
+        untimed.pause_clock();
+        assert!(!untimed.is_paused());
+
+        let (time, now) = manual_time();
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None).with_clock(time);
+        now.set(Duration::from_millis(3));
+        session.pause_clock();
+        assert!(session.is_paused());
+        now.set(Duration::from_millis(8));
+        assert_eq!(session.elapsed(), Some(Duration::from_millis(3)));
+        session.resume_clock();
+        assert!(!session.is_paused());
+        assert_eq!(session.paused_time(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_hold_clock() {
+        let (time, now) = manual_time();
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None).with_clock(time);
+        now.set(Duration::from_millis(5));
+        session.hold_clock();
+        now.set(Duration::from_millis(10));
+        assert_eq!(session.elapsed(), Some(Duration::ZERO));
+
+        // The first move starts it from nothing, owing nothing to the wait
+        assert!(session.apply_move(Direction::Down));
+        now.set(Duration::from_millis(12));
+        assert_eq!(session.elapsed(), Some(Duration::from_millis(2)));
+        assert_eq!(session.paused_time(), Duration::ZERO);
+        assert!(!session.is_paused());
+    }
+
+    #[test]
+    fn test_new_game() {
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None);
+        session.apply_moves(&Direction::parse_sequence("DR").unwrap());
+
+        let mut rng = StdRng::seed_from_u64(4);
+        session.new_game(DifficultyLevel::Medium, 4, &mut rng, Some(4));
+        assert_eq!(session.move_count, 0);
+        assert!(session.moves.is_empty());
+        assert_eq!(session.board, session.initial_board);
+        assert_eq!(
+            (session.start, session.seed),
+            (StartKind::Shuffled, Some(4))
+        );
+
+        let mut replayed = Board::goal_state(4);
+        replayed.apply_moves(session.scramble.as_deref().unwrap());
+        assert_eq!(replayed, session.initial_board);
+    }
+
+    #[test]
+    fn test_ghost_follows_restarts_of_the_same_size() {
+        let (time, _) = manual_time();
+        let won = GameSession::new(Board::new(), StartKind::Debug, None).with_clock(time.clone());
+        let ghost = Ghost::new(vec![won.snapshot(UNIX_EPOCH)], 3).unwrap();
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None)
+            .with_ghost(ghost)
+            .with_clock(time);
+        assert!(session.elapsed().is_some());
+
+        let mut rng = StdRng::seed_from_u64(4);
+        session.reset();
+        assert!(session.ghost.is_some());
+        session.new_game(DifficultyLevel::Easy, 3, &mut rng, None);
+        assert!(session.ghost.is_some());
+        session.new_game(DifficultyLevel::Easy, 4, &mut rng, None);
+        assert!(session.ghost.is_none());
+    }
+
+    #[test]
+    fn test_path_back() {
+        let scramble = Direction::parse_sequence("DRD").unwrap();
+        let mut start = Board::new();
+        start.apply_moves(&scramble);
+        let mut session =
+            GameSession::new(start, StartKind::Shuffled, None).with_scramble(scramble);
+        assert_eq!(session.path_back(), Direction::parse_sequence("ULU").ok());
+
+        // Following the path shortens it, and a slip is taken back first
+        session.apply_move(Direction::Up);
+        assert_eq!(session.path_back(), Direction::parse_sequence("LU").ok());
+        session.apply_move(Direction::Down);
+        assert_eq!(session.path_back(), Direction::parse_sequence("ULU").ok());
+        session.apply_move(Direction::Up);
+        session.apply_move(Direction::Right);
+        assert_eq!(session.path_back(), Direction::parse_sequence("LLU").ok());
+
+        let mut session = session.with_mode(GameMode::Rotation);
+        assert_eq!(session.path_back(), None);
+    }
+
+    #[test]
+    fn test_reset() {
+        let start: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut session = GameSession::new(start.clone(), StartKind::Custom, None)
+            .with_scramble(Direction::parse_sequence("DR").unwrap());
+        session.apply_moves(&Direction::parse_sequence("DR").unwrap());
+        session.hints_used = 2;
+
+        session.reset();
+        assert_eq!(session.attempt, 2);
+        assert_eq!(session.board, start);
+        assert!(session.moves.is_empty());
+        assert_eq!((session.move_count, session.hints_used), (0, 0));
+        assert_eq!(session.start, StartKind::Custom);
+        assert_eq!(session.scramble.as_ref().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn test_checkpoint_is_independent() {
+        let mut session = GameSession::new(Board::new(), StartKind::Debug, None);
+        session.apply_move(Direction::Down);
+        let checkpoint = session.checkpoint();
+
+        session.apply_move(Direction::Right);
+        assert_ne!(session, checkpoint);
+        assert_eq!(checkpoint.move_count, 1);
+        assert_eq!(checkpoint.moves, [Direction::Down]);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    board::{Board, Direction, Rotation, Tile},
+    game::GameMode,
+};
+use std::fmt;
+
+// Shades of the heatmap above nothing at all: a cell's count lands in one of
+// these by how it compares to the busiest cell
+pub const HEAT_LEVELS: u8 = 4;
+
+// Where a game's moves went, worked out after the fact by replaying them
+// from the start, so play needn't track anything beyond the move list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap {
+    size: usize,
+    cells: Vec<u32>, // By cell, how many times a tile slid or fell into it
+    tiles: Vec<u32>, // By tile number, how many times it moved; 0 is unused
+}
+
+impl Heatmap {
+    // The moves are played as `mode` plays them. Those that can't be made
+    // from where the replay has got to are skipped, as the board skips them
+    // in play. A magnetic pull is already a slide of its own in the list.
+    pub fn replay(start: &Board, moves: &[Direction], mode: GameMode) -> Self {
+        let mut heatmap = Self::empty(start);
+        let mut board = start.clone();
+        for &direction in moves {
+            let before = board.clone();
+            let moved = match mode {
+                GameMode::Wraparound => board.apply_wraparound_move(direction),
+                GameMode::Gravity => board.apply_gravity_move(direction),
+                _ => board.move_tile(direction),
+            };
+            if moved {
+                heatmap.count(&before, &board);
+            }
+        }
+        heatmap
+    }
+
+    // The same for rotation mode, where every tile in the line moves
+    pub fn replay_rotations(start: &Board, rotations: &[Rotation]) -> Self {
+        let mut heatmap = Self::empty(start);
+        let mut board = start.clone();
+        for rotation in rotations {
+            let before = board.clone();
+            if board.apply_rotation_move(rotation.axis, rotation.dir) {
+                heatmap.count(&before, &board);
+            }
+        }
+        heatmap
+    }
+
+    fn empty(start: &Board) -> Self {
+        let len = start.tiles().len();
+        Self {
+            size: start.size(),
+            cells: vec![0; len],
+            tiles: vec![0; len],
+        }
+    }
+
+    // Every tile that turned up in a new cell between the two boards
+    fn count(&mut self, before: &Board, after: &Board) {
+        let cells = before.tiles().iter().zip(after.tiles()).enumerate();
+        for (cell, (&was, &tile)) in cells {
+            if tile != 0 && tile != was {
+                self.cells[cell] += 1;
+                self.tiles[tile as usize] += 1;
+            }
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn cells(&self) -> &[u32] {
+        &self.cells
+    }
+
+    // Moves made by each tile, tile 1 first
+    pub fn tile_moves(&self) -> &[u32] {
+        &self.tiles[1..]
+    }
+
+    // The tile moved most, with its count. The lowest numbered wins a tie.
+    pub fn busiest_tile(&self) -> Option<(Tile, u32)> {
+        let (index, &moves) = self
+            .tile_moves()
+            .iter()
+            .enumerate()
+            .max_by_key(|&(index, &moves)| (moves, std::cmp::Reverse(index)))?;
+        (moves > 0).then_some((index as Tile + 1, moves))
+    }
+
+    // 0 for a cell nothing moved into, otherwise 1 to HEAT_LEVELS, with the
+    // busiest cell at the top
+    pub fn level(&self, count: u32) -> u8 {
+        let busiest = self.cells.iter().copied().max().unwrap_or(0);
+        match count {
+            0 => 0,
+            _ => (count * u32::from(HEAT_LEVELS)).div_ceil(busiest) as u8,
+        }
+    }
+}
+
+// The counts as a grid, for renderers without color
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let busiest = self.cells.iter().copied().max().unwrap_or(0);
+        let width = busiest.to_string().len();
+        for row in self.cells.chunks(self.size) {
+            let counts: Vec<_> = row
+                .iter()
+                .map(|count| format!("{:>width$}", count))
+                .collect();
+            writeln!(f, "{}", counts.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        use Direction::*;
+        let start: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        // Back and forth on 5, then 6 up to solve it. The second Up can't
+        // be made from the corner and counts for nothing.
+        let heatmap = Heatmap::replay(&start, &[Left, Right, Left, Up, Up], GameMode::Classic);
+        assert_eq!(heatmap.size(), 3);
+        assert_eq!(heatmap.cells(), [0, 0, 0, 0, 2, 2, 0, 0, 0]);
+        assert_eq!(heatmap.tile_moves(), [0, 0, 0, 0, 3, 1, 0, 0]);
+        assert_eq!(heatmap.busiest_tile(), Some((5, 3)));
+        assert_eq!(heatmap.level(0), 0);
+        assert_eq!(heatmap.level(1), 2);
+        assert_eq!(heatmap.level(2), HEAT_LEVELS);
+        assert_eq!(heatmap.to_string(), "0 0 0\n0 2 2\n0 0 0\n");
+
+        let still = Heatmap::replay(&start, &[], GameMode::Classic);
+        assert_eq!(still.busiest_tile(), None);
+        assert!(still.cells().iter().all(|&count| count == 0));
+    }
+
+    #[test]
+    fn test_replay_modes() {
+        use Direction::*;
+        // 7 comes round the edge into the corner, and back
+        let start: Board = "1 2 3 4 5 6 0 8 7".parse().unwrap();
+        let heatmap = Heatmap::replay(&start, &[Right, Left], GameMode::Wraparound);
+        assert_eq!(heatmap.cells(), [0, 0, 0, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(heatmap.tile_moves(), [0, 0, 0, 0, 0, 0, 2, 0]);
+        // Played flat, Right has nothing to slide and Left moves 8
+        let flat = Heatmap::replay(&start, &[Right, Left], GameMode::Classic);
+        assert_eq!(flat.tile_moves(), [0, 0, 0, 0, 0, 0, 0, 1]);
+
+        // 5 slides right and 1 falls into its cell
+        let start: Board = "1 2 3 5 0 6 7 8 4".parse().unwrap();
+        let heatmap = Heatmap::replay(&start, &[Right], GameMode::Gravity);
+        assert_eq!(heatmap.cells(), [0, 0, 0, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(heatmap.tile_moves(), [1, 0, 0, 0, 1, 0, 0, 0]);
+    }
+}
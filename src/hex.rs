@@ -0,0 +1,282 @@
+use crate::board::Tile;
+use rand::{seq::SliceRandom, Rng};
+use std::fmt;
+
+// Long enough for the largest board to come out well mixed
+const SHUFFLE_STEPS_PER_TILE: usize = 25;
+
+// A tile's way across a hexagonal board, named for the compass. As with
+// Direction, it's the way the tile moves, into the blank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    NE,
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+}
+
+impl HexDirection {
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::NE,
+        HexDirection::E,
+        HexDirection::SE,
+        HexDirection::SW,
+        HexDirection::W,
+        HexDirection::NW,
+    ];
+
+    // Step in axial coordinates (q, r): q grows to the east, r to the south
+    fn offset(self) -> (isize, isize) {
+        match self {
+            HexDirection::NE => (1, -1),
+            HexDirection::E => (1, 0),
+            HexDirection::SE => (0, 1),
+            HexDirection::SW => (-1, 1),
+            HexDirection::W => (-1, 0),
+            HexDirection::NW => (0, -1),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            HexDirection::NE => HexDirection::SW,
+            HexDirection::E => HexDirection::W,
+            HexDirection::SE => HexDirection::NW,
+            HexDirection::SW => HexDirection::NE,
+            HexDirection::W => HexDirection::E,
+            HexDirection::NW => HexDirection::SE,
+        }
+    }
+
+    // Any case, e.g. "ne" or "W"
+    pub fn parse(name: &str) -> Option<Self> {
+        HexDirection::ALL
+            .into_iter()
+            .find(|direction| direction.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for HexDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// The sliding puzzle on a hexagon of cells, `radius` rings out from the
+// middle one: 7 cells for radius 1, 19 for radius 2. Cells are in reading
+// order, top row first, and the goal is 1 upwards in that order with the
+// blank last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexBoard {
+    tiles: Vec<Tile>, // 0 represents the blank tile
+    radius: usize,
+}
+
+impl HexBoard {
+    pub fn goal_state(radius: usize) -> Self {
+        let cells = Self::cell_count(radius);
+        let mut tiles: Vec<Tile> = (1..cells as Tile).collect();
+        tiles.push(0);
+        Self { tiles, radius }
+    }
+
+    // A random walk away from solved, so it can always be solved again
+    pub fn shuffled(radius: usize, rng: &mut impl Rng) -> Self {
+        let mut board = Self::goal_state(radius);
+        let mut last: Option<HexDirection> = None;
+        for _ in 0..board.tiles.len() * SHUFFLE_STEPS_PER_TILE {
+            // Never straight back the way the last move came
+            let moves: Vec<_> = board
+                .valid_moves()
+                .into_iter()
+                .filter(|&direction| Some(direction.opposite()) != last)
+                .collect();
+            if let Some(&direction) = moves.choose(rng) {
+                board.move_tile(direction);
+                last = Some(direction);
+            }
+        }
+        board
+    }
+
+    // 1 + 3r(r + 1): the middle plus six cells more in each ring
+    pub fn cell_count(radius: usize) -> usize {
+        1 + 3 * radius * (radius + 1)
+    }
+
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::goal_state(self.radius)
+    }
+
+    // Slides the tile that moves `direction` into the blank. Returns whether
+    // there was one.
+    pub fn move_tile(&mut self, direction: HexDirection) -> bool {
+        let blank = self.blank_position();
+        let (dq, dr) = direction.offset();
+        let (q, r) = self.axial(blank);
+        // The tile comes from the side opposite the way it moves
+        match self.index(q - dq, r - dr) {
+            Some(source) => {
+                self.tiles.swap(blank, source);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn valid_moves(&self) -> Vec<HexDirection> {
+        let (q, r) = self.axial(self.blank_position());
+        HexDirection::ALL
+            .into_iter()
+            .filter(|direction| {
+                let (dq, dr) = direction.offset();
+                self.index(q - dq, r - dr).is_some()
+            })
+            .collect()
+    }
+
+    fn blank_position(&self) -> usize {
+        self.tiles
+            .iter()
+            .position(|&tile| tile == 0)
+            .expect("a hex board always has a blank")
+    }
+
+    // Cells in each row, top to bottom: radius + 1 at the edges, up to
+    // 2 * radius + 1 across the middle
+    fn row_lengths(&self) -> impl Iterator<Item = usize> {
+        let radius = self.radius;
+        (0..=2 * radius).map(move |row| 2 * radius + 1 - row.abs_diff(radius))
+    }
+
+    // Axial coordinates of a cell. Rows are r, from -radius at the top; each
+    // row's q starts where the hexagon's edge cuts it.
+    fn axial(&self, index: usize) -> (isize, isize) {
+        let radius = self.radius as isize;
+        let mut start = 0;
+        for (row, len) in self.row_lengths().enumerate() {
+            if index < start + len {
+                let r = row as isize - radius;
+                let q_min = (-radius).max(-radius - r);
+                return (q_min + (index - start) as isize, r);
+            }
+            start += len;
+        }
+        unreachable!("cell {} isn't on the board", index)
+    }
+
+    fn index(&self, q: isize, r: isize) -> Option<usize> {
+        let radius = self.radius as isize;
+        if q.abs() > radius || r.abs() > radius || (q + r).abs() > radius {
+            return None;
+        }
+        let row = (r + radius) as usize;
+        let start: usize = self.row_lengths().take(row).sum();
+        let q_min = (-radius).max(-radius - r);
+        Some(start + (q - q_min) as usize)
+    }
+}
+
+// One row of the hexagon to a line, each indented by half a cell per step
+// from the middle row so neighbors sit diagonally below each other. The
+// blank is a ⬡.
+pub fn render_hex_board(board: &HexBoard) -> String {
+    let width = (board.tiles.len() - 1).to_string().len();
+    let mut output = String::new();
+    let mut cells = board.tiles.iter();
+    for (row, len) in board.row_lengths().enumerate() {
+        let indent = row.abs_diff(board.radius) * (width + 1);
+        let line: Vec<_> = cells
+            .by_ref()
+            .take(len)
+            .map(|&tile| match tile {
+                0 => format!("{:>width$}", '⬡'),
+                tile => format!("{:>width$}", tile),
+            })
+            .collect();
+        // A cell and its gap are 2 * (width + 1) across, so the indent of
+        // half that puts each cell between the two above it
+        let line = line.join(&" ".repeat(width + 2));
+        output += format!("{}{}", " ".repeat(indent), line).trim_end();
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_cell_count() {
+        assert_eq!(HexBoard::cell_count(1), 7);
+        assert_eq!(HexBoard::cell_count(2), 19);
+        assert_eq!(HexBoard::goal_state(2).tiles().len(), 19);
+        assert!(HexBoard::goal_state(2).is_solved());
+    }
+
+    #[test]
+    fn test_coordinates_round_trip() {
+        let board = HexBoard::goal_state(3);
+        for index in 0..board.tiles().len() {
+            let (q, r) = board.axial(index);
+            assert_eq!(board.index(q, r), Some(index));
+        }
+        assert_eq!(board.axial(0), (0, -3));
+        assert_eq!(board.index(4, 0), None);
+    }
+
+    #[test]
+    fn test_move_tile() {
+        use HexDirection::*;
+        // The blank starts in the bottom right corner, with three neighbors:
+        // 6 to its west and 4 and 5 above it
+        let mut board = HexBoard::goal_state(1);
+        assert_eq!(board.valid_moves(), [E, SE, SW]);
+        assert!(!board.move_tile(W));
+
+        // 6 slides east, then 4 down to the south west
+        assert!(board.move_tile(E));
+        assert_eq!(board.tiles(), [1, 2, 3, 4, 5, 0, 6]);
+        assert!(board.move_tile(SW));
+        assert_eq!(board.tiles(), [1, 2, 3, 0, 5, 4, 6]);
+        assert!(!board.is_solved());
+
+        assert!(board.move_tile(NE));
+        assert!(board.move_tile(W));
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn test_shuffled() {
+        let board = HexBoard::shuffled(2, &mut StdRng::seed_from_u64(7));
+        assert!(!board.is_solved());
+        let mut tiles = board.tiles().to_vec();
+        tiles.sort_unstable();
+        assert_eq!(tiles, (0..19).collect::<Vec<Tile>>());
+    }
+
+    #[test]
+    fn test_parse_direction() {
+        assert_eq!(HexDirection::parse("ne"), Some(HexDirection::NE));
+        assert_eq!(HexDirection::parse("W"), Some(HexDirection::W));
+        assert_eq!(HexDirection::parse("up"), None);
+    }
+
+    #[test]
+    fn test_render_hex_board() {
+        let board = HexBoard::goal_state(1);
+        assert_eq!(render_hex_board(&board), "  1   2\n3   4   5\n  6   ⬡\n");
+    }
+}
@@ -0,0 +1,516 @@
+use crate::{
+    board::Direction,
+    difficulty::{DifficultyLevel, Rating},
+};
+use std::{fmt, str::FromStr};
+
+// Player-facing text by message id. Parameters are written as {name} in each
+// string so translations can put them wherever their word order needs.
+type Table = &'static [(&'static str, &'static str)];
+
+// The complete table; every other language falls back to it per message
+const EN: Table = &[
+    (
+        "key_help",
+        "arrows: move  h: hint  c: checkpoint  p: restore  n: new size  q: quit",
+    ),
+    (
+        "rotation_key_help",
+        "arrows: turn the picked tile's row or column  0-9: pick a tile  q: quit",
+    ),
+    ("tile_picked", "Arrows turn tile {tile}'s row and column"),
+    ("hint_reminder", "Hint available: press H"),
+    ("split_key_help", "first to solve wins  q: quit"),
+    ("too_small", "Terminal too small"),
+    ("too_small_need", "need {width}x{height}"),
+    ("won", "You won in {moves} moves!"),
+    ("won_players", "Player 1: {first}, Player 2: {second}"),
+    ("won_time", "Time: {time}"),
+    ("won_rate", "Average: {rate} moves/s"),
+    ("win_keys", "m: move heatmap  R: rematch"),
+    ("seed", "seed {seed}"),
+    ("attempt", "attempt {attempt}"),
+    (
+        "heatmap_title",
+        "Move heatmap: how many times a tile slid into each cell",
+    ),
+    ("heatmap_busiest", "Most moved: tile {tile}, {moves} moves"),
+    ("heatmap_keys", "any key: exit"),
+    ("idle", "Idle, the clock is paused: press any key"),
+    ("leaderboard_title", "Leaderboard"),
+    ("leaderboard_empty", "No scores yet"),
+    ("leaderboard_category", "{size}x{size} {mode}"),
+    ("leaderboard_moves", "{moves} moves"),
+    ("leaderboard_seed", "seed {seed}"),
+    ("leaderboard_keys", "any key: back to the game"),
+    (
+        "leaderboard_record",
+        "New record: #{rank} for {size}x{size} {mode}!",
+    ),
+    ("leaderboard_name", "Your name [{name}]: "),
+    ("leaderboard_new_name", "Your name: "),
+    ("leaderboard_saved", "Added to the leaderboard in {path}"),
+    (
+        "no_leaderboard",
+        "No leaderboard file: pass --leaderboard FILE",
+    ),
+    ("stats_rate", "{rate} moves/s"),
+    ("stats_par", "{percent}% of par"),
+    ("stats_moves", "{moves} effective moves"),
+    ("ghost", "Ghost: {moves} moves, {placed}/{tiles} in place"),
+    ("ghost_delta", "vs ghost: {time}, {moves} moves"),
+    ("status", "Moves: {moves}  Difficulty: {level} ({estimate})"),
+    ("turn", "Player {player}'s turn"),
+    ("split_moves", "Player {player}: {moves} moves"),
+    ("split_winner", "🏆 Player {player} wins!"),
+    ("difficulty_easy", "Easy"),
+    ("difficulty_medium", "Medium"),
+    ("difficulty_hard", "Hard"),
+    ("rating_trivial", "Trivial"),
+    ("rating_easy", "Easy"),
+    ("rating_medium", "Medium"),
+    ("rating_hard", "Hard"),
+    ("rating_brutal", "Brutal"),
+    (
+        "new_game_optimal",
+        "New {size}x{size}: {rating}, solved in {moves} moves at best",
+    ),
+    (
+        "new_game_bound",
+        "New {size}x{size}: {rating}, at least {moves} moves",
+    ),
+    ("direction_up", "Up"),
+    ("direction_down", "Down"),
+    ("direction_left", "Left"),
+    ("direction_right", "Right"),
+    ("hint", "Hint: try {direction}"),
+    ("no_hint", "No hint available"),
+    ("no_checkpoint", "No checkpoint to restore"),
+    ("scramble", "Scramble: {moves}"),
+    ("no_scramble", "No scramble known for this board"),
+    ("follow", "Back to solved: {moves} (f to stop)"),
+    ("no_path_back", "Can't work out the way back for this board"),
+    (
+        "tile_entry",
+        "Tile: {digits}_ (Enter to move, Esc to cancel)",
+    ),
+    ("tile_cant_move", "Tile {tile} can't move"),
+    ("size_menu", "New game size: {sizes} (Esc to cancel)"),
+    (
+        "size_confirm",
+        "Abandon this game for a new {size}x{size}? (y/n)",
+    ),
+    ("size_fixed", "A photo puzzle keeps its size"),
+    (
+        "walkthrough_step",
+        "Move {index} of {total}, {remaining} to go",
+    ),
+    (
+        "walkthrough_keys",
+        "left/right: step  Home/End: start/end  space: play  q: back",
+    ),
+    (
+        "no_solution",
+        "The solver can't show a solution for this board",
+    ),
+    (
+        "solving",
+        "{spinner} Solving: {nodes} nodes searched (Esc to cancel)",
+    ),
+    ("solve_cancelled", "Solve cancelled"),
+    (
+        "solve_timeout",
+        "No solution within {seconds}s; try again to give it twice as long",
+    ),
+    (
+        "reset_confirm",
+        "Start this puzzle over from the beginning? (y/n)",
+    ),
+    ("timeline_step", "Timeline: move {index} / {total}"),
+    ("theme", "Theme: {theme}"),
+    (
+        "numbers_picture",
+        "Numbers can only be hidden in the picture theme",
+    ),
+    ("intro_keys", "any key: skip to play"),
+    (
+        "timeline_keys",
+        "left/right: step  Home/End: scramble/now  Enter: play from here  Esc: back",
+    ),
+    (
+        "timeline_confirm",
+        "Play on from move {index}, dropping the {dropped} moves after it? (y/n)",
+    ),
+    (
+        "timeline_classic",
+        "The timeline is only kept in classic and wraparound mode",
+    ),
+    ("yes_key", "y"),
+    ("not_your_turn", "It's Player {player}'s turn"),
+    (
+        "replay_unsolved",
+        "Warning: sequence does not reach solved state",
+    ),
+    ("line_status", "Moves: {moves}"),
+    ("line_keys", "(u, d, l, r to move, q to quit)"),
+    ("no_tile_moves", "No tile can move that way"),
+    ("not_a_move", "'{key}' is not a move (use u, d, l, r or q)"),
+    ("hex_keys", "(ne, e, se, sw, w, nw to move, q to quit)"),
+    (
+        "hex_not_a_move",
+        "'{word}' is not a move (use ne, e, se, sw, w, nw or q)",
+    ),
+    ("puzzle_won", "Solved in {moves} moves!"),
+    ("cube_layer", "Layer {layer} of {layers}"),
+    (
+        "cube_keys",
+        "arrows: move  [ ]: move between layers  PgUp/PgDn: other layers  q: quit",
+    ),
+];
+
+const ES: Table = &[
+    (
+        "key_help",
+        "flechas: mover  h: pista  c: guardar  p: volver  n: tamaño  q: salir",
+    ),
+    (
+        "rotation_key_help",
+        "flechas: girar la fila o columna de la ficha  0-9: elegir ficha  q: salir",
+    ),
+    (
+        "tile_picked",
+        "Las flechas giran la fila y la columna de la ficha {tile}",
+    ),
+    ("hint_reminder", "Pista disponible: pulsa H"),
+    ("split_key_help", "gana quien resuelva primero  q: salir"),
+    ("too_small", "Terminal demasiado pequeña"),
+    ("too_small_need", "se necesitan {width}x{height}"),
+    ("won", "¡Ganaste en {moves} movimientos!"),
+    ("won_players", "Jugador 1: {first}, jugador 2: {second}"),
+    ("won_time", "Tiempo: {time}"),
+    ("won_rate", "Media: {rate} movimientos/s"),
+    ("win_keys", "m: mapa de calor  R: revancha"),
+    ("seed", "semilla {seed}"),
+    ("attempt", "intento {attempt}"),
+    (
+        "heatmap_title",
+        "Mapa de calor: cuántas veces entró una ficha en cada casilla",
+    ),
+    (
+        "heatmap_busiest",
+        "La más movida: ficha {tile}, {moves} movimientos",
+    ),
+    ("heatmap_keys", "cualquier tecla: salir"),
+    (
+        "idle",
+        "Inactivo, el reloj está en pausa: pulsa cualquier tecla",
+    ),
+    ("leaderboard_title", "Clasificación"),
+    ("leaderboard_empty", "Aún no hay puntuaciones"),
+    ("leaderboard_category", "{size}x{size} {mode}"),
+    ("leaderboard_moves", "{moves} movimientos"),
+    ("leaderboard_seed", "semilla {seed}"),
+    ("leaderboard_keys", "cualquier tecla: volver al juego"),
+    (
+        "leaderboard_record",
+        "¡Nuevo récord: n.º {rank} en {size}x{size} {mode}!",
+    ),
+    ("leaderboard_name", "Tu nombre [{name}]: "),
+    ("leaderboard_new_name", "Tu nombre: "),
+    ("leaderboard_saved", "Añadido a la clasificación en {path}"),
+    (
+        "no_leaderboard",
+        "Sin archivo de clasificación: usa --leaderboard ARCHIVO",
+    ),
+    ("stats_rate", "{rate} movimientos/s"),
+    ("stats_par", "{percent}% del par"),
+    ("stats_moves", "{moves} movimientos efectivos"),
+    (
+        "ghost",
+        "Fantasma: {moves} movimientos, {placed}/{tiles} en su sitio",
+    ),
+    (
+        "ghost_delta",
+        "frente al fantasma: {time}, {moves} movimientos",
+    ),
+    (
+        "status",
+        "Movimientos: {moves}  Dificultad: {level} ({estimate})",
+    ),
+    ("turn", "Turno del jugador {player}"),
+    ("split_moves", "Jugador {player}: {moves} movimientos"),
+    ("split_winner", "🏆 ¡Gana el jugador {player}!"),
+    ("difficulty_easy", "Fácil"),
+    ("difficulty_medium", "Media"),
+    ("difficulty_hard", "Difícil"),
+    ("rating_trivial", "Trivial"),
+    ("rating_easy", "Fácil"),
+    ("rating_medium", "Media"),
+    ("rating_hard", "Difícil"),
+    ("rating_brutal", "Brutal"),
+    (
+        "new_game_optimal",
+        "Nueva de {size}x{size}: {rating}, se resuelve en {moves} movimientos como mínimo",
+    ),
+    (
+        "new_game_bound",
+        "Nueva de {size}x{size}: {rating}, al menos {moves} movimientos",
+    ),
+    ("direction_up", "Arriba"),
+    ("direction_down", "Abajo"),
+    ("direction_left", "Izquierda"),
+    ("direction_right", "Derecha"),
+    ("hint", "Pista: prueba {direction}"),
+    ("no_hint", "No hay pista disponible"),
+    ("no_checkpoint", "No hay ningún punto guardado"),
+    ("scramble", "Mezcla: {moves}"),
+    ("no_scramble", "No se conoce la mezcla de este tablero"),
+    ("follow", "Vuelta al inicio: {moves} (f para parar)"),
+    (
+        "no_path_back",
+        "No se puede calcular el camino de vuelta de este tablero",
+    ),
+    (
+        "tile_entry",
+        "Ficha: {digits}_ (Intro para mover, Esc para cancelar)",
+    ),
+    ("tile_cant_move", "La ficha {tile} no puede moverse"),
+    (
+        "size_menu",
+        "Tamaño de la nueva partida: {sizes} (Esc para cancelar)",
+    ),
+    (
+        "size_confirm",
+        "¿Abandonar esta partida por una nueva de {size}x{size}? (s/n)",
+    ),
+    ("size_fixed", "Un puzle de foto no cambia de tamaño"),
+    (
+        "walkthrough_step",
+        "Movimiento {index} de {total}, quedan {remaining}",
+    ),
+    (
+        "walkthrough_keys",
+        "izq/der: paso  Inicio/Fin: principio/final  espacio: ver  q: volver",
+    ),
+    (
+        "no_solution",
+        "El solucionador no puede mostrar una solución para este tablero",
+    ),
+    (
+        "solving",
+        "{spinner} Resolviendo: {nodes} nodos explorados (Esc para cancelar)",
+    ),
+    ("solve_cancelled", "Resolución cancelada"),
+    (
+        "solve_timeout",
+        "Sin solución en {seconds} s; inténtalo de nuevo para darle el doble de tiempo",
+    ),
+    (
+        "reset_confirm",
+        "¿Empezar este puzle de nuevo desde el principio? (s/n)",
+    ),
+    ("timeline_step", "Historial: movimiento {index} / {total}"),
+    ("theme", "Tema: {theme}"),
+    (
+        "numbers_picture",
+        "Los números solo se pueden ocultar con el tema picture",
+    ),
+    ("intro_keys", "cualquier tecla: saltar al juego"),
+    (
+        "timeline_keys",
+        "izq/der: paso  Inicio/Fin: mezcla/ahora  Intro: jugar desde aquí  Esc: volver",
+    ),
+    (
+        "timeline_confirm",
+        "¿Seguir desde el movimiento {index}, descartando los {dropped} siguientes? (s/n)",
+    ),
+    (
+        "timeline_classic",
+        "El historial solo se guarda en los modos clásico y envolvente",
+    ),
+    ("yes_key", "s"),
+    ("not_your_turn", "Es el turno del jugador {player}"),
+    (
+        "replay_unsolved",
+        "Aviso: la secuencia no resuelve el tablero",
+    ),
+    ("line_status", "Movimientos: {moves}"),
+    ("line_keys", "(u, d, l, r para mover, q para salir)"),
+    ("no_tile_moves", "Ninguna ficha puede moverse hacia ahí"),
+    (
+        "not_a_move",
+        "'{key}' no es un movimiento (usa u, d, l, r o q)",
+    ),
+    (
+        "hex_keys",
+        "(ne, e, se, sw, w, nw para mover, q para salir)",
+    ),
+    (
+        "hex_not_a_move",
+        "'{word}' no es un movimiento (usa ne, e, se, sw, w, nw o q)",
+    ),
+    ("puzzle_won", "¡Resuelto en {moves} movimientos!"),
+    ("cube_layer", "Capa {layer} de {layers}"),
+    (
+        "cube_keys",
+        "flechas: mover  [ ]: mover entre capas  RePág/AvPág: otras capas  q: salir",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::En, Lang::Es];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+        }
+    }
+
+    // The language of a locale like "es_ES.UTF-8", as found in LANG
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale.split(['_', '-', '.', '@']).next()?;
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.code().eq_ignore_ascii_case(code))
+    }
+
+    // --lang wins, then LANG, then English
+    pub fn resolve(flag: Option<Lang>, env_lang: Option<&str>) -> Self {
+        flag.or_else(|| env_lang.and_then(Self::from_locale))
+            .unwrap_or_default()
+    }
+
+    fn table(self) -> Table {
+        match self {
+            Lang::En => EN,
+            Lang::Es => ES,
+        }
+    }
+
+    // The message in this language, or in English if it has no translation.
+    // An id missing from both comes back as itself so the gap is visible.
+    pub fn text(self, id: &'static str) -> &'static str {
+        let find = |table: Table| table.iter().find(|(key, _)| *key == id).map(|e| e.1);
+        find(self.table()).or_else(|| find(EN)).unwrap_or(id)
+    }
+
+    // Fills each {name} in the message with the matching argument
+    pub fn format(self, id: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let mut message = self.text(id).to_string();
+        for (name, value) in args {
+            message = message.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        message
+    }
+
+    pub fn direction(self, direction: Direction) -> &'static str {
+        self.text(match direction {
+            Direction::Up => "direction_up",
+            Direction::Down => "direction_down",
+            Direction::Left => "direction_left",
+            Direction::Right => "direction_right",
+        })
+    }
+
+    pub fn difficulty(self, level: DifficultyLevel) -> &'static str {
+        self.text(match level {
+            DifficultyLevel::Easy => "difficulty_easy",
+            DifficultyLevel::Medium => "difficulty_medium",
+            DifficultyLevel::Hard => "difficulty_hard",
+        })
+    }
+
+    pub fn rating(self, rating: Rating) -> &'static str {
+        self.text(match rating {
+            Rating::Trivial => "rating_trivial",
+            Rating::Easy => "rating_easy",
+            Rating::Medium => "rating_medium",
+            Rating::Hard => "rating_hard",
+            Rating::Brutal => "rating_brutal",
+        })
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_locale(s).ok_or_else(|| {
+            let codes: Vec<_> = Self::ALL.iter().map(|lang| lang.code()).collect();
+            format!("unknown language '{}', expected {}", s, codes.join(" or "))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The {name} placeholders in a message, in order
+    fn placeholders(message: &str) -> Vec<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_translations_match_english() {
+        for lang in Lang::ALL {
+            for (id, message) in lang.table() {
+                let english = EN.iter().find(|(key, _)| key == id);
+                let Some((_, english)) = english else {
+                    panic!("{} has '{}', which English doesn't", lang, id);
+                };
+                let (mut ours, mut theirs) = (placeholders(message), placeholders(english));
+                ours.sort();
+                theirs.sort();
+                assert_eq!(ours, theirs, "{} '{}' placeholders", lang, id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            Lang::En.format("won", &[("moves", &12)]),
+            "You won in 12 moves!"
+        );
+        assert_eq!(
+            Lang::Es.format("won", &[("moves", &12)]),
+            "¡Ganaste en 12 movimientos!"
+        );
+        assert_eq!(Lang::Es.text("no_such_message"), "no_such_message");
+        assert_eq!(Lang::Es.direction(Direction::Left), "Izquierda");
+        assert_eq!(
+            Lang::En.difficulty(DifficultyLevel::Medium),
+            DifficultyLevel::Medium.name()
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(Lang::from_locale("es_ES.UTF-8"), Some(Lang::Es));
+        assert_eq!(Lang::from_locale("C"), None);
+        assert_eq!(Lang::resolve(None, Some("es_MX")), Lang::Es);
+        assert_eq!(Lang::resolve(Some(Lang::En), Some("es_MX")), Lang::En);
+        assert_eq!(Lang::resolve(None, Some("fr_FR.UTF-8")), Lang::En);
+        assert_eq!(Lang::resolve(None, None), Lang::En);
+        assert!("klingon".parse::<Lang>().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::terminal;
+use crossterm::event::{self, Event, KeyEvent, KeyEventKind};
+use std::{
+    collections::VecDeque,
+    io,
+    time::{Duration, Instant},
+};
+
+// Terminal input for the game loops. Holding a key queues a stream of
+// presses, and after a slow frame they would all land at once, so the loops
+// call drain_repeats after each move to cut a burst of the same key down to
+// `key_repeat` moves. Anything else read while doing so waits its turn.
+pub struct Input {
+    queued: VecDeque<Event>,
+    key_repeat: u32, // 0 lets every repeat through
+}
+
+impl Input {
+    pub fn new(key_repeat: u32) -> Self {
+        Self {
+            queued: VecDeque::new(),
+            key_repeat,
+        }
+    }
+
+    // Waits for the next event, or None once a SIGINT asks to quit. Polls
+    // every tick rather than blocks so the signal is noticed without a key
+    // press.
+    pub fn next(&mut self, tick: Duration) -> io::Result<Option<Event>> {
+        if let Some(event) = self.queued.pop_front() {
+            return Ok(Some(event));
+        }
+        loop {
+            while !event::poll(tick)? {
+                if terminal::take_interrupt() {
+                    return Ok(None);
+                }
+            }
+            if let Some(event) = read()? {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    // Whether an event arrives before `deadline`, checking every tick
+    pub fn wait(&mut self, tick: Duration, deadline: Instant) -> io::Result<bool> {
+        if !self.queued.is_empty() {
+            return Ok(true);
+        }
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if event::poll(left.min(tick))? {
+                return Ok(true);
+            }
+            if left <= tick {
+                return Ok(false);
+            }
+        }
+    }
+
+    // Reads whatever is already waiting and drops the repeats of `key` past
+    // the limit, counting the press just handled
+    pub fn drain_repeats(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.key_repeat == 0 {
+            return Ok(());
+        }
+        while event::poll(Duration::ZERO)? {
+            self.queued.extend(read()?);
+        }
+        collapse(&mut self.queued, key, self.key_repeat);
+        Ok(())
+    }
+}
+
+// Windows reports releases as well as presses, which would make one press
+// count twice. Elsewhere they only arrive if asked for, which the game
+// doesn't.
+fn read() -> io::Result<Option<Event>> {
+    let event = event::read()?;
+    log::trace!("input event {:?}", event);
+    Ok(match event {
+        Event::Key(key) if key.kind == KeyEventKind::Release => None,
+        event => Some(event),
+    })
+}
+
+// Only the run of `key` at the front is a held key; a repeat after some other
+// key is a fresh press and stays
+fn collapse(queued: &mut VecDeque<Event>, key: KeyEvent, key_repeat: u32) {
+    let same = |event: &Event| matches!(event, Event::Key(next) if next.code == key.code && next.modifiers == key.modifiers);
+    let held = queued.iter().take_while(|event| same(event)).count();
+    let keep = (key_repeat as usize - 1).min(held);
+    let dropped = queued.drain(keep..held).count();
+    if dropped > 0 {
+        log::debug!("dropped {} repeats of {:?}", dropped, key.code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_collapse_keeps_other_keys() {
+        let up = KeyEvent::new(KeyCode::Up, KeyModifiers::NONE);
+        let mut queued = [
+            KeyCode::Up,
+            KeyCode::Up,
+            KeyCode::Up,
+            KeyCode::Char('q'),
+            KeyCode::Up,
+        ]
+        .into_iter()
+        .map(key)
+        .collect::<VecDeque<_>>();
+        collapse(&mut queued, up, 2);
+        assert_eq!(
+            queued,
+            [key(KeyCode::Up), key(KeyCode::Char('q')), key(KeyCode::Up)]
+        );
+
+        collapse(&mut queued, up, 1);
+        assert_eq!(queued, [key(KeyCode::Char('q')), key(KeyCode::Up)]);
+
+        let mut queued = VecDeque::from([key(KeyCode::Left), key(KeyCode::Up)]);
+        collapse(&mut queued, up, 1);
+        assert_eq!(queued.len(), 2);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    duration::{format_duration, TimeStyle},
+    i18n::Lang,
+    renderer::TextLine,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+#[cfg(feature = "tui")]
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Instant, SystemTime},
+};
+
+// How long an append waits for another game holding the lock, and how old a
+// lock has to be before it's taken to be left over from a crash
+#[cfg(feature = "tui")]
+const LOCK_WAIT: Duration = Duration::from_secs(2);
+#[cfg(feature = "tui")]
+const LOCK_RETRY: Duration = Duration::from_millis(50);
+#[cfg(feature = "tui")]
+const STALE_LOCK: Duration = Duration::from_secs(30);
+
+// One solve worth remembering
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub size: usize,
+    pub mode: String,
+    pub time_ms: u64,
+    pub moves: u32,
+    pub date: String, // As snapshot::utc_timestamp writes it
+    pub seed: Option<u64>,
+}
+
+impl LeaderboardEntry {
+    fn category(&self) -> (usize, &str) {
+        (self.size, &self.mode)
+    }
+
+    // Faster first, then fewer moves, then earlier
+    fn sort_key(&self) -> (u64, u32, &str) {
+        (self.time_ms, self.moves, &self.date)
+    }
+}
+
+// Every solve in the file, one line of JSON each so that games can append to
+// it without rewriting what's there. Scores are ranked within a category: a
+// board size and mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    // Lines that aren't an entry are skipped, each with a warning
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut entries = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => warnings.push(format!("line {}: {}", number + 1, err)),
+            }
+        }
+        (Self { entries }, warnings)
+    }
+
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries
+    }
+
+    // Every size and mode with a score, smallest boards first
+    pub fn categories(&self) -> Vec<(usize, &str)> {
+        let mut categories: Vec<_> = self.entries.iter().map(|e| e.category()).collect();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+
+    // The best `count` in one category, best first
+    pub fn top(&self, size: usize, mode: &str, count: usize) -> Vec<&LeaderboardEntry> {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.category() == (size, mode))
+            .collect();
+        entries.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        entries.truncate(count);
+        entries
+    }
+
+    // Where `entry` would place in its category, counting from 1
+    pub fn rank(&self, entry: &LeaderboardEntry) -> usize {
+        let ahead = self
+            .entries
+            .iter()
+            .filter(|other| other.category() == entry.category())
+            .filter(|other| other.sort_key() <= entry.sort_key())
+            .count();
+        ahead + 1
+    }
+
+    // The top `count` of each category, or only those for `size`, ready to
+    // show. Entries under `player` are highlighted.
+    pub fn lines(
+        &self,
+        size: Option<usize>,
+        count: usize,
+        player: Option<&str>,
+        lang: Lang,
+    ) -> Vec<TextLine> {
+        let mut lines = vec![TextLine::plain(lang.text("leaderboard_title"))];
+        let categories: Vec<_> = self
+            .categories()
+            .into_iter()
+            .filter(|&(category_size, _)| size.is_none_or(|size| size == category_size))
+            .collect();
+        if categories.is_empty() {
+            lines.push(TextLine::plain(lang.text("leaderboard_empty")));
+        }
+
+        for (size, mode) in categories {
+            lines.push(TextLine::plain(""));
+            lines.push(TextLine::plain(&lang.format(
+                "leaderboard_category",
+                &[("size", &size), ("mode", &mode)],
+            )));
+            let top = self.top(size, mode, count);
+            let width = top
+                .iter()
+                .map(|e| e.name.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (i, entry) in top.into_iter().enumerate() {
+                let time =
+                    format_duration(Duration::from_millis(entry.time_ms), TimeStyle::Precise);
+                let mut text = format!(
+                    "{:>3}. {:<width$}  {:>9}  {}  {}",
+                    i + 1,
+                    entry.name,
+                    time,
+                    lang.format("leaderboard_moves", &[("moves", &entry.moves)]),
+                    entry.date.get(..10).unwrap_or(&entry.date),
+                );
+                if let Some(seed) = entry.seed {
+                    text += "  ";
+                    text += &lang.format("leaderboard_seed", &[("seed", &seed)]);
+                }
+                lines.push(TextLine {
+                    text,
+                    highlight: player == Some(entry.name.as_str()),
+                });
+            }
+        }
+        lines
+    }
+}
+
+// The file and its lock, for the terminal game; the core keeps to the
+// entries and their ranking, with no files or clock
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+impl Leaderboard {
+    // A missing file is an empty leaderboard
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(err) => (
+                Self::default(),
+                vec![format!("couldn't read {}: {}", path.display(), err)],
+            ),
+        }
+    }
+
+    // Adds `entry` to the end of the file under a lock, so two games
+    // finishing together can't interleave their lines
+    pub fn append(path: &Path, entry: &LeaderboardEntry) -> io::Result<()> {
+        let line = serde_json::to_string(entry).expect("entries only contain plain data") + "\n";
+        let _lock = FileLock::acquire(path)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())
+    }
+}
+
+// A lock file beside the leaderboard, created only if there isn't one, and
+// removed again when dropped
+#[cfg(feature = "tui")]
+struct FileLock(PathBuf);
+
+#[cfg(feature = "tui")]
+impl FileLock {
+    fn acquire(path: &Path) -> io::Result<Self> {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".lock");
+        let lock = PathBuf::from(name);
+        let deadline = Instant::now() + LOCK_WAIT;
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&lock) {
+                Ok(_) => return Ok(Self(lock)),
+                Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return Err(err),
+                Err(_) if Instant::now() < deadline => sleep(LOCK_RETRY),
+                Err(err) => {
+                    if !is_stale(&lock) {
+                        return Err(err);
+                    }
+                    log::warn!("removing stale leaderboard lock {}", lock.display());
+                    fs::remove_file(&lock)?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn is_stale(lock: &Path) -> bool {
+    fs::metadata(lock)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= STALE_LOCK)
+}
+
+#[cfg(feature = "tui")]
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, size: usize, time_ms: u64, moves: u32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: name.to_string(),
+            size,
+            mode: "classic".to_string(),
+            time_ms,
+            moves,
+            date: "2024-03-09T17:05:42Z".to_string(),
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_skips_corrupt_lines() {
+        let good = serde_json::to_string(&entry("Ann", 3, 5000, 20)).unwrap();
+        let text = format!("{}\n\n{{\"name\": \"Bo\"}}\nnot json\n{}\n", good, good);
+        let (leaderboard, warnings) = Leaderboard::parse(&text);
+        assert_eq!(leaderboard.entries().len(), 2);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("line 3:"));
+        assert!(warnings[1].starts_with("line 4:"));
+    }
+
+    #[test]
+    fn test_ranking() {
+        let leaderboard = Leaderboard {
+            entries: vec![
+                entry("Ann", 3, 9000, 30),
+                entry("Bo", 3, 5000, 40),
+                entry("Cy", 3, 5000, 25),
+                entry("Ann", 4, 1000, 90),
+            ],
+        };
+        assert_eq!(leaderboard.categories(), [(3, "classic"), (4, "classic")]);
+        let names: Vec<_> = leaderboard
+            .top(3, "classic", 2)
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, ["Cy", "Bo"]);
+        assert_eq!(leaderboard.rank(&entry("Di", 3, 6000, 10)), 3);
+        assert_eq!(leaderboard.rank(&entry("Di", 3, 100, 10)), 1);
+        assert_eq!(leaderboard.rank(&entry("Di", 5, 9999, 10)), 1);
+
+        let lines = leaderboard.lines(Some(3), 10, Some("Ann"), Lang::En);
+        let text: Vec<_> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(
+            text,
+            [
+                "Leaderboard",
+                "",
+                "3x3 classic",
+                "  1. Cy      0:05.0  25 moves  2024-03-09",
+                "  2. Bo      0:05.0  40 moves  2024-03-09",
+                "  3. Ann     0:09.0  30 moves  2024-03-09",
+            ]
+        );
+        assert_eq!(
+            lines.iter().map(|line| line.highlight).collect::<Vec<_>>(),
+            [false, false, false, false, false, true]
+        );
+        let empty = Leaderboard::default().lines(None, 10, None, Lang::En);
+        assert_eq!(empty[1].text, "No scores yet");
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn test_append_and_load() {
+        let dir = std::env::temp_dir().join(format!("tile-game-board-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("leaderboard.jsonl");
+        let missing = Leaderboard::load(&path);
+        Leaderboard::append(&path, &entry("Ann", 3, 5000, 20)).unwrap();
+        Leaderboard::append(&path, &entry("Bo", 3, 4000, 20)).unwrap();
+        let (loaded, warnings) = Leaderboard::load(&path);
+        let lock_left = dir.join("leaderboard.jsonl.lock").exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(missing, (Leaderboard::default(), Vec::new()));
+        assert!(warnings.is_empty());
+        assert_eq!(loaded.entries().len(), 2);
+        assert_eq!(loaded.entries()[1].name, "Bo");
+        assert!(!lock_left);
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub mod backend;
+pub mod board;
+pub mod catalog;
+pub mod clock;
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub mod config;
+pub mod difficulty;
+pub mod distance_table;
+pub mod duration;
+pub mod events;
+pub mod fixed_board;
+pub mod game;
+pub mod heatmap;
+pub mod hex;
+pub mod i18n;
+pub mod leaderboard;
+pub mod nd_puzzle;
+pub mod pattern_db;
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+pub mod photo;
+pub mod puzzle_text;
+pub mod renderer;
+pub mod result;
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub mod settings;
+pub mod snapshot;
+pub mod solver;
+pub mod stats;
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub mod theme;
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub mod ui;
+pub mod visualization;
+pub mod walking_distance;
+pub mod walkthrough;
+pub mod zobrist;
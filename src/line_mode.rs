@@ -0,0 +1,123 @@
+use crate::error::GameError;
+use std::io::{self, BufRead};
+use tile_game_rs::{
+    board::{Direction, MoveSequence, Tile},
+    game::{GameMode, GameSession},
+    hex::{render_hex_board, HexBoard, HexDirection},
+    i18n::Lang,
+    renderer::{LineRenderer, RendererHandle},
+};
+
+// Plays on plain lines when stdout isn't a terminal: the board is printed
+// after every move and moves are read a line at a time from stdin
+pub fn run(session: GameSession, lang: Lang) -> Result<GameSession, GameError> {
+    let renderer = LineRenderer::new(io::stdout()).with_lang(lang);
+    let mut session = session.with_renderer(RendererHandle::new(renderer));
+    session.render()?;
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let mut chars = line.chars().filter(|ch| !ch.is_whitespace()).peekable();
+        while let Some(ch) = chars.next() {
+            session.message = None;
+            if ch.eq_ignore_ascii_case(&'q') {
+                return Ok(session);
+            }
+            // In rotation mode a number picks the tile the moves turn with
+            if ch.is_ascii_digit() && session.mode == GameMode::Rotation {
+                let mut number = ch.to_string();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    number.push(digit);
+                }
+                let tile = number.parse::<Tile>().ok();
+                session.message = Some(
+                    match tile.filter(|&tile| session.board.position_of(tile).is_some()) {
+                        Some(tile) => {
+                            session.selected = tile;
+                            lang.format("tile_picked", &[("tile", &tile)])
+                        }
+                        None => lang.format("not_a_move", &[("key", &number)]),
+                    },
+                );
+                session.render()?;
+                continue;
+            }
+            match Direction::from_char(ch) {
+                Some(direction) => {
+                    if !session.apply_move(direction) {
+                        session.message = Some(lang.text("no_tile_moves").to_string());
+                    }
+                }
+                None => session.message = Some(lang.format("not_a_move", &[("key", &ch)])),
+            }
+            session.render()?;
+            if session.is_won() {
+                return Ok(session);
+            }
+        }
+    }
+    Ok(session)
+}
+
+// The hexagonal board, always on lines: a move is a compass word, e.g. "ne",
+// and a line can hold several. Returns whether it was solved.
+pub fn run_hex(mut board: HexBoard, lang: Lang) -> Result<bool, GameError> {
+    let mut moves = 0;
+    // Laid out as LineRenderer lays out the square board
+    let show = |board: &HexBoard, moves: u32, message: Option<String>| {
+        print!("{}", render_hex_board(board));
+        match board.is_solved() {
+            true => println!("{}", lang.format("puzzle_won", &[("moves", &moves)])),
+            false => println!(
+                "{}  {}",
+                lang.format("line_status", &[("moves", &moves)]),
+                lang.text("hex_keys")
+            ),
+        }
+        if let Some(message) = message {
+            println!("{}", message);
+        }
+        println!();
+    };
+    show(&board, moves, None);
+
+    for line in io::stdin().lock().lines() {
+        for word in line?.split_whitespace() {
+            if word.eq_ignore_ascii_case("q") {
+                return Ok(false);
+            }
+            let message = match HexDirection::parse(word) {
+                Some(direction) if board.move_tile(direction) => {
+                    moves += 1;
+                    None
+                }
+                Some(_) => Some(lang.text("no_tile_moves").to_string()),
+                None => Some(lang.format("hex_not_a_move", &[("word", &word)])),
+            };
+            show(&board, moves, message);
+            if board.is_solved() {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+// --replay without a terminal prints every step straight away
+pub fn replay(
+    session: GameSession,
+    moves: &MoveSequence,
+    lang: Lang,
+) -> Result<GameSession, GameError> {
+    let renderer = LineRenderer::new(io::stdout()).with_lang(lang);
+    let mut session = session.with_renderer(RendererHandle::new(renderer));
+    session.render()?;
+    for &direction in moves.moves() {
+        session.apply_move(direction);
+        session.render()?;
+    }
+    if !session.board.is_goal(&session.goal) {
+        println!("{}", lang.text("replay_unsolved"));
+    }
+    Ok(session)
+}
@@ -0,0 +1,98 @@
+use crate::error::GameError;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    env,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+// Writes log records to a file and nowhere else, so logging can't corrupt the
+// game screen while the terminal is in raw mode
+struct FileLogger {
+    writer: Mutex<BufWriter<File>>,
+    started: Instant,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let elapsed = self.started.elapsed();
+        if let Ok(mut writer) = self.writer.lock() {
+            // A failed write has nowhere to be reported, so it's dropped
+            let _ = writeln!(
+                writer,
+                "{:>6}.{:03}s {:<5} {}: {}",
+                elapsed.as_secs(),
+                elapsed.subsec_millis(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            let _ = writer.flush();
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+// The level from --log-level, then RUST_LOG, then info
+fn resolve_level(level: Option<LevelFilter>) -> LevelFilter {
+    level
+        .or_else(|| env::var("RUST_LOG").ok()?.parse().ok())
+        .unwrap_or(LevelFilter::Info)
+}
+
+// Without a log file no logger is installed; the log macros then compare
+// against the Off max level and skip formatting entirely
+pub fn init(path: Option<&Path>, level: Option<LevelFilter>) -> Result<(), GameError> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+
+    let logger = FileLogger {
+        writer: Mutex::new(BufWriter::new(
+            File::create(path).map_err(|err| GameError::save(path, err))?,
+        )),
+        started: Instant::now(),
+    };
+    let level = resolve_level(level);
+    // Only fails if a logger is already installed, which init is never asked to do
+    let _ = log::set_boxed_logger(Box::new(logger));
+    log::set_max_level(level);
+
+    let unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    log::info!(
+        "tile-game {} started at unix time {}.{:03}, level {}",
+        env!("CARGO_PKG_VERSION"),
+        unix_ms / 1000,
+        unix_ms % 1000,
+        level
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_overrides_env_level() {
+        assert_eq!(resolve_level(Some(LevelFilter::Trace)), LevelFilter::Trace);
+    }
+}
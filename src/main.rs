@@ -1,208 +1,2199 @@
-use crossterm::{
-    cursor::{Hide, MoveTo, Show},
-    event::{self, Event, KeyCode},
-    execute,
-    style::{Print, ResetColor, SetBackgroundColor, SetForegroundColor},
-    terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
-        LeaveAlternateScreen,
-    },
-    QueueableCommand,
+mod audio;
+mod bench;
+mod cli;
+mod error;
+mod input;
+mod line_mode;
+mod logging;
+mod summary;
+mod terminal;
+
+use clap::Parser;
+use cli::{
+    BenchArgs, Cli, Command, ConfigCommand, DailyArgs, ExportArgs, JsonArgs, LeaderboardArgs, Mode,
+    PdbCommand, PlayArgs, ReplayArgs, SolveArgs, SolveFormat, StatsArgs,
 };
-use rand::seq::SliceRandom;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use error::{GameError, Outcome, EXIT_INVALID_INPUT, EXIT_OK, EXIT_PANIC};
+use input::Input;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::{
-    io::{self, Write},
-    thread::sleep,
-    time::Duration,
+    cell::Cell,
+    env, fs,
+    io::{self, IsTerminal, Read, Write},
+    ops::RangeInclusive,
+    panic,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, sleep},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use terminal::TerminalGuard;
+use tile_game_rs::{
+    board::{Board, Direction, MoveSequence, Pattern, Rotation, Tile},
+    catalog::CATALOG,
+    clock::TimeSource,
+    config::{Config, Cue},
+    difficulty::{self, DifficultyLevel, DifficultyRating},
+    distance_table,
+    events::{EventHandle, GameEvent, JsonlLogger},
+    game::{GameMode, GameSession, StartKind},
+    hex::HexBoard,
+    i18n::Lang,
+    leaderboard::{Leaderboard, LeaderboardEntry},
+    nd_puzzle::NdBoard,
+    pattern_db::{self, PatternDb, PatternDbs, PDB_SIZE},
+    puzzle_text::Puzzle,
+    renderer::{CrosstermRenderer, RendererHandle, TextLine},
+    result::{BoardSummary, GameResult},
+    settings::{self, Settings},
+    snapshot::{self, Ghost},
+    solver::{self, Heuristic, PatternDbSolver, Progress, SolveError, Solver, SolverResult, Watch},
+    theme::Theme,
+    ui::TileArt,
+    walkthrough::Walkthrough,
+};
+
+// What timed sessions read the time from: how long since this was called
+fn monotonic_time() -> TimeSource {
+    let started = Instant::now();
+    TimeSource::new(move || started.elapsed())
+}
+
+// The date by the system clock, as time since the Unix epoch
+fn wall_time() -> TimeSource {
+    TimeSource::new(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    })
+}
+
+// Photo puzzles pass the pictures to draw instead of tile numbers. `level`
+// is how hard to shuffle a new game started with 'n', or None when the size
+// can't change.
+fn run_game(
+    session: GameSession,
+    config: Config,
+    art: Option<TileArt>,
+    level: Option<DifficultyLevel>,
+) -> Result<GameSession, GameError> {
+    if !io::stdout().is_terminal() {
+        return line_mode::run(session, config.lang);
+    }
+
+    let mut renderer = CrosstermRenderer::new(config.clone());
+    if let Some(art) = art {
+        renderer = renderer.with_art(art);
+    }
+    let renderer = RendererHandle::new(renderer);
+    let mut session = session.with_renderer(renderer).with_clock(monotonic_time());
+    session.assist = config.assist;
+    audio::enable(config.sound);
+
+    // Dropping the guard restores the terminal before any error is reported,
+    // so the message is readable
+    let guard = TerminalGuard::new()?;
+    let mut input = Input::new(config.key_repeat);
+    let result = match config.shuffle_intro {
+        true => shuffle_intro(&mut session, &mut input, &config),
+        false => Ok(true),
+    }
+    .and_then(|play| match play {
+        true => game_loop(&mut session, &config, input, level),
+        false => Ok(()),
+    });
+    drop(guard);
+
+    result?;
+    Ok(session)
+}
+
+// In two-player mode a key only moves a tile on its own player's turn
+fn player_move(session: &mut GameSession, player: u8, direction: Direction, lang: Lang) -> bool {
+    on_turn(session, player, lang) && session.apply_move(direction)
+}
+
+// Shift+arrow: the whole line of tiles beyond the blank slides at once
+fn player_push(session: &mut GameSession, player: u8, direction: Direction, lang: Lang) -> bool {
+    on_turn(session, player, lang) && session.push(direction) > 0
+}
+
+fn on_turn(session: &mut GameSession, player: u8, lang: Lang) -> bool {
+    match session.current_player() {
+        Some(current) if current != player => {
+            session.message = Some(lang.format("not_your_turn", &[("player", &current)]));
+            false
+        }
+        _ => true,
+    }
+}
+
+// Player 1's arrows move one tile, or with Shift push the whole line
+fn arrow_move(session: &mut GameSession, direction: Direction, key: KeyEvent, lang: Lang) -> bool {
+    match key.modifiers.contains(KeyModifiers::SHIFT) {
+        true => player_push(session, 1, direction, lang),
+        false => player_move(session, 1, direction, lang),
+    }
+}
+
+// The sound for a key that tried to move a tile
+fn move_cue(moved: bool) -> Option<Cue> {
+    Some(match moved {
+        true => Cue::Move,
+        false => Cue::Invalid,
+    })
+}
 
-const BOARD_SIZE: usize = 3;
+// How long a digit waits for the next one on boards with two-digit tiles
+const TILE_ENTRY_WINDOW: Duration = Duration::from_millis(600);
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Board {
-    tiles: Vec<u8>, // 0 represents the blank tile
+// Digits typed toward a tile number, for moving a tile by typing it
+struct TileEntry {
+    digits: String,
+    deadline: Instant, // When the number counts as finished
 }
 
-impl Board {
-    fn new() -> Self {
-        let mut tiles = (1..=8).collect::<Vec<_>>();
-        tiles.push(0); // Add the blank tile
-        Self { tiles }
+impl TileEntry {
+    // Adds `digit` to the number being typed and returns the tile once no
+    // further digit could make a tile on this board
+    fn type_digit(entry: &mut Option<Self>, digit: char, largest: Tile) -> Option<Tile> {
+        let mut digits = entry.take().map(|entry| entry.digits).unwrap_or_default();
+        digits.push(digit);
+        let tile = digits.parse::<Tile>().unwrap_or(Tile::MAX);
+        if tile == 0 || tile.saturating_mul(10) > largest {
+            return Some(tile);
+        }
+        *entry = Some(Self {
+            digits,
+            deadline: Instant::now() + TILE_ENTRY_WINDOW,
+        });
+        None
     }
 
-    fn shuffle(&mut self) {
-        let mut rng = rand::thread_rng();
-        let possible_moves = vec![
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ];
-        let mut previous_move = None;
+    fn tile(&self) -> Tile {
+        self.digits.parse().unwrap_or(Tile::MAX)
+    }
+}
 
-        for _ in 0..100 {
-            let mut moves = possible_moves.clone();
-            if let Some(prev_move) = previous_move {
-                moves.retain(|&m| m != prev_move);
+// Slides the numbered tile into the blank for whoever's turn it is
+fn move_numbered(session: &mut GameSession, tile: Tile, lang: Lang) -> Option<Cue> {
+    // In rotation mode the number picks the tile the arrows turn with
+    if session.mode == GameMode::Rotation {
+        session.selected = tile;
+        session.message = Some(lang.format("tile_picked", &[("tile", &tile)]));
+        return None;
+    }
+    match session.board.direction_for_tile(tile) {
+        Some(direction) => {
+            let player = session.current_player().unwrap_or(1);
+            move_cue(player_move(session, player, direction, lang))
+        }
+        None => {
+            session.message = Some(lang.format("tile_cant_move", &[("tile", &tile)]));
+            Some(Cue::Invalid)
+        }
+    }
+}
+
+// Sizes the 'n' menu offers, picked with their digit
+const NEW_GAME_SIZES: RangeInclusive<usize> = 3..=6;
+
+// The 'n' menu: pick a size, then confirm if there are moves to lose
+#[derive(Debug, Clone, Copy)]
+enum SizeMenu {
+    Choosing,
+    Confirming(usize),
+}
+
+impl SizeMenu {
+    fn prompt(self, lang: Lang) -> String {
+        match self {
+            SizeMenu::Choosing => {
+                let sizes = format!("{}-{}", NEW_GAME_SIZES.start(), NEW_GAME_SIZES.end());
+                lang.format("size_menu", &[("sizes", &sizes)])
             }
+            SizeMenu::Confirming(size) => lang.format("size_confirm", &[("size", &size)]),
+        }
+    }
 
-            let direction = moves.choose(&mut rng).unwrap();
-            self.move_tile(*direction);
-            previous_move = Some(*direction);
+    // The size to start a game at, or None with `menu` left open or closed
+    fn choose(
+        self,
+        key: KeyEvent,
+        lang: Lang,
+        moved: bool,
+        menu: &mut Option<Self>,
+    ) -> Option<usize> {
+        let KeyCode::Char(ch) = key.code else {
+            return None;
+        };
+        match self {
+            SizeMenu::Choosing => {
+                let size = ch.to_digit(10).map(|digit| digit as usize);
+                match size.filter(|size| NEW_GAME_SIZES.contains(size)) {
+                    Some(size) if moved => *menu = Some(SizeMenu::Confirming(size)),
+                    Some(size) => return Some(size),
+                    None => *menu = Some(self),
+                }
+                None
+            }
+            SizeMenu::Confirming(size) => is_yes(ch, lang).then_some(size),
         }
     }
+}
 
-    fn is_solved(&self) -> bool {
-        self.tiles == [1, 2, 3, 4, 5, 6, 7, 8, 0]
+// What the player is in for, shown when a new game starts
+fn rating_message(board: &Board, lang: Lang) -> String {
+    let rating = board.difficulty_estimate();
+    let id = match rating.optimal {
+        Some(_) => "new_game_optimal",
+        None => "new_game_bound",
+    };
+    lang.format(
+        id,
+        &[
+            ("size", &board.size()),
+            ("rating", &lang.rating(rating.rating)),
+            ("moves", &rating.moves()),
+        ],
+    )
+}
+
+// Any key other than the language's yes is a no
+fn is_yes(ch: char, lang: Lang) -> bool {
+    lang.text("yes_key").starts_with(ch.to_ascii_lowercase())
+}
+
+fn reset(session: &mut GameSession) {
+    log::info!(
+        "restarting the puzzle, abandoning {} moves",
+        session.move_count
+    );
+    session.emit(GameEvent::Abandon);
+    session.reset();
+}
+
+// How often the stats strip's rolling rate is redrawn with no keys pressed
+const STATS_REFRESH: Duration = Duration::from_secs(1);
+
+// When the screen next changes without a key: the ghost reaching its next
+// snapshot or the stats strip's rate moving on. None if nothing will.
+fn redraw_due(session: &GameSession) -> Option<Instant> {
+    let elapsed = session.elapsed()?;
+    let ghost = session
+        .ghost
+        .as_ref()
+        .and_then(|ghost| ghost.next_change(elapsed))
+        .map(|next| next - elapsed);
+    let stats = session.show_stats.then_some(STATS_REFRESH);
+    Some(Instant::now() + ghost.into_iter().chain(stats).min()?)
+}
+
+// The solved board stays up for the celebration, or until a key cuts it
+// short. 'm' swaps it for the heatmap of the game's moves, up until the
+// next key. True if 'R' asked for a rematch of the same puzzle.
+fn win_screen(session: &GameSession, input: &mut Input, config: &Config) -> io::Result<bool> {
+    let tick = config.timings.tick();
+    if !input.wait(tick, Instant::now() + config.timings.celebration())? {
+        return Ok(false);
     }
+    let Some(Event::Key(key)) = input.next(tick)? else {
+        return Ok(false);
+    };
+    match key.code {
+        KeyCode::Char('R') => return Ok(true),
+        KeyCode::Char('m') => {}
+        _ => return Ok(false),
+    }
+    let heatmap = session.heatmap();
+    if let Some(renderer) = &session.renderer {
+        renderer.render_heatmap(&heatmap)?;
+    }
+    while let Some(event) = input.next(tick)? {
+        if let Event::Key(_) = event {
+            break;
+        }
+    }
+    Ok(false)
+}
 
-    fn get_blank_position(&self) -> usize {
-        self.tiles.iter().position(|&tile| tile == 0).unwrap()
+// The leaderboard in place of the board until a key comes, with the clock
+// stopped meanwhile unless racing a ghost. False for Ctrl+C, as for
+// idle_pause.
+fn leaderboard_screen(
+    session: &mut GameSession,
+    path: &Path,
+    input: &mut Input,
+    config: &Config,
+) -> io::Result<bool> {
+    let lang = config.lang;
+    let leaderboard = load_leaderboard(path);
+    let mut lines = leaderboard.lines(
+        None,
+        LEADERBOARD_SCREEN_PLACES,
+        last_name().as_deref(),
+        lang,
+    );
+    lines.push(TextLine::plain(""));
+    lines.push(TextLine::plain(lang.text("leaderboard_keys")));
+    let racing = session.ghost.is_some();
+    if !racing {
+        session.pause_clock();
     }
+    loop {
+        if let Some(renderer) = &session.renderer {
+            renderer.render_text(&lines)?;
+        }
+        let Some(event) = input.next(config.timings.tick())? else {
+            return Ok(false);
+        };
+        if let Event::Key(key) = event {
+            session.resume_clock();
+            return Ok(!is_ctrl_c(key));
+        }
+    }
+}
 
-    fn move_tile(&mut self, direction: Direction) {
-        let blank_pos = self.get_blank_position();
+// Steps back and forth through every position the game has been in, with
+// the clock stopped meanwhile unless racing a ghost. Enter plays on from the
+// position shown, once confirmed if that drops moves; Esc goes back to the
+// present as it was. False for Ctrl+C, as for idle_pause.
+fn timeline_screen(
+    session: &mut GameSession,
+    input: &mut Input,
+    config: &Config,
+) -> io::Result<bool> {
+    let lang = config.lang;
+    let racing = session.ghost.is_some();
+    if !racing {
+        session.pause_clock();
+    }
+    let mut timeline = Walkthrough::new(session.initial_board.clone(), session.moves.clone());
+    timeline.to_end();
+    // Lends the game's renderer and settings, and its clock as it stopped
+    let mut view = session.clone();
+    view.key_help = Some(lang.text("timeline_keys").to_string());
+    let mut confirming = false;
+    let keep_playing = loop {
+        view.move_count = timeline.index() as u32;
+        view.message = Some(match confirming {
+            true => lang.format(
+                "timeline_confirm",
+                &[
+                    ("index", &timeline.index()),
+                    ("dropped", &timeline.remaining()),
+                ],
+            ),
+            false => lang.format(
+                "timeline_step",
+                &[("index", &timeline.index()), ("total", &timeline.len())],
+            ),
+        });
+        if let Some(renderer) = &view.renderer {
+            renderer.render(timeline.board(), &view)?;
+        }
+        let Some(event) = input.next(config.timings.tick())? else {
+            break false;
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+        if is_ctrl_c(key) {
+            break false;
+        }
+        if confirming {
+            confirming = false;
+            if matches!(key.code, KeyCode::Char(ch) if is_yes(ch, lang)) {
+                log::info!(
+                    "rewinding to move {}, dropping {} moves",
+                    timeline.index(),
+                    timeline.remaining()
+                );
+                // The timeline only opens in classic mode, so this can't fail
+                if let Err(err) = session.rewind(timeline.index()) {
+                    log::warn!("{}", err);
+                    break false;
+                }
+                break true;
+            }
+            continue;
+        }
+        match key.code {
+            KeyCode::Left => {
+                timeline.back();
+            }
+            KeyCode::Right => {
+                timeline.forward();
+            }
+            KeyCode::Home => timeline.to_start(),
+            KeyCode::End => timeline.to_end(),
+            KeyCode::Enter if !timeline.at_end() => confirming = true,
+            KeyCode::Enter | KeyCode::Esc => break true,
+            _ => {}
+        }
+    };
+    session.resume_clock();
+    Ok(keep_playing)
+}
+
+// The solved board stays up this long before the intro starts shuffling it
+const SHUFFLE_INTRO_HOLD: Duration = Duration::from_millis(600);
+
+// Plays the scramble out from the goal, a move every shuffle step, so the
+// player sees how the start came about. Any key skips to play, which starts
+// the clock with the first move; false for Ctrl+C. Nothing to show without a
+// recorded scramble or with motion turned off.
+fn shuffle_intro(
+    session: &mut GameSession,
+    input: &mut Input,
+    config: &Config,
+) -> io::Result<bool> {
+    let timings = &config.timings;
+    let Some(scramble) = session.scramble.clone() else {
+        return Ok(true);
+    };
+    // Another mode's moves may not have made the same start from the goal
+    let mut board = session.goal.clone();
+    board.apply_moves(&scramble);
+    if timings.shuffle_step().is_zero() || board != session.initial_board {
+        return Ok(true);
+    }
+    let mut view = session.clone();
+    view.key_help = Some(config.lang.text("intro_keys").to_string());
+    let mut board = session.goal.clone();
+    let mut due = Instant::now() + SHUFFLE_INTRO_HOLD;
+    for direction in std::iter::once(None).chain(scramble.into_iter().map(Some)) {
+        if let Some(direction) = direction {
+            board.move_tile(direction);
+            due = Instant::now() + timings.shuffle_step();
+        }
+        if let Some(renderer) = &view.renderer {
+            renderer.render(&board, &view)?;
+        }
+        if input.wait(timings.tick(), due)? {
+            match input.next(timings.tick())? {
+                Some(Event::Key(key)) if is_ctrl_c(key) => return Ok(false),
+                Some(Event::Key(_)) => break,
+                Some(_) => {}
+                None => return Ok(false),
+            }
+        }
+    }
+    session.hold_clock();
+    Ok(true)
+}
 
-        let tile_to_move_pos = match direction {
-            Direction::Up => blank_pos.checked_add(BOARD_SIZE), // Move tile UP into blank space
-            Direction::Down => blank_pos.checked_sub(BOARD_SIZE), // Move tile DOWN into blank space
-            Direction::Left => blank_pos.checked_add(1).filter(|pos| pos % BOARD_SIZE != 0), // Move tile LEFT into blank space
-            Direction::Right => blank_pos
-                .checked_sub(1)
-                .filter(|pos| pos % BOARD_SIZE != BOARD_SIZE - 1), // Move tile RIGHT into blank space
+// Stops the clock under a banner until a key comes. That key only resumes
+// the game, unless it's Ctrl+C, which still quits: false for that.
+fn idle_pause(session: &mut GameSession, input: &mut Input, config: &Config) -> io::Result<bool> {
+    log::info!("idle, pausing the clock at {:?}", session.elapsed());
+    session.pause_clock();
+    session.message = Some(config.lang.text("idle").to_string());
+    loop {
+        session.render()?;
+        let Some(event) = input.next(config.timings.tick())? else {
+            return Ok(false);
         };
+        if let Event::Key(key) = event {
+            session.message = None;
+            session.resume_clock();
+            log::info!("resumed after {:?} paused", session.paused_time());
+            return Ok(!is_ctrl_c(key));
+        }
+    }
+}
+
+// Frames of the spinner shown while a solve runs, one a tick
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
-        if let Some(tile_to_move_pos) = tile_to_move_pos {
-            if tile_to_move_pos < self.tiles.len() {
-                self.tiles.swap(blank_pos, tile_to_move_pos);
+// How long the first solve in a game gets; a try after one that ran out of
+// time gets twice what that one had
+const SOLVE_TIME_LIMIT: Duration = Duration::from_secs(20);
+
+// Whether a 4x4 can be solved by the pattern databases, loading them from
+// the cache the first time
+fn pattern_db_loaded() -> bool {
+    load_pattern_db();
+    pattern_db::installed().is_some()
+}
+
+// The solver's moves from the board, found on another thread while the
+// board shows a spinner and the nodes searched so far. Other keys wait; Esc
+// cancels the search, and Ctrl+C cancels it and quits: None for that.
+fn solve_with_spinner(
+    session: &mut GameSession,
+    input: &mut Input,
+    config: &Config,
+    limit: Duration,
+) -> io::Result<Option<SolverResult>> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let nodes = Arc::new(AtomicU64::new(0));
+    let search = thread::spawn({
+        let (board, cancel, nodes) = (session.board.clone(), cancel.clone(), nodes.clone());
+        move || {
+            let started = Instant::now();
+            let mut watch = Watch::new(&cancel)
+                .with_clock(|| started.elapsed())
+                .with_time_limit(limit)
+                .with_progress(|progress: Progress| {
+                    nodes.store(progress.nodes_expanded, Ordering::Relaxed)
+                });
+            PatternDbSolver.solve_watched(&board, &mut watch)
+        }
+    });
+    let tick = config.timings.tick();
+    let mut quit = false;
+    for frame in SPINNER.iter().cycle() {
+        if search.is_finished() {
+            break;
+        }
+        let searched = nodes.load(Ordering::Relaxed);
+        session.message = Some(
+            config
+                .lang
+                .format("solving", &[("spinner", frame), ("nodes", &searched)]),
+        );
+        session.render()?;
+        if !input.wait(tick, Instant::now() + tick)? {
+            continue;
+        }
+        match input.next(tick)? {
+            Some(Event::Key(key)) if key.code == KeyCode::Esc => {
+                cancel.store(true, Ordering::Relaxed)
             }
+            Some(Event::Key(key)) if !is_ctrl_c(key) => {}
+            Some(Event::Key(_)) | None => {
+                cancel.store(true, Ordering::Relaxed);
+                quit = true;
+            }
+            Some(_) => {}
         }
     }
+    session.message = None;
+    let solved = search
+        .join()
+        .unwrap_or_else(|panic| panic::resume_unwind(panic));
+    Ok((!quit).then_some(solved))
+}
 
-    // Additional helper methods can be added here if needed
+// Says the solve ran out of time, and gives the next one twice as long
+fn solve_timed_out(limit: &mut Duration, lang: Lang) -> String {
+    let message = lang.format("solve_timeout", &[("seconds", &limit.as_secs())]);
+    *limit *= 2;
+    message
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+fn game_loop(
+    session: &mut GameSession,
+    config: &Config,
+    mut input: Input,
+    level: Option<DifficultyLevel>,
+) -> Result<(), io::Error> {
+    let (timings, lang) = (&config.timings, config.lang);
+    // 'c' pushes a checkpoint and 'p' pops back to the most recent one
+    let mut checkpoints: Vec<GameSession> = Vec::new();
+    // Played once the frame showing what caused it is on screen
+    let mut pending_cue = None;
+    let mut size_menu: Option<SizeMenu> = None;
+    let mut tile_entry: Option<TileEntry> = None;
+    // Set by 'R' while it waits for a yes
+    let mut confirming_reset = false;
+    // 'f' picks out each next move of the way back to solved
+    let mut following = false;
+    // 'T' steps through the themes
+    let mut theme = config.theme;
+    let mut last_input = Instant::now();
+    let mut solve_limit = SOLVE_TIME_LIMIT;
+
+    loop {
+        // The tile the arrows turn with in rotation mode, once one's picked
+        session.highlight = (session.selected != 0).then_some(session.selected);
+        if let Some(elapsed) = session.elapsed() {
+            session.move_rate.prune(elapsed);
+        }
+        if following {
+            match session.path_back() {
+                Some(path) => {
+                    session.highlight = path
+                        .first()
+                        .and_then(|&direction| session.board.tile_for_direction(direction));
+                    if session.message.is_none() {
+                        let moves = Direction::sequence_to_spaced(&path);
+                        session.message = Some(lang.format("follow", &[("moves", &moves)]));
+                    }
+                }
+                None => following = false,
+            }
+        }
+        if let Some(menu) = size_menu {
+            session.message = Some(menu.prompt(lang));
+        }
+        if confirming_reset {
+            session.message = Some(lang.text("reset_confirm").to_string());
+        }
+        if let Some(entry) = &tile_entry {
+            session.message = Some(lang.format("tile_entry", &[("digits", &entry.digits)]));
+        }
+        session.render()?;
+        let shown = session.message.take();
+        if let Some(cue) = pending_cue.take() {
+            audio::cue(cue);
+        }
+
+        if let Some(entry) = &tile_entry {
+            if !input.wait(timings.tick(), entry.deadline)? {
+                let tile = entry.tile();
+                tile_entry = None;
+                pending_cue = move_numbered(session, tile, lang);
+                continue;
+            }
+        }
+        // Races keep their clock running however long a key takes
+        let idle_due = match (&session.ghost, session.elapsed()) {
+            (None, Some(_)) => config.idle_pause().map(|after| last_input + after),
+            _ => None,
+        };
+        // Redraws when the ghost or the rate moves on, unless a key comes first
+        if let Some(due) = redraw_due(session).into_iter().chain(idle_due).min() {
+            if !input.wait(timings.tick(), due)? {
+                if idle_due.is_some_and(|idle| idle <= Instant::now()) {
+                    if !idle_pause(session, &mut input, config)? {
+                        break;
+                    }
+                    last_input = Instant::now();
+                }
+                session.message = shown;
+                continue;
+            }
+        }
+        let Some(event) = input.next(timings.tick())? else {
+            break;
+        };
+        last_input = Instant::now();
+        if let Event::Key(key) = event {
+            if let Some(entry) = tile_entry.take() {
+                match key.code {
+                    KeyCode::Enter => {
+                        pending_cue = move_numbered(session, entry.tile(), lang);
+                        continue;
+                    }
+                    KeyCode::Esc => continue,
+                    KeyCode::Char(digit) if digit.is_ascii_digit() => tile_entry = Some(entry),
+                    // Any other key drops the number and does what it usually does
+                    _ => {}
+                }
+            }
+            if let Some(menu) = size_menu.take() {
+                if is_ctrl_c(key) {
+                    break;
+                }
+                let chosen = menu.choose(key, lang, session.move_count > 0, &mut size_menu);
+                if let (Some(size), Some(level)) = (chosen, level) {
+                    if session.move_count > 0 {
+                        session.emit(GameEvent::Abandon);
+                    }
+                    let seed = make_rng(None).gen();
+                    log::info!(
+                        "new {}x{} game, abandoning {} moves",
+                        size,
+                        size,
+                        session.move_count
+                    );
+                    session.new_game(level, size, &mut make_rng(Some(seed)), Some(seed));
+                    session.message = Some(rating_message(&session.board, lang));
+                    checkpoints.clear();
+                }
+                continue;
+            }
+            if confirming_reset {
+                confirming_reset = false;
+                if is_ctrl_c(key) {
+                    break;
+                }
+                if matches!(key.code, KeyCode::Char(ch) if is_yes(ch, lang)) {
+                    reset(session);
+                    checkpoints.clear();
+                }
+                continue;
+            }
+
+            pending_cue = match key.code {
+                KeyCode::Up => move_cue(arrow_move(session, Direction::Up, key, lang)),
+                KeyCode::Down => move_cue(arrow_move(session, Direction::Down, key, lang)),
+                KeyCode::Left => move_cue(arrow_move(session, Direction::Left, key, lang)),
+                KeyCode::Right => move_cue(arrow_move(session, Direction::Right, key, lang)),
+                // Raw mode delivers Ctrl+C as a key rather than a signal
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    terminal::suspend()?;
+                    None
+                }
+                KeyCode::Char('h') => {
+                    // A 4x4's hint is the first move of a shortest solution
+                    // once the pattern databases are there, and otherwise the
+                    // quick guess
+                    let hint = match session.board.size() {
+                        // The solver only slides tiles
+                        _ if session.mode == GameMode::Rotation => Ok(None),
+                        PDB_SIZE if pattern_db_loaded() => {
+                            match solve_with_spinner(session, &mut input, config, solve_limit)? {
+                                Some(solved) => match solved.moves() {
+                                    Some(moves) => Ok(moves.first().copied()),
+                                    None => Err(solved),
+                                },
+                                None => break,
+                            }
+                        }
+                        _ => Ok(solver::hint(&session.board)),
+                    };
+                    session.message = Some(match hint {
+                        Ok(Some(direction)) => {
+                            session.hints_used += 1;
+                            session.emit(GameEvent::Hint(direction));
+                            lang.format("hint", &[("direction", &lang.direction(direction))])
+                        }
+                        Err(SolverResult::Cancelled) => lang.text("solve_cancelled").to_string(),
+                        Err(SolverResult::Timeout) => solve_timed_out(&mut solve_limit, lang),
+                        _ => lang.text("no_hint").to_string(),
+                    });
+                    None
+                }
+                KeyCode::Char('m') => {
+                    let scramble = match &session.rotation_scramble {
+                        Some(rotations) => Some(Rotation::sequence_to_spaced(rotations)),
+                        None => session.find_scramble().map(Direction::sequence_to_spaced),
+                    };
+                    session.message = Some(match scramble {
+                        Some(moves) => lang.format("scramble", &[("moves", &moves)]),
+                        None => lang.text("no_scramble").to_string(),
+                    });
+                    None
+                }
+                // Giving up for now: the solver's moves from here, after which
+                // the game carries on where it was
+                KeyCode::Char('v') => {
+                    let solution = match session.mode {
+                        // The solver slides tiles, which rotation mode can't,
+                        // and knows nothing of falling or pulled ones
+                        GameMode::Rotation | GameMode::Gravity | GameMode::Magnetic => {
+                            SolverResult::Unsolvable
+                        }
+                        _ if session.board.size() == PDB_SIZE && pattern_db_loaded() => {
+                            match solve_with_spinner(session, &mut input, config, solve_limit)? {
+                                Some(solved) => solved,
+                                None => break,
+                            }
+                        }
+                        // No 4x4 without the pattern databases, nor anything
+                        // bigger: both just have no solution to show
+                        _ => PatternDbSolver.solve(&session.board),
+                    };
+                    match (solution.moves(), &solution) {
+                        (Some(moves), _) => {
+                            session.hints_used += 1;
+                            let mut view = session.clone();
+                            let mut walkthrough =
+                                Walkthrough::new(session.board.clone(), moves.to_vec());
+                            walkthrough_loop(&mut view, &mut walkthrough, config)?;
+                        }
+                        (None, SolverResult::Cancelled) => {
+                            session.message = Some(lang.text("solve_cancelled").to_string())
+                        }
+                        (None, SolverResult::Timeout) => {
+                            session.message = Some(solve_timed_out(&mut solve_limit, lang))
+                        }
+                        (None, _) => session.message = Some(lang.text("no_solution").to_string()),
+                    }
+                    None
+                }
+                KeyCode::Char('f') => {
+                    if following {
+                        following = false;
+                    } else if session.path_back().is_some() {
+                        following = true;
+                    } else {
+                        session.message = Some(lang.text("no_path_back").to_string());
+                    }
+                    None
+                }
+                // Left to player 2 when it's one of their keys
+                KeyCode::Char('i')
+                    if session.current_player().is_none()
+                        || config.player2_keys.direction_for('i').is_none() =>
+                {
+                    session.show_stats = !session.show_stats;
+                    None
+                }
+                KeyCode::Char('g')
+                    if session.current_player().is_none()
+                        || config.player2_keys.direction_for('g').is_none() =>
+                {
+                    session.assist = !session.assist;
+                    if let Err(err) = save_assist(session.assist) {
+                        session.message = Some(err);
+                    }
+                    None
+                }
+                KeyCode::Char('T') => {
+                    theme = theme.next();
+                    if let Some(renderer) = &session.renderer {
+                        renderer.set_theme(theme);
+                    }
+                    session.message = Some(lang.format("theme", &[("theme", &theme.name())]));
+                    None
+                }
+                // Numbers over the picture, to play by, or the picture alone
+                KeyCode::Char('N') => {
+                    match theme {
+                        Theme::Picture => session.hide_numbers = !session.hide_numbers,
+                        _ => session.message = Some(lang.text("numbers_picture").to_string()),
+                    }
+                    None
+                }
+                // Rotations and falls aren't in the move list to step through
+                KeyCode::Char('t') => {
+                    match session.mode {
+                        GameMode::Classic | GameMode::Wraparound => {
+                            if !timeline_screen(session, &mut input, config)? {
+                                break;
+                            }
+                        }
+                        _ => session.message = Some(lang.text("timeline_classic").to_string()),
+                    }
+                    None
+                }
+                KeyCode::Char('L') => {
+                    match &config.leaderboard {
+                        Some(path) => {
+                            if !leaderboard_screen(session, path, &mut input, config)? {
+                                break;
+                            }
+                        }
+                        None => session.message = Some(lang.text("no_leaderboard").to_string()),
+                    }
+                    None
+                }
+                KeyCode::Char('c') => {
+                    log::debug!("checkpoint saved at move {}", session.move_count);
+                    checkpoints.push(session.checkpoint());
+                    None
+                }
+                KeyCode::Char('n') => {
+                    match level {
+                        Some(_) => size_menu = Some(SizeMenu::Choosing),
+                        None => session.message = Some(lang.text("size_fixed").to_string()),
+                    }
+                    None
+                }
+                // Capital so it isn't hit by accident
+                KeyCode::Char('R') => {
+                    if session.move_count > 0 {
+                        confirming_reset = true;
+                    }
+                    None
+                }
+                KeyCode::Char('p') => {
+                    match checkpoints.pop() {
+                        Some(checkpoint) => {
+                            log::debug!("restored checkpoint at move {}", checkpoint.move_count);
+                            *session = checkpoint
+                        }
+                        None => session.message = Some(lang.text("no_checkpoint").to_string()),
+                    }
+                    None
+                }
+                KeyCode::Char(digit) if digit.is_ascii_digit() => {
+                    let largest = (session.board.size() * session.board.size() - 1) as Tile;
+                    TileEntry::type_digit(&mut tile_entry, digit, largest)
+                        .and_then(|tile| move_numbered(session, tile, lang))
+                }
+                KeyCode::Esc | KeyCode::Char('q') => break,
+                // Checks that a crash mid-game still restores the terminal
+                #[cfg(feature = "debug-panic")]
+                KeyCode::Char('!') => panic!("debug panic requested"),
+                KeyCode::Char(key) if session.current_player().is_some() => {
+                    match config.player2_keys.direction_for(key) {
+                        Some(direction) => move_cue(player_move(session, 2, direction, lang)),
+                        None => None,
+                    }
+                }
+                _ => None,
+            };
+            // Only keys that tried to move a tile have a cue
+            if pending_cue.is_some() {
+                input.drain_repeats(key)?;
+            }
+
+            if session.is_won() {
+                log::info!("solved in {} moves", session.move_count);
+                session.key_help = Some(lang.text("win_keys").to_string());
+                session.render()?;
+                audio::cue(Cue::Win);
+                if !win_screen(session, &mut input, config)? {
+                    break;
+                }
+                // Already won, so not abandoned
+                log::info!("rematch, attempt {}", session.attempt + 1);
+                session.reset();
+                checkpoints.clear();
+            }
+        }
+    }
+
+    Ok(())
 }
 
-fn render_board(board: &Board) -> Result<(), io::Error> {
-    // need to add a message about pressing q to quit
+// The walkthrough in a terminal of its own, for `solve --interactive`
+fn run_walkthrough(mut walkthrough: Walkthrough, config: Config) -> Result<(), GameError> {
+    let renderer = RendererHandle::new(CrosstermRenderer::new(config.clone()));
+    let mut view = GameSession::new(walkthrough.board().clone(), StartKind::Custom, None)
+        .with_renderer(renderer);
 
-    let mut stdout = io::stdout();
+    let guard = TerminalGuard::new()?;
+    let result = walkthrough_loop(&mut view, &mut walkthrough, &config);
+    drop(guard);
 
-    stdout.queue(Clear(ClearType::All))?;
-    stdout.queue(Hide)?;
+    Ok(result?)
+}
 
-    for (i, &tile) in board.tiles.iter().enumerate() {
-        let row = i / BOARD_SIZE;
-        let col = i % BOARD_SIZE;
+// Steps through a solution with the arrows, Home and End, or plays it with
+// space, until q, Esc or Ctrl+C. `view` lends its renderer and settings to
+// draw the walkthrough's board; its own board is left alone.
+fn walkthrough_loop(
+    view: &mut GameSession,
+    walkthrough: &mut Walkthrough,
+    config: &Config,
+) -> Result<(), io::Error> {
+    let (timings, lang) = (&config.timings, config.lang);
+    let mut input = Input::new(0);
+    let mut playing = false;
+    view.key_help = Some(lang.text("walkthrough_keys").to_string());
 
-        stdout.queue(MoveTo(col as u16 * 4, row as u16 * 2))?;
+    loop {
+        view.move_count = walkthrough.index() as u32;
+        view.message = Some(lang.format(
+            "walkthrough_step",
+            &[
+                ("index", &walkthrough.index()),
+                ("total", &walkthrough.len()),
+                ("remaining", &walkthrough.remaining()),
+            ],
+        ));
+        if let Some(renderer) = &view.renderer {
+            renderer.render(walkthrough.board(), view)?;
+        }
 
-        if tile == 0 {
-            stdout.queue(Print("    "))?;
-        } else {
-            stdout.queue(SetBackgroundColor(crossterm::style::Color::DarkGrey))?;
-            stdout.queue(SetForegroundColor(crossterm::style::Color::White))?;
-            stdout.queue(Print(format!("{:^3}", tile)))?;
-            stdout.queue(ResetColor)?;
+        playing &= !walkthrough.at_end();
+        if playing && !input.wait(timings.tick(), Instant::now() + timings.autosolve_step())? {
+            walkthrough.forward();
+            continue;
         }
+        let Some(event) = input.next(timings.tick())? else {
+            return Ok(());
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+        if is_quit_key(key) {
+            return Ok(());
+        }
+        playing = match key.code {
+            KeyCode::Right => {
+                walkthrough.forward();
+                false
+            }
+            KeyCode::Left => {
+                walkthrough.back();
+                false
+            }
+            KeyCode::Home => {
+                walkthrough.to_start();
+                false
+            }
+            KeyCode::End => {
+                walkthrough.to_end();
+                false
+            }
+            // From the end, playing starts over
+            KeyCode::Char(' ') if !playing => {
+                if walkthrough.at_end() {
+                    walkthrough.to_start();
+                }
+                true
+            }
+            KeyCode::Char(' ') => false,
+            _ => playing,
+        };
+    }
+}
+
+// Plays a move sequence back one move at a time, then waits for a key so the
+// end state can be looked at. q, Esc or Ctrl+C stops the playback early.
+fn run_replay_mode(
+    session: GameSession,
+    moves: &MoveSequence,
+    config: Config,
+) -> Result<GameSession, GameError> {
+    if !io::stdout().is_terminal() {
+        return line_mode::replay(session, moves, config.lang);
     }
 
-    stdout.queue(Show)?;
-    stdout.flush()?;
+    let renderer = RendererHandle::new(CrosstermRenderer::new(config.clone()));
+    let mut session = session.with_renderer(renderer);
+
+    let guard = TerminalGuard::new()?;
+    let result = replay_loop(&mut session, moves, &config);
+    drop(guard);
+
+    result?;
+    Ok(session)
+}
 
+fn replay_loop(
+    session: &mut GameSession,
+    moves: &MoveSequence,
+    config: &Config,
+) -> Result<(), io::Error> {
+    let timings = &config.timings;
+    session.render()?;
+    for &direction in moves.moves() {
+        if event::poll(timings.autosolve_step())? {
+            if let Event::Key(key) = event::read()? {
+                if is_quit_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+        if terminal::take_interrupt() {
+            return Ok(());
+        }
+        session.apply_move(direction);
+        session.render()?;
+    }
+
+    if !session.board.is_goal(&session.goal) {
+        session.message = Some(config.lang.text("replay_unsolved").to_string());
+        session.render()?;
+    }
+    Input::new(0).next(timings.tick())?;
     Ok(())
 }
 
-fn main() -> Result<(), io::Error> {
-    let mut board = Board::new();
-    board.shuffle();
+fn is_quit_key(key: KeyEvent) -> bool {
+    matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) || is_ctrl_c(key)
+}
 
-    enable_raw_mode()?;
-    execute!(io::stdout(), EnterAlternateScreen)?;
+fn is_ctrl_c(key: KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
 
+// Split-screen race: both players start from copies of the same board, and
+// the game returned is the winner's, or player 1's if nobody finished
+fn run_versus(session: GameSession, config: Config) -> Result<GameSession, GameError> {
+    if !io::stdout().is_terminal() {
+        return Err(GameError::Config(
+            "versus mode needs a terminal".to_string(),
+        ));
+    }
+
+    let renderer = RendererHandle::new(CrosstermRenderer::new(config.clone()));
+    let mut sessions = [session.clone(), session];
+    audio::enable(config.sound);
+
+    let guard = TerminalGuard::new()?;
+    let result = versus_loop(&renderer, &mut sessions, &config);
+    drop(guard);
+
+    result?;
+    let [first, second] = sessions;
+    Ok(match second.is_won() {
+        true => second,
+        false => first,
+    })
+}
+
+fn versus_loop(
+    renderer: &RendererHandle,
+    sessions: &mut [GameSession; 2],
+    config: &Config,
+) -> Result<(), io::Error> {
+    let timings = &config.timings;
+    let mut pending_cue = None;
+    let mut input = Input::new(config.key_repeat);
     loop {
-        render_board(&board)?;
-
-        if let Event::Key(key) = event::read()? {
-            match key.code {
-                KeyCode::Up => board.move_tile(Direction::Up),
-                KeyCode::Down => board.move_tile(Direction::Down),
-                KeyCode::Left => board.move_tile(Direction::Left),
-                KeyCode::Right => board.move_tile(Direction::Right),
-                KeyCode::Esc | KeyCode::Char('q') => break,
-                _ => {}
+        renderer.render_split([&sessions[0], &sessions[1]])?;
+        if let Some(cue) = pending_cue.take() {
+            audio::cue(cue);
+        }
+
+        let Some(event) = input.next(timings.tick())? else {
+            break;
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+
+        let (player, direction) = match key.code {
+            KeyCode::Up => (0, Direction::Up),
+            KeyCode::Down => (0, Direction::Down),
+            KeyCode::Left => (0, Direction::Left),
+            KeyCode::Right => (0, Direction::Right),
+            KeyCode::Esc | KeyCode::Char('q') => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                terminal::suspend()?;
+                continue;
             }
+            KeyCode::Char(key) => match config.player2_keys.direction_for(key) {
+                Some(direction) => (1, direction),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        let session = &mut sessions[player];
+        let moved = match player == 0 && key.modifiers.contains(KeyModifiers::SHIFT) {
+            true => session.push(direction) > 0,
+            false => session.apply_move(direction),
+        };
+        if moved && session.is_won() {
+            log::info!("player {} won in {} moves", player + 1, session.move_count);
+            renderer.render_split([&sessions[0], &sessions[1]])?;
+            audio::cue(Cue::Win);
+            sleep(timings.celebration());
+            break;
+        }
+        pending_cue = move_cue(moved);
+        input.drain_repeats(key)?;
+    }
+
+    Ok(())
+}
+
+// The cube a layer at a time, in a terminal of its own. Returns whether it
+// was solved.
+fn run_cube(board: NdBoard, config: Config) -> Result<bool, GameError> {
+    if !io::stdout().is_terminal() {
+        return Err(GameError::Config("the cube needs a terminal".to_string()));
+    }
 
-            if board.is_solved() {
-                println!("You won!");
-                sleep(Duration::from_secs(2));
-                break;
+    let renderer = RendererHandle::new(CrosstermRenderer::new(config.clone()));
+    let guard = TerminalGuard::new()?;
+    let result = cube_loop(&renderer, board, &config);
+    drop(guard);
+
+    Ok(result?)
+}
+
+// The arrows slide tiles within the layer shown and [ and ] between layers,
+// after which the view follows the blank. PageUp and PageDown look at the
+// other layers.
+fn cube_loop(renderer: &RendererHandle, mut board: NdBoard, config: &Config) -> io::Result<bool> {
+    let lang = config.lang;
+    let mut input = Input::new(config.key_repeat);
+    let mut slice = board.blank_slice();
+    let mut moves = 0;
+    loop {
+        let mut lines = vec![TextLine::plain(&lang.format(
+            "cube_layer",
+            &[("layer", &(slice + 1)), ("layers", &board.slice_count())],
+        ))];
+        lines.extend(board.render_slice(slice).lines().map(TextLine::plain));
+        lines.push(TextLine::plain(""));
+        if board.is_solved() {
+            lines.push(TextLine::plain(
+                &lang.format("puzzle_won", &[("moves", &moves)]),
+            ));
+            renderer.render_text(&lines)?;
+            sleep(config.timings.celebration());
+            return Ok(true);
+        }
+        lines.push(TextLine::plain(
+            &lang.format("line_status", &[("moves", &moves)]),
+        ));
+        lines.push(TextLine {
+            text: lang.text("cube_keys").to_string(),
+            highlight: true,
+        });
+        renderer.render_text(&lines)?;
+
+        let Some(event) = input.next(config.timings.tick())? else {
+            return Ok(false);
+        };
+        let Event::Key(key) = event else {
+            continue;
+        };
+        if is_quit_key(key) {
+            return Ok(false);
+        }
+        let (axis, up) = match key.code {
+            KeyCode::Left => (0, false),
+            KeyCode::Right => (0, true),
+            KeyCode::Up => (1, false),
+            KeyCode::Down => (1, true),
+            KeyCode::Char('[') => (2, false),
+            KeyCode::Char(']') => (2, true),
+            KeyCode::PageUp => {
+                slice = slice.saturating_sub(1);
+                continue;
+            }
+            KeyCode::PageDown => {
+                slice = (slice + 1).min(board.slice_count() - 1);
+                continue;
             }
+            _ => continue,
+        };
+        if board.move_tile(axis, up) {
+            moves += 1;
+            slice = board.blank_slice();
         }
+        input.drain_repeats(key)?;
     }
+}
 
-    execute!(io::stdout(), LeaveAlternateScreen)?;
-    disable_raw_mode()?;
+// Only called once the terminal is back on the main screen, so the output isn't lost
+fn write_json(args: &JsonArgs, json: String) -> Result<(), GameError> {
+    match &args.json_out {
+        Some(path) => {
+            log::info!("writing result to {}", path.display());
+            fs::write(path, json + "\n").map_err(|err| GameError::save(path, err))
+        }
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
 
-    Ok(())
+fn print_board(board: &Board, json: &JsonArgs) -> Result<(), GameError> {
+    let summary = BoardSummary::from_board(board);
+    match &json.json_out {
+        Some(path) => {
+            fs::write(path, summary.to_json() + "\n").map_err(|err| GameError::save(path, err))
+        }
+        None if json.json => {
+            println!("{}", summary.to_json());
+            Ok(())
+        }
+        None => {
+            print!("{}", board);
+            Ok(())
+        }
+    }
+}
+
+// How many places a score has to make for the game to offer to record it,
+// and how many of each category the in-game screen lists
+const LEADERBOARD_PLACES: usize = 10;
+const LEADERBOARD_SCREEN_PLACES: usize = 5;
+
+// Kept beside the settings file, when there's somewhere to keep it
+fn data_file(name: &str) -> Option<PathBuf> {
+    Some(settings::default_path()?.with_file_name(name))
+}
+
+fn leaderboard_path(flag: Option<PathBuf>) -> Option<PathBuf> {
+    flag.or_else(|| data_file("leaderboard.jsonl"))
+}
+
+// The name last put on the leaderboard from this machine
+fn last_name() -> Option<String> {
+    let name = fs::read_to_string(data_file("player_name")?).ok()?;
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+fn save_name(name: &str) -> io::Result<()> {
+    let Some(path) = data_file("player_name") else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, format!("{}\n", name))
+}
+
+// What a command runs with: the defaults, then the settings file, the
+// TILEGAME_ variables and the command's own flags, each over the last
+fn effective_config(flags: Config) -> Result<Config, GameError> {
+    Ok(Config::merge(
+        Config::merge(Config::default(), saved_config()?),
+        Config::merge(Config::from_env(), flags),
+    ))
+}
+
+// Lines of the settings file that can't be used are only logged, but a
+// config that breaks the rules as a whole stops here, listing every problem
+fn saved_config() -> Result<Config, GameError> {
+    let path = settings::default_path();
+    let (settings, problems) = Settings::load(path.clone());
+    for problem in problems {
+        log::warn!("{}", problem);
+    }
+    if let Err(errors) = settings.current.validate() {
+        let lines: Vec<_> = errors.iter().map(|err| format!("  {}", err)).collect();
+        let path = path.map(|path| path.display().to_string());
+        return Err(GameError::Config(format!(
+            "the settings in {} need fixing:\n{}",
+            path.as_deref().unwrap_or("the settings file"),
+            lines.join("\n")
+        )));
+    }
+    Ok(settings.current)
+}
+
+fn save_assist(assist: bool) -> Result<(), String> {
+    let (mut settings, _) = Settings::load(settings::default_path());
+    settings.change(|config| config.assist = assist)
+}
+
+// Lines that can't be read are only logged, so one bad write doesn't lose
+// everyone's scores
+fn load_leaderboard(path: &Path) -> Leaderboard {
+    let (leaderboard, warnings) = Leaderboard::load(path);
+    for warning in warnings {
+        log::warn!("leaderboard {}: {}", path.display(), warning);
+    }
+    leaderboard
+}
+
+// Offers a won, timed game that made the top places of its size and mode a
+// line on the leaderboard, asking for a name on the console once the game
+// screen has gone. Games that took a hint don't count. Failing to save is a
+// warning: the game was still won.
+fn record_score(session: &GameSession, mode: &str, path: &Path, lang: Lang) {
+    let Some(elapsed) = session.elapsed() else {
+        return;
+    };
+    if !session.leaderboard_eligible() || !io::stdin().is_terminal() || !io::stdout().is_terminal()
+    {
+        return;
+    }
+    let mut entry = LeaderboardEntry {
+        name: String::new(),
+        size: session.board.size(),
+        mode: mode.to_string(),
+        time_ms: elapsed.as_millis() as u64,
+        moves: session.move_count,
+        date: snapshot::utc_timestamp(SystemTime::now()),
+        seed: session.seed,
+    };
+    let rank = load_leaderboard(path).rank(&entry);
+    if rank > LEADERBOARD_PLACES {
+        return;
+    }
+    println!(
+        "{}",
+        lang.format(
+            "leaderboard_record",
+            &[("rank", &rank), ("size", &entry.size), ("mode", &mode)],
+        )
+    );
+    let last = last_name();
+    let prompt = match &last {
+        Some(name) => lang.format("leaderboard_name", &[("name", name)]),
+        None => lang.text("leaderboard_new_name").to_string(),
+    };
+    print!("{}", prompt);
+    let mut line = String::new();
+    if io::stdout()
+        .flush()
+        .and(io::stdin().read_line(&mut line))
+        .is_err()
+    {
+        return;
+    }
+    entry.name = match (line.trim(), last) {
+        ("", Some(last)) => last,
+        ("", None) => return,
+        (typed, _) => typed.to_string(),
+    };
+
+    let saved = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| Leaderboard::append(path, &entry));
+    match saved {
+        Ok(()) => println!(
+            "{}",
+            lang.format("leaderboard_saved", &[("path", &path.display())])
+        ),
+        Err(err) => eprintln!(
+            "warning: couldn't add to the leaderboard {}: {}",
+            path.display(),
+            err
+        ),
+    }
+    if let Err(err) = save_name(&entry.name) {
+        log::warn!("couldn't remember the player's name: {}", err);
+    }
+}
+
+fn leaderboard(args: LeaderboardArgs, lang: Lang) -> Result<Outcome, GameError> {
+    let Some(path) = leaderboard_path(args.file) else {
+        return Err(GameError::Config(
+            "there's no config directory for the leaderboard, pass --file".to_string(),
+        ));
+    };
+    let (leaderboard, warnings) = Leaderboard::load(&path);
+    for warning in warnings {
+        eprintln!("warning: {}: {}", path.display(), warning);
+    }
+    let size = args.size.map(usize::from);
+    for line in leaderboard.lines(size, args.top, last_name().as_deref(), lang) {
+        println!("{}", line.text);
+    }
+    Ok(Outcome::Done)
+}
+
+fn stats(args: StatsArgs) -> Result<Outcome, GameError> {
+    let leaderboard = match leaderboard_path(args.file) {
+        Some(path) => {
+            let (leaderboard, warnings) = Leaderboard::load(&path);
+            for warning in warnings {
+                eprintln!("warning: {}: {}", path.display(), warning);
+            }
+            leaderboard
+        }
+        None if !args.results.is_empty() => Leaderboard::default(),
+        None => {
+            return Err(GameError::Config(
+                "there's no config directory for the leaderboard, pass --file".to_string(),
+            ))
+        }
+    };
+    // A file can hold one result or, one to a line, several
+    let mut results = Vec::new();
+    for path in &args.results {
+        let text = fs::read_to_string(path).map_err(|source| GameError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let result = serde_json::from_str::<GameResult>(line).map_err(|err| {
+                GameError::Config(format!("{}: line {}: {}", path.display(), number + 1, err))
+            })?;
+            results.push(result);
+        }
+    }
+    print!("{}", summary::summarize(&leaderboard, &results).table());
+    Ok(Outcome::Done)
+}
+
+fn config(command: ConfigCommand) -> Result<Outcome, GameError> {
+    let path = settings::default_path();
+    match command {
+        ConfigCommand::Show => {
+            for line in settings::describe(&effective_config(Config::default())?) {
+                println!("{}", line);
+            }
+        }
+        // Everything saved_config and the variables would only log or stop
+        // at the first of
+        ConfigCommand::Validate => {
+            let (settings, mut problems) = Settings::load(path.clone());
+            let mut config = settings.current;
+            problems.extend(settings::apply_env(&mut config, |name| env::var(name).ok()));
+            // TILEGAME_SIZE and TILEGAME_SEED, against the flags left out
+            let mut play = Cli::parse_from(["tile-game"]).play;
+            problems.extend(play.apply_env(|name| env::var(name).ok()));
+            if let Err(errors) = config.validate() {
+                problems.extend(errors.iter().map(ToString::to_string));
+            }
+            let name = path.map(|path| path.display().to_string());
+            let name = name.as_deref().unwrap_or("the settings file");
+            if !problems.is_empty() {
+                let lines: Vec<_> = problems
+                    .iter()
+                    .map(|problem| format!("  {}", problem))
+                    .collect();
+                return Err(GameError::Config(format!(
+                    "the settings in {} or the TILEGAME_ variables need fixing:\n{}",
+                    name,
+                    lines.join("\n")
+                )));
+            }
+            println!("{}: ok", name);
+        }
+        ConfigCommand::Path => match path {
+            Some(path) => println!("{}", path.display()),
+            None => {
+                return Err(GameError::Config(
+                    "there's no config directory: neither XDG_CONFIG_HOME nor HOME is set"
+                        .to_string(),
+                ))
+            }
+        },
+    }
+    Ok(Outcome::Done)
+}
+
+fn make_rng(seed: Option<u64>) -> StdRng {
+    seed.map(StdRng::seed_from_u64)
+        .unwrap_or_else(StdRng::from_entropy)
+}
+
+fn play(mut args: PlayArgs, lang: Lang) -> Result<Outcome, GameError> {
+    for problem in args.apply_env(|name| env::var(name).ok()) {
+        log::warn!("{}", problem);
+    }
+    if let Some(board) = &args.print_board {
+        print_board(board, &args.json)?;
+        return Ok(Outcome::Done);
+    }
+    if let Some(radius) = args.hex {
+        let board = HexBoard::shuffled(radius as usize, &mut make_rng(args.seed));
+        return Ok(match line_mode::run_hex(board, lang)? {
+            true => Outcome::Solved,
+            false => Outcome::Quit,
+        });
+    }
+    if let Some(size) = args.cube {
+        let board = NdBoard::shuffled(&[size as usize; 3], &mut make_rng(args.seed));
+        let config = effective_config(Config {
+            theme: args.theme,
+            lang,
+            ..Config::default()
+        })?;
+        return Ok(match run_cube(board, config)? {
+            true => Outcome::Solved,
+            false => Outcome::Quit,
+        });
+    }
+
+    let timings = args.timings().map_err(GameError::Config)?;
+    let mut board = Board::goal_state(args.size as usize);
+
+    // The scramble is kept where the start makes one; the others are worked
+    // out later if they're asked for. Random starts pick their seed up front
+    // when there isn't one, so it can be shown and played again.
+    let mut seed = args.seed;
+    let mut random_seed = || *seed.get_or_insert_with(|| make_rng(None).gen());
+    let mode = GameMode::from(args.mode);
+    let mut rotation_scramble = None;
+    let (start, scramble) = if let Some(state) = args.state {
+        board = state;
+        (StartKind::Custom, None)
+    } else if let Some(scramble) = &args.scramble {
+        board = board.scrambled_by(scramble)?;
+        (StartKind::Custom, Direction::parse_sequence(scramble).ok())
+    } else if args.no_shuffle {
+        (StartKind::Debug, Some(Vec::new()))
+    } else if let Some(locks) = args.locked {
+        board = difficulty::locked_challenge(
+            args.difficulty.into(),
+            args.size as usize,
+            locks,
+            &mut make_rng(Some(random_seed())),
+        );
+        (StartKind::Shuffled, None)
+    } else if let Some(steps) = args.moves_from_solved {
+        let moves = board.scramble_from_position(&mut make_rng(Some(random_seed())), steps);
+        (StartKind::Debug, Some(moves))
+    } else if mode == GameMode::Rotation {
+        let level: DifficultyLevel = args.difficulty.into();
+        let rng = &mut make_rng(Some(random_seed()));
+        rotation_scramble = Some(board.scramble_rotation(rng, level.shuffle_moves()));
+        (StartKind::Shuffled, None)
+    } else {
+        let moves;
+        (board, moves) = mode.shuffle(
+            args.difficulty.into(),
+            args.size as usize,
+            &mut make_rng(Some(random_seed())),
+        );
+        (StartKind::Shuffled, Some(moves))
+    };
+
+    // Across the edge of an odd width the blank moves an even distance in
+    // one slide, so every arrangement can be solved
+    let solvable = match mode {
+        GameMode::Wraparound if board.size() % 2 == 1 => true,
+        GameMode::Rotation => board.is_rotation_solvable(),
+        _ => board.is_solvable(),
+    };
+    if !solvable {
+        return Err(GameError::Unsolvable(board));
+    }
+
+    #[cfg(feature = "image")]
+    let art = match &args.image {
+        Some(path) => {
+            let photo =
+                Board::from_image(path, board.size(), args.image_fit).map_err(|source| {
+                    GameError::Image {
+                        path: path.clone(),
+                        source,
+                    }
+                })?;
+            let (width, height) = photo.fit(crossterm::terminal::size()?);
+            Some(photo.tile_art(width, height))
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "image"))]
+    let art = None;
+
+    if let Some(depth) = args.dot {
+        print!("{}", board.to_dot_graph(depth));
+        return Ok(Outcome::Done);
+    }
+
+    log::info!("starting {:?} game on {:?}", start, board.tiles());
+    let mut session = GameSession::new(board, start, seed).with_mode(mode);
+    if let Some(scramble) = scramble {
+        session = session.with_scramble(scramble);
+    }
+    if let Some(scramble) = rotation_scramble {
+        session = session.with_rotation_scramble(scramble);
+    }
+    // Snapshots and ghosts are timed even in line mode
+    if args.snapshots.is_some() {
+        session = session
+            .with_snapshots(wall_time())
+            .with_clock(monotonic_time());
+    }
+    // Without a ghost there's just nothing to race
+    if let Some(path) = &args.ghost {
+        match snapshot::load_snapshots(path) {
+            Ok(snapshots) => match Ghost::new(snapshots, session.board.size()) {
+                Some(ghost) => session = session.with_ghost(ghost).with_clock(monotonic_time()),
+                None => log::info!(
+                    "{} isn't a won {0}x{0} game, playing without a ghost",
+                    path.display()
+                ),
+            },
+            Err(err) => log::info!("couldn't read ghost {}: {}", path.display(), err),
+        }
+    }
+    if let Some(path) = &args.event_log {
+        let logger = JsonlLogger::append(path).map_err(|err| GameError::save(path, err))?;
+        session = session.with_events(EventHandle::new(logger));
+    }
+    let started = Instant::now();
+    let leaderboard_file = leaderboard_path(args.leaderboard.clone());
+    let flags = Config {
+        theme: args.theme,
+        player2_keys: args.player2_keys,
+        timings,
+        lang,
+        time_style: args.time_style,
+        sound: args.sound,
+        key_repeat: args.key_repeat,
+        idle_pause_secs: args.idle_pause,
+        leaderboard: leaderboard_file.clone(),
+        emoji_tiles: args.emoji,
+        shuffle_intro: args.shuffle_intro,
+        ..Config::default()
+    };
+    let config = effective_config(flags)?;
+    let lang = config.lang;
+    let mut session = match (args.pipe, args.mode, &args.replay) {
+        (true, Mode::Versus, _) => {
+            return Err(GameError::Config(
+                "versus mode needs the terminal UI and can't be used with --pipe".to_string(),
+            ))
+        }
+        (true, _, _) => run_pipe(session, !args.json.enabled())?,
+        (false, _, Some(moves)) => run_replay_mode(session, moves, config)?,
+        (false, Mode::Versus, None) => run_versus(session, config)?,
+        (false, _, None) => {
+            // Only the terminal UI shows labels
+            config
+                .check_tile_labels(session.board.tiles().len() - 1)
+                .map_err(GameError::Config)?;
+            let level = art.is_none().then(|| args.difficulty.into());
+            run_game(session, config, art, level)?
+        }
+    };
+    if !session.is_won() {
+        session.emit(GameEvent::Quit);
+    }
+    // Only games played by hand at the terminal make the leaderboard
+    if !args.pipe && args.replay.is_none() && args.mode != Mode::Versus {
+        if let Some(path) = &leaderboard_file {
+            record_score(&session, args.mode.name(), path, lang);
+        }
+    }
+
+    if let Some(path) = &args.snapshots {
+        log::info!(
+            "writing {} snapshots to {}",
+            session.snapshots.len(),
+            path.display()
+        );
+        snapshot::save_snapshots(path, &session.snapshots)
+            .map_err(|err| GameError::save(path, err))?;
+    }
+
+    if args.json.enabled() {
+        // Time spent suspended with Ctrl+Z or paused while idle isn't playing
+        // time
+        let elapsed = started
+            .elapsed()
+            .saturating_sub(terminal::suspended_time())
+            .saturating_sub(session.paused_time());
+        let elapsed_ms = elapsed.as_millis() as u64;
+        session.find_scramble();
+        write_json(
+            &args.json,
+            GameResult::from_session(&session, elapsed_ms, args.mode.name()).to_json(),
+        )?;
+    }
+
+    // Like pipe mode, a replay that doesn't solve the board is a failure
+    let scripted = args.pipe || args.replay.is_some();
+    Ok(match (scripted, session.board.is_goal(&session.goal)) {
+        (_, true) => Outcome::Solved,
+        (true, false) => Outcome::NotSolved,
+        (false, false) => Outcome::Quit,
+    })
+}
+
+// Applies moves read from stdin without touching the terminal, for scripts and tests
+fn run_pipe(mut session: GameSession, print_summary: bool) -> Result<GameSession, GameError> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+
+    let moves = Direction::parse_sequence(&input)?;
+    session.apply_moves(&moves);
+
+    if print_summary {
+        print!("{}", session.board);
+        println!(
+            "Moves: {}, solved: {}",
+            session.move_count,
+            if session.board.is_goal(&session.goal) {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+    }
+    Ok(session)
+}
+
+// Everyone playing on the same UTC date gets the same seed
+fn daily_seed() -> u64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or(0);
+    days ^ 0x7469_6c65_6761_6d65 // "tilegame"
+}
+
+fn daily(args: DailyArgs, lang: Lang) -> Result<Outcome, GameError> {
+    let seed = daily_seed();
+    let (board, scramble) = difficulty::shuffle_with_scramble(
+        DifficultyLevel::Medium,
+        args.size as usize,
+        &mut make_rng(Some(seed)),
+    );
+    let session = run_game(
+        GameSession::new(board, StartKind::Shuffled, Some(seed)).with_scramble(scramble),
+        effective_config(Config {
+            theme: args.theme,
+            lang,
+            ..Config::default()
+        })?,
+        None,
+        Some(DifficultyLevel::Medium),
+    )?;
+
+    Ok(match session.board.is_goal(&session.goal) {
+        true => Outcome::Solved,
+        false => Outcome::Quit,
+    })
+}
+
+fn solve(args: SolveArgs, lang: Lang) -> Result<Outcome, GameError> {
+    let started = Instant::now();
+    let mut puzzle = match &args.puzzle {
+        Some(path) => read_puzzle(path)?,
+        None => Puzzle::new(args.state.expect("clap requires --state without --puzzle")),
+    };
+    // The solvers only know the standard goal
+    if puzzle.goal != Pattern::Standard {
+        return Err(GameError::Config(
+            "solve only works towards the standard goal".to_string(),
+        ));
+    }
+    let state = puzzle.board.clone();
+    let solver: Box<dyn Solver> = match args.solver {
+        Some(kind) => kind.with(Heuristic::Manhattan),
+        None => {
+            if state.size() == PDB_SIZE {
+                load_pattern_db();
+            }
+            Box::new(PatternDbSolver)
+        }
+    };
+    // Ctrl+C already stops the command, so nothing else cancels
+    let cancel = AtomicBool::new(false);
+    let reported = Cell::new(false);
+    let mut watch = Watch::new(&cancel).with_clock(|| started.elapsed());
+    if let Some(seconds) = args.time_limit {
+        watch = watch.with_time_limit(Duration::from_secs(seconds));
+    }
+    if args.progress {
+        watch = watch.with_progress(|progress: Progress| {
+            reported.set(true);
+            eprint!(
+                "\r{} nodes, bound {}, {:.1}s",
+                progress.nodes_expanded,
+                progress.bound,
+                progress.elapsed.as_secs_f64()
+            );
+        });
+    }
+    let solved = solver.solve_watched(&state, &mut watch).into_moves();
+    // Off the end of the progress line, if there was one
+    if reported.get() {
+        eprintln!();
+    }
+    // Rated even when the board is too big to solve, as that's when the
+    // rating helps most
+    if args.analyze {
+        let rating = state.difficulty_estimate();
+        let rating = match &solved {
+            Ok(moves) => rating.with_optimal(moves.len() as u32, state.size()),
+            Err(_) => rating,
+        };
+        print_analysis(&rating);
+    }
+    // An unsolvable board is still reported as one, in JSON or as no
+    // moves; everything else that stops the solve is an error of its own
+    let solution = match solved {
+        Ok(moves) => Some(moves),
+        Err(SolveError::Unsolvable) => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    if args.json.enabled() {
+        let mut session = GameSession::new(state.clone(), StartKind::Custom, None);
+        if let Some(moves) = &solution {
+            session.scramble = Some(Direction::invert_sequence(moves));
+            session.apply_moves(moves);
+        }
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        write_json(
+            &args.json,
+            GameResult::from_session(&session, elapsed_ms, Mode::Classic.name()).to_json(),
+        )?;
+    } else if let Some(moves) = &solution {
+        if args.print_inverse {
+            let scramble = Direction::invert_sequence(moves);
+            let inverse = Direction::invert_sequence(&scramble);
+            println!("Scramble: {}", Direction::sequence_to_spaced(&scramble));
+            println!("Inverse: {}", Direction::sequence_to_spaced(&inverse));
+        }
+        if args.interactive && io::stdout().is_terminal() {
+            let walkthrough = Walkthrough::new(state.clone(), moves.clone());
+            run_walkthrough(
+                walkthrough,
+                effective_config(Config {
+                    lang,
+                    ..Config::default()
+                })?,
+            )?;
+        } else if args.format == SolveFormat::Text {
+            puzzle.solution = Some(moves.clone());
+            print!("{}", puzzle);
+        } else {
+            println!(
+                "{} ({} moves)",
+                Direction::sequence_to_string(moves),
+                moves.len()
+            );
+        }
+    }
+
+    match solution {
+        Some(_) => Ok(Outcome::Solved),
+        None => Err(GameError::Unsolvable(state)),
+    }
+}
+
+// Puzzle text from a file, or stdin for "-"
+fn read_puzzle(path: &Path) -> Result<Puzzle, GameError> {
+    let text = match path.to_str() {
+        Some("-") => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        _ => fs::read_to_string(path).map_err(|source| GameError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?,
+    };
+    Puzzle::parse(&text).map_err(|source| GameError::Puzzle {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+// A board from a snapshots file, written out as puzzle text
+fn export(args: ExportArgs) -> Result<Outcome, GameError> {
+    let snapshots =
+        snapshot::load_snapshots(&args.snapshots).map_err(|source| GameError::Read {
+            path: args.snapshots.clone(),
+            source,
+        })?;
+    let snapshot = match args.at_move {
+        Some(moves) => snapshots.iter().find(|s| s.move_count == moves),
+        None => snapshots.last(),
+    };
+    let Some(snapshot) = snapshot else {
+        let taken: Vec<_> = snapshots.iter().map(|s| s.move_count.to_string()).collect();
+        return Err(GameError::Config(format!(
+            "{} has no snapshot at that move (it has {})",
+            args.snapshots.display(),
+            match taken.is_empty() {
+                true => "none".to_string(),
+                false => taken.join(", "),
+            }
+        )));
+    };
+    print!("{}", snapshot.board.to_puzzle_text());
+    Ok(Outcome::Done)
+}
+
+fn print_analysis(rating: &DifficultyRating) {
+    println!("Manhattan distance: {}", rating.manhattan);
+    println!("Linear conflicts: {}", rating.linear_conflicts);
+    println!("Inversions: {}", rating.inversions);
+    println!("Lower bound: {} moves", rating.lower_bound);
+    match rating.optimal {
+        Some(moves) => println!("Optimal: {} moves", moves),
+        None => println!("Optimal: unknown"),
+    }
+    println!("Rating: {}", rating.rating.name());
+}
+
+fn replay(args: ReplayArgs) -> Result<Outcome, GameError> {
+    let moves = Direction::parse_sequence(&args.moves)?;
+
+    let mut board = args.state;
+    for direction in moves {
+        board.move_tile(direction);
+    }
+
+    let solved = board.is_goal(&Board::goal_state(board.size()));
+    print!("{}", board);
+    println!("{}", if solved { "Solved" } else { "Not solved" });
+
+    Ok(match solved {
+        true => Outcome::Solved,
+        false => Outcome::NotSolved,
+    })
+}
+
+fn bench_heuristics(args: BenchArgs) -> Result<Outcome, GameError> {
+    let report = bench::run(&args);
+    if args.json.enabled() {
+        write_json(&args.json, report.to_json())?;
+    } else {
+        print!("{}", report.table());
+    }
+    Ok(Outcome::Done)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_new_board() {
-        let board = Board::new();
-        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
-    }
-
-    #[test]
-    fn test_shuffle_board() {
-        let mut board = Board::new();
-        let initial_board = board.clone();
-        board.shuffle();
-        assert_ne!(board, initial_board);
-    }
-
-    #[test]
-    fn test_is_solved() {
-        let mut board = Board::new();
-        board.shuffle();
-        assert!(!board.is_solved());
-        board.tiles = vec![1, 2, 3, 4, 5, 6, 7, 8, 0];
-        assert!(board.is_solved());
-    }
-
-    #[test]
-    fn test_get_blank_position() {
-        let board = Board::new();
-        assert_eq!(board.get_blank_position(), 8);
-    }
-
-    #[test]
-    fn test_move_tiles() {
-        let mut board = Board::new();
-        board.move_tile(Direction::Up);
-        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
-        board.move_tile(Direction::Left);
-        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
-        board.move_tile(Direction::Down);
-        assert_eq!(board.tiles, [1, 2, 3, 4, 5, 0, 7, 8, 6]);
-        board.move_tile(Direction::Right);
-        assert_eq!(board.tiles, [1, 2, 3, 4, 0, 5, 7, 8, 6]);
-        board.move_tile(Direction::Right);
-        assert_eq!(board.tiles, [1, 2, 3, 0, 4, 5, 7, 8, 6]);
-        board.move_tile(Direction::Down);
-        assert_eq!(board.tiles, [0, 2, 3, 1, 4, 5, 7, 8, 6]);
-        board.move_tile(Direction::Up);
-        assert_eq!(board.tiles, [1, 2, 3, 0, 4, 5, 7, 8, 6]);
+// $XDG_CACHE_HOME/tilegame, falling back to ~/.cache/tilegame
+fn cache_dir() -> Option<PathBuf> {
+    let cache = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".cache")))?;
+    Some(cache.join("tilegame"))
+}
+
+fn distance_table_path() -> Option<PathBuf> {
+    Some(cache_dir()?.join("distance_table.bin"))
+}
+
+// One file per group, named for its tiles: pdb-4x4-1-5-6-9-10-13.bin
+fn pattern_db_path(dir: &Path, group: &[Tile]) -> PathBuf {
+    let tiles: Vec<_> = group.iter().map(Tile::to_string).collect();
+    dir.join(format!("pdb-{0}x{0}-{1}.bin", PDB_SIZE, tiles.join("-")))
+}
+
+fn read_pattern_db(dir: &Path, group: &[Tile]) -> Option<PatternDb> {
+    let bytes = fs::read(pattern_db_path(dir, group)).ok()?;
+    PatternDb::from_bytes(&bytes, PDB_SIZE, group)
+}
+
+// Installs the pattern databases from the cache when every one of them is
+// there and current. Missing ones aren't built here: solving says how.
+fn load_pattern_db() {
+    if pattern_db::installed().is_some() {
+        return;
+    }
+    let Some(dir) = cache_dir() else {
+        return;
+    };
+    let tables: Option<Vec<_>> = pattern_db::GROUPS
+        .iter()
+        .map(|group| read_pattern_db(&dir, group))
+        .collect();
+    match tables {
+        Some(tables) => {
+            pattern_db::install(PatternDbs::new(tables));
+        }
+        None => log::info!("no pattern databases in {}", dir.display()),
+    }
+}
+
+// Builds each group's table that isn't cached already and writes it there,
+// with the progress on stderr as it goes
+fn build_pattern_db() -> Result<Outcome, GameError> {
+    let dir = cache_dir().ok_or_else(|| {
+        GameError::Config("no cache directory: set XDG_CACHE_HOME or HOME".to_string())
+    })?;
+    let groups = pattern_db::GROUPS;
+    for (number, group) in groups.iter().enumerate() {
+        let path = pattern_db_path(&dir, group);
+        let tiles: Vec<_> = group.iter().map(Tile::to_string).collect();
+        let label = format!(
+            "Group {} of {} ({})",
+            number + 1,
+            groups.len(),
+            tiles.join(" ")
+        );
+        if read_pattern_db(&dir, group).is_some() {
+            eprintln!("{}: already built", label);
+            continue;
+        }
+        let started = Instant::now();
+        let states = pattern_db::state_count(PDB_SIZE, group.len());
+        let table = PatternDb::build(PDB_SIZE, group, |settled| {
+            eprint!("\r{}: {}%", label, settled * 100 / states);
+        });
+        fs::create_dir_all(&dir)
+            .and_then(|()| fs::write(&path, table.to_bytes()))
+            .map_err(|err| GameError::save(&path, err))?;
+        eprintln!(
+            "\r{}: {} states in {:.1}s, written to {}",
+            label,
+            states,
+            started.elapsed().as_secs_f64(),
+            path.display()
+        );
+    }
+    Ok(Outcome::Done)
+}
+
+// Reads the 3x3 distance table from the cache, or builds it and caches it.
+// The cache is only an optimization, so a missing, stale or unwritable file
+// just means building the table again.
+fn precompute() {
+    let started = Instant::now();
+    let path = distance_table_path();
+    let cached = path
+        .as_deref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| distance_table::from_bytes(&bytes));
+    let source = match cached {
+        Some(_) => "read from cache",
+        None => "computed",
+    };
+
+    let table = cached.unwrap_or_else(|| {
+        let table = distance_table::compute();
+        if let Some(path) = &path {
+            let saved = fs::create_dir_all(path.parent().unwrap_or(path))
+                .and_then(|()| fs::write(path, distance_table::to_bytes(&table)));
+            match saved {
+                Ok(()) => log::info!("cached distance table in {}", path.display()),
+                Err(err) => log::warn!(
+                    "couldn't cache distance table in {}: {}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+        table
+    });
+
+    let boards = table.len();
+    distance_table::install(table);
+    eprintln!(
+        "Distance table {}: {} boards in {:.2}s",
+        source,
+        boards,
+        started.elapsed().as_secs_f64()
+    );
+}
+
+fn catalog() {
+    for entry in CATALOG {
+        let state: Vec<_> = entry.board().tiles().iter().map(Tile::to_string).collect();
+        println!(
+            "{:<10} {:<40} {}",
+            entry.name,
+            state.join(" "),
+            entry.description
+        );
+    }
+}
+
+fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            // --help and --version also arrive here, as "errors" printed to stdout
+            let _ = err.print();
+            process::exit(match err.use_stderr() {
+                true => EXIT_INVALID_INPUT,
+                false => EXIT_OK,
+            });
+        }
+    };
+
+    // The panic hook still prints the message; only the exit code changes
+    terminal::install_panic_hook();
+    terminal::install_interrupt_handler();
+    let code = match panic::catch_unwind(|| run(cli)) {
+        Ok(Ok(outcome)) => outcome.exit_code(),
+        Ok(Err(err)) => {
+            log::error!("{}", err);
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+        Err(_) => EXIT_PANIC,
+    };
+    process::exit(code);
+}
+
+fn run(cli: Cli) -> Result<Outcome, GameError> {
+    logging::init(cli.log_file.as_deref(), cli.log_level)?;
+    let lang = Lang::resolve(cli.lang, env::var("LANG").ok().as_deref());
+    if cli.precompute {
+        precompute();
+    }
+
+    match cli.command.unwrap_or(Command::Play(cli.play)) {
+        Command::Play(args) => play(args, lang),
+        Command::Leaderboard(args) => leaderboard(args, lang),
+        Command::Stats(args) => stats(args),
+        Command::Solve(args) => solve(args, lang),
+        Command::Daily(args) => daily(args, lang),
+        Command::Replay(args) => replay(args),
+        Command::Export(args) => export(args),
+        Command::Catalog => {
+            catalog();
+            Ok(Outcome::Done)
+        }
+        Command::BenchHeuristics(args) => bench_heuristics(args),
+        Command::Pdb(PdbCommand::Build) => build_pattern_db(),
+        Command::Config(command) => config(command),
     }
 }
@@ -0,0 +1,288 @@
+use crate::board::Tile;
+use rand::{seq::SliceRandom, Rng};
+
+// Long enough for a 4x4x4 cube to come out well mixed
+const SHUFFLE_STEPS_PER_TILE: usize = 25;
+
+// The sliding puzzle with any number of dimensions: a 3x3x3 cube is 26
+// tiles and a blank, each able to slide along any of three axes. Cells are
+// in order with the first axis changing fastest, so a cube is its layers
+// along the last axis one after another, each read row by row. The goal is
+// 1 upwards in that order with the blank last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NdBoard {
+    tiles: Vec<Tile>, // 0 represents the blank tile
+    dimensions: Vec<usize>,
+}
+
+// A move: the axis a tile slides along, and whether it goes up the axis
+// rather than down. On the first two axes, down is left and up.
+pub type NdMove = (usize, bool);
+
+impl NdBoard {
+    // Every dimension needs at least 2 cells, or the puzzle can't be mixed
+    pub fn goal_state(dimensions: &[usize]) -> Self {
+        assert!(
+            dimensions.len() >= 2 && dimensions.iter().all(|&len| len >= 2),
+            "a board needs two or more dimensions of 2 or more cells, not {:?}",
+            dimensions
+        );
+        let cells: usize = dimensions.iter().product();
+        let mut tiles: Vec<Tile> = (1..cells as Tile).collect();
+        tiles.push(0);
+        Self {
+            tiles,
+            dimensions: dimensions.to_vec(),
+        }
+    }
+
+    pub fn cube(size: usize) -> Self {
+        Self::goal_state(&[size; 3])
+    }
+
+    // A random walk away from solved, so it can always be solved again
+    pub fn shuffled(dimensions: &[usize], rng: &mut impl Rng) -> Self {
+        let mut board = Self::goal_state(dimensions);
+        let mut last: Option<NdMove> = None;
+        for _ in 0..board.tiles.len() * SHUFFLE_STEPS_PER_TILE {
+            // Never straight back the way the last move came
+            let moves: Vec<_> = board
+                .valid_moves()
+                .into_iter()
+                .filter(|&(axis, up)| last != Some((axis, !up)))
+                .collect();
+            if let Some(&(axis, up)) = moves.choose(rng) {
+                board.move_tile(axis, up);
+                last = Some((axis, up));
+            }
+        }
+        board
+    }
+
+    pub fn dimensions(&self) -> &[usize] {
+        &self.dimensions
+    }
+
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::goal_state(&self.dimensions)
+    }
+
+    // Slides the tile that moves along `axis` into the blank, up the axis
+    // if `up`. Returns whether there was one.
+    pub fn move_tile(&mut self, axis: usize, up: bool) -> bool {
+        match self.move_source(axis, up) {
+            Some(source) => {
+                let blank = self.blank_position();
+                self.tiles.swap(blank, source);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn valid_moves(&self) -> Vec<NdMove> {
+        (0..self.dimensions.len())
+            .flat_map(|axis| [(axis, false), (axis, true)])
+            .filter(|&(axis, up)| self.move_source(axis, up).is_some())
+            .collect()
+    }
+
+    // The same test as the square board's: every move swaps the blank with
+    // a tile and moves it one cell, so the permutation's parity has to match
+    // how far the blank is from home
+    pub fn is_solvable(&self) -> bool {
+        let cells = self.tiles.len();
+        // Where each cell's tile belongs, the blank in the last cell
+        let goal: Vec<usize> = self
+            .tiles
+            .iter()
+            .map(|&tile| match tile {
+                0 => cells - 1,
+                tile => tile as usize - 1,
+            })
+            .collect();
+        let mut seen = vec![false; cells];
+        let mut swaps = 0;
+        for start in 0..cells {
+            let mut cell = start;
+            let mut length = 0;
+            while !seen[cell] {
+                seen[cell] = true;
+                cell = goal[cell];
+                length += 1;
+            }
+            swaps += length.max(1) - 1;
+        }
+        let blank_distance = self.distance(self.blank_position(), cells - 1);
+        swaps % 2 == blank_distance % 2
+    }
+
+    // Sum over the tiles of how many single moves each is from its goal cell
+    pub fn manhattan_distance(&self) -> usize {
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|&(_, &tile)| tile != 0)
+            .map(|(cell, &tile)| self.distance(cell, tile as usize - 1))
+            .sum()
+    }
+
+    // The 2D layers across the first two axes, one for every combination of
+    // the rest: a cube's layers along its third axis
+    pub fn slice_count(&self) -> usize {
+        self.dimensions[2..].iter().product()
+    }
+
+    // The layer holding the blank
+    pub fn blank_slice(&self) -> usize {
+        self.blank_position() / self.slice_len()
+    }
+
+    // One layer as Board prints the square board: rows along the first axis
+    // stacked down the second, the blank a '.'
+    pub fn render_slice(&self, slice: usize) -> String {
+        let width = (self.tiles.len() - 1).to_string().len();
+        let layer = &self.tiles[slice * self.slice_len()..][..self.slice_len()];
+        let mut output = String::new();
+        for row in layer.chunks(self.dimensions[0]) {
+            let cells: Vec<_> = row
+                .iter()
+                .map(|&tile| match tile {
+                    0 => format!("{:>width$}", "."),
+                    tile => format!("{:>width$}", tile),
+                })
+                .collect();
+            output += &cells.join(" ");
+            output.push('\n');
+        }
+        output
+    }
+
+    fn slice_len(&self) -> usize {
+        self.dimensions[0] * self.dimensions[1]
+    }
+
+    fn blank_position(&self) -> usize {
+        self.tiles
+            .iter()
+            .position(|&tile| tile == 0)
+            .expect("a board always has a blank")
+    }
+
+    // The tile comes from the side of the blank opposite the way it moves
+    fn move_source(&self, axis: usize, up: bool) -> Option<usize> {
+        let blank = self.blank_position();
+        let mut coordinates = self.coordinates(blank);
+        let len = *self.dimensions.get(axis)?;
+        coordinates[axis] = match up {
+            true => coordinates[axis].checked_sub(1)?,
+            false => Some(coordinates[axis] + 1).filter(|&c| c < len)?,
+        };
+        Some(self.index(&coordinates))
+    }
+
+    // A cell's place along each axis
+    fn coordinates(&self, mut index: usize) -> Vec<usize> {
+        self.dimensions
+            .iter()
+            .map(|&len| {
+                let coordinate = index % len;
+                index /= len;
+                coordinate
+            })
+            .collect()
+    }
+
+    fn index(&self, coordinates: &[usize]) -> usize {
+        coordinates
+            .iter()
+            .zip(&self.dimensions)
+            .rev()
+            .fold(0, |index, (&coordinate, &len)| index * len + coordinate)
+    }
+
+    fn distance(&self, a: usize, b: usize) -> usize {
+        self.coordinates(a)
+            .iter()
+            .zip(self.coordinates(b))
+            .map(|(&a, b)| a.abs_diff(b))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_goal_state() {
+        let cube = NdBoard::cube(3);
+        assert_eq!(cube.tiles().len(), 27);
+        assert_eq!(cube.slice_count(), 3);
+        assert!(cube.is_solved());
+        assert!(cube.is_solvable());
+        assert_eq!(cube.manhattan_distance(), 0);
+        assert_eq!(NdBoard::goal_state(&[2, 2, 2, 2]).slice_count(), 4);
+    }
+
+    #[test]
+    fn test_coordinates_round_trip() {
+        let board = NdBoard::goal_state(&[2, 3, 4]);
+        for index in 0..board.tiles().len() {
+            assert_eq!(board.index(&board.coordinates(index)), index);
+        }
+        assert_eq!(board.coordinates(7), [1, 0, 1]);
+    }
+
+    #[test]
+    fn test_move_tile() {
+        // The blank starts in the far corner, so tiles can only come up
+        // each axis into it
+        let mut cube = NdBoard::cube(3);
+        assert_eq!(cube.valid_moves(), [(0, true), (1, true), (2, true)]);
+        assert!(!cube.move_tile(2, false));
+        assert!(!cube.move_tile(3, true));
+
+        // 18 comes up the third axis from the layer before, then goes back
+        assert!(cube.move_tile(2, true));
+        assert_eq!(cube.tiles()[17..], [0, 19, 20, 21, 22, 23, 24, 25, 26, 18]);
+        assert_eq!(cube.blank_slice(), 1);
+        assert_eq!(cube.manhattan_distance(), 1);
+        assert_eq!(cube.valid_moves().len(), 4);
+        assert!(!cube.is_solved());
+
+        assert!(cube.move_tile(2, false));
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn test_is_solvable() {
+        let mut cube = NdBoard::cube(2);
+        cube.tiles.swap(0, 1);
+        assert!(!cube.is_solvable());
+        cube.tiles.swap(2, 3);
+        assert!(cube.is_solvable());
+    }
+
+    #[test]
+    fn test_shuffled() {
+        let cube = NdBoard::shuffled(&[3, 3, 3], &mut StdRng::seed_from_u64(7));
+        assert!(!cube.is_solved());
+        assert!(cube.is_solvable());
+        let mut tiles = cube.tiles().to_vec();
+        tiles.sort_unstable();
+        assert_eq!(tiles, (0..27).collect::<Vec<Tile>>());
+    }
+
+    #[test]
+    fn test_render_slice() {
+        let cube = NdBoard::cube(3);
+        assert_eq!(cube.render_slice(0), " 1  2  3\n 4  5  6\n 7  8  9\n");
+        assert_eq!(cube.render_slice(2), "19 20 21\n22 23 24\n25 26  .\n");
+    }
+}
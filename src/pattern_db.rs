@@ -0,0 +1,339 @@
+use crate::board::{Board, Tile};
+use std::sync::OnceLock;
+
+// The size the databases are for, and its standard split of the 15 tiles:
+// the left two columns below 1, the right two below the top row, and the
+// rest of the top row. Each group's table counts only its own tiles' moves,
+// so the three costs add up to a bound on the whole board.
+pub const PDB_SIZE: usize = 4;
+pub const GROUPS: [&[Tile]; 3] = [&[1, 5, 6, 9, 10, 13], &[7, 8, 11, 12, 14, 15], &[2, 3, 4]];
+
+// Start of a saved table, bumped whenever the layout changes
+const MAGIC: &[u8; 6] = b"TGPDB1";
+
+// A state packs each tile's cell and the blank's in four bits apiece
+const MAX_CELLS: usize = 16;
+const MAX_GROUP: usize = 7;
+
+static INSTALLED: OnceLock<PatternDbs> = OnceLock::new();
+
+// For one group of tiles, the fewest moves of those tiles that bring them
+// home from each placement, wherever the blank and the other tiles are
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternDb {
+    size: usize,
+    group: Vec<Tile>,
+    costs: Vec<u8>, // By rank of the group's cells
+}
+
+// Ways to put `k` tiles on `cells` cells, each number of them no more than
+// that: cells * (cells - 1) * ... for k factors
+fn placements(cells: usize, k: usize) -> usize {
+    (cells - k + 1..=cells).product()
+}
+
+// How many states a build settles: every placement with the blank on each of
+// the cells left over
+pub fn state_count(size: usize, k: usize) -> u64 {
+    let cells = size * size;
+    (placements(cells, k) * (cells - k)) as u64
+}
+
+// The group's cells as one number below placements(): each counted among
+// the cells the tiles before it left free
+fn rank(positions: &[usize], cells: usize) -> usize {
+    positions.iter().enumerate().fold(0, |rank, (i, &cell)| {
+        let before = positions[..i].iter().filter(|&&other| other < cell).count();
+        rank * (cells - i) + cell - before
+    })
+}
+
+fn pack(positions: &[usize], blank: usize) -> u32 {
+    positions
+        .iter()
+        .fold(blank as u32, |state, &cell| state << 4 | cell as u32)
+}
+
+fn unpack(mut state: u32, k: usize) -> ([usize; MAX_GROUP], usize) {
+    let mut positions = [0; MAX_GROUP];
+    for position in positions[..k].iter_mut().rev() {
+        *position = (state & 0xF) as usize;
+        state >>= 4;
+    }
+    (positions, state as usize)
+}
+
+fn neighbors(cell: usize, size: usize) -> impl Iterator<Item = usize> {
+    let (row, col) = (cell / size, cell % size);
+    [
+        (row > 0).then(|| cell - size),
+        (row + 1 < size).then(|| cell + size),
+        (col > 0).then(|| cell - 1),
+        (col + 1 < size).then(|| cell + 1),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+impl PatternDb {
+    // Breadth-first search back from the goal over the group's tiles and the
+    // blank, with the other tiles left anonymous. The blank swapping with one
+    // of those is free, so each level runs as a stack until only moves of
+    // the group's own tiles are left, which make the next level. `progress`
+    // hears every so often how many states have been settled, out of
+    // state_count.
+    pub fn build(size: usize, group: &[Tile], mut progress: impl FnMut(u64)) -> Self {
+        let cells = size * size;
+        let k = group.len();
+        assert!(
+            cells <= MAX_CELLS && k <= MAX_GROUP && k < cells,
+            "a pattern database holds up to {} tiles of a board up to 4x4",
+            MAX_GROUP
+        );
+        let mut costs = vec![u8::MAX; placements(cells, k)];
+        let mut visited = vec![0u64; (costs.len() * cells).div_ceil(64)];
+        let goal: Vec<usize> = group.iter().map(|&tile| tile as usize - 1).collect();
+        let mut level = vec![pack(&goal, cells - 1)];
+        let mut settled = 0u64;
+        let mut cost = 0u8;
+
+        while !level.is_empty() {
+            let mut next = Vec::new();
+            while let Some(state) = level.pop() {
+                let (positions, blank) = unpack(state, k);
+                let placement = rank(&positions[..k], cells);
+                let index = placement * cells + blank;
+                if visited[index / 64] & 1 << (index % 64) != 0 {
+                    continue;
+                }
+                visited[index / 64] |= 1 << (index % 64);
+                settled += 1;
+                if settled.is_multiple_of(1 << 20) {
+                    progress(settled);
+                }
+                costs[placement] = costs[placement].min(cost);
+
+                for into in neighbors(blank, size) {
+                    match positions[..k].iter().position(|&cell| cell == into) {
+                        None => {
+                            let index = placement * cells + into;
+                            if visited[index / 64] & 1 << (index % 64) == 0 {
+                                level.push(pack(&positions[..k], into));
+                            }
+                        }
+                        Some(tile) => {
+                            let mut moved = positions;
+                            moved[tile] = blank;
+                            next.push(pack(&moved[..k], into));
+                        }
+                    }
+                }
+            }
+            level = next;
+            cost += 1;
+        }
+        progress(settled);
+        Self {
+            size,
+            group: group.to_vec(),
+            costs,
+        }
+    }
+
+    pub fn group(&self) -> &[Tile] {
+        &self.group
+    }
+
+    // `cells[tile]` is where each tile of the board is
+    fn cost_at(&self, cells: &[usize]) -> u8 {
+        let mut positions = [0; MAX_GROUP];
+        for (position, &tile) in positions.iter_mut().zip(&self.group) {
+            *position = cells[tile as usize];
+        }
+        self.costs[rank(&positions[..self.group.len()], self.size * self.size)]
+    }
+
+    pub fn cost(&self, board: &Board) -> u8 {
+        self.cost_at(&tile_cells(board))
+    }
+
+    // The magic bytes, the size, the group's length and tiles, then the
+    // costs in rank order
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 2 + self.group.len() + self.costs.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.size as u8);
+        bytes.push(self.group.len() as u8);
+        bytes.extend(self.group.iter().map(|&tile| tile as u8));
+        bytes.extend_from_slice(&self.costs);
+        bytes
+    }
+
+    // None for anything but a complete table for `group` on `size`, such as
+    // a file written by another version or cut short
+    pub fn from_bytes(bytes: &[u8], size: usize, group: &[Tile]) -> Option<Self> {
+        let rest = bytes.strip_prefix(MAGIC)?;
+        let (&[saved_size, k], rest) = rest.split_first_chunk()?;
+        let (tiles, costs) = rest.split_at_checked(k as usize)?;
+        let matches = saved_size as usize == size
+            && tiles
+                .iter()
+                .map(|&tile| tile as Tile)
+                .eq(group.iter().copied())
+            && costs.len() == placements(size * size, group.len());
+        matches.then(|| Self {
+            size,
+            group: group.to_vec(),
+            costs: costs.to_vec(),
+        })
+    }
+}
+
+// By tile number, the cell it's on
+fn tile_cells(board: &Board) -> Vec<usize> {
+    let mut cells = vec![0; board.tiles().len()];
+    for (cell, &tile) in board.tiles().iter().enumerate() {
+        cells[tile as usize] = cell;
+    }
+    cells
+}
+
+// Tables for groups that share no tile, whose costs add up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternDbs {
+    tables: Vec<PatternDb>,
+}
+
+impl PatternDbs {
+    pub fn new(tables: Vec<PatternDb>) -> Self {
+        Self { tables }
+    }
+
+    pub fn estimate(&self, board: &Board) -> u32 {
+        let cells = tile_cells(board);
+        self.tables
+            .iter()
+            .map(|table| u32::from(table.cost_at(&cells)))
+            .sum()
+    }
+}
+
+// Makes `tables` the ones the solver uses. Returns false if some were
+// already in place, in which case `tables` is dropped.
+pub fn install(tables: PatternDbs) -> bool {
+    INSTALLED.set(tables).is_ok()
+}
+
+// Only what was installed: the tables take far too long to build on the way
+// to a solve
+pub fn installed() -> Option<&'static PatternDbs> {
+    INSTALLED.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::Direction,
+        solver::{ida_star, ida_star_by, Heuristic},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // Small enough to build in a test, and disjoint like the real split
+    const SMALL_GROUPS: [&[Tile]; 5] = [
+        &[1, 2, 5],
+        &[3, 4, 8],
+        &[6, 7, 11],
+        &[9, 10, 13],
+        &[12, 14, 15],
+    ];
+
+    fn small_tables() -> PatternDbs {
+        PatternDbs::new(
+            SMALL_GROUPS
+                .iter()
+                .map(|group| PatternDb::build(4, group, |_| {}))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_rank_is_a_bijection() {
+        let mut seen = vec![false; placements(16, 2)];
+        for a in 0..16 {
+            for b in (0..16).filter(|&b| b != a) {
+                let rank = rank(&[a, b], 16);
+                assert!(!seen[rank]);
+                seen[rank] = true;
+            }
+        }
+        assert!(seen.iter().all(|&seen| seen));
+        assert_eq!(unpack(pack(&[3, 15, 0], 9), 3), ([3, 15, 0, 0, 0, 0, 0], 9));
+    }
+
+    #[test]
+    fn test_build() {
+        let mut reported = 0;
+        let table = PatternDb::build(4, &[2, 3, 4], |settled| reported = settled);
+        assert_eq!(reported, state_count(4, 3));
+        assert_eq!(table.cost(&Board::goal_state(4)), 0);
+        assert!(table.costs.iter().all(|&cost| cost < u8::MAX));
+
+        // Moves of other tiles are free: only the 4 coming down counts
+        let mut board = Board::goal_state(4);
+        board.apply_moves(&Direction::parse_sequence("DD").unwrap());
+        assert_eq!(table.cost(&board), 0);
+        board.move_tile(Direction::Down);
+        assert_eq!(table.cost(&board), 1);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let table = PatternDb::build(3, &[1, 2, 3], |_| {});
+        let bytes = table.to_bytes();
+        assert_eq!(PatternDb::from_bytes(&bytes, 3, &[1, 2, 3]), Some(table));
+        assert_eq!(PatternDb::from_bytes(&bytes, 3, &[1, 2, 4]), None);
+        assert_eq!(PatternDb::from_bytes(&bytes, 4, &[1, 2, 3]), None);
+        assert_eq!(PatternDb::from_bytes(&bytes[..40], 3, &[1, 2, 3]), None);
+        assert_eq!(PatternDb::from_bytes(b"TGPDB0", 3, &[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_additive_and_admissible() {
+        let tables = small_tables();
+        let mut rng = StdRng::seed_from_u64(5);
+        let (mut manhattan_nodes, mut pdb_nodes) = (0, 0);
+        for _ in 0..6 {
+            let mut board = Board::goal_state(4);
+            board.scramble_from_position(&mut rng, 30);
+            let optimal = ida_star(&board, Heuristic::WalkingDistance).unwrap();
+            let estimate = tables.estimate(&board);
+            // Each group's own moves are part of any solution, and no move
+            // is counted by two groups
+            assert!(
+                estimate <= optimal.moves.len() as u32,
+                "{:?}",
+                board.tiles()
+            );
+            assert!(
+                estimate >= board.manhattan_distance(),
+                "{:?}",
+                board.tiles()
+            );
+
+            let search = ida_star_by(&board, "pattern db", |board| tables.estimate(board));
+            let search = search.unwrap();
+            assert_eq!(search.moves.len(), optimal.moves.len());
+            pdb_nodes += search.nodes_expanded;
+            manhattan_nodes += ida_star(&board, Heuristic::Manhattan)
+                .unwrap()
+                .nodes_expanded;
+        }
+        assert!(
+            pdb_nodes < manhattan_nodes,
+            "{} nodes against {} for Manhattan distance",
+            pdb_nodes,
+            manhattan_nodes
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    board::{Board, MAX_SIZE, MIN_SIZE},
+    ui::TileArt,
+};
+use clap::ValueEnum;
+use image::{
+    error::{ParameterError, ParameterErrorKind},
+    imageops::{self, FilterType},
+    DynamicImage, GenericImageView, ImageError, RgbaImage,
+};
+use std::path::Path;
+
+// What to do with a photo that isn't square
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImageFit {
+    /// Keep all of it; the tiles take its shape
+    #[default]
+    Whole,
+    /// Cut a square from the middle
+    Crop,
+    /// Pad it out to a square with black bars
+    Letterbox,
+}
+
+impl ImageFit {
+    pub fn apply(self, image: DynamicImage) -> DynamicImage {
+        let (width, height) = image.dimensions();
+        let side = match self {
+            ImageFit::Whole => return image,
+            ImageFit::Crop => width.min(height),
+            ImageFit::Letterbox => width.max(height),
+        };
+        let (x, y) = ((width.abs_diff(side)) / 2, (height.abs_diff(side)) / 2);
+        match self {
+            ImageFit::Crop => image.crop_imm(x, y, side, side),
+            _ => {
+                let mut square = RgbaImage::from_pixel(side, side, [0, 0, 0, 255].into());
+                imageops::overlay(&mut square, &image, x.into(), y.into());
+                DynamicImage::ImageRgba8(square)
+            }
+        }
+    }
+}
+
+// A picture cut into tiles. The board starts solved: tile n is the nth piece
+// counting left to right, top to bottom, and the bottom right piece is the
+// one hidden under the blank.
+#[derive(Debug, Clone)]
+pub struct PhotoPuzzle {
+    pub board: Board,
+    pub tiles: Vec<DynamicImage>, // In the same order, the hidden piece last
+}
+
+impl Board {
+    pub fn from_image(path: &Path, size: usize, fit: ImageFit) -> Result<PhotoPuzzle, ImageError> {
+        PhotoPuzzle::cut(&fit.apply(image::open(path)?), size)
+    }
+}
+
+fn parameter_error(kind: ParameterErrorKind) -> ImageError {
+    ImageError::Parameter(ParameterError::from_kind(kind))
+}
+
+impl PhotoPuzzle {
+    // Pixels that don't divide evenly are trimmed off the right and bottom
+    pub fn cut(image: &DynamicImage, size: usize) -> Result<Self, ImageError> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+            return Err(parameter_error(ParameterErrorKind::Generic(format!(
+                "board size must be from {} to {}, not {}",
+                MIN_SIZE, MAX_SIZE, size
+            ))));
+        }
+        let (width, height) = image.dimensions();
+        let (tile_width, tile_height) = (width / size as u32, height / size as u32);
+        if tile_width == 0 || tile_height == 0 {
+            return Err(parameter_error(ParameterErrorKind::DimensionMismatch));
+        }
+
+        let tiles = (0..size * size)
+            .map(|i| {
+                let (x, y) = ((i % size) as u32, (i / size) as u32);
+                image.crop_imm(x * tile_width, y * tile_height, tile_width, tile_height)
+            })
+            .collect();
+        Ok(Self {
+            board: Board::goal_state(size),
+            tiles,
+        })
+    }
+
+    // The largest tiles that fit the terminal with the status lines, keeping
+    // the pieces' shape. A cell shows two pixels stacked, so a square piece
+    // is twice as wide in cells as it is tall.
+    pub fn fit(&self, (columns, rows): (u16, u16)) -> (u16, u16) {
+        let size = self.board.size() as u32;
+        let max_width = ((columns as u32 + 1) / size).saturating_sub(1).max(1);
+        let max_height = (rows.saturating_sub(2) as u32 / size)
+            .saturating_sub(1)
+            .max(1);
+        let (piece_width, piece_height) = self.tiles[0].dimensions();
+
+        let height = (max_width * piece_height / (piece_width * 2)).clamp(1, max_height);
+        let width = (height * 2 * piece_width / piece_height).clamp(1, max_width);
+        (width as u16, height as u16)
+    }
+
+    // Every piece scaled down to `width` by `height` cells
+    pub fn tile_art(&self, width: u16, height: u16) -> TileArt {
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.resize_exact(width as u32, height as u32 * 2, FilterType::Triangle)
+                    .to_rgb8()
+                    .pixels()
+                    .map(|pixel| pixel.0)
+                    .collect()
+            })
+            .collect();
+        TileArt {
+            width,
+            height,
+            tiles,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    // Each quarter of a 2x2 grid in its own color
+    fn quarters() -> DynamicImage {
+        let colors = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        DynamicImage::ImageRgb8(RgbImage::from_fn(40, 20, |x, y| {
+            Rgb(colors[(y / 10 * 2 + x / 20) as usize])
+        }))
+    }
+
+    #[test]
+    fn test_cut() {
+        let puzzle = PhotoPuzzle::cut(&quarters(), 2).unwrap();
+        assert_eq!(puzzle.board, Board::goal_state(2));
+        assert_eq!(puzzle.tiles.len(), 4);
+        assert_eq!(puzzle.tiles[1].dimensions(), (20, 10));
+        assert_eq!(puzzle.tiles[1].get_pixel(5, 5).0, [0, 255, 0, 255]);
+
+        let art = puzzle.tile_art(4, 1);
+        assert_eq!(art.tiles.len(), 4);
+        assert_eq!(art.tiles[2].len(), 4 * 2);
+        assert!(art.tiles[2].iter().all(|&pixel| pixel == [0, 0, 255]));
+    }
+
+    #[test]
+    fn test_cut_rejects_bad_sizes() {
+        assert!(PhotoPuzzle::cut(&quarters(), 1).is_err());
+        let tiny = DynamicImage::ImageRgb8(RgbImage::new(2, 2));
+        assert!(PhotoPuzzle::cut(&tiny, 3).is_err());
+    }
+
+    #[test]
+    fn test_from_image() {
+        let path = std::env::temp_dir().join(format!("tile-game-{}.png", std::process::id()));
+        quarters().save(&path).unwrap();
+        let puzzle = Board::from_image(&path, 2, ImageFit::Whole);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(puzzle.unwrap().tiles[3].get_pixel(0, 0).0, [255; 4]);
+
+        let missing = Board::from_image(Path::new("/no/such/photo.png"), 3, ImageFit::Whole);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_image_fit() {
+        // The 40x20 quarters: red and green across the top
+        let cropped = ImageFit::Crop.apply(quarters());
+        assert_eq!(cropped.dimensions(), (20, 20));
+        assert_eq!(cropped.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(cropped.get_pixel(19, 0).0, [0, 255, 0, 255]);
+
+        let boxed = ImageFit::Letterbox.apply(quarters());
+        assert_eq!(boxed.dimensions(), (40, 40));
+        assert_eq!(boxed.get_pixel(0, 9).0, [0, 0, 0, 255]);
+        assert_eq!(boxed.get_pixel(0, 10).0, [255, 0, 0, 255]);
+        assert_eq!(ImageFit::Whole.apply(quarters()).dimensions(), (40, 20));
+    }
+
+    #[test]
+    fn test_fit_keeps_shape() {
+        // 20x10 pieces are twice as wide as tall, so four times as many
+        // cells wide as tall
+        let puzzle = PhotoPuzzle::cut(&quarters(), 2).unwrap();
+        assert_eq!(puzzle.fit((81, 42)), (40, 10));
+        assert_eq!(puzzle.fit((3, 3)), (1, 1));
+    }
+}
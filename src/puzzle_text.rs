@@ -0,0 +1,606 @@
+// A plain-text format for passing puzzles, and optionally their solutions,
+// to and from other tools:
+//
+//     puzzle 3x3 goal=snake seed=42
+//     1 2 3
+//     4 . 5
+//     7 8 6
+//     SOLUTION RD
+//
+// The header names the size, then any of these as key=value:
+//
+//     goal  the solved layout: standard (the default), snake, reverse or
+//           center_blank, as Pattern names them
+//     seed  the number the board was shuffled from
+//
+// A line per row follows, tiles separated by whitespace, with the blank as
+// '.' or 0. Last, and optional, is a SOLUTION line in move notation (U, D,
+// L, R; whitespace ignored), which has to be legal from the board and end
+// on the goal. Blank lines and lines starting with '#' are skipped wherever
+// they are. Errors give the line and column where the problem starts, both
+// counting from 1.
+//
+// Written with the header keys in that order, values only when they aren't
+// the default, and the rows aligned as Board's Display aligns them. Any
+// text that parses writes back the same puzzle.
+use crate::board::{Board, BoardError, Direction, Pattern, Tile, MAX_SIZE, MIN_SIZE};
+use std::{fmt, str::FromStr};
+
+const HEADER: &str = "puzzle";
+const SOLUTION: &str = "SOLUTION";
+
+// Everything a puzzle text can say
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub board: Board,
+    pub goal: Pattern,
+    pub seed: Option<u64>,
+    pub solution: Option<Vec<Direction>>,
+}
+
+impl Puzzle {
+    // A board with the standard goal and nothing else known about it
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            goal: Pattern::Standard,
+            seed: None,
+            solution: None,
+        }
+    }
+
+    pub fn goal_board(&self) -> Board {
+        Board::goal_state_for_pattern(self.board.size(), self.goal)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, PuzzleTextError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (number + 1, line))
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+        let last_line = text.lines().count().max(1);
+
+        let (header_line, header) = lines.next().ok_or(PuzzleTextError::at(
+            last_line,
+            1,
+            PuzzleTextErrorKind::MissingHeader,
+        ))?;
+        let (size, goal, seed) = parse_header(header_line, header)?;
+
+        let cells = size * size;
+        let mut tiles = Vec::with_capacity(cells);
+        // Where each tile was written, to point at duplicates and strays
+        let mut places = Vec::with_capacity(cells);
+        for row in 0..size {
+            let (number, line) = lines.next().ok_or(PuzzleTextError::at(
+                last_line,
+                1,
+                PuzzleTextErrorKind::MissingRows {
+                    expected: size,
+                    found: row,
+                },
+            ))?;
+            let tokens = tokens(line);
+            if tokens.len() != size {
+                let column = tokens.get(size).map_or(1, |&(column, _)| column);
+                return Err(PuzzleTextError::at(
+                    number,
+                    column,
+                    PuzzleTextErrorKind::RowLength {
+                        expected: size,
+                        found: tokens.len(),
+                    },
+                ));
+            }
+            for (column, token) in tokens {
+                let tile = match token {
+                    "." => 0,
+                    _ => token.parse::<Tile>().map_err(|_| {
+                        PuzzleTextError::at(
+                            number,
+                            column,
+                            PuzzleTextErrorKind::Board(BoardError::InvalidNumber {
+                                token: token.to_string(),
+                            }),
+                        )
+                    })?,
+                };
+                tiles.push(tile);
+                places.push((number, column));
+            }
+        }
+
+        let mut seen = vec![false; cells];
+        for (&tile, &(number, column)) in tiles.iter().zip(&places) {
+            let kind = match seen.get_mut(tile as usize) {
+                None => BoardError::InvalidTile { tile },
+                Some(true) => BoardError::DuplicateTile { tile },
+                Some(slot) => {
+                    *slot = true;
+                    continue;
+                }
+            };
+            return Err(PuzzleTextError::at(
+                number,
+                column,
+                PuzzleTextErrorKind::Board(kind),
+            ));
+        }
+        let board = Board::from_tiles(tiles)
+            .map_err(|err| PuzzleTextError::at(header_line, 1, PuzzleTextErrorKind::Board(err)))?;
+        let mut puzzle = Self {
+            board,
+            goal,
+            seed,
+            solution: None,
+        };
+
+        if let Some((number, line)) = lines.next() {
+            let start = line.len() - line.trim_start().len();
+            let moves = line[start..]
+                .strip_prefix(SOLUTION)
+                .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+                .ok_or_else(|| {
+                    PuzzleTextError::at(
+                        number,
+                        column_of(line, start),
+                        PuzzleTextErrorKind::Unexpected {
+                            found: tokens(line)[0].1.to_string(),
+                        },
+                    )
+                })?;
+            let offset = line.len() - moves.len();
+            puzzle.solution = Some(parse_solution(&puzzle, number, line, offset)?);
+        }
+        if let Some((number, line)) = lines.next() {
+            let (column, token) = tokens(line)[0];
+            return Err(PuzzleTextError::at(
+                number,
+                column,
+                PuzzleTextErrorKind::Unexpected {
+                    found: token.to_string(),
+                },
+            ));
+        }
+        Ok(puzzle)
+    }
+}
+
+// The size, goal and seed from the header line, `number`
+fn parse_header(
+    number: usize,
+    line: &str,
+) -> Result<(usize, Pattern, Option<u64>), PuzzleTextError> {
+    let error = |column, kind| PuzzleTextError::at(number, column, kind);
+    let tokens = tokens(line);
+    let (column, first) = tokens[0];
+    if first != HEADER {
+        return Err(error(column, PuzzleTextErrorKind::MissingHeader));
+    }
+    let Some(&(column, dimensions)) = tokens.get(1) else {
+        return Err(error(
+            line.chars().count() + 1,
+            PuzzleTextErrorKind::BadSize {
+                found: String::new(),
+            },
+        ));
+    };
+    let bad_size = || {
+        error(
+            column,
+            PuzzleTextErrorKind::BadSize {
+                found: dimensions.to_string(),
+            },
+        )
+    };
+    let (rows, cols) = dimensions.split_once('x').ok_or_else(bad_size)?;
+    let size: usize = rows.parse().map_err(|_| bad_size())?;
+    if cols.parse() != Ok(size) || !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+        return Err(bad_size());
+    }
+
+    let (mut goal, mut seed) = (Pattern::Standard, None);
+    for &(column, token) in &tokens[2..] {
+        let (key, value) = token.split_once('=').unwrap_or((token, ""));
+        let bad_value = || {
+            error(
+                column + key.chars().count() + 1,
+                PuzzleTextErrorKind::BadValue {
+                    key: key.to_string(),
+                    found: value.to_string(),
+                },
+            )
+        };
+        match key {
+            "goal" => {
+                goal = Pattern::ALL
+                    .into_iter()
+                    .find(|pattern| pattern_name(*pattern) == value && pattern.fits(size))
+                    .ok_or_else(bad_value)?;
+            }
+            "seed" => seed = Some(value.parse().map_err(|_| bad_value())?),
+            _ => {
+                return Err(error(
+                    column,
+                    PuzzleTextErrorKind::UnknownKey {
+                        key: key.to_string(),
+                    },
+                ))
+            }
+        }
+    }
+    Ok((size, goal, seed))
+}
+
+// The moves after SOLUTION, which start at byte `offset` of `line`
+fn parse_solution(
+    puzzle: &Puzzle,
+    number: usize,
+    line: &str,
+    offset: usize,
+) -> Result<Vec<Direction>, PuzzleTextError> {
+    let mut board = puzzle.board.clone();
+    let mut moves = Vec::new();
+    for (index, ch) in line[offset..].char_indices() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        let column = column_of(line, offset + index);
+        let direction = Direction::from_char(ch).ok_or(PuzzleTextError::at(
+            number,
+            column,
+            PuzzleTextErrorKind::InvalidMove { ch },
+        ))?;
+        if !board.move_tile(direction) {
+            return Err(PuzzleTextError::at(
+                number,
+                column,
+                PuzzleTextErrorKind::IllegalMove { direction },
+            ));
+        }
+        moves.push(direction);
+    }
+    if !board.is_goal(&puzzle.goal_board()) {
+        return Err(PuzzleTextError::at(
+            number,
+            line.chars().count() + 1,
+            PuzzleTextErrorKind::NotSolved,
+        ));
+    }
+    Ok(moves)
+}
+
+// Each whitespace-separated token of `line` with the column it starts at
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (index, ch) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (start, ch.is_whitespace()) {
+            (None, false) => start = Some(index),
+            (Some(from), true) => {
+                tokens.push((column_of(line, from), &line[from..index]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+// Columns count characters, not bytes
+fn column_of(line: &str, byte: usize) -> usize {
+    line[..byte].chars().count() + 1
+}
+
+// As the header writes them, the same names Pattern serializes to
+fn pattern_name(pattern: Pattern) -> &'static str {
+    match pattern {
+        Pattern::Standard => "standard",
+        Pattern::Snake => "snake",
+        Pattern::Reverse => "reverse",
+        Pattern::CenterBlank => "center_blank",
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.board.size();
+        write!(f, "{} {}x{}", HEADER, size, size)?;
+        if self.goal != Pattern::Standard {
+            write!(f, " goal={}", pattern_name(self.goal))?;
+        }
+        if let Some(seed) = self.seed {
+            write!(f, " seed={}", seed)?;
+        }
+        write!(f, "\n{}", self.board)?;
+        match &self.solution {
+            Some(moves) if moves.is_empty() => writeln!(f, "{}", SOLUTION),
+            Some(moves) => writeln!(f, "{} {}", SOLUTION, Direction::sequence_to_string(moves)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = PuzzleTextError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Board {
+    // Just the board, with the standard goal
+    pub fn to_puzzle_text(&self) -> String {
+        Puzzle::new(self.clone()).to_string()
+    }
+
+    // The board from a puzzle text, whatever else it says
+    pub fn from_puzzle_text(text: &str) -> Result<Board, PuzzleTextError> {
+        Puzzle::parse(text).map(|puzzle| puzzle.board)
+    }
+}
+
+// What's wrong with a puzzle text, and where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleTextError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: PuzzleTextErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleTextErrorKind {
+    MissingHeader,
+    BadSize { found: String },
+    UnknownKey { key: String },
+    BadValue { key: String, found: String },
+    RowLength { expected: usize, found: usize },
+    MissingRows { expected: usize, found: usize },
+    Board(BoardError), // A tile that isn't a number, is out of range or repeats
+    InvalidMove { ch: char },
+    IllegalMove { direction: Direction },
+    NotSolved,
+    Unexpected { found: String },
+}
+
+impl PuzzleTextError {
+    fn at(line: usize, column: usize, kind: PuzzleTextErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl fmt::Display for PuzzleTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            PuzzleTextErrorKind::MissingHeader => {
+                write!(f, "expected a header like \"{} 3x3\"", HEADER)
+            }
+            PuzzleTextErrorKind::BadSize { found } => write!(
+                f,
+                "'{}' isn't a board size like 3x3, from {} to {}",
+                found, MIN_SIZE, MAX_SIZE
+            ),
+            PuzzleTextErrorKind::UnknownKey { key } => {
+                write!(f, "unknown header key '{}' (use goal or seed)", key)
+            }
+            PuzzleTextErrorKind::BadValue { key, found } => {
+                write!(f, "'{}' isn't a {} for this board", found, key)
+            }
+            PuzzleTextErrorKind::RowLength { expected, found } => {
+                write!(
+                    f,
+                    "expected {} tiles in the row but found {}",
+                    expected, found
+                )
+            }
+            PuzzleTextErrorKind::MissingRows { expected, found } => {
+                write!(f, "expected {} rows but found {}", expected, found)
+            }
+            PuzzleTextErrorKind::Board(err) => write!(f, "{}", err),
+            PuzzleTextErrorKind::InvalidMove { ch } => {
+                write!(f, "'{}' is not a move (use U, D, L, R)", ch)
+            }
+            PuzzleTextErrorKind::IllegalMove { direction } => {
+                write!(f, "move '{}' can't be made here", direction.to_char())
+            }
+            PuzzleTextErrorKind::NotSolved => write!(f, "the solution doesn't reach the goal"),
+            PuzzleTextErrorKind::Unexpected { found } => {
+                write!(f, "unexpected '{}' after the board", found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleTextError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> (usize, usize, PuzzleTextErrorKind) {
+        let err = Puzzle::parse(text).unwrap_err();
+        (err.line, err.column, err.kind)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(board.to_puzzle_text(), "puzzle 3x3\n1 2 3\n4 . 5\n7 8 6\n");
+        assert_eq!(
+            Board::from_puzzle_text(&board.to_puzzle_text()),
+            Ok(board.clone())
+        );
+
+        let puzzle = Puzzle {
+            board: "1 2 3 4 5 6 7 0 8".parse().unwrap(),
+            goal: Pattern::Standard,
+            seed: Some(42),
+            solution: Some(vec![Direction::Left]),
+        };
+        let text = puzzle.to_string();
+        assert_eq!(
+            text,
+            "puzzle 3x3 seed=42\n1 2 3\n4 5 6\n7 . 8\nSOLUTION L\n"
+        );
+        assert_eq!(text.parse(), Ok(puzzle));
+
+        // Every goal, and sizes whose tiles need padding
+        for pattern in Pattern::ALL {
+            let size = 5;
+            let puzzle = Puzzle {
+                board: Board::goal_state_for_pattern(size, pattern),
+                goal: pattern,
+                seed: None,
+                solution: Some(Vec::new()),
+            };
+            assert_eq!(Puzzle::parse(&puzzle.to_string()), Ok(puzzle));
+        }
+    }
+
+    #[test]
+    fn test_lenient_layout() {
+        let text =
+            "# from another tool\n\n  puzzle 2x2 goal=reverse\n\t0  3\n 2 1\n# done\nSOLUTION\n";
+        let puzzle = Puzzle::parse(text).unwrap();
+        assert_eq!(puzzle.board.tiles(), [0, 3, 2, 1]);
+        assert_eq!(puzzle.goal, Pattern::Reverse);
+        assert_eq!(puzzle.solution, Some(Vec::new()));
+        let puzzle = Puzzle::parse("puzzle 2x2\n1 2\n3 .\nSOLUTION  r l\n").unwrap();
+        assert_eq!(
+            puzzle.solution,
+            Some(vec![Direction::Right, Direction::Left])
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        use PuzzleTextErrorKind::*;
+        assert_eq!(error(""), (1, 1, MissingHeader));
+        assert_eq!(error("1 2\n3 0\n"), (1, 1, MissingHeader));
+        let bad_size = |found: &str| BadSize {
+            found: found.to_string(),
+        };
+        assert_eq!(error("puzzle 2x3\n"), (1, 8, bad_size("2x3")));
+        assert_eq!(error("puzzle 1x1\n0\n"), (1, 8, bad_size("1x1")));
+        assert_eq!(error("puzzle"), (1, 7, bad_size("")));
+        assert_eq!(
+            error("puzzle 2x2 level=3\n"),
+            (
+                1,
+                12,
+                UnknownKey {
+                    key: "level".to_string()
+                }
+            )
+        );
+        assert_eq!(
+            error("puzzle 2x2 goal=center_blank\n"),
+            (
+                1,
+                17,
+                BadValue {
+                    key: "goal".to_string(),
+                    found: "center_blank".to_string()
+                }
+            )
+        );
+        assert_eq!(
+            error("puzzle 2x2 seed=-1\n"),
+            (
+                1,
+                17,
+                BadValue {
+                    key: "seed".to_string(),
+                    found: "-1".to_string()
+                }
+            )
+        );
+
+        assert_eq!(
+            error("puzzle 2x2\n1 2 3\n0\n"),
+            (
+                2,
+                5,
+                RowLength {
+                    expected: 2,
+                    found: 3
+                }
+            )
+        );
+        assert_eq!(
+            error("puzzle 2x2\n1 2\n"),
+            (
+                2,
+                1,
+                MissingRows {
+                    expected: 2,
+                    found: 1
+                }
+            )
+        );
+        assert_eq!(
+            error("puzzle 2x2\n1 x\n3 0\n"),
+            (
+                2,
+                3,
+                Board(BoardError::InvalidNumber {
+                    token: "x".to_string()
+                })
+            )
+        );
+        assert_eq!(
+            error("puzzle 2x2\n1 2\n2 0\n"),
+            (3, 1, Board(BoardError::DuplicateTile { tile: 2 }))
+        );
+        assert_eq!(
+            error("puzzle 2x2\n1 2\n4 0\n"),
+            (3, 1, Board(BoardError::InvalidTile { tile: 4 }))
+        );
+
+        let board = "puzzle 2x2\n1 2\n3 .\n";
+        assert_eq!(
+            error(&format!("{}SOLUTION RX\n", board)),
+            (4, 11, InvalidMove { ch: 'X' })
+        );
+        assert_eq!(
+            error(&format!("{}SOLUTION R L  L\n", board)),
+            (
+                4,
+                15,
+                IllegalMove {
+                    direction: Direction::Left
+                }
+            )
+        );
+        assert_eq!(error(&format!("{}SOLUTION R\n", board)), (4, 11, NotSolved));
+        assert_eq!(
+            error(&format!("{}solution\n", board)),
+            (
+                4,
+                1,
+                Unexpected {
+                    found: "solution".to_string()
+                }
+            )
+        );
+        assert_eq!(
+            error(&format!("{}SOLUTION\n1 2\n", board)),
+            (
+                5,
+                1,
+                Unexpected {
+                    found: "1".to_string()
+                }
+            )
+        );
+        assert_eq!(
+            Puzzle::parse("puzzle 2x2\n1 2\n2 0\n")
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 1: tile 2 appears more than once"
+        );
+    }
+}
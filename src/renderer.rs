@@ -0,0 +1,380 @@
+#[cfg(feature = "tui")]
+use crate::{
+    backend::{Backend, CrosstermBackend},
+    config::Config,
+    game::GameMode,
+    stats,
+    theme::Theme,
+    ui::{self, GhostState, GoalColors, SplitState, StatsState, TileArt, TurnState, UiState},
+};
+use crate::{board::Board, game::GameSession, heatmap::Heatmap, i18n::Lang};
+#[cfg(feature = "tui")]
+use std::time::{Duration, Instant};
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, Write},
+    rc::Rc,
+};
+
+// Draws whole game screens. The board is passed separately from the session
+// so callers can show a board other than the one being played.
+pub trait Renderer {
+    fn render(&mut self, board: &Board, session: &GameSession) -> io::Result<()>;
+    // Split-screen mode: player 1's game on the left, player 2's on the right
+    fn render_split(&mut self, sessions: [&GameSession; 2]) -> io::Result<()>;
+    // A screen of text instead of a board, such as the leaderboard
+    fn render_text(&mut self, lines: &[TextLine]) -> io::Result<()>;
+    // Where the moves of a finished game went
+    fn render_heatmap(&mut self, heatmap: &Heatmap) -> io::Result<()>;
+    fn clear(&mut self) -> io::Result<()>;
+    // From the next frame on, for renderers with colors to change
+    #[cfg(feature = "tui")]
+    fn set_theme(&mut self, _theme: Theme) {}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextLine {
+    pub text: String,
+    pub highlight: bool, // Drawn in the hint style
+}
+
+impl TextLine {
+    pub fn plain(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            highlight: false,
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tui")))]
+pub struct CrosstermRenderer {
+    backend: CrosstermBackend<io::Stdout>,
+    config: Config,
+    art: Option<Rc<TileArt>>,
+    tile_labels: Option<Rc<[String]>>, // The config's, shared with every frame
+    emoji: Option<Rc<[String]>>,       // Likewise, when emoji_tiles is on
+}
+
+#[cfg(feature = "tui")]
+impl CrosstermRenderer {
+    pub fn new(config: Config) -> Self {
+        Self {
+            backend: CrosstermBackend::new(io::stdout(), config.theme),
+            tile_labels: config.tile_labels.as_deref().map(Rc::from),
+            emoji: config
+                .emoji_tiles
+                .then(|| Rc::from(config.emoji_labels.as_slice())),
+            config,
+            art: None,
+        }
+    }
+
+    // Draws the tiles as pictures, for photo puzzles
+    pub fn with_art(mut self, art: TileArt) -> Self {
+        self.art = Some(Rc::new(art));
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+#[cfg(feature = "tui")]
+impl Renderer for CrosstermRenderer {
+    fn render(&mut self, board: &Board, session: &GameSession) -> io::Result<()> {
+        let started = Instant::now();
+        let state = UiState {
+            move_count: session.move_count,
+            won: session.is_won(),
+            message: session.message.clone(),
+            key_help: session.key_help.clone().or_else(|| {
+                (session.mode == GameMode::Rotation)
+                    .then(|| self.config.lang.text("rotation_key_help").to_string())
+            }),
+            highlight: session.highlight,
+            pulled: session.pulled,
+            legal_moves: session.assist.then(|| match session.mode {
+                // Rotation mode doesn't slide tiles at all
+                GameMode::Rotation => Vec::new(),
+                GameMode::Wraparound => session.board.valid_wraparound_moves(),
+                _ => session.board.valid_moves(),
+            }),
+            goal_colors: self
+                .config
+                .theme
+                .goal_paint()
+                .map(|paint| GoalColors::new(paint, &session.goal)),
+            hide_numbers: session.hide_numbers,
+            tile_labels: self.tile_labels.clone(),
+            emoji: self.emoji.clone(),
+            terminal_size: self.backend.size()?,
+            turn: session.current_player().map(|current_player| TurnState {
+                current_player,
+                player_moves: session.player_moves,
+            }),
+            hint_available: session.is_stuck(),
+            elapsed: session.elapsed(),
+            time_style: self.config.time_style,
+            lang: self.config.lang,
+            art: self.art.clone(),
+            ghost: ghost_state(session),
+            stats: stats_state(session),
+            seed: session.seed,
+            attempt: session.attempt,
+        };
+        ui::render_board(&mut self.backend, board, &state)?;
+        log::trace!("rendered frame in {:?}", started.elapsed());
+        Ok(())
+    }
+
+    fn render_split(&mut self, [a, b]: [&GameSession; 2]) -> io::Result<()> {
+        let started = Instant::now();
+        let state = SplitState {
+            move_counts: [a.move_count, b.move_count],
+            winner: [a, b]
+                .iter()
+                .position(|session| session.is_won())
+                .map(|player| player as u8 + 1),
+            terminal_size: self.backend.size()?,
+            lang: self.config.lang,
+        };
+        ui::render_split_screen(&mut self.backend, &a.board, &b.board, &state)?;
+        log::trace!("rendered split frame in {:?}", started.elapsed());
+        Ok(())
+    }
+
+    fn render_text(&mut self, lines: &[TextLine]) -> io::Result<()> {
+        let size = self.backend.size()?;
+        ui::render_text(&mut self.backend, lines, size)
+    }
+
+    fn render_heatmap(&mut self, heatmap: &Heatmap) -> io::Result<()> {
+        let size = self.backend.size()?;
+        ui::render_heatmap(&mut self.backend, heatmap, self.config.lang, size)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.backend.clear()?;
+        self.backend.flush()
+    }
+
+    fn set_theme(&mut self, theme: Theme) {
+        self.config.theme = theme;
+        self.backend.set_theme(theme);
+    }
+}
+
+#[cfg(feature = "tui")]
+fn ghost_state(session: &GameSession) -> Option<GhostState> {
+    let ghost = session.ghost.as_deref()?;
+    let progress = ghost
+        .at(session.elapsed()?)
+        .map(|snapshot| (snapshot.move_count, snapshot.tiles_placed()));
+    let finish = ghost.finish();
+    Some(GhostState {
+        progress,
+        finish_moves: finish.move_count,
+        finish_time: Duration::from_millis(finish.elapsed_ms),
+    })
+}
+
+#[cfg(feature = "tui")]
+fn stats_state(session: &GameSession) -> Option<StatsState> {
+    if !session.show_stats {
+        return None;
+    }
+    Some(StatsState {
+        tenths_per_second: session
+            .elapsed()
+            .map_or(0, |elapsed| session.move_rate.tenths_per_second(elapsed)),
+        efficiency: session
+            .par()
+            .and_then(|par| stats::efficiency(session.move_count, par)),
+    })
+}
+
+// Prints each frame as plain lines for terminals that can't be driven with
+// escape codes, like redirected output or dumb remote shells
+pub struct LineRenderer<W: Write> {
+    writer: W,
+    lang: Lang,
+}
+
+impl<W: Write> LineRenderer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            lang: Lang::default(),
+        }
+    }
+
+    pub fn with_lang(mut self, lang: Lang) -> Self {
+        self.lang = lang;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn status(&self, session: &GameSession) -> String {
+        let lang = self.lang;
+        if session.is_won() {
+            return lang.format("won", &[("moves", &session.move_count)]);
+        }
+        let mut status = lang.format("line_status", &[("moves", &session.move_count)]);
+        if let Some(player) = session.current_player() {
+            status += "  ";
+            status += &lang.format("turn", &[("player", &player)]);
+        }
+        status + "  " + lang.text("line_keys")
+    }
+}
+
+impl<W: Write> Renderer for LineRenderer<W> {
+    fn render(&mut self, board: &Board, session: &GameSession) -> io::Result<()> {
+        write!(self.writer, "{}", board)?;
+        let status = self.status(session);
+        writeln!(self.writer, "{}", status)?;
+        if let Some(message) = &session.message {
+            writeln!(self.writer, "{}", message)?;
+        }
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    fn render_split(&mut self, [a, b]: [&GameSession; 2]) -> io::Result<()> {
+        for (player, session) in [(1, a), (2, b)] {
+            let status = self.lang.format(
+                "split_moves",
+                &[("player", &player), ("moves", &session.move_count)],
+            );
+            writeln!(self.writer, "{}", status)?;
+            write!(self.writer, "{}", session.board)?;
+        }
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    fn render_text(&mut self, lines: &[TextLine]) -> io::Result<()> {
+        for line in lines {
+            writeln!(self.writer, "{}", line.text)?;
+        }
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    fn render_heatmap(&mut self, heatmap: &Heatmap) -> io::Result<()> {
+        writeln!(self.writer, "{}", self.lang.text("heatmap_title"))?;
+        write!(self.writer, "{}", heatmap)?;
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+
+    // Output already on screen stays there
+    fn clear(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Keeps every frame as the board's plain-text Display form, for tests and
+// headless runs. A split frame is both boards with a blank line between, a
+// text screen is its lines, and a clear is recorded as an empty frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MockRenderer {
+    pub frames: Vec<String>,
+}
+
+impl MockRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Renderer for MockRenderer {
+    fn render(&mut self, board: &Board, _session: &GameSession) -> io::Result<()> {
+        self.frames.push(board.to_string());
+        Ok(())
+    }
+
+    fn render_split(&mut self, [a, b]: [&GameSession; 2]) -> io::Result<()> {
+        self.frames.push(format!("{}\n{}", a.board, b.board));
+        Ok(())
+    }
+
+    fn render_text(&mut self, lines: &[TextLine]) -> io::Result<()> {
+        let text: Vec<_> = lines.iter().map(|line| line.text.as_str()).collect();
+        self.frames.push(text.join("\n") + "\n");
+        Ok(())
+    }
+
+    fn render_heatmap(&mut self, heatmap: &Heatmap) -> io::Result<()> {
+        self.frames.push(heatmap.to_string());
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.frames.push(String::new());
+        Ok(())
+    }
+}
+
+// Shared so that checkpoints cloned from a session keep drawing to the same
+// screen; two handles are equal when they point at the same renderer
+#[derive(Clone)]
+pub struct RendererHandle(Rc<RefCell<dyn Renderer>>);
+
+impl RendererHandle {
+    pub fn new(renderer: impl Renderer + 'static) -> Self {
+        Self(Rc::new(RefCell::new(renderer)))
+    }
+
+    pub fn render(&self, board: &Board, session: &GameSession) -> io::Result<()> {
+        self.0.borrow_mut().render(board, session)
+    }
+
+    pub fn render_split(&self, sessions: [&GameSession; 2]) -> io::Result<()> {
+        self.0.borrow_mut().render_split(sessions)
+    }
+
+    pub fn render_text(&self, lines: &[TextLine]) -> io::Result<()> {
+        self.0.borrow_mut().render_text(lines)
+    }
+
+    pub fn render_heatmap(&self, heatmap: &Heatmap) -> io::Result<()> {
+        self.0.borrow_mut().render_heatmap(heatmap)
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        self.0.borrow_mut().clear()
+    }
+
+    #[cfg(feature = "tui")]
+    pub fn set_theme(&self, theme: Theme) {
+        self.0.borrow_mut().set_theme(theme)
+    }
+}
+
+impl From<Rc<RefCell<dyn Renderer>>> for RendererHandle {
+    fn from(renderer: Rc<RefCell<dyn Renderer>>) -> Self {
+        Self(renderer)
+    }
+}
+
+impl fmt::Debug for RendererHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RendererHandle")
+    }
+}
+
+impl PartialEq for RendererHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for RendererHandle {}
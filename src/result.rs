@@ -0,0 +1,140 @@
+use crate::{
+    board::{Board, Direction, Rotation, Tile},
+    game::{GameMode, GameSession},
+    stats,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Bump whenever a field is added, removed or changes meaning
+pub const RESULT_SCHEMA_VERSION: u32 = 7;
+
+// Summary of a finished game or solve, printed by --json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub schema_version: u32,
+    pub initial_board: Board,
+    pub final_board: Board,
+    pub seed: Option<u64>,
+    // From solved to initial_board, e.g. "D R U", and in rotation mode the
+    // rotations, e.g. "R2+ C1-"
+    pub scramble: Option<String>,
+    pub moves: String, // Move notation, e.g. "ULDR", or rotations as in `scramble`
+    pub move_count: u32,
+    pub elapsed_ms: u64,
+    pub idle_ms: u64,     // Paused while idle, which elapsed_ms leaves out
+    pub average_mps: f64, // Moves per second over the whole game
+    pub solved: bool,
+    pub mode: String,
+    pub hints_used: u32,
+    pub tile_moves: Vec<u32>, // How many times each tile moved, tile 1 first
+    pub attempt: u32,         // 1 unless the puzzle was reset or rematched
+}
+
+impl GameResult {
+    pub fn from_session(session: &GameSession, elapsed_ms: u64, mode: &str) -> Self {
+        Self {
+            schema_version: RESULT_SCHEMA_VERSION,
+            initial_board: session.initial_board.clone(),
+            final_board: session.board.clone(),
+            seed: session.seed,
+            scramble: match session.mode {
+                GameMode::Rotation => session
+                    .rotation_scramble
+                    .as_deref()
+                    .map(Rotation::sequence_to_spaced),
+                _ => session
+                    .scramble
+                    .as_deref()
+                    .map(Direction::sequence_to_spaced),
+            },
+            moves: match session.mode {
+                GameMode::Rotation => Rotation::sequence_to_spaced(&session.rotations),
+                _ => Direction::sequence_to_string(&session.moves),
+            },
+            move_count: session.move_count,
+            elapsed_ms,
+            idle_ms: session.paused_time().as_millis() as u64,
+            average_mps: stats::average_per_second(
+                session.move_count,
+                Duration::from_millis(elapsed_ms),
+            ),
+            solved: session.board.is_goal(&session.goal),
+            mode: mode.to_string(),
+            hints_used: session.hints_used,
+            tile_moves: session.heatmap().tile_moves().to_vec(),
+            attempt: session.attempt,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("results only contain plain data")
+    }
+}
+
+// A single board described for scripts, printed by --print-board --json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardSummary {
+    pub tiles: Vec<Tile>,
+    pub solved: bool,
+    pub distance: u32, // Manhattan distance from the goal
+}
+
+impl BoardSummary {
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            tiles: board.tiles().to_vec(),
+            solved: board.is_goal(&Board::goal_state(board.size())),
+            distance: board.manhattan_distance(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("summaries only contain plain data")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::StartKind;
+
+    #[test]
+    fn test_result_round_trip() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut session = GameSession::new(board, StartKind::Custom, Some(9))
+            .with_scramble(vec![Direction::Down, Direction::Right]);
+        session.apply_moves(&[Direction::Left, Direction::Up]);
+
+        let result = GameResult::from_session(&session, 1234, "classic");
+        assert!(result.solved);
+        assert_eq!(result.moves, "LU");
+        assert!((result.average_mps - 2.0 / 1.234).abs() < 1e-9);
+
+        let json = result.to_json();
+        assert!(json.contains(r#""initial_board":[1,2,3,4,0,5,7,8,6]"#));
+        assert!(json.contains(r#""scramble":"D R""#));
+        let parsed: GameResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, result);
+    }
+
+    #[test]
+    fn test_board_summary_json() {
+        assert_eq!(
+            BoardSummary::from_board(&Board::new()).to_json(),
+            r#"{"tiles":[1,2,3,4,5,6,7,8,0],"solved":true,"distance":0}"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_board_rejected() {
+        let json = GameResult::from_session(
+            &GameSession::new(Board::new(), StartKind::Debug, None),
+            0,
+            "classic",
+        )
+        .to_json()
+        .replace("[1,2,3,4,5,6,7,8,0]", "[1,1,3,4,5,6,7,8,0]");
+        assert!(serde_json::from_str::<GameResult>(&json).is_err());
+    }
+}
@@ -0,0 +1,436 @@
+use crate::{
+    config::{self, Config, KeyBindings, Sound},
+    duration::TimeStyle,
+    i18n::Lang,
+    theme::Theme,
+};
+use clap::ValueEnum;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+// $XDG_CONFIG_HOME/tilegame/settings.conf, falling back to ~/.config, or None
+// when neither is set
+pub fn default_path() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+    Some(config.join("tilegame").join("settings.conf"))
+}
+
+// The user's settings file: one `key = value` per line, with # comments.
+// Lines are kept as written, so saving only rewrites the values of keys it
+// knows and leaves comments, blank lines and unknown keys alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsFile {
+    lines: Vec<String>,
+}
+
+// Every key the file can set, in the order new ones are appended
+const KEYS: [&str; 15] = [
+    "theme",
+    "player2_keys",
+    "lang",
+    "time_style",
+    "sound",
+    "key_repeat",
+    "idle_pause_secs",
+    "assist",
+    "tile_labels",
+    "emoji_labels",
+    "tick_ms",
+    "slide_ms",
+    "autosolve_step_ms",
+    "celebration_ms",
+    "shuffle_step_ms",
+];
+
+fn value_enum_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn value_of(config: &Config, key: &str) -> String {
+    match key {
+        "theme" => value_enum_name(config.theme),
+        "player2_keys" => config.player2_keys.to_string(),
+        "lang" => config.lang.to_string(),
+        "time_style" => value_enum_name(config.time_style),
+        "sound" => config.sound.to_string(),
+        "key_repeat" => config.key_repeat.to_string(),
+        "idle_pause_secs" => config.idle_pause_secs.to_string(),
+        "assist" => match config.assist {
+            true => "on".to_string(),
+            false => "off".to_string(),
+        },
+        "tile_labels" => match &config.tile_labels {
+            Some(labels) => serde_json::to_string(labels).expect("labels are plain strings"),
+            None => "none".to_string(),
+        },
+        "emoji_labels" => match config.emoji_labels == config::DEFAULT_EMOJI {
+            true => "default".to_string(),
+            false => serde_json::to_string(&config.emoji_labels).expect("emoji are plain strings"),
+        },
+        "tick_ms" => config.timings.tick_ms.to_string(),
+        "slide_ms" => config.timings.slide_ms.to_string(),
+        "autosolve_step_ms" => config.timings.autosolve_step_ms.to_string(),
+        "celebration_ms" => config.timings.celebration_ms.to_string(),
+        "shuffle_step_ms" => config.timings.shuffle_step_ms.to_string(),
+        _ => unreachable!("{} isn't a settings key", key),
+    }
+}
+
+// Every key as `key = value` with its value in `config`, as the file would
+// have it
+pub fn describe(config: &Config) -> Vec<String> {
+    KEYS.iter()
+        .map(|key| format!("{} = {}", key, value_of(config, key)))
+        .collect()
+}
+
+// Sets one known key, or says why the value doesn't fit it
+fn set_value(config: &mut Config, key: &str, value: &str) -> Result<(), String> {
+    let ms = || {
+        value
+            .parse::<u64>()
+            .map_err(|_| format!("'{}' isn't a number of milliseconds", value))
+    };
+    match key {
+        "theme" => config.theme = Theme::from_str(value, true)?,
+        "player2_keys" => config.player2_keys = value.parse::<KeyBindings>()?,
+        "lang" => config.lang = value.parse::<Lang>()?,
+        "time_style" => config.time_style = TimeStyle::from_str(value, true)?,
+        "sound" => config.sound = value.parse::<Sound>()?,
+        "key_repeat" => {
+            config.key_repeat = value
+                .parse()
+                .map_err(|_| format!("'{}' isn't a number of moves", value))?
+        }
+        "idle_pause_secs" => {
+            config.idle_pause_secs = value
+                .parse()
+                .map_err(|_| format!("'{}' isn't a number of seconds", value))?
+        }
+        "assist" => {
+            config.assist = match value {
+                "on" => true,
+                "off" => false,
+                _ => return Err(format!("'{}' isn't on or off", value)),
+            }
+        }
+        "tile_labels" => {
+            config.tile_labels = match value {
+                "none" => None,
+                _ => Some(config::parse_tile_labels(value)?),
+            }
+        }
+        "emoji_labels" => {
+            config.emoji_labels = match value {
+                "default" => config::DEFAULT_EMOJI.map(String::from).to_vec(),
+                _ => config::parse_tile_labels(value)?,
+            }
+        }
+        "tick_ms" => config.timings.tick_ms = ms()?,
+        "slide_ms" => config.timings.slide_ms = ms()?,
+        "autosolve_step_ms" => config.timings.autosolve_step_ms = ms()?,
+        "celebration_ms" => config.timings.celebration_ms = ms()?,
+        "shuffle_step_ms" => config.timings.shuffle_step_ms = ms()?,
+        _ => unreachable!("{} isn't a settings key", key),
+    }
+    Ok(())
+}
+
+// The key and value on a setting line, or None for comments and blank lines
+fn split_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (key, value) = line.split_once('=')?;
+    Some((key.trim(), value.trim()))
+}
+
+impl SettingsFile {
+    pub fn parse(text: &str) -> Self {
+        Self {
+            lines: text.lines().map(str::to_string).collect(),
+        }
+    }
+
+    // Applies every recognized setting on top of `config`. A bad value is
+    // skipped and reported rather than failing the lot; unknown keys are
+    // assumed to belong to a newer version and ignored.
+    pub fn apply(&self, config: &mut Config) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (number, line) in self.lines.iter().enumerate() {
+            let Some((key, value)) = split_line(line) else {
+                continue;
+            };
+            if !KEYS.contains(&key) {
+                continue;
+            }
+            if let Err(err) = set_value(config, key, value) {
+                problems.push(format!("line {}: {}: {}", number + 1, key, err));
+            }
+        }
+        if let Err(err) = config.timings.validate() {
+            problems.push(err);
+            config.timings = Default::default();
+        }
+        problems
+    }
+
+    // Writes `config` into the file: known keys are updated where they are
+    // and missing ones are added at the end
+    pub fn update(&mut self, config: &Config) {
+        let mut written = Vec::new();
+        for line in &mut self.lines {
+            let Some((key, _)) = split_line(line) else {
+                continue;
+            };
+            let Some(&key) = KEYS.iter().find(|&&known| known == key) else {
+                continue;
+            };
+            let indent = &line[..line.len() - line.trim_start().len()];
+            *line = format!("{}{} = {}", indent, key, value_of(config, key));
+            written.push(key);
+        }
+        for key in KEYS.into_iter().filter(|key| !written.contains(key)) {
+            self.lines
+                .push(format!("{} = {}", key, value_of(config, key)));
+        }
+    }
+}
+
+// The environment variable for a key: TILEGAME_ and the key in capitals,
+// e.g. TILEGAME_KEY_REPEAT
+pub fn env_var(key: &str) -> String {
+    format!("TILEGAME_{}", key.to_ascii_uppercase())
+}
+
+// Applies every key's variable that `lookup` finds, as the file's lines are
+// applied: a bad value is skipped and reported
+pub fn apply_env(config: &mut Config, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let mut problems = Vec::new();
+    for key in KEYS {
+        let name = env_var(key);
+        let Some(value) = lookup(&name) else {
+            continue;
+        };
+        if let Err(err) = set_value(config, key, value.trim()) {
+            problems.push(format!("{}: {}", name, err));
+        }
+    }
+    if let Err(err) = config.timings.validate() {
+        problems.push(err);
+        config.timings = Default::default();
+    }
+    problems
+}
+
+impl std::fmt::Display for SettingsFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+// Settings as read from disk plus whatever this session overrides. Changes
+// made in the game go to both and are written back straight away; overrides
+// such as command-line flags only go to `current`, and only reach the file
+// through save_as_default.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    path: Option<PathBuf>, // None when there's no config directory
+    file: SettingsFile,
+    saved: Config,
+    pub current: Config,
+}
+
+impl Settings {
+    // A missing file just means defaults. Anything else that goes wrong comes
+    // back as messages to show the player.
+    pub fn load(mut path: Option<PathBuf>) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let file = match path.as_deref().map(fs::read_to_string) {
+            Some(Ok(text)) => SettingsFile::parse(&text),
+            // Saving over a file that couldn't be read would clobber it
+            Some(Err(err)) if err.kind() != io::ErrorKind::NotFound => {
+                problems.push(format!("couldn't read settings: {}", err));
+                path = None;
+                SettingsFile::default()
+            }
+            _ => SettingsFile::default(),
+        };
+
+        let mut saved = Config::default();
+        problems.extend(file.apply(&mut saved));
+        let settings = Self {
+            path,
+            file,
+            current: saved.clone(),
+            saved,
+        };
+        (settings, problems)
+    }
+
+    // Applies a change made in the game and saves it. On an error the change
+    // still holds for this session, and the message says it wasn't saved.
+    pub fn change(&mut self, edit: impl Fn(&mut Config)) -> Result<(), String> {
+        edit(&mut self.current);
+        edit(&mut self.saved);
+        self.write()
+    }
+
+    // Makes everything in effect now, overrides included, the saved default
+    pub fn save_as_default(&mut self) -> Result<(), String> {
+        self.saved = self.current.clone();
+        self.write()
+    }
+
+    fn write(&mut self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Err("no config directory, settings apply to this session only".to_string());
+        };
+        self.file.update(&self.saved);
+        write_file(path, &self.file.to_string()).map_err(|err| {
+            format!(
+                "couldn't save settings to {} ({}), they apply to this session only",
+                path.display(),
+                err
+            )
+        })
+    }
+}
+
+// Writes beside the file and renames, so a failed write can't leave it half
+// written
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "\
+# My tile game settings
+theme = ocean
+  sound = win
+future_option = yes
+
+tick_ms = ten
+";
+
+    #[test]
+    fn test_apply() {
+        let mut config = Config::default();
+        let problems = SettingsFile::parse(FILE).apply(&mut config);
+        assert_eq!(config.theme, Theme::Ocean);
+        assert!(config.sound.win && !config.sound.moves);
+        assert_eq!(config.timings, Default::default());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("line 6: tick_ms:"));
+    }
+
+    #[test]
+    fn test_describe() {
+        let lines = describe(&Config::default());
+        assert_eq!(lines.len(), KEYS.len());
+        assert_eq!(lines[0], "theme = classic");
+        // What it describes reads back the same
+        let config = Config {
+            theme: Theme::Ocean,
+            ..Config::default()
+        };
+        let mut read = Config::default();
+        let problems = SettingsFile::parse(&describe(&config).join("\n")).apply(&mut read);
+        assert!(problems.is_empty());
+        assert_eq!(read, config);
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let vars = [
+            ("TILEGAME_THEME", "ocean"),
+            ("TILEGAME_KEY_REPEAT", " 5 "),
+            ("TILEGAME_IDLE_PAUSE_SECS", "soon"),
+            ("TILEGAME_SIZE", "4"),
+        ];
+        let lookup = |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        };
+        let mut config = Config::default();
+        let problems = apply_env(&mut config, lookup);
+        assert_eq!(config.theme, Theme::Ocean);
+        assert_eq!(config.key_repeat, 5);
+        assert_eq!(config.idle_pause_secs, Config::default().idle_pause_secs);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("TILEGAME_IDLE_PAUSE_SECS:"));
+    }
+
+    #[test]
+    fn test_update_keeps_comments_and_unknown_keys() {
+        let mut file = SettingsFile::parse(FILE);
+        let config = Config {
+            theme: Theme::Mono,
+            tile_labels: Some(vec!["A".to_string(), "🐱".to_string()]),
+            ..Config::default()
+        };
+        file.update(&config);
+        let text = file.to_string();
+        assert!(text.contains("\ntile_labels = [\"A\",\"🐱\"]\nemoji_labels = default\n"));
+
+        assert!(text.starts_with("# My tile game settings\ntheme = mono\n  sound = off\n"));
+        assert!(text.contains("\nfuture_option = yes\n\ntick_ms = 100\n"));
+        assert!(text.ends_with("celebration_ms = 2000\nshuffle_step_ms = 8\n"));
+        assert_eq!(text.matches("theme =").count(), 1);
+
+        let mut round_trip = Config::default();
+        assert!(SettingsFile::parse(&text).apply(&mut round_trip).is_empty());
+        assert_eq!(round_trip, config);
+    }
+
+    #[test]
+    fn test_overrides_stay_out_of_the_file() {
+        let dir = std::env::temp_dir().join(format!("tile-game-settings-{}", std::process::id()));
+        let path = dir.join("settings.conf");
+        let (mut settings, problems) = Settings::load(Some(path.clone()));
+        assert!(problems.is_empty());
+
+        // Like a --theme flag: in effect now, but not saved
+        settings.current.theme = Theme::Ocean;
+        let changed = settings.change(|config| config.lang = Lang::Es);
+        let (after_change, _) = Settings::load(Some(path.clone()));
+        let defaulted = settings.save_as_default();
+        let (after_default, _) = Settings::load(Some(path.clone()));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(changed, Ok(()));
+        assert_eq!(after_change.current.lang, Lang::Es);
+        assert_eq!(after_change.current.theme, Theme::Classic);
+        assert_eq!(defaulted, Ok(()));
+        assert_eq!(after_default.current.theme, Theme::Ocean);
+    }
+
+    #[test]
+    fn test_session_only_without_config_dir() {
+        let (mut settings, _) = Settings::load(None);
+        let err = settings.change(|config| config.theme = Theme::Mono);
+        assert!(err.unwrap_err().contains("this session only"));
+        assert_eq!(settings.current.theme, Theme::Mono);
+    }
+}
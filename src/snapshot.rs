@@ -0,0 +1,181 @@
+use crate::{board::Board, solver::Heuristic};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// The board and counters at one moment of a game, for replays and analysis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardSnapshot {
+    pub board: Board,
+    pub move_count: u32,
+    pub elapsed_ms: u64,
+    pub hints_used: u32,
+    pub timestamp_utc: String, // e.g. "2024-03-09T17:05:42Z"
+}
+
+// Reads a JSON array of snapshots, as written by save_snapshots
+pub fn load_snapshots(path: &Path) -> io::Result<Vec<BoardSnapshot>> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn save_snapshots(path: &Path, snapshots: &[BoardSnapshot]) -> io::Result<()> {
+    let json = serde_json::to_string(snapshots).expect("snapshots only contain plain data");
+    fs::write(path, json + "\n")
+}
+
+// A won run to race against, from the snapshots it saved along the way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ghost {
+    snapshots: Vec<BoardSnapshot>, // In the order they were taken
+}
+
+impl Ghost {
+    // None unless the last snapshot is a solved board of `size`, so only a
+    // finished game on the same board size is raced
+    pub fn new(snapshots: Vec<BoardSnapshot>, size: usize) -> Option<Self> {
+        let last = snapshots.last()?;
+        let solved = last.board.size() == size && last.board.is_goal(&Board::goal_state(size));
+        solved.then_some(Self { snapshots })
+    }
+
+    // Where the run had got to `elapsed` into the game, or None before its
+    // first snapshot
+    pub fn at(&self, elapsed: Duration) -> Option<&BoardSnapshot> {
+        let ms = elapsed.as_millis() as u64;
+        let taken = self.snapshots.partition_point(|s| s.elapsed_ms <= ms);
+        taken.checked_sub(1).map(|i| &self.snapshots[i])
+    }
+
+    // When the next snapshot after `elapsed` was taken, or None once the run
+    // has finished
+    pub fn next_change(&self, elapsed: Duration) -> Option<Duration> {
+        let ms = elapsed.as_millis() as u64;
+        self.snapshots
+            .iter()
+            .find(|s| s.elapsed_ms > ms)
+            .map(|s| Duration::from_millis(s.elapsed_ms))
+    }
+
+    // The winning snapshot
+    pub fn finish(&self) -> &BoardSnapshot {
+        self.snapshots.last().expect("a ghost always ends in a win")
+    }
+}
+
+impl BoardSnapshot {
+    // Tiles already on their goal cell
+    pub fn tiles_placed(&self) -> usize {
+        let tiles = self.board.tiles().len() - 1;
+        tiles - Heuristic::Misplaced.estimate(&self.board) as usize
+    }
+}
+
+// RFC 3339 in UTC to the second, without pulling in a date library
+pub fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+// Days since 1970-01-01 to a Gregorian (year, month, day), after Howard
+// Hinnant's days_from_civil inverse
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            "2023-11-14T22:13:20Z"
+        );
+        // Leap day
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_snapshots_round_trip() {
+        let snapshots = vec![BoardSnapshot {
+            board: "1 2 3 4 0 5 7 8 6".parse().unwrap(),
+            move_count: 10,
+            elapsed_ms: 4200,
+            hints_used: 1,
+            timestamp_utc: "2024-03-09T17:05:42Z".to_string(),
+        }];
+        let path = std::env::temp_dir().join(format!("tile-game-{}.json", std::process::id()));
+        save_snapshots(&path, &snapshots).unwrap();
+        let loaded = load_snapshots(&path);
+
+        fs::write(&path, "[{}]").unwrap();
+        let invalid = load_snapshots(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), snapshots);
+        assert_eq!(invalid.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_ghost() {
+        let snapshot = |board: &str, move_count, elapsed_ms| BoardSnapshot {
+            board: board.parse().unwrap(),
+            move_count,
+            elapsed_ms,
+            hints_used: 0,
+            timestamp_utc: String::new(),
+        };
+        let snapshots = vec![
+            snapshot("1 2 3 4 0 5 7 8 6", 10, 4000),
+            snapshot("1 2 3 4 5 6 7 8 0", 12, 5000),
+        ];
+        let ghost = Ghost::new(snapshots.clone(), 3).unwrap();
+        assert_eq!(ghost.at(Duration::from_millis(3999)), None);
+        assert_eq!(ghost.at(Duration::from_millis(4000)), Some(&snapshots[0]));
+        assert_eq!(ghost.at(Duration::from_secs(60)), Some(&snapshots[1]));
+        assert_eq!(
+            ghost.next_change(Duration::ZERO),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(ghost.next_change(Duration::from_secs(5)), None);
+        assert_eq!(ghost.finish().move_count, 12);
+        assert_eq!(snapshots[0].tiles_placed(), 6);
+
+        // Not won, or won on another size
+        assert_eq!(Ghost::new(snapshots[..1].to_vec(), 3), None);
+        assert_eq!(Ghost::new(snapshots, 4), None);
+        assert_eq!(Ghost::new(Vec::new(), 3), None);
+    }
+}
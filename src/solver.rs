@@ -0,0 +1,1230 @@
+use crate::{
+    board::{Board, Direction},
+    distance_table,
+    fixed_board::{FixedBoard, FixedBoard2, FixedBoard3, FixedBoard4},
+    pattern_db::{self, PDB_SIZE},
+    walking_distance::walking_distance,
+};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+// Largest board bfs_solve can search in reasonable time and memory
+pub const BFS_MAX_SIZE: usize = 3;
+
+// Longest a watched search goes between progress reports
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// Nodes a watched search expands between looks at the clock and the cancel
+// flag: a few milliseconds' work at most
+const WATCH_EVERY: u64 = 1 << 12;
+
+// Never set, for searches no one can stop
+static NEVER_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Unsolvable,
+    TooLarge { size: usize },
+    NoPatternDb, // A 4x4 solve before the pattern databases were loaded
+    Cancelled,
+    TimedOut,    // Past a Watch's time limit
+    OutOfMemory, // Past a Watch's memory limit
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Unsolvable => write!(f, "this board cannot be solved"),
+            SolveError::TooLarge { size } => write!(
+                f,
+                "the solver handles boards up to {max}x{max}, not {size}x{size}",
+                max = BFS_MAX_SIZE,
+                size = size
+            ),
+            SolveError::NoPatternDb => write!(
+                f,
+                "solving a 4x4 board needs the pattern databases; build them once with \
+                 `tile-game pdb build`"
+            ),
+            SolveError::Cancelled => write!(f, "the solve was cancelled"),
+            SolveError::TimedOut => write!(f, "the solve ran out of time"),
+            SolveError::OutOfMemory => write!(f, "the solve ran out of memory"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+// The shortest solution, refusing boards the search can't finish on
+pub fn solve(board: &Board) -> Result<Vec<Direction>, SolveError> {
+    if board.size() > BFS_MAX_SIZE {
+        return Err(SolveError::TooLarge { size: board.size() });
+    }
+    if !board.is_solvable() {
+        return Err(SolveError::Unsolvable);
+    }
+    let moves = match table_solve(board) {
+        Some(moves) => moves,
+        None => bfs_solve(board).ok_or(SolveError::Unsolvable)?,
+    };
+    debug_assert_solves(board, &moves);
+    Ok(moves)
+}
+
+// Replays a solution in debug builds and panics unless every move is legal
+// and together they reach the goal. That's O(n) in the solution length on
+// every solve; release builds skip it.
+fn debug_assert_solves(board: &Board, moves: &[Direction]) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let mut replay = board.clone();
+    let applied = moves
+        .iter()
+        .take_while(|&&direction| replay.move_tile(direction))
+        .count();
+    debug_assert_eq!(
+        applied,
+        moves.len(),
+        "move {} of the solution for {:?} is illegal",
+        applied + 1,
+        board.tiles()
+    );
+    debug_assert!(
+        replay.is_goal(&Board::goal_state(board.size())),
+        "the solution for {:?} ends on {:?}",
+        board.tiles(),
+        replay.tiles()
+    );
+}
+
+// The precomputed table's answer, once --precompute or an earlier lookup has
+// built it; the solver never builds it on its own
+fn table_solve(board: &Board) -> Option<Vec<Direction>> {
+    match board.size() == distance_table::TABLE_SIZE && distance_table::is_ready() {
+        true => distance_table::solve(board),
+        false => None,
+    }
+}
+
+// Breadth-first search from the given board to the goal state. Returns the
+// shortest move sequence, or None if the goal can't be reached. Every visited
+// state is kept in memory, so this is only practical for 3x3 boards.
+pub fn bfs_solve(board: &Board) -> Option<Vec<Direction>> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("bfs_solve", board = ?board.tiles()).entered();
+    log::debug!("bfs_solve started on {:?}", board.tiles());
+    // Sizes with a FixedBoard search without allocating per node. Those know
+    // nothing of locked tiles, so a board with any searches as it is.
+    let moves = match board.size() {
+        _ if board.has_locked_tiles() => bfs(board.clone()),
+        2 => bfs(FixedBoard2::try_from(board).ok()?),
+        3 => bfs(FixedBoard3::try_from(board).ok()?),
+        4 => bfs(FixedBoard4::try_from(board).ok()?),
+        _ => bfs(board.clone()),
+    }?;
+    debug_assert_solves(board, &moves);
+    Some(moves)
+}
+
+// What a search needs from a board, so the same search code runs on both the
+// dynamic Board and the fixed-size one
+trait SearchState: Clone {
+    fn at_goal(&self) -> bool;
+    fn goal(&self) -> Self;
+    fn zobrist(&self) -> u64;
+    // Each legal move with the board it leads to and that board's hash,
+    // updated incrementally from this board's `hash`
+    fn neighbors(&self, hash: u64) -> Vec<(Direction, Self, u64)>;
+}
+
+impl SearchState for Board {
+    fn at_goal(&self) -> bool {
+        self.is_goal(&Board::goal_state(self.size()))
+    }
+
+    // Without the locks, which only bibfs_solve's caller can rule out
+    fn goal(&self) -> Self {
+        Board::goal_state(self.size())
+    }
+
+    fn zobrist(&self) -> u64 {
+        Board::zobrist(self)
+    }
+
+    fn neighbors(&self, hash: u64) -> Vec<(Direction, Self, u64)> {
+        self.neighbor_states()
+            .into_iter()
+            .map(|(direction, next)| {
+                let next_hash = self
+                    .zobrist_after_move(hash, direction)
+                    .expect("neighbor_states only yields legal moves");
+                (direction, next, next_hash)
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> SearchState for FixedBoard<N> {
+    fn at_goal(&self) -> bool {
+        self.is_solved()
+    }
+
+    fn goal(&self) -> Self {
+        FixedBoard::goal()
+    }
+
+    fn zobrist(&self) -> u64 {
+        FixedBoard::zobrist(self)
+    }
+
+    fn neighbors(&self, hash: u64) -> Vec<(Direction, Self, u64)> {
+        self.successors()
+            .map(|(direction, next)| {
+                let next_hash = self
+                    .zobrist_after_move(hash, direction)
+                    .expect("successors only yields legal moves");
+                (direction, next, next_hash)
+            })
+            .collect()
+    }
+}
+
+fn bfs<S: SearchState>(start: S) -> Option<Vec<Direction>> {
+    // Visited states are keyed by Zobrist hash, so the map never stores or
+    // rehashes a board. Each entry points back at the state it was reached from.
+    let mut parents: HashMap<u64, Option<(u64, Direction)>> = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    let hash = start.zobrist();
+    parents.insert(hash, None);
+    queue.push_back((start, hash));
+
+    while let Some((current, hash)) = queue.pop_front() {
+        if current.at_goal() {
+            let path = reconstruct_path(&parents, hash);
+            log::debug!(
+                "bfs_solve found {} moves after {} nodes",
+                path.len(),
+                parents.len()
+            );
+            return Some(path);
+        }
+
+        for (direction, next, next_hash) in current.neighbors(hash) {
+            if let Entry::Vacant(entry) = parents.entry(next_hash) {
+                entry.insert(Some((hash, direction)));
+                queue.push_back((next, next_hash));
+            }
+        }
+    }
+
+    log::debug!(
+        "bfs_solve exhausted {} nodes without a solution",
+        parents.len()
+    );
+    None
+}
+
+// Breadth-first search from both ends at once, a layer at a time from
+// whichever of the start's and the goal's frontiers is smaller, until they
+// meet. Each side only goes about half as deep, so far fewer boards are
+// visited than by bfs_solve for a solution just as short.
+pub fn bibfs_solve(board: &Board) -> Option<Vec<Direction>> {
+    if !board.is_solvable() {
+        return None;
+    }
+    let found = match board.size() {
+        // The search from the goal would know nothing of the locks
+        _ if board.has_locked_tiles() => return bfs_solve(board),
+        2 => bibfs(FixedBoard2::try_from(board).ok()?),
+        3 => bibfs(FixedBoard3::try_from(board).ok()?),
+        4 => bibfs(FixedBoard4::try_from(board).ok()?),
+        _ => bibfs(board.clone()),
+    };
+    let (moves, visited) = found?;
+    log::debug!("bibfs found {} moves after {} nodes", moves.len(), visited);
+    debug_assert_solves(board, &moves);
+    Some(moves)
+}
+
+// One end of bibfs: every state it has reached, pointing back the way it
+// came as in bfs, and the newest layer
+struct Side<S> {
+    parents: HashMap<u64, Option<(u64, Direction)>>,
+    frontier: Vec<(S, u64)>,
+}
+
+impl<S: SearchState> Side<S> {
+    fn new(state: S) -> Self {
+        let hash = state.zobrist();
+        Self {
+            parents: HashMap::from([(hash, None)]),
+            frontier: vec![(state, hash)],
+        }
+    }
+
+    // Moves the frontier out a layer, returning a state the other side has
+    // reached too. The layers behind both frontiers never met, so the first
+    // meeting in a layer is as short as any other in it, and shortest.
+    fn expand(&mut self, other: &Side<S>) -> Option<u64> {
+        let mut next = Vec::new();
+        for (state, hash) in std::mem::take(&mut self.frontier) {
+            for (direction, next_state, next_hash) in state.neighbors(hash) {
+                if let Entry::Vacant(entry) = self.parents.entry(next_hash) {
+                    entry.insert(Some((hash, direction)));
+                    if other.parents.contains_key(&next_hash) {
+                        return Some(next_hash);
+                    }
+                    next.push((next_state, next_hash));
+                }
+            }
+        }
+        self.frontier = next;
+        None
+    }
+}
+
+// The moves and how many states both sides visited
+fn bibfs<S: SearchState>(start: S) -> Option<(Vec<Direction>, usize)> {
+    if start.at_goal() {
+        return Some((Vec::new(), 1));
+    }
+    let mut forward = Side::new(start.clone());
+    let mut backward = Side::new(start.goal());
+    while !forward.frontier.is_empty() && !backward.frontier.is_empty() {
+        let meeting = match forward.frontier.len() <= backward.frontier.len() {
+            true => forward.expand(&backward),
+            false => backward.expand(&forward),
+        };
+        if let Some(hash) = meeting {
+            // The goal's side holds the moves from the goal out to the
+            // meeting point; undone in reverse, they lead on to the goal
+            let mut moves = reconstruct_path(&forward.parents, hash);
+            moves.extend(Direction::invert_sequence(&reconstruct_path(
+                &backward.parents,
+                hash,
+            )));
+            return Some((moves, forward.parents.len() + backward.parents.len()));
+        }
+    }
+    None
+}
+
+// Lower bounds on the moves left to the goal, for guiding ida_star
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heuristic {
+    Misplaced, // Tiles not on their goal cell
+    Manhattan,
+    WalkingDistance, // Manhattan on boards too large for the table
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 3] = [
+        Heuristic::Misplaced,
+        Heuristic::Manhattan,
+        Heuristic::WalkingDistance,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::Misplaced => "misplaced",
+            Heuristic::Manhattan => "manhattan",
+            Heuristic::WalkingDistance => "walking",
+        }
+    }
+
+    pub fn estimate(self, board: &Board) -> u32 {
+        match self {
+            Heuristic::Misplaced => board
+                .tiles()
+                .iter()
+                .enumerate()
+                .filter(|&(pos, &tile)| tile != 0 && tile as usize != pos + 1)
+                .count() as u32,
+            Heuristic::Manhattan => board.manhattan_distance(),
+            Heuristic::WalkingDistance => walking_distance(board),
+        }
+    }
+}
+
+// A solution found by ida_star or a_star, with how much work it took. The
+// time is only known when the search was watched with a clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    pub moves: Vec<Direction>,
+    pub nodes_expanded: u64,
+    pub elapsed: Option<Duration>,
+}
+
+enum Bound {
+    Found,
+    Next(u32),
+    Stopped(SolveError),
+}
+
+// How far a search has got: for ida_star the bound it's searching up to,
+// and for a_star the estimate of the boards it's expanding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub nodes_expanded: u64,
+    pub bound: u32,
+    pub elapsed: Duration,
+}
+
+// Lets a long search be followed and stopped. Once `cancel` is set, or the
+// search runs past a limit, it gives up within WATCH_EVERY nodes.
+pub struct Watch<'a> {
+    cancel: &'a AtomicBool,
+    // Time since the search started, by the caller's clock: the core has
+    // none of its own, and there isn't one at all on wasm. Reports and the
+    // time limit need it.
+    clock: Option<Box<dyn Fn() -> Duration + 'a>>,
+    progress: Option<Box<dyn FnMut(Progress) + 'a>>,
+    reported: Duration,
+    time_limit: Option<Duration>,
+    // Most boards the search may keep at once
+    memory_limit: Option<usize>,
+}
+
+impl<'a> Watch<'a> {
+    // Can be cancelled, but doesn't report and never looks at the time
+    pub fn new(cancel: &'a AtomicBool) -> Self {
+        Self {
+            cancel,
+            clock: None,
+            progress: None,
+            reported: Duration::ZERO,
+            time_limit: None,
+            memory_limit: None,
+        }
+    }
+
+    // No reports, limits or way to cancel
+    pub fn unwatched() -> Watch<'static> {
+        Watch::new(&NEVER_CANCELLED)
+    }
+
+    pub fn with_clock(mut self, elapsed: impl Fn() -> Duration + 'a) -> Self {
+        self.clock = Some(Box::new(elapsed));
+        self
+    }
+
+    // `progress` hears how it's going at most every PROGRESS_INTERVAL, once
+    // there's a clock
+    pub fn with_progress(mut self, progress: impl FnMut(Progress) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    // Gives up with SolveError::TimedOut after `limit`, once there's a clock
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    // Gives up with SolveError::OutOfMemory once the search keeps more than
+    // `boards`
+    pub fn with_memory_limit(mut self, boards: usize) -> Self {
+        self.memory_limit = Some(boards);
+        self
+    }
+
+    // Time since the search started, if there's a clock to tell
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.clock.as_ref().map(|clock| clock())
+    }
+
+    // Called for every node with the boards the search is keeping; why the
+    // search should stop, if it should
+    fn stop(&mut self, nodes_expanded: u64, bound: u32, boards_kept: usize) -> Option<SolveError> {
+        if !nodes_expanded.is_multiple_of(WATCH_EVERY) {
+            return None;
+        }
+        if self.memory_limit.is_some_and(|limit| boards_kept > limit) {
+            return Some(SolveError::OutOfMemory);
+        }
+        if let Some(elapsed) = self.elapsed() {
+            if self.time_limit.is_some_and(|limit| elapsed >= limit) {
+                return Some(SolveError::TimedOut);
+            }
+            if let Some(progress) = &mut self.progress {
+                if elapsed >= self.reported + PROGRESS_INTERVAL {
+                    self.reported = elapsed;
+                    progress(Progress {
+                        nodes_expanded,
+                        bound,
+                        elapsed,
+                    });
+                }
+            }
+        }
+        self.cancel
+            .load(Ordering::Relaxed)
+            .then_some(SolveError::Cancelled)
+    }
+}
+
+// Iterative deepening A*: repeated depth-first searches, each cut off once
+// moves so far plus the heuristic exceed the bound, which grows to the
+// smallest value that was cut off. Memory stays flat, and with an admissible
+// heuristic the first solution found is a shortest one.
+pub fn ida_star(board: &Board, heuristic: Heuristic) -> Option<Search> {
+    ida_star_by(board, heuristic.name(), |board| heuristic.estimate(board))
+}
+
+// ida_star guided by any lower bound, such as one needing tables the
+// Heuristic variants don't have. `name` is only for the log.
+pub fn ida_star_by(board: &Board, name: &str, estimate: impl Fn(&Board) -> u32) -> Option<Search> {
+    ida_star_watched(board, name, estimate, &mut Watch::unwatched()).ok()
+}
+
+// ida_star_by under a Watch
+pub fn ida_star_watched(
+    board: &Board,
+    name: &str,
+    estimate: impl Fn(&Board) -> u32,
+    watch: &mut Watch,
+) -> Result<Search, SolveError> {
+    if !board.is_solvable() {
+        return Err(SolveError::Unsolvable);
+    }
+
+    let mut current = board.clone();
+    let mut search = Search {
+        moves: Vec::new(),
+        nodes_expanded: 0,
+        elapsed: None,
+    };
+    let mut bound = estimate(board);
+    loop {
+        match ida_search(&mut current, &estimate, bound, &mut search, watch) {
+            Bound::Found => {
+                log::debug!(
+                    "ida_star ({}) found {} moves after {} nodes",
+                    name,
+                    search.moves.len(),
+                    search.nodes_expanded
+                );
+                debug_assert_solves(board, &search.moves);
+                search.elapsed = watch.elapsed();
+                return Ok(search);
+            }
+            Bound::Next(next) => bound = next,
+            Bound::Stopped(err) => {
+                log::debug!(
+                    "ida_star ({}) stopped after {} nodes: {}",
+                    name,
+                    search.nodes_expanded,
+                    err
+                );
+                return Err(err);
+            }
+        }
+    }
+}
+
+fn ida_search(
+    board: &mut Board,
+    estimate: &impl Fn(&Board) -> u32,
+    bound: u32,
+    search: &mut Search,
+    watch: &mut Watch,
+) -> Bound {
+    let remaining = estimate(board);
+    let total = search.moves.len() as u32 + remaining;
+    if total > bound {
+        return Bound::Next(total);
+    }
+    if remaining == 0 {
+        return Bound::Found;
+    }
+
+    search.nodes_expanded += 1;
+    // Only the path is kept, one board per move
+    if let Some(err) = watch.stop(search.nodes_expanded, bound, search.moves.len()) {
+        return Bound::Stopped(err);
+    }
+    let mut next_bound = u32::MAX;
+    for direction in Direction::ALL {
+        // Undoing the last move never leads anywhere new
+        if search.moves.last() == Some(&direction.opposite()) || !board.move_tile(direction) {
+            continue;
+        }
+        search.moves.push(direction);
+        match ida_search(board, estimate, bound, search, watch) {
+            Bound::Found => return Bound::Found,
+            Bound::Next(next) => next_bound = next_bound.min(next),
+            Bound::Stopped(err) => return Bound::Stopped(err),
+        }
+        search.moves.pop();
+        board.undo_move(direction);
+    }
+    Bound::Next(next_bound)
+}
+
+// A*: always expands the open board with the fewest moves so far plus the
+// heuristic, and among those the one the heuristic puts nearest the goal.
+// Unlike ida_star it never searches a board twice and needs no restarts, at
+// the cost of keeping every board it has seen. With an admissible heuristic
+// the first goal taken from the queue was reached by a shortest path.
+pub fn a_star(board: &Board, heuristic: Heuristic) -> Option<Search> {
+    a_star_watched(board, heuristic, &mut Watch::unwatched()).ok()
+}
+
+// a_star under a Watch
+pub fn a_star_watched(
+    board: &Board,
+    heuristic: Heuristic,
+    watch: &mut Watch,
+) -> Result<Search, SolveError> {
+    if !board.is_solvable() {
+        return Err(SolveError::Unsolvable);
+    }
+
+    // Keyed by Zobrist hash like bfs: the fewest moves found to each board
+    // and the move that got there, plus the boards still waiting to be
+    // expanded. The queue holds (f, h, hash) with f = g + h.
+    let mut costs: HashMap<u64, u32> = HashMap::new();
+    let mut parents: HashMap<u64, Option<(u64, Direction)>> = HashMap::new();
+    let mut open: HashMap<u64, Board> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    let mut nodes_expanded = 0;
+
+    let hash = board.zobrist();
+    let estimate = heuristic.estimate(board);
+    costs.insert(hash, 0);
+    parents.insert(hash, None);
+    open.insert(hash, board.clone());
+    queue.push(Reverse((estimate, estimate, hash)));
+
+    while let Some(Reverse((f, h, hash))) = queue.pop() {
+        let g = f - h;
+        // Queued before a shorter way there turned up, or already expanded
+        if costs[&hash] < g {
+            continue;
+        }
+        let Some(current) = open.remove(&hash) else {
+            continue;
+        };
+        if h == 0 {
+            let moves = reconstruct_path(&parents, hash);
+            log::debug!(
+                "a_star ({}) found {} moves after {} nodes",
+                heuristic.name(),
+                moves.len(),
+                nodes_expanded
+            );
+            debug_assert_solves(board, &moves);
+            return Ok(Search {
+                moves,
+                nodes_expanded,
+                elapsed: watch.elapsed(),
+            });
+        }
+
+        nodes_expanded += 1;
+        if let Some(err) = watch.stop(nodes_expanded, f, costs.len()) {
+            log::debug!(
+                "a_star ({}) stopped after {} nodes: {}",
+                heuristic.name(),
+                nodes_expanded,
+                err
+            );
+            return Err(err);
+        }
+        for (direction, next) in current.neighbor_states() {
+            let next_hash = current
+                .zobrist_after_move(hash, direction)
+                .expect("neighbor_states only yields legal moves");
+            if costs.get(&next_hash).is_some_and(|&cost| cost <= g + 1) {
+                continue;
+            }
+            let estimate = heuristic.estimate(&next);
+            costs.insert(next_hash, g + 1);
+            parents.insert(next_hash, Some((hash, direction)));
+            open.insert(next_hash, next);
+            queue.push(Reverse((g + 1 + estimate, estimate, next_hash)));
+        }
+    }
+    Err(SolveError::Unsolvable)
+}
+
+// How a Solver got on: a shortest solution with the work it took, or why
+// there isn't one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverResult {
+    Solved(Search),
+    AlreadySolved,
+    Unsolvable,
+    Timeout,
+    MemoryExceeded,
+    Cancelled,
+    TooLarge { size: usize },
+    NoPatternDb,
+}
+
+impl SolverResult {
+    // None unless it was solved, with no moves when it already was
+    pub fn moves(&self) -> Option<&[Direction]> {
+        match self {
+            SolverResult::Solved(search) => Some(&search.moves),
+            SolverResult::AlreadySolved => Some(&[]),
+            _ => None,
+        }
+    }
+
+    // The moves, or the SolveError saying why there aren't any
+    pub fn into_moves(self) -> Result<Vec<Direction>, SolveError> {
+        match self {
+            SolverResult::Solved(search) => Ok(search.moves),
+            SolverResult::AlreadySolved => Ok(Vec::new()),
+            SolverResult::Unsolvable => Err(SolveError::Unsolvable),
+            SolverResult::Timeout => Err(SolveError::TimedOut),
+            SolverResult::MemoryExceeded => Err(SolveError::OutOfMemory),
+            SolverResult::Cancelled => Err(SolveError::Cancelled),
+            SolverResult::TooLarge { size } => Err(SolveError::TooLarge { size }),
+            SolverResult::NoPatternDb => Err(SolveError::NoPatternDb),
+        }
+    }
+}
+
+impl From<Result<Search, SolveError>> for SolverResult {
+    fn from(result: Result<Search, SolveError>) -> Self {
+        match result {
+            Ok(search) if search.moves.is_empty() => SolverResult::AlreadySolved,
+            Ok(search) => SolverResult::Solved(search),
+            Err(SolveError::Unsolvable) => SolverResult::Unsolvable,
+            Err(SolveError::TimedOut) => SolverResult::Timeout,
+            Err(SolveError::OutOfMemory) => SolverResult::MemoryExceeded,
+            Err(SolveError::Cancelled) => SolverResult::Cancelled,
+            Err(SolveError::TooLarge { size }) => SolverResult::TooLarge { size },
+            Err(SolveError::NoPatternDb) => SolverResult::NoPatternDb,
+        }
+    }
+}
+
+// A search that can stand in for any other, so callers pick one by what it
+// costs rather than by how it's called
+pub trait Solver {
+    fn name(&self) -> &'static str;
+
+    // Under a Watch, which can report on the search and stop it
+    fn solve_watched(&self, board: &Board, watch: &mut Watch) -> SolverResult;
+
+    fn solve(&self, board: &Board) -> SolverResult {
+        self.solve_watched(board, &mut Watch::unwatched())
+    }
+}
+
+// ida_star: flat memory, but boards are searched again on every restart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdaStarSolver {
+    pub heuristic: Heuristic,
+}
+
+impl Solver for IdaStarSolver {
+    fn name(&self) -> &'static str {
+        "ida*"
+    }
+
+    fn solve_watched(&self, board: &Board, watch: &mut Watch) -> SolverResult {
+        let heuristic = self.heuristic;
+        ida_star_watched(
+            board,
+            heuristic.name(),
+            |board| heuristic.estimate(board),
+            watch,
+        )
+        .into()
+    }
+}
+
+// a_star: each board is expanded once, but every board seen is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AStarSolver {
+    pub heuristic: Heuristic,
+}
+
+impl Solver for AStarSolver {
+    fn name(&self) -> &'static str {
+        "a*"
+    }
+
+    fn solve_watched(&self, board: &Board, watch: &mut Watch) -> SolverResult {
+        a_star_watched(board, self.heuristic, watch).into()
+    }
+}
+
+// A shortest solution for a 4x4 board, by ida_star over the installed
+// pattern databases. Building those takes a while and a lot of memory, so
+// this never does; without them it's an error saying how to. Other sizes go
+// to solve.
+pub fn solve_with_pattern_db(board: &Board) -> Result<Vec<Direction>, SolveError> {
+    solve_watched(board, &mut Watch::unwatched())
+}
+
+// solve_with_pattern_db under a Watch. Only the 4x4 search takes long
+// enough to need one: the smaller boards are solved as solve does, neither
+// reporting nor stopping.
+pub fn solve_watched(board: &Board, watch: &mut Watch) -> Result<Vec<Direction>, SolveError> {
+    PatternDbSolver.solve_watched(board, watch).into_moves()
+}
+
+// solve_with_pattern_db as a Solver, for the game and the solve command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternDbSolver;
+
+impl Solver for PatternDbSolver {
+    fn name(&self) -> &'static str {
+        "pattern db"
+    }
+
+    fn solve_watched(&self, board: &Board, watch: &mut Watch) -> SolverResult {
+        if board.size() != PDB_SIZE {
+            // Looked up or searched breadth first, neither of which counts
+            // nodes
+            let solved = solve(board).map(|moves| Search {
+                moves,
+                nodes_expanded: 0,
+                elapsed: watch.elapsed(),
+            });
+            return solved.into();
+        }
+        match pattern_db::installed() {
+            Some(tables) => {
+                ida_star_watched(board, self.name(), |board| tables.estimate(board), watch).into()
+            }
+            None => SolverResult::NoPatternDb,
+        }
+    }
+}
+
+// A suggested next move: the first step of the shortest solution on 3x3
+// boards, and otherwise whichever move lowers the Manhattan distance most
+pub fn hint(board: &Board) -> Option<Direction> {
+    if board.is_goal(&Board::goal_state(board.size())) || !board.is_solvable() {
+        return None;
+    }
+    if let Some(moves) = table_solve(board) {
+        return moves.first().copied();
+    }
+    if board.size() <= BFS_MAX_SIZE {
+        return bfs_solve(board)?.first().copied();
+    }
+
+    board
+        .neighbor_states()
+        .into_iter()
+        .min_by_key(|(_, next)| next.manhattan_distance())
+        .map(|(direction, _)| direction)
+}
+
+// The moves that undo `moves`: reversed, each replaced by its opposite. For a
+// solution this gives back the scramble that leads from solved to the start.
+pub fn invert_solution(moves: &[Direction]) -> Vec<Direction> {
+    Direction::invert_sequence(moves)
+}
+
+fn reconstruct_path(
+    parents: &HashMap<u64, Option<(u64, Direction)>>,
+    mut current: u64,
+) -> Vec<Direction> {
+    let mut path = Vec::new();
+    while let Some(Some((parent, direction))) = parents.get(&current) {
+        path.push(*direction);
+        current = *parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::{cell::Cell, time::Instant};
+
+    #[test]
+    fn test_bfs_solve() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let moves = bfs_solve(&board).unwrap();
+        assert_eq!(moves, [Direction::Left, Direction::Up]);
+
+        let mut replayed = board.clone();
+        for direction in moves {
+            replayed.move_tile(direction);
+        }
+        assert!(replayed.is_goal(&Board::new()));
+
+        assert_eq!(bfs_solve(&Board::new()), Some(vec![]));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "the solution for [1, 2, 3, 4, 0, 5, 7, 8, 6] ends on")]
+    fn test_debug_assert_solves() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        debug_assert_solves(&board, &bfs_solve(&board).unwrap());
+        debug_assert_solves(&board, &[]);
+    }
+
+    #[test]
+    fn test_hint() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(hint(&board), Some(Direction::Left));
+        assert_eq!(hint(&Board::new()), None);
+
+        let mut board = Board::goal_state(4);
+        board.move_tile(Direction::Down);
+        assert_eq!(hint(&board), Some(Direction::Up));
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let board: Board = "2 1 3 0".parse().unwrap();
+        assert_eq!(solve(&board), Err(SolveError::Unsolvable));
+        assert_eq!(
+            solve(&Board::goal_state(4)),
+            Err(SolveError::TooLarge { size: 4 })
+        );
+        // The unit tests never install the full tables
+        assert_eq!(
+            solve_with_pattern_db(&Board::goal_state(4)),
+            Err(SolveError::NoPatternDb)
+        );
+        assert_eq!(
+            solve_with_pattern_db(&Board::goal_state(5)),
+            Err(SolveError::TooLarge { size: 5 })
+        );
+    }
+
+    #[test]
+    fn test_invert_solution() {
+        let board: Board = "4 1 3 7 2 5 8 0 6".parse().unwrap();
+        let moves = bfs_solve(&board).unwrap();
+        let mut replayed = board.clone();
+        assert_eq!(replayed.apply_moves(&moves), moves.len());
+        assert!(replayed.is_goal(&Board::new()));
+
+        let scramble = invert_solution(&moves);
+        assert_eq!(replayed.apply_moves(&scramble), scramble.len());
+        crate::assert_eq_board!(replayed, board);
+        assert_eq!(
+            invert_solution(&[Direction::Up, Direction::Left]),
+            [Direction::Right, Direction::Down]
+        );
+    }
+
+    #[test]
+    fn test_fixed_board_bfs_matches_dynamic() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for size in [2, 3] {
+            for _ in 0..10 {
+                let mut board = Board::goal_state(size);
+                board.scramble_from_position(&mut rng, 12);
+                assert_eq!(bfs_solve(&board), bfs(board.clone()));
+            }
+        }
+
+        let mut board = Board::goal_state(4);
+        board.apply_moves(&Direction::parse_sequence("DDRRUL").unwrap());
+        let moves = bfs_solve(&board).unwrap();
+        assert_eq!(moves, bfs(board.clone()).unwrap());
+        assert_eq!(moves.len(), 6);
+    }
+
+    #[test]
+    fn test_ida_star() {
+        let board: Board = "4 1 3 7 2 5 8 0 6".parse().unwrap();
+        let shortest = bfs_solve(&board).unwrap().len();
+        for heuristic in Heuristic::ALL {
+            let search = ida_star(&board, heuristic).unwrap();
+            assert_eq!(search.moves.len(), shortest);
+
+            let mut replayed = board.clone();
+            replayed.apply_moves(&search.moves);
+            assert!(replayed.is_goal(&Board::new()));
+        }
+
+        let solved = ida_star(&Board::goal_state(4), Heuristic::Manhattan).unwrap();
+        assert_eq!(solved.moves, []);
+        assert_eq!(
+            ida_star(&"2 1 3 0".parse().unwrap(), Heuristic::Misplaced),
+            None
+        );
+    }
+
+    #[test]
+    fn test_a_star_matches_ida_star() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(13);
+        let mut boards = (0..8)
+            .map(|i| {
+                let mut board = Board::goal_state(3);
+                board.scramble_from_position(&mut rng, 10 + i * 5);
+                board
+            })
+            .collect::<Vec<_>>();
+        let mut board = Board::goal_state(4);
+        board.scramble_from_position(&mut rng, 20);
+        boards.push(board);
+
+        for board in &boards {
+            for heuristic in Heuristic::ALL {
+                let solvers: [&dyn Solver; 2] =
+                    [&AStarSolver { heuristic }, &IdaStarSolver { heuristic }];
+                let [found, expected] = solvers.map(|solver| solver.solve(board));
+                let (moves, expected) = (found.moves().unwrap(), expected.moves().unwrap());
+                assert_eq!(moves.len(), expected.len(), "{:?}", board);
+
+                let mut replayed = board.clone();
+                assert_eq!(replayed.apply_moves(moves), moves.len());
+                crate::assert_eq_board!(replayed, Board::goal_state(board.size()));
+            }
+        }
+
+        assert_eq!(
+            a_star(&Board::new(), Heuristic::Manhattan).unwrap().moves,
+            []
+        );
+        assert_eq!(
+            a_star(&"2 1 3 0".parse().unwrap(), Heuristic::Manhattan),
+            None
+        );
+    }
+
+    #[test]
+    fn test_heuristic_estimates() {
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(Heuristic::Manhattan.estimate(&board), 21);
+        assert_eq!(Heuristic::Misplaced.estimate(&board), 7);
+        assert_eq!(Heuristic::Misplaced.estimate(&Board::new()), 0);
+    }
+
+    #[test]
+    fn test_bibfs_matches_bfs() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut boards: Vec<Board> = (0..20)
+            .map(|i| {
+                let mut board = Board::goal_state(3);
+                board.scramble_from_position(&mut rng, 5 + i * 3);
+                board
+            })
+            .collect();
+        boards.push("8 6 7 2 5 4 3 0 1".parse().unwrap());
+        boards.push("1 2 3 4 5 6 7 8 0".parse().unwrap());
+        let mut board = Board::goal_state(4);
+        board.apply_moves(&Direction::parse_sequence("DDRRUL").unwrap());
+        boards.push(board);
+
+        for board in &boards {
+            let moves = bibfs_solve(board).unwrap();
+            assert_eq!(moves.len(), bfs_solve(board).unwrap().len(), "{:?}", board);
+            let mut replayed = board.clone();
+            assert_eq!(replayed.apply_moves(&moves), moves.len());
+            crate::assert_eq_board!(replayed, Board::goal_state(board.size()));
+        }
+        assert_eq!(bibfs_solve(&"2 1 3 0".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_bibfs_visits_fewer_boards() {
+        // 31 moves out, as deep as a 3x3 goes: bfs has to visit almost every
+        // solvable board before it gets there
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        let fixed = FixedBoard3::try_from(&board).unwrap();
+        let (moves, visited) = bibfs(fixed).unwrap();
+        assert_eq!(moves.len(), 31);
+        assert!(
+            visited < distance_table::SOLVABLE_3X3 / 4,
+            "{} boards visited",
+            visited
+        );
+    }
+
+    #[test]
+    fn test_bfs_unsolvable() {
+        let board: Board = "2 1 3 0".parse().unwrap();
+        assert_eq!(bfs_solve(&board), None);
+    }
+
+    // Far out of reach of ida_star with only misplaced tiles to go on
+    fn hard_4x4() -> Board {
+        let mut board = Board::goal_state(4);
+        board.scramble_from_position(&mut rand::rngs::StdRng::seed_from_u64(3), 200);
+        board
+    }
+
+    #[test]
+    fn test_cancel_interrupts_search() {
+        let board = hard_4x4();
+        let cancel = AtomicBool::new(false);
+        let started = Instant::now();
+        let result = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                cancel.store(true, Ordering::Relaxed);
+            });
+            let mut watch = Watch::new(&cancel);
+            ida_star_watched(
+                &board,
+                "misplaced",
+                |board| Heuristic::Misplaced.estimate(board),
+                &mut watch,
+            )
+        });
+        assert_eq!(result, Err(SolveError::Cancelled));
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "{:?}",
+            started.elapsed()
+        );
+    }
+
+    // Runs `search` until its first report, which cancels it
+    fn first_report(search: impl FnOnce(&mut Watch) -> Result<Search, SolveError>) -> Progress {
+        let cancel = AtomicBool::new(false);
+        let mut reports = Vec::new();
+        let started = Instant::now();
+        let mut watch = Watch::new(&cancel)
+            .with_clock(|| started.elapsed())
+            .with_progress(|progress| {
+                reports.push(progress);
+                cancel.store(true, Ordering::Relaxed);
+            });
+        assert_eq!(search(&mut watch), Err(SolveError::Cancelled));
+        drop(watch);
+        match reports[..] {
+            [progress] => progress,
+            _ => panic!("{} reports", reports.len()),
+        }
+    }
+
+    #[test]
+    fn test_progress_reports() {
+        let board = hard_4x4();
+        let estimate = |board: &Board| Heuristic::Misplaced.estimate(board);
+        let reports = [
+            first_report(|watch| ida_star_watched(&board, "misplaced", estimate, watch)),
+            first_report(|watch| a_star_watched(&board, Heuristic::Misplaced, watch)),
+        ];
+        for progress in reports {
+            assert!(progress.nodes_expanded > 0);
+            assert!(progress.bound >= estimate(&board));
+            assert!(progress.elapsed >= PROGRESS_INTERVAL);
+        }
+    }
+
+    #[test]
+    fn test_unwatched_has_no_clock() {
+        // Long enough to pass the watch many times, which mustn't read the
+        // time: there's no clock on wasm
+        assert!(Watch::unwatched().clock.is_none());
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        let search = ida_star(&board, Heuristic::Misplaced).unwrap();
+        assert!(search.nodes_expanded > WATCH_EVERY);
+        assert_eq!(search.moves.len(), 31);
+        assert_eq!(search.elapsed, None);
+
+        // Given one, the watch reads it and the search says how long it took
+        let cancel = AtomicBool::new(false);
+        let reads = Cell::new(0);
+        let mut watch = Watch::new(&cancel).with_clock(|| {
+            reads.set(reads.get() + 1);
+            Duration::from_secs(2)
+        });
+        let search = ida_star_watched(
+            &board,
+            "misplaced",
+            |board| Heuristic::Misplaced.estimate(board),
+            &mut watch,
+        )
+        .unwrap();
+        assert!(reads.get() > 0);
+        assert_eq!(search.elapsed, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_solver_results() {
+        let solver = IdaStarSolver {
+            heuristic: Heuristic::Manhattan,
+        };
+        let board: Board = "4 1 3 7 2 5 8 0 6".parse().unwrap();
+        let SolverResult::Solved(search) = solver.solve(&board) else {
+            panic!("{:?} should solve", board);
+        };
+        assert_eq!(search.moves.len(), bfs_solve(&board).unwrap().len());
+        assert!(search.nodes_expanded > 0);
+        assert_eq!(solver.solve(&Board::new()), SolverResult::AlreadySolved);
+        assert_eq!(
+            solver.solve(&"2 1 3 0".parse().unwrap()),
+            SolverResult::Unsolvable
+        );
+
+        // A clock that moves on 50ms every time it's read
+        let cancel = AtomicBool::new(false);
+        let reads = Cell::new(0);
+        let mut watch = Watch::new(&cancel)
+            .with_clock(|| {
+                reads.set(reads.get() + 1);
+                Duration::from_millis(50) * reads.get()
+            })
+            .with_time_limit(Duration::from_secs(1));
+        let misplaced = IdaStarSolver {
+            heuristic: Heuristic::Misplaced,
+        };
+        assert_eq!(
+            misplaced.solve_watched(&hard_4x4(), &mut watch),
+            SolverResult::Timeout
+        );
+        assert_eq!(reads.get(), 20);
+
+        let mut watch = Watch::new(&cancel).with_memory_limit(10_000);
+        let a_star = AStarSolver {
+            heuristic: Heuristic::Misplaced,
+        };
+        assert_eq!(
+            a_star.solve_watched(&hard_4x4(), &mut watch),
+            SolverResult::MemoryExceeded
+        );
+    }
+
+    #[test]
+    fn test_pattern_db_solver() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let solved = PatternDbSolver.solve(&board);
+        assert_eq!(solved.moves(), Some(&[Direction::Left, Direction::Up][..]));
+        assert_eq!(
+            PatternDbSolver.solve(&Board::new()),
+            SolverResult::AlreadySolved
+        );
+        assert_eq!(
+            PatternDbSolver.solve(&"2 1 3 0".parse().unwrap()),
+            SolverResult::Unsolvable
+        );
+        // Each failure keeps its own reason, there and back
+        assert_eq!(
+            PatternDbSolver.solve(&Board::goal_state(4)),
+            SolverResult::NoPatternDb
+        );
+        let too_large = PatternDbSolver.solve(&Board::goal_state(5));
+        assert_eq!(too_large, SolverResult::TooLarge { size: 5 });
+        assert_eq!(
+            too_large.into_moves(),
+            Err(SolveError::TooLarge { size: 5 })
+        );
+        assert_eq!(
+            SolverResult::from(Err(SolveError::NoPatternDb)),
+            SolverResult::NoPatternDb
+        );
+        assert_eq!(
+            SolverResult::MemoryExceeded.into_moves(),
+            Err(SolveError::OutOfMemory)
+        );
+    }
+}
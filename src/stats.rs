@@ -0,0 +1,91 @@
+use std::{collections::VecDeque, time::Duration};
+
+// How far back the rolling moves-per-second rate looks
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+// When each recent move was made, as time into the game, for a rolling rate.
+// Only moves inside the window are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveRate {
+    times: VecDeque<Duration>,
+}
+
+impl MoveRate {
+    pub fn record(&mut self, at: Duration) {
+        self.times.push_back(at);
+        self.prune(at);
+    }
+
+    // Forgets the moves that have dropped out of the window ending at `now`
+    pub fn prune(&mut self, now: Duration) {
+        while self
+            .times
+            .front()
+            .is_some_and(|&at| now.saturating_sub(at) >= RATE_WINDOW)
+        {
+            self.times.pop_front();
+        }
+    }
+
+    // In tenths of a move per second over the window, or since the start for
+    // a game younger than that
+    pub fn tenths_per_second(&self, now: Duration) -> u32 {
+        let recent = self
+            .times
+            .iter()
+            .filter(|&&at| now.saturating_sub(at) < RATE_WINDOW)
+            .count() as u128;
+        let span = now.min(RATE_WINDOW).as_millis();
+        match span {
+            0 => 0,
+            _ => (recent * 10_000 / span) as u32,
+        }
+    }
+}
+
+// Over the whole game
+pub fn average_per_second(moves: u32, elapsed: Duration) -> f64 {
+    match elapsed.is_zero() {
+        true => 0.0,
+        false => f64::from(moves) / elapsed.as_secs_f64(),
+    }
+}
+
+// Moves so far as a percentage of par, so 100 is a perfect game once won.
+// None when par is zero, as it is for a board that starts solved.
+pub fn efficiency(moves: u32, par: u32) -> Option<u32> {
+    (par > 0).then(|| moves * 100 / par)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_rate() {
+        let secs = Duration::from_secs;
+        let mut rate = MoveRate::default();
+        assert_eq!(rate.tenths_per_second(Duration::ZERO), 0);
+        for at in [1, 2, 3, 4] {
+            rate.record(secs(at));
+        }
+        // Four moves in the game's first four seconds
+        assert_eq!(rate.tenths_per_second(secs(4)), 10);
+        // The first has dropped out of the window by 11s
+        assert_eq!(rate.tenths_per_second(secs(11)), 3);
+
+        rate.prune(secs(13));
+        assert_eq!(rate.times, [secs(4)]);
+        rate.prune(secs(60));
+        assert_eq!(rate.tenths_per_second(secs(60)), 0);
+        assert!(rate.times.is_empty());
+    }
+
+    #[test]
+    fn test_averages() {
+        assert_eq!(average_per_second(30, Duration::from_secs(20)), 1.5);
+        assert_eq!(average_per_second(3, Duration::ZERO), 0.0);
+        assert_eq!(efficiency(30, 20), Some(150));
+        assert_eq!(efficiency(4, 0), None);
+    }
+}
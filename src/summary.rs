@@ -0,0 +1,203 @@
+use std::{fmt::Write, time::Duration};
+use tile_game_rs::{
+    duration::{format_duration, TimeStyle},
+    leaderboard::Leaderboard,
+    result::GameResult,
+};
+
+// What `stats` prints: every solve on the leaderboard by board size and
+// mode, and, when there are any, the saved results taken together
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub categories: Vec<CategorySummary>,
+    pub results: Option<ResultsSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategorySummary {
+    pub size: usize,
+    pub mode: String,
+    pub solves: usize,
+    pub best_ms: u64,
+    pub mean_ms: f64,
+    pub fewest_moves: u32,
+    pub mean_moves: f64,
+    pub first: String, // Dates of the first and latest solve
+    pub latest: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultsSummary {
+    pub games: usize,
+    pub solved: usize,
+    pub mean_moves: f64,
+    pub mean_ms: f64,
+    pub hints_used: u32,
+}
+
+pub fn summarize(leaderboard: &Leaderboard, results: &[GameResult]) -> Summary {
+    let categories = leaderboard
+        .categories()
+        .into_iter()
+        .map(|(size, mode)| {
+            let entries: Vec<_> = leaderboard
+                .entries()
+                .iter()
+                .filter(|entry| entry.size == size && entry.mode == mode)
+                .collect();
+            let mut dates: Vec<_> = entries.iter().map(|entry| day(&entry.date)).collect();
+            dates.sort_unstable();
+            CategorySummary {
+                size,
+                mode: mode.to_string(),
+                solves: entries.len(),
+                best_ms: entries.iter().map(|entry| entry.time_ms).min().unwrap_or(0),
+                mean_ms: mean(entries.iter().map(|entry| entry.time_ms as f64)),
+                fewest_moves: entries.iter().map(|entry| entry.moves).min().unwrap_or(0),
+                mean_moves: mean(entries.iter().map(|entry| f64::from(entry.moves))),
+                first: dates.first().copied().unwrap_or_default().to_string(),
+                latest: dates.last().copied().unwrap_or_default().to_string(),
+            }
+        })
+        .collect();
+
+    let results = (!results.is_empty()).then(|| ResultsSummary {
+        games: results.len(),
+        solved: results.iter().filter(|result| result.solved).count(),
+        mean_moves: mean(results.iter().map(|result| f64::from(result.move_count))),
+        mean_ms: mean(results.iter().map(|result| result.elapsed_ms as f64)),
+        hints_used: results.iter().map(|result| result.hints_used).sum(),
+    });
+
+    Summary {
+        categories,
+        results,
+    }
+}
+
+impl Summary {
+    pub fn table(&self) -> String {
+        let mut out = String::new();
+        if self.categories.is_empty() {
+            out += "No solves on the leaderboard yet\n";
+        } else {
+            let _ = writeln!(
+                out,
+                "{:<16} {:>6} {:>9} {:>9} {:>6} {:>7}  {:<10}  {:<10}",
+                "board", "solves", "best", "mean", "fewest", "mean", "first", "latest"
+            );
+        }
+        for category in &self.categories {
+            let board = format!("{}x{} {}", category.size, category.size, category.mode);
+            let _ = writeln!(
+                out,
+                "{:<16} {:>6} {:>9} {:>9} {:>6} {:>7.1}  {:<10}  {:<10}",
+                board,
+                category.solves,
+                time(category.best_ms as f64),
+                time(category.mean_ms),
+                category.fewest_moves,
+                category.mean_moves,
+                category.first,
+                category.latest
+            );
+        }
+
+        if let Some(results) = &self.results {
+            let _ = writeln!(
+                out,
+                "\n{} results, {} solved, {:.1} moves and {} on average, {} hints",
+                results.games,
+                results.solved,
+                results.mean_moves,
+                time(results.mean_ms),
+                results.hints_used
+            );
+        }
+        out
+    }
+}
+
+// The date part of a leaderboard timestamp
+fn day(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+fn time(ms: f64) -> String {
+    format_duration(Duration::from_millis(ms.round() as u64), TimeStyle::Precise)
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tile_game_rs::{
+        board::{Board, Direction},
+        game::{GameSession, StartKind},
+    };
+
+    fn leaderboard() -> Leaderboard {
+        let line = |size: usize, mode: &str, time_ms: u64, moves: u32, date: &str| {
+            format!(
+                r#"{{"name":"ann","size":{},"mode":"{}","time_ms":{},"moves":{},"date":"{}","seed":null}}"#,
+                size, mode, time_ms, moves, date
+            )
+        };
+        let text = [
+            line(3, "classic", 40_000, 30, "2026-03-02T10:00:00Z"),
+            line(3, "classic", 20_000, 34, "2026-01-05T09:00:00Z"),
+            line(4, "gravity", 90_000, 80, "2026-02-01T12:00:00Z"),
+        ]
+        .join("\n");
+        Leaderboard::parse(&text).0
+    }
+
+    #[test]
+    fn test_summarize_leaderboard() {
+        let summary = summarize(&leaderboard(), &[]);
+        assert_eq!(summary.categories.len(), 2);
+        let classic = &summary.categories[0];
+        assert_eq!((classic.size, classic.mode.as_str()), (3, "classic"));
+        assert_eq!(classic.solves, 2);
+        assert_eq!((classic.best_ms, classic.mean_ms), (20_000, 30_000.0));
+        assert_eq!((classic.fewest_moves, classic.mean_moves), (30, 32.0));
+        assert_eq!(classic.first, "2026-01-05");
+        assert_eq!(classic.latest, "2026-03-02");
+        assert_eq!(summary.results, None);
+
+        let table = summary.table();
+        assert!(table.lines().nth(1).unwrap().starts_with("3x3 classic"));
+        assert!(table.contains("2026-02-01"));
+    }
+
+    #[test]
+    fn test_summarize_results() {
+        let result = |solved: bool, move_count: u32, hints_used: u32| {
+            let start: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+            let mut session = GameSession::new(start, StartKind::Custom, None);
+            if solved {
+                session.apply_move(Direction::Left);
+            }
+            session.move_count = move_count;
+            session.hints_used = hints_used;
+            GameResult::from_session(&session, 3000, "classic")
+        };
+        let results = [result(true, 10, 1), result(false, 20, 2)];
+        let summary = summarize(&Leaderboard::default(), &results);
+        assert!(summary.categories.is_empty());
+        let results = summary.results.as_ref().unwrap();
+        assert_eq!((results.games, results.solved), (2, 1));
+        assert_eq!((results.mean_moves, results.hints_used), (15.0, 3));
+        let table = summary.table();
+        assert!(table.starts_with("No solves on the leaderboard yet\n"));
+        assert!(table.contains("2 results, 1 solved, 15.0 moves"));
+    }
+}
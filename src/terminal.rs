@@ -0,0 +1,122 @@
+use crate::error::EXIT_INTERRUPTED;
+use crossterm::{
+    cursor::Show,
+    execute,
+    style::ResetColor,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::{
+    io, panic, process,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+// Set while a guard holds the terminal, so the panic hook only restores a
+// terminal that was actually taken over and leaves pipe output alone
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// Set by a SIGINT during play until the game loop picks it up
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// Total time spent stopped by Ctrl+Z, kept off the game clock
+static SUSPENDED_MS: AtomicU64 = AtomicU64::new(0);
+
+// Puts the terminal in raw mode on the alternate screen and puts it back when
+// dropped, including on early returns and unwinding panics
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> io::Result<Self> {
+        let guard = TerminalGuard;
+        take_over()?;
+        Ok(guard)
+    }
+}
+
+fn take_over() -> io::Result<()> {
+    enable_raw_mode()?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    execute!(io::stdout(), EnterAlternateScreen)
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// Best effort: every step runs even if an earlier one fails
+fn restore() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = execute!(io::stdout(), ResetColor, Show, LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+}
+
+// A SIGINT during play asks the game loop to quit as if 'q' was pressed. A
+// second one before the loop has handled the first, or any SIGINT outside
+// play, restores the terminal and exits straight away.
+pub fn install_interrupt_handler() {
+    let result = ctrlc::set_handler(|| {
+        if ACTIVE.load(Ordering::SeqCst) && !INTERRUPTED.swap(true, Ordering::SeqCst) {
+            log::info!("SIGINT received, quitting");
+            return;
+        }
+        log::info!("SIGINT received, exiting immediately");
+        restore();
+        process::exit(EXIT_INTERRUPTED);
+    });
+    if let Err(err) = result {
+        log::warn!("couldn't install the SIGINT handler: {}", err);
+    }
+}
+
+// Raw mode turns Ctrl+Z into a key press, so the game stops itself: hand the
+// terminal back to the shell, stop with SIGTSTP, and take the terminal again
+// once `fg` sends SIGCONT. The caller redraws the whole screen afterwards.
+#[cfg(unix)]
+pub fn suspend() -> io::Result<()> {
+    if !ACTIVE.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    log::info!("suspending");
+    restore();
+
+    let stopped = std::time::Instant::now();
+    // SAFETY: raise has no preconditions; SIGTSTP's default action stops the
+    // process and raise returns once it is continued
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+    let elapsed = stopped.elapsed();
+    SUSPENDED_MS.fetch_add(elapsed.as_millis() as u64, Ordering::SeqCst);
+    log::info!("resumed after {:?}", elapsed);
+
+    take_over()
+}
+
+// There is no job control to hand back to
+#[cfg(not(unix))]
+pub fn suspend() -> io::Result<()> {
+    Ok(())
+}
+
+pub fn suspended_time() -> Duration {
+    Duration::from_millis(SUSPENDED_MS.load(Ordering::SeqCst))
+}
+
+// Whether a SIGINT arrived since the last call
+pub fn take_interrupt() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+// The default hook prints the panic message before unwinding reaches the
+// guard, which would leave it on the alternate screen, so restore first
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        default_hook(info);
+    }));
+}
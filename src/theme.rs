@@ -0,0 +1,133 @@
+use crate::ui::{CellStyle, GoalPaint};
+use clap::ValueEnum;
+use crossterm::style::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Theme {
+    /// White numbers on grey tiles
+    Classic,
+    /// White numbers on blue tiles
+    Ocean,
+    /// The terminal's own colors, tiles shown inverted
+    Mono,
+    /// Each tile in the color of the row it belongs in, brighter once there
+    Rows,
+    /// Each tile in the color of the column it belongs in, brighter once there
+    Columns,
+    /// Each tile a block of a rainbow that comes together once solved
+    Picture,
+}
+
+// Backgrounds for goal rows or columns, from the 16 colors every terminal
+// has, with the bright one for a tile that's home. Boards with more lines
+// than this start over from the top.
+const LINE_COLORS: [(Color, Color); 8] = [
+    (Color::DarkRed, Color::Red),
+    (Color::DarkGreen, Color::Green),
+    (Color::DarkBlue, Color::Blue),
+    (Color::DarkYellow, Color::Yellow),
+    (Color::DarkMagenta, Color::Magenta),
+    (Color::DarkCyan, Color::Cyan),
+    (Color::DarkGrey, Color::Grey),
+    (Color::Black, Color::White),
+];
+
+impl Theme {
+    // (foreground, background) for a numbered tile
+    pub fn tile_colors(self) -> (Color, Color) {
+        match self {
+            Theme::Classic => (Color::White, Color::DarkGrey),
+            Theme::Ocean => (Color::White, Color::DarkBlue),
+            Theme::Mono => (Color::Black, Color::Grey),
+            Theme::Rows | Theme::Columns | Theme::Picture => (Color::White, Color::DarkGrey),
+        }
+    }
+
+    // Whether tiles take their color from where they belong, and how. None
+    // for the themes that color every tile alike.
+    pub fn goal_paint(self) -> Option<GoalPaint> {
+        match self {
+            Theme::Rows => Some(GoalPaint::Rows),
+            Theme::Columns => Some(GoalPaint::Columns),
+            Theme::Picture => Some(GoalPaint::Picture),
+            _ => None,
+        }
+    }
+
+    // As --theme takes it
+    pub fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    // The one after this in the list, back to the first after the last
+    pub fn next(self) -> Self {
+        let themes = Self::value_variants();
+        let index = themes.iter().position(|&theme| theme == self).unwrap_or(0);
+        themes[(index + 1) % themes.len()]
+    }
+
+    // (foreground, background) for a cell; None keeps the terminal default
+    pub fn style_colors(self, style: CellStyle) -> (Option<Color>, Option<Color>) {
+        match style {
+            CellStyle::Default => (None, None),
+            CellStyle::Tile => {
+                let (foreground, background) = self.tile_colors();
+                (Some(foreground), Some(background))
+            }
+            CellStyle::Win => (Some(Color::Green), None),
+            CellStyle::Hint => (Some(Color::DarkGrey), None),
+            CellStyle::Warning => (Some(Color::Yellow), None),
+            CellStyle::Line { line, placed } => {
+                let (dark, bright) = LINE_COLORS[line as usize % LINE_COLORS.len()];
+                match placed {
+                    true => (Some(Color::Black), Some(bright)),
+                    false => (Some(Color::White), Some(dark)),
+                }
+            }
+            CellStyle::Pulled => (Some(Color::Black), Some(Color::DarkMagenta)),
+            // Cooler to hotter whatever the theme
+            CellStyle::Heat(level) => match level {
+                1 => (Some(Color::White), Some(Color::DarkBlue)),
+                2 => (Some(Color::Black), Some(Color::DarkCyan)),
+                3 => (Some(Color::Black), Some(Color::DarkYellow)),
+                _ => (Some(Color::White), Some(Color::DarkRed)),
+            },
+            CellStyle::Block(color) => (Some(rgb(faint(color))), Some(rgb(color))),
+            CellStyle::Pixel { top, bottom } => (Some(rgb(top)), Some(rgb(bottom))),
+        }
+    }
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb { r, g, b }
+}
+
+// A number on a block of `color`: a third of the way to black on light
+// blocks and to white on dark ones, readable without spoiling the picture
+fn faint(color: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = color.map(u32::from);
+    let target = match (299 * r + 587 * g + 114 * b) / 1000 {
+        luma if luma > 128 => 0,
+        _ => 255,
+    };
+    color.map(|c| ((u32::from(c) * 2 + target) / 3) as u8)
+}
+
+// Whether the terminal says it takes 24-bit color, as most now do
+pub fn truecolor_supported() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit")
+}
+
+// The nearest of the 256-color palette's 6x6x6 cube, for terminals without
+// 24-bit color; anything else is left as it is
+pub fn to_256(color: Color) -> Color {
+    match color {
+        Color::Rgb { r, g, b } => {
+            let level = |c: u8| (u16::from(c) * 5 + 127) / 255;
+            Color::AnsiValue((16 + 36 * level(r) + 6 * level(g) + level(b)) as u8)
+        }
+        color => color,
+    }
+}
@@ -0,0 +1,1402 @@
+use crate::{
+    backend::Backend,
+    board::{Board, Direction, Tile},
+    difficulty::DifficultyLevel,
+    duration::{format_duration, TimeStyle},
+    heatmap::Heatmap,
+    i18n::Lang,
+    renderer::TextLine,
+    stats,
+    theme::Theme,
+};
+use std::{io, rc::Rc, time::Duration};
+
+// Split-screen mode needs at least this many columns whatever the board size
+pub const SPLIT_MIN_WIDTH: u16 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellStyle {
+    Default,
+    Tile,
+    Win,
+    Hint,
+    Warning,
+    Pulled,
+    // A tile in the rows or columns theme, by its goal line, and whether
+    // it's in its goal cell
+    Line { line: u8, placed: bool },
+    Heat(u8),       // A heatmap cell, from 1 up to heatmap::HEAT_LEVELS
+    Block([u8; 3]), // A tile in the picture theme, in its goal cell's color
+    // Two stacked pixels of a photo tile, top as the foreground of a '▀'
+    Pixel { top: [u8; 3], bottom: [u8; 3] },
+}
+
+impl CellStyle {
+    // How the style appears in plain-text snapshots
+    fn marker(self) -> &'static str {
+        match self {
+            CellStyle::Default => "{/}",
+            CellStyle::Tile => "{tile}",
+            CellStyle::Win => "{win}",
+            CellStyle::Hint => "{hint}",
+            CellStyle::Warning => "{warn}",
+            CellStyle::Pulled => "{pull}",
+            CellStyle::Line { placed: false, .. } => "{line}",
+            CellStyle::Line { placed: true, .. } => "{home}",
+            CellStyle::Heat(1) => "{heat1}",
+            CellStyle::Heat(2) => "{heat2}",
+            CellStyle::Heat(3) => "{heat3}",
+            CellStyle::Heat(_) => "{heat4}",
+            CellStyle::Block(_) => "{block}",
+            CellStyle::Pixel { .. } => "{px}",
+        }
+    }
+}
+
+// The cell under the right half of a wide character, drawn as nothing
+pub(crate) const WIDE: char = '\0';
+
+// Columns a character takes in the terminal: 2 for emoji and the wide CJK
+// blocks, close enough for tile labels without a width table
+pub(crate) fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x26AA..=0x26AB
+        | 0x2B1B..=0x2B1C
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F7E0..=0x1F7EB
+        | 0x1F900..=0x1FAFF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: CellStyle::Default,
+        }
+    }
+}
+
+// Everything that will be on screen, cell by cell. Both the terminal and the
+// plain-text renderer are built from this so they can't disagree on layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    // Text running past the right edge is cut off, so a translation that
+    // runs long is clipped rather than wrapping onto the next row. A wide
+    // character takes its cell and the one after, left empty.
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, style: CellStyle) {
+        if y >= self.height {
+            return;
+        }
+        let mut x = x as usize;
+        for ch in text.chars() {
+            let width = char_width(ch);
+            if x + width > self.width as usize {
+                break;
+            }
+            let row = y as usize * self.width as usize;
+            self.cells[row + x] = Cell { ch, style };
+            if width == 2 {
+                self.cells[row + x + 1] = Cell { ch: WIDE, style };
+            }
+            x += width;
+        }
+    }
+
+    pub fn put(&mut self, x: u16, y: u16, cell: Cell) {
+        if x < self.width && y < self.height {
+            self.cells[y as usize * self.width as usize + x as usize] = cell;
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+}
+
+// Per-frame game state that isn't part of the board itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiState {
+    pub move_count: u32,
+    pub won: bool,
+    pub message: Option<String>,
+    pub key_help: Option<String>, // None for the game's own keys
+    pub highlight: Option<Tile>,  // Drawn in the hint style
+    pub pulled: Option<Tile>,     // Pulled along by a magnetic move
+    pub legal_moves: Option<Vec<Direction>>, // Arrows beside the board, while assist is on
+    pub goal_colors: Option<GoalColors>, // For the themes that color tiles by goal cell
+    pub hide_numbers: bool,       // Only in the picture theme
+    pub tile_labels: Option<Rc<[String]>>, // Shown in place of the numbers
+    pub emoji: Option<Rc<[String]>>, // With --emoji, drawn over any labels
+    pub terminal_size: (u16, u16),
+    pub turn: Option<TurnState>,   // Only in two-player mode
+    pub hint_available: bool,      // Set once the player seems stuck
+    pub elapsed: Option<Duration>, // Only for timed sessions
+    pub time_style: TimeStyle,
+    pub lang: Lang,
+    pub art: Option<Rc<TileArt>>, // Photo puzzles draw this instead of numbers
+    pub ghost: Option<GhostState>,
+    pub stats: Option<StatsState>, // Only while the stats strip is shown
+    pub seed: Option<u64>,         // What the board was shuffled from, if anything
+    pub attempt: u32,
+}
+
+// How the themes that color tiles by where they belong pick the color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalPaint {
+    Rows,
+    Columns,
+    Picture, // A different color for every goal cell, together a rainbow
+}
+
+// Where each tile belongs, so it can be colored by its goal cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoalColors {
+    pub paint: GoalPaint,
+    pub goal_cells: Vec<usize>, // By tile number, the blank's at 0
+}
+
+impl GoalColors {
+    pub fn new(paint: GoalPaint, goal: &Board) -> Self {
+        let mut goal_cells = vec![0; goal.tiles().len()];
+        for (cell, &tile) in goal.tiles().iter().enumerate() {
+            goal_cells[tile as usize] = cell;
+        }
+        Self { paint, goal_cells }
+    }
+
+    fn style(&self, cell: usize, tile: Tile, size: usize) -> CellStyle {
+        let goal = self.goal_cells[tile as usize];
+        let line = match self.paint {
+            GoalPaint::Rows => goal / size,
+            GoalPaint::Columns => goal % size,
+            GoalPaint::Picture => return CellStyle::Block(picture_color(goal, size)),
+        };
+        CellStyle::Line {
+            line: line as u8,
+            placed: goal == cell,
+        }
+    }
+}
+
+// Red on the left through to violet on the right, darker down the board, so
+// no two cells share a color
+fn picture_color(cell: usize, size: usize) -> [u8; 3] {
+    let last = (size - 1).max(1) as f32;
+    let (row, column) = ((cell / size) as f32, (cell % size) as f32);
+    hsv(column / last * 280.0, 0.85, 1.0 - 0.55 * row / last)
+}
+
+// Hue in degrees, below 360; saturation and value from 0 to 1
+fn hsv(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let chroma = value * saturation;
+    let sector = hue / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
+
+// Figures for the stats strip, on top of the move count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatsState {
+    pub tenths_per_second: u32,  // Over the last stats::RATE_WINDOW
+    pub efficiency: Option<u32>, // Percent of par, once par is known
+}
+
+// A past run raced on the same clock: how far it had got by now and how it
+// finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GhostState {
+    pub progress: Option<(u32, usize)>, // Moves and tiles placed, once it has any
+    pub finish_moves: u32,
+    pub finish_time: Duration,
+}
+
+// A picture for every tile, drawn with half-block characters so each cell
+// shows two pixels. Each tile takes `width` by `height` cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileArt {
+    pub width: u16,
+    pub height: u16,
+    // By tile number counting from 1, and last the piece under the blank;
+    // each is `width` by `height * 2` pixels in row-major order
+    pub tiles: Vec<Vec<[u8; 3]>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnState {
+    pub current_player: u8,
+    pub player_moves: [u32; 2],
+}
+
+// Per-frame state for split-screen mode, player 1 on the left
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitState {
+    pub move_counts: [u32; 2],
+    pub winner: Option<u8>,
+    pub terminal_size: (u16, u16),
+    pub lang: Lang,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub theme: Theme,
+    // Encode styles as {tile}...{/} markers in plain-text output
+    pub markers: bool,
+}
+
+// What the tiles show instead of their numbers, if anything
+#[derive(Debug, Clone, Copy)]
+enum Faces<'a> {
+    Numbers,
+    Labels(&'a [String]), // By tile number counting from 1
+    Emoji(&'a [String]),  // As for labels, with room each side
+    Art(&'a TileArt),
+}
+
+impl<'a> Faces<'a> {
+    // Art, then emoji, then labels. Labels only count when there's one for
+    // every tile, so a new game on another size goes back to numbers; emoji
+    // are taken from the front of the set, as many as the board needs.
+    fn new(board: &Board, state: &'a UiState) -> Self {
+        let tiles = board.tiles().len() - 1;
+        match (
+            state.art.as_deref(),
+            state.emoji.as_deref(),
+            state.tile_labels.as_deref(),
+        ) {
+            (Some(art), _, _) => Faces::Art(art),
+            (None, Some(emoji), _) if emoji.len() >= tiles => Faces::Emoji(&emoji[..tiles]),
+            (None, _, Some(labels)) if labels.len() == tiles => Faces::Labels(labels),
+            _ => Faces::Numbers,
+        }
+    }
+
+    fn labels(self) -> Option<&'a [String]> {
+        match self {
+            Faces::Labels(labels) | Faces::Emoji(labels) => Some(labels),
+            _ => None,
+        }
+    }
+}
+
+// Width of one tile, wide enough for the largest number or label plus
+// padding, or for it in brackets when some tiles are locked. Emoji get a
+// space each side, so the usual two-column ones sit five columns apart.
+fn tile_width(board: &Board, faces: Faces) -> u16 {
+    let widest = |labels: &[String]| {
+        labels
+            .iter()
+            .map(|label| text_width(label))
+            .max()
+            .unwrap_or(1) as u16
+    };
+    let padding = match board.has_locked_tiles() {
+        true => 2,
+        false => 1,
+    };
+    match faces {
+        Faces::Emoji(emoji) => (widest(emoji) + 2).max(4),
+        Faces::Labels(labels) => (widest(labels) + padding).max(3),
+        _ => ((board.tiles().len() - 1).to_string().len() as u16 + padding).max(3),
+    }
+}
+
+// Space the board and its status lines need
+pub fn required_size(board: &Board) -> (u16, u16) {
+    required_size_with(board, Faces::Numbers)
+}
+
+// The cells one tile takes up, not counting the gap after it
+fn tile_cells(board: &Board, faces: Faces) -> (u16, u16) {
+    match faces {
+        Faces::Art(art) => (art.width, art.height),
+        faces => (tile_width(board, faces), 1),
+    }
+}
+
+// Tiles are one column apart and one row apart, and the status lines start
+// under the last row's gap
+fn status_row(board: &Board, faces: Faces) -> u16 {
+    board.size() as u16 * (tile_cells(board, faces).1 + 1)
+}
+
+fn required_size_with(board: &Board, faces: Faces) -> (u16, u16) {
+    let size = board.size() as u16;
+    let width = size * (tile_cells(board, faces).0 + 1) - 1;
+    (width, status_row(board, faces) + 2)
+}
+
+// Space split-screen mode needs for two boards of this size and a divider
+pub fn required_split_size(board: &Board) -> (u16, u16) {
+    let (width, height) = required_size(board);
+    ((width * 2 + 3).max(SPLIT_MIN_WIDTH), height)
+}
+
+// Left edges of the two boards; the divider sits one column before the second
+pub fn split_offsets(terminal_width: u16) -> (u16, u16) {
+    (0, terminal_width / 2 + 1)
+}
+
+// Returns the warning frame if the terminal is smaller than needed
+fn too_small(frame: &mut Frame, (needed_width, needed_height): (u16, u16), lang: Lang) -> bool {
+    if frame.width >= needed_width && frame.height >= needed_height {
+        return false;
+    }
+    frame.put_str(0, 0, lang.text("too_small"), CellStyle::Warning);
+    frame.put_str(
+        0,
+        1,
+        &lang.format(
+            "too_small_need",
+            &[("width", &needed_width), ("height", &needed_height)],
+        ),
+        CellStyle::Warning,
+    );
+    true
+}
+
+// `style` picks each tile's style from its cell and number. Without
+// `numbers` the tiles are blocks of their color alone. `labels` go on the
+// tiles in place of their numbers.
+fn draw_tiles(
+    frame: &mut Frame,
+    board: &Board,
+    offset_x: u16,
+    (numbers, faces): (bool, Faces),
+    style: impl Fn(usize, Tile) -> CellStyle,
+) {
+    let size = board.size();
+    let tile_width = tile_width(board, faces) as usize;
+    let labels = faces.labels();
+    for (i, &tile) in board.tiles().iter().enumerate() {
+        let x = offset_x + (i % size * (tile_width + 1)) as u16;
+        let y = (i / size * 2) as u16;
+        if tile == 0 {
+            continue;
+        }
+
+        let face = match labels {
+            Some(labels) => labels[tile as usize - 1].clone(),
+            None => tile.to_string(),
+        };
+        let label = match (numbers, board.is_locked(i)) {
+            (false, _) => String::new(),
+            (true, true) => format!("[{}]", face),
+            (true, false) => face,
+        };
+        frame.put_str(x, y, &centered(&label, tile_width), style(i, tile));
+    }
+}
+
+// `text` in the middle of `width` columns, any odd one over on the right
+fn centered(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(text_width(text));
+    format!(
+        "{}{}{}",
+        " ".repeat(padding / 2),
+        text,
+        " ".repeat(padding - padding / 2)
+    )
+}
+
+// The blank in the picture theme, shaded so it stands out among the blocks,
+// until the last piece fills it on the win
+fn draw_hole(frame: &mut Frame, board: &Board, faces: Faces, colors: &GoalColors, won: bool) {
+    let size = board.size();
+    let tile_width = tile_width(board, faces) as usize;
+    let blank = board.get_blank_position();
+    let (x, y) = (blank % size * (tile_width + 1), blank / size * 2);
+    let (fill, style) = match won {
+        true => (' ', colors.style(blank, 0, size)),
+        false => ('░', CellStyle::Hint),
+    };
+    let hole: String = std::iter::repeat_n(fill, tile_width).collect();
+    frame.put_str(x as u16, y as u16, &hole, style);
+}
+
+// A compass of the directions a tile can slide, in a 3x3 block from (x, 0)
+fn draw_assist(frame: &mut Frame, x: u16, legal_moves: &[Direction]) {
+    for direction in legal_moves {
+        let (dx, dy, arrow) = match direction {
+            Direction::Up => (1, 0, "↑"),
+            Direction::Down => (1, 2, "↓"),
+            Direction::Left => (0, 1, "←"),
+            Direction::Right => (2, 1, "→"),
+        };
+        frame.put_str(x + dx, dy, arrow, CellStyle::Hint);
+    }
+}
+
+// Each cell is a '▀' in the top pixel's color over the bottom pixel's, or a
+// full block when they match. Once solved the blank shows its piece and the
+// gaps close up, so the picture is whole.
+fn draw_art(frame: &mut Frame, board: &Board, art: &TileArt, won: bool) {
+    let size = board.size();
+    let (width, height) = (art.width as usize, art.height as usize);
+    for (i, &tile) in board.tiles().iter().enumerate() {
+        let piece = match tile {
+            0 if won => art.tiles.last(),
+            0 => None,
+            _ => art.tiles.get(tile as usize - 1),
+        };
+        let Some(pixels) = piece else {
+            continue;
+        };
+        let gap = usize::from(!won);
+        let (x, y) = (i % size * (width + gap), i / size * (height + gap));
+
+        for row in 0..height {
+            for col in 0..width {
+                let top = pixels[row * 2 * width + col];
+                let bottom = pixels[(row * 2 + 1) * width + col];
+                let cell = Cell {
+                    ch: if top == bottom { '█' } else { '▀' },
+                    style: CellStyle::Pixel { top, bottom },
+                };
+                frame.put((x + col) as u16, (y + row) as u16, cell);
+            }
+        }
+    }
+}
+
+pub fn layout(board: &Board, state: &UiState) -> Frame {
+    let (width, height) = state.terminal_size;
+    let mut frame = Frame::new(width, height);
+    let lang = state.lang;
+    let faces = Faces::new(board, state);
+    if too_small(&mut frame, required_size_with(board, faces), lang) {
+        return frame;
+    }
+
+    let picture = state
+        .goal_colors
+        .as_ref()
+        .filter(|colors| colors.paint == GoalPaint::Picture);
+    match faces {
+        Faces::Art(art) => draw_art(&mut frame, board, art, state.won),
+        faces => {
+            let numbers = picture.is_none() || !state.hide_numbers;
+            draw_tiles(&mut frame, board, 0, (numbers, faces), |cell, tile| match (
+                state.highlight == Some(tile),
+                state.pulled == Some(tile),
+            ) {
+                (true, _) => CellStyle::Hint,
+                (_, true) => CellStyle::Pulled,
+                _ => match &state.goal_colors {
+                    Some(colors) => colors.style(cell, tile, board.size()),
+                    None => CellStyle::Tile,
+                },
+            });
+            if let Some(colors) = picture {
+                draw_hole(&mut frame, board, faces, colors, state.won);
+            }
+        }
+    }
+    // Beside the board, where there's room for it
+    if let Some(legal_moves) = state.legal_moves.as_deref().filter(|_| !state.won) {
+        let x = required_size_with(board, faces).0 + 2;
+        if x + 3 <= width {
+            draw_assist(&mut frame, x, legal_moves);
+        }
+    }
+
+    let status_y = status_row(board, faces);
+    if state.won {
+        let mut status = lang.format("won", &[("moves", &state.move_count)]);
+        if let Some(turn) = &state.turn {
+            status += "  ";
+            status += &lang.format(
+                "won_players",
+                &[
+                    ("first", &turn.player_moves[0]),
+                    ("second", &turn.player_moves[1]),
+                ],
+            );
+        }
+        if let Some(elapsed) = state.elapsed {
+            let time = format_duration(elapsed, state.time_style);
+            status += "  ";
+            status += &lang.format("won_time", &[("time", &time)]);
+            if state.stats.is_some() {
+                let rate = stats::average_per_second(state.move_count, elapsed);
+                status += "  ";
+                status += &lang.format("won_rate", &[("rate", &format!("{:.1}", rate))]);
+            }
+            if let Some(ghost) = &state.ghost {
+                // Positive when the ghost did better
+                let (sign, behind) = match elapsed.checked_sub(ghost.finish_time) {
+                    Some(behind) => ('+', behind),
+                    None => ('-', ghost.finish_time - elapsed),
+                };
+                let time = format!("{}{}", sign, format_duration(behind, state.time_style));
+                let moves = i64::from(state.move_count) - i64::from(ghost.finish_moves);
+                status += "  ";
+                status += &lang.format(
+                    "ghost_delta",
+                    &[("time", &time), ("moves", &format!("{:+}", moves))],
+                );
+            }
+        }
+        frame.put_str(0, status_y, &status, CellStyle::Win);
+    } else {
+        let estimate = board.difficulty_score();
+        let mut status = lang.format(
+            "status",
+            &[
+                ("moves", &state.move_count),
+                (
+                    "level",
+                    &lang.difficulty(DifficultyLevel::from_estimate(estimate)),
+                ),
+                ("estimate", &estimate),
+            ],
+        );
+        if let Some(turn) = &state.turn {
+            status += "  ";
+            status += &lang.format("turn", &[("player", &turn.current_player)]);
+        }
+        if let Some((moves, placed)) = state.ghost.and_then(|ghost| ghost.progress) {
+            status += "  ";
+            status += &lang.format(
+                "ghost",
+                &[
+                    ("moves", &moves),
+                    ("placed", &placed),
+                    ("tiles", &board.tiles().len().saturating_sub(1)),
+                ],
+            );
+        }
+        if let Some(stats) = &state.stats {
+            let rate = stats.tenths_per_second;
+            let rate = format!("{}.{}", rate / 10, rate % 10);
+            let figures = [
+                Some(lang.format("stats_rate", &[("rate", &rate)])),
+                stats
+                    .efficiency
+                    .map(|percent| lang.format("stats_par", &[("percent", &percent)])),
+                Some(lang.format("stats_moves", &[("moves", &state.move_count)])),
+            ];
+            // Least important last, so a narrow terminal loses those first
+            for figure in figures.into_iter().flatten() {
+                if status.chars().count() + 2 + figure.chars().count() > width as usize {
+                    break;
+                }
+                status += "  ";
+                status += &figure;
+            }
+        }
+        frame.put_str(0, status_y, &status, CellStyle::Default);
+        if state.hint_available {
+            let x = status.chars().count() as u16 + 2;
+            frame.put_str(x, status_y, lang.text("hint_reminder"), CellStyle::Hint);
+        }
+    }
+    // The win screen only has keys when the game gives it some
+    let key_help = match state.won {
+        true => state.key_help.as_deref(),
+        false => Some(state.key_help.as_deref().unwrap_or(lang.text("key_help"))),
+    };
+    if let Some(key_help) = key_help {
+        frame.put_str(0, status_y + 1, key_help, CellStyle::Hint);
+    }
+    // Out of the way in the corner, and only where the keys leave room
+    let corner = seed_corner(state, lang);
+    let keys_width = key_help.map_or(0, |keys| keys.chars().count());
+    let corner_width = corner.chars().count();
+    if !corner.is_empty() && keys_width + 2 + corner_width <= width as usize {
+        let x = width - corner_width as u16;
+        frame.put_str(x, status_y + 1, &corner, CellStyle::Hint);
+    }
+
+    if let Some(message) = &state.message {
+        frame.put_str(0, status_y + 2, message, CellStyle::Warning);
+    }
+
+    frame
+}
+
+// Both players' boards side by side. Both start from the same scramble, so
+// only the left board is used to size the layout.
+// The seed a puzzle was shuffled from, and which attempt at it this is once
+// it's been played more than once
+fn seed_corner(state: &UiState, lang: Lang) -> String {
+    let seed = state
+        .seed
+        .map(|seed| lang.format("seed", &[("seed", &seed)]));
+    let attempt =
+        (state.attempt > 1).then(|| lang.format("attempt", &[("attempt", &state.attempt)]));
+    [seed, attempt]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+pub fn layout_split(board_a: &Board, board_b: &Board, state: &SplitState) -> Frame {
+    let (width, height) = state.terminal_size;
+    let mut frame = Frame::new(width, height);
+    let lang = state.lang;
+    if too_small(&mut frame, required_split_size(board_a), lang) {
+        return frame;
+    }
+
+    let (offset_a, offset_b) = split_offsets(width);
+    let status_y = board_a.size() as u16 * 2;
+    draw_tiles(
+        &mut frame,
+        board_a,
+        offset_a,
+        (true, Faces::Numbers),
+        |_, _| CellStyle::Tile,
+    );
+    draw_tiles(
+        &mut frame,
+        board_b,
+        offset_b,
+        (true, Faces::Numbers),
+        |_, _| CellStyle::Tile,
+    );
+    for y in 0..=status_y {
+        frame.put_str(offset_b - 1, y, "│", CellStyle::Hint);
+    }
+    for (player, offset) in [(1, offset_a), (2, offset_b)] {
+        frame.put_str(
+            offset,
+            status_y,
+            &lang.format(
+                "split_moves",
+                &[
+                    ("player", &player),
+                    ("moves", &state.move_counts[player - 1]),
+                ],
+            ),
+            CellStyle::Default,
+        );
+    }
+
+    match state.winner {
+        Some(player) => {
+            let banner = lang.format("split_winner", &[("player", &player)]);
+            let x = (width / 2).saturating_sub(banner.chars().count() as u16 / 2);
+            frame.put_str(x, status_y + 1, &banner, CellStyle::Win);
+        }
+        None => frame.put_str(
+            0,
+            status_y + 1,
+            lang.text("split_key_help"),
+            CellStyle::Hint,
+        ),
+    }
+
+    frame
+}
+
+pub fn render_board(backend: &mut dyn Backend, board: &Board, state: &UiState) -> io::Result<()> {
+    draw_frame(backend, &layout(board, state))
+}
+
+// Lines from the top left, cut off at the edges of the terminal
+pub fn layout_text(lines: &[TextLine], (width, height): (u16, u16)) -> Frame {
+    let mut frame = Frame::new(width, height);
+    for (y, line) in lines.iter().enumerate().take(height as usize) {
+        let style = match line.highlight {
+            true => CellStyle::Hint,
+            false => CellStyle::Default,
+        };
+        frame.put_str(0, y as u16, &line.text, style);
+    }
+    frame
+}
+
+pub fn render_text(
+    backend: &mut dyn Backend,
+    lines: &[TextLine],
+    terminal_size: (u16, u16),
+) -> io::Result<()> {
+    draw_frame(backend, &layout_text(lines, terminal_size))
+}
+
+// The board's cells shaded by how many moves ended on each, with the counts
+// in them, above the tile moved most
+pub fn layout_heatmap(heatmap: &Heatmap, lang: Lang, (width, height): (u16, u16)) -> Frame {
+    let mut frame = Frame::new(width, height);
+    let size = heatmap.size();
+    let busiest = heatmap.cells().iter().copied().max().unwrap_or(0);
+    let cell_width = busiest.to_string().len().max(2) + 2;
+    let grid_y = 2;
+    let needed = (
+        (size * (cell_width + 1)) as u16,
+        (grid_y + size * 2 + 2) as u16,
+    );
+    if too_small(&mut frame, needed, lang) {
+        return frame;
+    }
+
+    frame.put_str(0, 0, lang.text("heatmap_title"), CellStyle::Default);
+    for (i, &count) in heatmap.cells().iter().enumerate() {
+        let x = (i % size * (cell_width + 1)) as u16;
+        let y = (grid_y + i / size * 2) as u16;
+        let style = match heatmap.level(count) {
+            0 => CellStyle::Default,
+            level => CellStyle::Heat(level),
+        };
+        let label = format!("{:^width$}", count, width = cell_width);
+        frame.put_str(x, y, &label, style);
+    }
+    let below = (grid_y + size * 2) as u16;
+    if let Some((tile, moves)) = heatmap.busiest_tile() {
+        let busiest = lang.format("heatmap_busiest", &[("tile", &tile), ("moves", &moves)]);
+        frame.put_str(0, below - 1, &busiest, CellStyle::Default);
+    }
+    frame.put_str(0, below, lang.text("heatmap_keys"), CellStyle::Hint);
+    frame
+}
+
+pub fn render_heatmap(
+    backend: &mut dyn Backend,
+    heatmap: &Heatmap,
+    lang: Lang,
+    terminal_size: (u16, u16),
+) -> io::Result<()> {
+    draw_frame(backend, &layout_heatmap(heatmap, lang, terminal_size))
+}
+
+pub fn render_split_screen(
+    backend: &mut dyn Backend,
+    board_a: &Board,
+    board_b: &Board,
+    state: &SplitState,
+) -> io::Result<()> {
+    draw_frame(backend, &layout_split(board_a, board_b, state))
+}
+
+// Draws the frame on a backend, one run of equally styled cells at a time
+fn draw_frame(backend: &mut dyn Backend, frame: &Frame) -> io::Result<()> {
+    backend.clear()?;
+    for (y, row) in frame.rows().enumerate() {
+        backend.move_to(0, y as u16)?;
+        for run in row.chunk_by(|a, b| a.style == b.style) {
+            let text = run
+                .iter()
+                .map(|cell| cell.ch)
+                .filter(|&ch| ch != WIDE)
+                .collect::<String>();
+            backend.print_styled(&text, run[0].style)?;
+        }
+    }
+    backend.flush()
+}
+
+pub fn render_to_string(board: &Board, state: &UiState, opts: &RenderOptions) -> String {
+    frame_to_string(&layout(board, state), opts)
+}
+
+fn frame_to_string(frame: &Frame, opts: &RenderOptions) -> String {
+    let mut output = String::new();
+
+    for row in frame.rows() {
+        let mut line = String::new();
+        let mut current = CellStyle::Default;
+
+        for cell in row {
+            if opts.markers && cell.style != current {
+                line.push_str(cell.style.marker());
+                current = cell.style;
+            }
+            if cell.ch != WIDE {
+                line.push(cell.ch);
+            }
+        }
+        if opts.markers && current != CellStyle::Default {
+            line.push_str(CellStyle::Default.marker());
+        }
+
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    // Drop the empty rows below the content
+    let trimmed = output.trim_end_matches('\n').len();
+    output.truncate(trimmed);
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::CaptureBackend, board::Pattern, config::DEFAULT_EMOJI};
+
+    fn state(move_count: u32, won: bool) -> UiState {
+        UiState {
+            move_count,
+            won,
+            message: None,
+            key_help: None,
+            highlight: None,
+            pulled: None,
+            legal_moves: None,
+            goal_colors: None,
+            hide_numbers: false,
+            tile_labels: None,
+            emoji: None,
+            terminal_size: (80, 12),
+            turn: None,
+            hint_available: false,
+            elapsed: None,
+            time_style: TimeStyle::Compact,
+            art: None,
+            lang: Lang::En,
+            ghost: None,
+            stats: None,
+            seed: None,
+            attempt: 1,
+        }
+    }
+
+    const PLAIN: RenderOptions = RenderOptions {
+        theme: Theme::Classic,
+        markers: false,
+    };
+
+    #[test]
+    fn test_render_3x3() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        assert_eq!(
+            render_to_string(&board, &state(3, false), &PLAIN),
+            " 1   2   3
+
+ 4       5
+
+ 7   8   6
+
+Moves: 3  Difficulty: Easy (6)
+arrows: move  h: hint  c: checkpoint  p: restore  n: new size  q: quit
+"
+        );
+    }
+
+    #[test]
+    fn test_render_4x4() {
+        let board = Board::goal_state(4);
+        let rendered = render_to_string(&board, &state(0, false), &PLAIN);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], " 1   2   3   4");
+        assert_eq!(lines[6], "13  14  15");
+        assert_eq!(lines[8], "Moves: 0  Difficulty: Easy (0)");
+    }
+
+    #[test]
+    fn test_render_locked_tiles() {
+        let mut board = Board::goal_state(4);
+        board.lock_tile(6);
+        board.lock_tile(12);
+        let rendered = render_to_string(&board, &state(0, false), &PLAIN);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert_eq!(lines[2], " 5   [6]   7    8");
+        assert_eq!(lines[4], " 9    10   11  [12]");
+    }
+
+    #[test]
+    fn test_render_20x20() {
+        let board = Board::goal_state(20);
+        let mut ui = state(0, false);
+        ui.terminal_size = (100, 44);
+        assert_eq!(required_size(&board), (99, 42));
+
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with(" 1    2    3 "));
+        assert!(lines[38].ends_with("397  398  399"));
+        assert_eq!(lines[40], "Moves: 0  Difficulty: Easy (0)");
+    }
+
+    #[test]
+    fn test_render_ghost() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut ui = state(3, false);
+        let ghost = GhostState {
+            progress: None,
+            finish_moves: 20,
+            finish_time: Duration::from_secs(30),
+        };
+        ui.ghost = Some(ghost);
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.contains("\nMoves: 3  Difficulty: Easy (6)\n"));
+
+        ui.ghost = Some(GhostState {
+            progress: Some((10, 5)),
+            ..ghost
+        });
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.contains("(6)  Ghost: 10 moves, 5/8 in place\n"));
+
+        ui.won = true;
+        ui.move_count = 24;
+        ui.elapsed = Some(Duration::from_millis(28_500));
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(
+            rendered.contains("vs ghost: -0:01, +4 moves"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let mut ui = state(3, false);
+        ui.stats = Some(StatsState {
+            tenths_per_second: 12,
+            efficiency: Some(150),
+        });
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.contains("(6)  1.2 moves/s  150% of par  3 effective moves\n"));
+
+        // 60 columns only leave room for the first two
+        ui.terminal_size = (60, 12);
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.contains("(6)  1.2 moves/s  150% of par\n"));
+
+        ui.won = true;
+        ui.elapsed = Some(Duration::from_secs(2));
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.contains("Time: 0:02  Average: 1.5 moves/s"));
+    }
+
+    #[test]
+    fn test_render_markers() {
+        let board: Board = "1 2 3 0".parse().unwrap();
+        let opts = RenderOptions {
+            markers: true,
+            ..PLAIN
+        };
+        let rendered = render_to_string(&board, &state(0, false), &opts);
+        assert_eq!(rendered.lines().next(), Some("{tile} 1 {/} {tile} 2 {/}"));
+        assert!(rendered.contains("{hint}arrows: move"));
+
+        let mut ui = state(0, false);
+        ui.highlight = Some(2);
+        let rendered = render_to_string(&board, &ui, &opts);
+        assert_eq!(rendered.lines().next(), Some("{tile} 1 {/} {hint} 2 {/}"));
+
+        ui.highlight = None;
+        ui.pulled = Some(1);
+        let rendered = render_to_string(&board, &ui, &opts);
+        assert_eq!(rendered.lines().next(), Some("{pull} 1 {/} {tile} 2 {/}"));
+    }
+
+    #[test]
+    fn test_render_heatmap() {
+        use crate::board::Direction::*;
+        let start: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let heatmap = Heatmap::replay(
+            &start,
+            &[Left, Right, Left, Up],
+            crate::game::GameMode::Classic,
+        );
+        let opts = RenderOptions {
+            markers: true,
+            ..PLAIN
+        };
+        let rendered = frame_to_string(&layout_heatmap(&heatmap, Lang::En, (40, 12)), &opts);
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[2], " 0    0    0");
+        assert_eq!(lines[4], " 0   {heat4} 2  {/} {heat4} 2  {/}");
+        assert_eq!(lines[6], " 0    0    0");
+        assert!(lines[7].ends_with("tile 5, 3 moves"));
+        assert_eq!(lines[8], "{hint}any key: exit{/}");
+
+        assert!(
+            frame_to_string(&layout_heatmap(&heatmap, Lang::En, (20, 5)), &PLAIN)
+                .contains("Terminal too small")
+        );
+    }
+
+    #[test]
+    fn test_goal_colors() {
+        let board: Board = "1 2 3 4 5 6 8 7 0".parse().unwrap();
+        let mut ui = state(0, false);
+        ui.goal_colors = Some(GoalColors::new(GoalPaint::Rows, &Board::new()));
+        let styles = |ui: &UiState| {
+            let frame = layout(&board, ui);
+            let rows: Vec<_> = frame.rows().collect();
+            // The middle of each tile, a row of the board at a time
+            [0, 2, 4].map(|y| [1, 5, 9].map(|x| rows[y][x].style))
+        };
+        let line = |line, placed| CellStyle::Line { line, placed };
+        assert_eq!(
+            styles(&ui),
+            [
+                [line(0, true); 3],
+                [line(1, true); 3],
+                [line(2, false), line(2, false), CellStyle::Default],
+            ]
+        );
+
+        // The snake goal runs the middle row backwards
+        let snake = Board::goal_state_for_pattern(3, Pattern::Snake);
+        ui.goal_colors = Some(GoalColors::new(GoalPaint::Columns, &snake));
+        assert_eq!(
+            styles(&ui)[1..],
+            [
+                [line(2, false), line(1, true), line(0, false)],
+                [line(1, false), line(0, false), CellStyle::Default],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_picture_colors() {
+        let board: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+        let mut ui = state(0, false);
+        ui.goal_colors = Some(GoalColors::new(GoalPaint::Picture, &Board::new()));
+        let opts = RenderOptions {
+            markers: true,
+            ..PLAIN
+        };
+        let rendered = render_to_string(&board, &ui, &opts);
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[4], "{block} 7 {/} {hint}░░░{/} {block} 8 {/}");
+
+        // Every goal cell its own color, from red in the top left
+        let colors: Vec<_> = (0..9).map(|cell| picture_color(cell, 3)).collect();
+        assert_eq!(colors[0], [255, 38, 38]);
+        assert!((1..9).all(|i| !colors[..i].contains(&colors[i])));
+        let frame = layout(&board, &ui);
+        let row: Vec<_> = frame.rows().nth(4).unwrap().to_vec();
+        assert_eq!(row[1].style, CellStyle::Block(colors[6]));
+        assert_eq!(row[9].style, CellStyle::Block(colors[7]));
+
+        ui.hide_numbers = true;
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.starts_with("\n\n\n\n    ░░░\n"));
+
+        // The last piece fills the hole once solved
+        ui.won = true;
+        let frame = layout(&Board::new(), &ui);
+        let row: Vec<_> = frame.rows().nth(4).unwrap().to_vec();
+        assert_eq!(row[9].style, CellStyle::Block(colors[8]));
+    }
+
+    #[test]
+    fn test_render_tile_labels() {
+        let board: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+        let mut ui = state(0, false);
+        let labels = ["A", "B", "C", "D", "E", "F", "G", "H"];
+        ui.tile_labels = Some(labels.map(String::from).into());
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.starts_with(" A   B   C\n\n D   E   F\n\n G       H\n"));
+
+        // Emoji take two columns, and the tiles widen to fit
+        let animals = ["🐶", "🐱", "🐭", "🐹", "🐰", "🦊", "🐻", "🐼"];
+        ui.tile_labels = Some(animals.map(String::from).into());
+        let frame = layout(&board, &ui);
+        let row: Vec<_> = frame.rows().next().unwrap().to_vec();
+        assert_eq!(row[0].ch, '🐶');
+        assert_eq!(row[1].ch, WIDE);
+        assert_eq!(row[4].ch, '🐱');
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.starts_with("🐶  🐱  🐭\n"));
+        let mut backend = CaptureBackend::new(20, 8);
+        render_board(&mut backend, &board, &ui).unwrap();
+        assert_eq!(backend.cell(4, 0).ch, '🐱');
+
+        // Labels for another size are left out
+        let rendered = render_to_string(&Board::goal_state(4), &ui, &PLAIN);
+        assert!(rendered.starts_with(" 1   2   3   4\n"));
+    }
+
+    #[test]
+    fn test_render_emoji_tiles() {
+        let board: Board = "1 2 3 4 5 6 7 0 8".parse().unwrap();
+        let mut ui = state(0, false);
+        ui.tile_labels = Some(["A"; 8].map(String::from).into());
+        ui.emoji = Some(DEFAULT_EMOJI.map(String::from).into());
+        // Each emoji is two columns in a tile of four, five with the gap
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.starts_with(" 🟦   🟩   🟨\n\n 🟧   🟥   🟪\n\n 🟫        ⬛\n"));
+        let mut backend = CaptureBackend::new(20, 8);
+        render_board(&mut backend, &board, &ui).unwrap();
+        assert_eq!(backend.cell(6, 0).ch, '🟩');
+        assert_eq!(backend.cell(11, 0).ch, '🟨');
+        assert_eq!(backend.cell(12, 0).ch, WIDE);
+        assert_eq!(required_size_with(&board, Faces::new(&board, &ui)).0, 14);
+
+        // A 5x5 board takes the first 24, and a larger one goes back to numbers
+        let rendered = render_to_string(&Board::goal_state(5), &ui, &PLAIN);
+        assert!(rendered.starts_with(" 🟦   🟩"));
+        ui.terminal_size = (40, 20);
+        let rendered = render_to_string(&Board::goal_state(6), &ui, &PLAIN);
+        assert!(rendered.starts_with(" 1   2"));
+    }
+
+    #[test]
+    fn test_render_assist() {
+        use crate::board::Direction::*;
+        let mut ui = state(0, false);
+        ui.legal_moves = Some(vec![Down, Right]);
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[0], " 1   2   3");
+        assert_eq!(lines[1], format!("{}→", " ".repeat(15)));
+        assert_eq!(lines[2], " 4   5   6    ↓");
+
+        // Only where it fits, and not once solved
+        ui.terminal_size = (15, 12);
+        assert!(!render_to_string(&Board::new(), &ui, &PLAIN).contains('→'));
+        ui.terminal_size = (80, 12);
+        ui.won = true;
+        assert!(!render_to_string(&Board::new(), &ui, &PLAIN).contains('→'));
+    }
+
+    #[test]
+    fn test_render_seed_corner() {
+        let mut ui = state(0, false);
+        ui.seed = Some(42);
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.contains("q: quit   seed 42\n"));
+        assert_eq!(
+            rendered.lines().map(|line| line.chars().count()).max(),
+            Some(80)
+        );
+
+        ui.attempt = 3;
+        ui.won = true;
+        ui.key_help = Some("R: rematch".to_string());
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.contains("\nR: rematch  "));
+        assert!(rendered.ends_with("seed 42  attempt 3\n"));
+
+        // Dropped rather than crowding the keys
+        ui.terminal_size = (24, 12);
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(!rendered.contains("seed"));
+    }
+
+    #[test]
+    fn test_render_win_keys() {
+        let mut ui = state(12, true);
+        ui.key_help = Some("m: move heatmap".to_string());
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.ends_with("You won in 12 moves!\nm: move heatmap\n"));
+    }
+
+    #[test]
+    fn test_render_tile_art() {
+        // Two cells wide and one tall: tile n is gray level n on top of black
+        let tiles = (1..=4u8)
+            .map(|n| vec![[n; 3], [n; 3], [0; 3], [0; 3]])
+            .collect();
+        let mut ui = state(0, false);
+        ui.art = Some(Rc::new(TileArt {
+            width: 2,
+            height: 1,
+            tiles,
+        }));
+        let board: Board = "1 2 3 0".parse().unwrap();
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.starts_with("▀▀ ▀▀\n\n▀▀\n\nMoves: 0"));
+
+        let frame = layout(&board, &ui);
+        let cell = frame.rows().nth(2).unwrap()[0];
+        assert_eq!(
+            cell.style,
+            CellStyle::Pixel {
+                top: [3; 3],
+                bottom: [0; 3]
+            }
+        );
+
+        // The hidden piece fills the blank once solved, with no gaps between
+        ui.won = true;
+        let rendered = render_to_string(&Board::goal_state(2), &ui, &PLAIN);
+        assert!(rendered.starts_with("▀▀▀▀\n▀▀▀▀\n\n\nYou won"));
+    }
+
+    #[test]
+    fn test_render_win_screen() {
+        let board = Board::new();
+        let rendered = render_to_string(&board, &state(12, true), &PLAIN);
+        assert!(rendered.ends_with(" 7   8\n\nYou won in 12 moves!\n"));
+        assert!(!rendered.contains("q: quit"));
+
+        let mut timed = state(12, true);
+        timed.elapsed = Some(Duration::from_millis(133_400));
+        timed.time_style = TimeStyle::Precise;
+        let rendered = render_to_string(&board, &timed, &PLAIN);
+        assert!(rendered.ends_with("\nYou won in 12 moves!  Time: 2:13.4\n"));
+    }
+
+    #[test]
+    fn test_render_two_player_status() {
+        let mut ui = state(3, false);
+        ui.turn = Some(TurnState {
+            current_player: 2,
+            player_moves: [2, 1],
+        });
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.contains("Moves: 3  Difficulty: Easy (6)  Player 2's turn\n"));
+
+        ui.won = true;
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.ends_with("You won in 3 moves!  Player 1: 2, Player 2: 1\n"));
+    }
+
+    #[test]
+    fn test_render_translated_status_fits() {
+        let mut ui = state(3, false);
+        ui.lang = Lang::Es;
+        ui.terminal_size = (40, 12);
+        ui.turn = Some(TurnState {
+            current_player: 2,
+            player_moves: [2, 1],
+        });
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let rendered = render_to_string(&board, &ui, &PLAIN);
+        assert!(rendered.contains("\nMovimientos: 3  Dificultad: Fácil (6)  T\n"));
+        assert!(rendered.contains("\nflechas: mover  h: pista  c: guardar  p:\n"));
+        assert!(rendered.lines().all(|line| line.chars().count() <= 40));
+
+        ui.won = true;
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.ends_with("¡Ganaste en 3 movimientos!  Jugador 1: 2\n"));
+    }
+
+    #[test]
+    fn test_render_hint_reminder() {
+        let mut ui = state(12, false);
+        ui.hint_available = true;
+        let opts = RenderOptions {
+            markers: true,
+            ..PLAIN
+        };
+        let rendered = render_to_string(&Board::goal_state(4), &ui, &opts);
+        assert!(rendered
+            .contains("Moves: 12  Difficulty: Easy (0)  {hint}Hint available: press H{/}\n"));
+    }
+
+    #[test]
+    fn test_render_message() {
+        let mut ui = state(0, false);
+        ui.message = Some("No checkpoint to restore".to_string());
+        let rendered = render_to_string(&Board::new(), &ui, &PLAIN);
+        assert!(rendered.ends_with("q: quit\nNo checkpoint to restore\n"));
+    }
+
+    #[test]
+    fn test_capture_matches_render_to_string() {
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let ui = state(3, false);
+        let mut backend = CaptureBackend::new(80, 12);
+        render_board(&mut backend, &board, &ui).unwrap();
+
+        assert_eq!(
+            backend.to_plain_string(),
+            render_to_string(&board, &ui, &PLAIN)
+        );
+        assert_eq!(backend.cell(1, 0).style, CellStyle::Tile);
+        assert_eq!(backend.cell(5, 2).style, CellStyle::Default);
+    }
+
+    fn split_state(winner: Option<u8>) -> SplitState {
+        SplitState {
+            move_counts: [3, 5],
+            winner,
+            terminal_size: (40, 10),
+            lang: Lang::En,
+        }
+    }
+
+    #[test]
+    fn test_render_split_screen() {
+        let board_a: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let board_b = Board::new();
+        let mut backend = CaptureBackend::new(40, 10);
+        render_split_screen(&mut backend, &board_a, &board_b, &split_state(None)).unwrap();
+
+        assert_eq!(
+            backend.to_plain_string(),
+            " 1   2   3          │ 1   2   3
+                    │
+ 4       5          │ 4   5   6
+                    │
+ 7   8   6          │ 7   8
+                    │
+Player 1: 3 moves   │Player 2: 5 moves
+first to solve wins  q: quit
+"
+        );
+    }
+
+    #[test]
+    fn test_render_split_screen_winner() {
+        let frame = layout_split(&Board::new(), &Board::new(), &split_state(Some(2)));
+        let line = frame.rows().nth(7).unwrap().iter().map(|cell| cell.ch);
+        let line: String = line.filter(|&ch| ch != WIDE).collect();
+        assert_eq!(line.trim(), "🏆 Player 2 wins!");
+    }
+
+    #[test]
+    fn test_split_screen_needs_40_columns() {
+        let mut state = split_state(None);
+        state.terminal_size = (39, 10);
+        let frame = layout_split(&Board::new(), &Board::new(), &state);
+        let first = frame.rows().next().unwrap().iter().map(|cell| cell.ch);
+        assert_eq!(first.collect::<String>().trim(), "Terminal too small");
+        assert_eq!(required_split_size(&Board::goal_state(8)), (65, 18));
+    }
+
+    #[test]
+    fn test_render_too_small() {
+        let mut ui = state(0, false);
+        ui.terminal_size = (20, 4);
+        assert_eq!(
+            render_to_string(&Board::goal_state(4), &ui, &PLAIN),
+            "Terminal too small\nneed 15x10\n"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::board::Board;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Write,
+};
+
+impl Board {
+    // The breadth-first search tree rooted at this board, `depth` moves deep,
+    // as Graphviz DOT. Each board appears once, at the depth BFS first reaches
+    // it, so there are no edges back up the tree. Nodes are named by Zobrist
+    // hash and edges by the direction the tile moves.
+    pub fn to_dot_graph(&self, depth: u8) -> String {
+        let mut out = String::from("digraph {\n    node [shape=box, fontname=monospace];\n");
+        let root = self.zobrist();
+        let _ = writeln!(out, "    {} [{}, style=bold];", node_id(root), label(self));
+
+        let mut seen = HashSet::from([root]);
+        let mut frontier = VecDeque::from([(self.clone(), root, 0)]);
+        while let Some((board, hash, level)) = frontier.pop_front() {
+            if level == depth {
+                continue;
+            }
+            for (direction, next) in board.neighbor_states() {
+                let next_hash = next.zobrist();
+                if !seen.insert(next_hash) {
+                    continue;
+                }
+                let _ = writeln!(out, "    {} [{}];", node_id(next_hash), label(&next));
+                let _ = writeln!(
+                    out,
+                    "    {} -> {} [label=\"{:?}\"];",
+                    node_id(hash),
+                    node_id(next_hash),
+                    direction
+                );
+                frontier.push_back((next, next_hash, level + 1));
+            }
+        }
+
+        out + "}\n"
+    }
+}
+
+fn node_id(hash: u64) -> String {
+    format!("n{:016x}", hash)
+}
+
+// The board's usual text form, one DOT line per row
+fn label(board: &Board) -> String {
+    format!(
+        "label=\"{}\"",
+        board.to_string().trim_end().replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(dot: &str) -> usize {
+        dot.lines().filter(|line| line.contains(" -> ")).count()
+    }
+
+    #[test]
+    fn test_dot_graph() {
+        // From the center the blank has four moves, then two new ones from
+        // each edge cell it lands on
+        let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let dot = board.to_dot_graph(2);
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(edges(&dot), 4 + 8);
+        assert!(dot.contains("[label=\"1 2 3\\n4 . 5\\n7 8 6\", style=bold]"));
+        assert!(dot.contains("[label=\"Left\"]"));
+
+        assert_eq!(edges(&board.to_dot_graph(0)), 0);
+        assert_eq!(edges(&board.to_dot_graph(1)), 4);
+    }
+
+    #[test]
+    fn test_dot_graph_visits_each_board_once() {
+        // The 2x2 puzzle has only 12 reachable boards, in a single cycle
+        let dot = Board::goal_state(2).to_dot_graph(20);
+        assert_eq!(edges(&dot), 11);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::board::Board;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::OnceLock,
+};
+
+// Largest board with a table: a 4x4's has 24,964 patterns, while a 5x5's
+// would run to millions. Larger boards fall back to Manhattan distance.
+pub const WD_MAX_SIZE: usize = 4;
+
+// Each pattern's distance from the goal's, per board size; built the first
+// time a board of that size asks
+static TABLES: [OnceLock<HashMap<u64, u8>>; WD_MAX_SIZE + 1] =
+    [const { OnceLock::new() }; WD_MAX_SIZE + 1];
+
+// One axis of a board, seen as rows of tiles grouped by the row they belong
+// in: counts[row * size + goal] tiles, and the blank in its own row. Which
+// tile is which inside a group doesn't matter, so far fewer of these exist
+// than boards. The same shapes come up for columns, so one table serves
+// both axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pattern {
+    counts: [u8; WD_MAX_SIZE * WD_MAX_SIZE],
+    blank: usize,
+}
+
+impl Pattern {
+    // Every row full of its own tiles, short the blank going in the last
+    fn goal(size: usize) -> Self {
+        let mut counts = [0; WD_MAX_SIZE * WD_MAX_SIZE];
+        for row in 0..size {
+            counts[row * size + row] = size as u8;
+        }
+        counts[size * size - 1] -= 1;
+        Self {
+            counts,
+            blank: size - 1,
+        }
+    }
+
+    // The board's rows, or with `rows` false its columns
+    fn of(board: &Board, rows: bool) -> Self {
+        let size = board.size();
+        let mut counts = [0; WD_MAX_SIZE * WD_MAX_SIZE];
+        let mut blank = 0;
+        for (cell, &tile) in board.tiles().iter().enumerate() {
+            let (row, col) = (cell / size, cell % size);
+            let line = if rows { row } else { col };
+            if tile == 0 {
+                blank = line;
+                continue;
+            }
+            let goal = tile as usize - 1;
+            let goal = if rows { goal / size } else { goal % size };
+            counts[line * size + goal] += 1;
+        }
+        Self { counts, blank }
+    }
+
+    // Three bits a count, as no count goes past the size, then the blank's row
+    fn key(&self, size: usize) -> u64 {
+        self.counts[..size * size]
+            .iter()
+            .fold(self.blank as u64, |key, &count| key << 3 | count as u64)
+    }
+
+    // A tile from the row above or below steps into the blank's row, taking
+    // the blank back with it: one for each group in those rows
+    fn neighbors(&self, size: usize) -> impl Iterator<Item = Pattern> + '_ {
+        let rows = [self.blank.checked_sub(1), Some(self.blank + 1)];
+        rows.into_iter()
+            .flatten()
+            .filter(move |&row| row < size)
+            .flat_map(move |row| {
+                (0..size)
+                    .filter(move |&goal| self.counts[row * size + goal] > 0)
+                    .map(move |goal| {
+                        let mut next = *self;
+                        next.counts[row * size + goal] -= 1;
+                        next.counts[self.blank * size + goal] += 1;
+                        next.blank = row;
+                        next
+                    })
+            })
+    }
+}
+
+// Every pattern reachable from the goal with its distance, by breadth-first
+// search out from the goal's
+fn compute(size: usize) -> HashMap<u64, u8> {
+    let goal = Pattern::goal(size);
+    let mut distances = HashMap::from([(goal.key(size), 0u8)]);
+    let mut frontier = VecDeque::from([goal]);
+    while let Some(pattern) = frontier.pop_front() {
+        let distance = distances[&pattern.key(size)] + 1;
+        for next in pattern.neighbors(size) {
+            distances.entry(next.key(size)).or_insert_with(|| {
+                frontier.push_back(next);
+                distance
+            });
+        }
+    }
+    distances
+}
+
+fn table(size: usize) -> &'static HashMap<u64, u8> {
+    TABLES[size].get_or_init(|| {
+        log::debug!("computing the {0}x{0} walking distance table", size);
+        compute(size)
+    })
+}
+
+// A lower bound on the moves to solved that's rarely below Manhattan
+// distance and often well above it. Each move shifts a tile one row or one
+// column, never both, so the rows' distance and the columns' add up; and
+// each counts a tile's way past others in its line that Manhattan distance
+// lets through for free.
+pub fn walking_distance(board: &Board) -> u32 {
+    let size = board.size();
+    if size > WD_MAX_SIZE {
+        return board.manhattan_distance();
+    }
+    let table = table(size);
+    [true, false]
+        .into_iter()
+        .map(|rows| {
+            let key = Pattern::of(board, rows).key(size);
+            table.get(&key).copied().map_or(0, u32::from)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::Direction,
+        distance_table,
+        solver::{ida_star, Heuristic},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_table_sizes() {
+        assert_eq!(table(4).len(), 24_964);
+        assert_eq!(walking_distance(&Board::goal_state(4)), 0);
+        assert_eq!(walking_distance(&Board::goal_state(3)), 0);
+    }
+
+    #[test]
+    fn test_walking_distance() {
+        // One move off solved is one either way
+        let mut board = Board::goal_state(4);
+        board.move_tile(Direction::Down);
+        assert_eq!(walking_distance(&board), 1);
+
+        // One of the hardest 3x3 boards, 31 moves from solved, where
+        // Manhattan distance only sees 21
+        let board: Board = "8 6 7 2 5 4 3 0 1".parse().unwrap();
+        assert_eq!(board.manhattan_distance(), 21);
+        assert_eq!(walking_distance(&board), 25);
+
+        let large = Board::goal_state(5);
+        assert_eq!(walking_distance(&large), large.manhattan_distance());
+    }
+
+    #[test]
+    fn test_admissible_on_every_3x3() {
+        for (board, distance) in distance_table::compute() {
+            let estimate = walking_distance(&board);
+            assert!(estimate <= u32::from(distance), "{:?}", board.tiles());
+            assert!(
+                estimate >= board.manhattan_distance(),
+                "{:?}",
+                board.tiles()
+            );
+        }
+    }
+
+    #[test]
+    fn test_fewer_nodes_than_manhattan() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for _ in 0..3 {
+            let mut board = Board::goal_state(4);
+            board.scramble_from_position(&mut rng, 40);
+            let manhattan = ida_star(&board, Heuristic::Manhattan).unwrap();
+            let walking = ida_star(&board, Heuristic::WalkingDistance).unwrap();
+            assert_eq!(walking.moves.len(), manhattan.moves.len());
+            assert!(
+                walking.nodes_expanded < manhattan.nodes_expanded,
+                "{} nodes against {} for {:?}",
+                walking.nodes_expanded,
+                manhattan.nodes_expanded,
+                board.tiles()
+            );
+        }
+    }
+}
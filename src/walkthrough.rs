@@ -0,0 +1,120 @@
+use crate::board::{Board, Direction};
+
+// A solution stepped through one move at a time in either direction. The
+// board always shows the first `index` moves applied to the start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Walkthrough {
+    board: Board,
+    moves: Vec<Direction>,
+    index: usize,
+}
+
+impl Walkthrough {
+    pub fn new(start: Board, moves: Vec<Direction>) -> Self {
+        Self {
+            board: start,
+            moves,
+            index: 0,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.moves.len() - self.index
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.index == self.moves.len()
+    }
+
+    // Each returns whether there was a move to step over
+    pub fn forward(&mut self) -> bool {
+        let Some(&direction) = self.moves.get(self.index) else {
+            return false;
+        };
+        // Across the edges on a board that wraps
+        match self.board.wraps() {
+            true => self.board.apply_wraparound_move(direction),
+            false => self.board.move_tile(direction),
+        };
+        self.index += 1;
+        true
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.index == 0 {
+            return false;
+        }
+        self.index -= 1;
+        let direction = self.moves[self.index];
+        match self.board.wraps() {
+            true => self.board.apply_wraparound_move(direction.opposite()),
+            false => self.board.undo_move(direction),
+        };
+        true
+    }
+
+    pub fn to_start(&mut self) {
+        while self.back() {}
+    }
+
+    pub fn to_end(&mut self) {
+        self.board.apply_moves(&self.moves[self.index..]);
+        self.index = self.moves.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    #[test]
+    fn test_walkthrough() {
+        let start: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+        let moves = solver::solve(&start).unwrap();
+        let mut walkthrough = Walkthrough::new(start.clone(), moves);
+        assert_eq!((walkthrough.index(), walkthrough.remaining()), (0, 2));
+        assert!(!walkthrough.back());
+
+        assert!(walkthrough.forward());
+        assert_eq!(walkthrough.board().tiles(), [1, 2, 3, 4, 5, 0, 7, 8, 6]);
+        walkthrough.to_end();
+        assert!(walkthrough.at_end());
+        assert!(walkthrough.board().is_goal(&Board::goal_state(3)));
+        assert!(!walkthrough.forward());
+
+        assert!(walkthrough.back());
+        assert_eq!(walkthrough.remaining(), 1);
+        walkthrough.to_start();
+        crate::assert_eq_board!(walkthrough.board(), &start);
+        assert_eq!(walkthrough.index(), 0);
+    }
+
+    #[test]
+    fn test_walkthrough_wraps() {
+        // 7 comes round the edge into the corner
+        let mut start: Board = "1 2 3 4 5 6 0 8 7".parse().unwrap();
+        start.set_wraparound(true);
+        let mut walkthrough = Walkthrough::new(start.clone(), vec![Direction::Right]);
+        assert!(walkthrough.forward());
+        assert!(walkthrough.board().is_goal(&Board::goal_state(3)));
+        assert!(walkthrough.back());
+        crate::assert_eq_board!(walkthrough.board(), &start);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::board::Tile;
+
+// Fixed so hashes are the same on every run and every machine
+const ZOBRIST_SEED: u64 = 0x5a6f_6272_6973_7421;
+
+// The random key for `tile` sitting in `cell`. A board's Zobrist hash is the
+// XOR of the keys for every cell, so a move only changes the keys of the two
+// cells it swaps. Keys come from splitmix64 over (cell, tile) rather than a
+// stored table, which on a 32x32 board would need a million entries.
+pub fn key(cell: usize, tile: Tile) -> u64 {
+    splitmix64(ZOBRIST_SEED ^ ((cell as u64) << 16 | tile as u64))
+}
+
+// The hash after the tile in `from` slides into the blank at `blank`
+pub fn after_swap(hash: u64, blank: usize, from: usize, tile: Tile) -> u64 {
+    hash ^ key(blank, 0) ^ key(from, tile) ^ key(blank, tile) ^ key(from, 0)
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let keys: HashSet<_> = (0..256)
+            .flat_map(|cell| (0..256).map(move |tile| key(cell, tile)))
+            .collect();
+        assert_eq!(keys.len(), 256 * 256);
+        assert_eq!(key(3, 7), key(3, 7));
+    }
+}
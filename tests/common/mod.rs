@@ -0,0 +1,41 @@
+// Each test binary compiles its own copy and uses only some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+// Runs the binary with the given arguments, feeding `input` to stdin
+pub fn run_game(args: &[&str], input: &str) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_tile-game"));
+    // Output is checked in English whatever the machine's locale, and with
+    // none of its TILEGAME_ settings
+    for (name, _) in std::env::vars_os() {
+        if name.to_string_lossy().starts_with("TILEGAME_") {
+            command.env_remove(name);
+        }
+    }
+    let mut child = command
+        .args(args)
+        .env_remove("LANG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+pub fn run_pipe(args: &[&str], input: &str) -> Output {
+    let mut pipe_args = vec!["--pipe"];
+    pipe_args.extend_from_slice(args);
+    run_game(&pipe_args, input)
+}
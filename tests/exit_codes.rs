@@ -0,0 +1,60 @@
+mod common;
+
+use common::{run_game, run_pipe};
+
+#[test]
+fn test_solved_exits_zero() {
+    let output = run_pipe(&["--state", "1,2,3,4,5,6,7,0,8"], "L");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_not_solved_exits_two() {
+    let output = run_pipe(&["--state", "1,2,3,4,5,6,7,0,8"], "");
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run_game(&["replay", "--state", "1,2,3,4,5,6,7,0,8", "R"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_unsolvable_exits_three() {
+    let output = run_pipe(&["--state", "1,2,3,4,5,6,8,7,0"], "");
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = run_game(&["solve", "--state", "2,1,3,0"], "");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot be solved"));
+}
+
+#[test]
+fn test_invalid_input_exits_four() {
+    let output = run_pipe(&["--state", "1,2,3,3"], "");
+    assert_eq!(output.status.code(), Some(4));
+
+    let output = run_pipe(&["--size", "99"], "");
+    assert_eq!(output.status.code(), Some(4));
+
+    let output = run_pipe(&["--no-shuffle"], "UDX");
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_io_error_exits_five() {
+    let output = run_pipe(
+        &["--no-shuffle", "--json-out", "/nonexistent/dir/result.json"],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(5));
+}
+
+#[test]
+fn test_help_exits_zero_and_documents_codes() {
+    let output = run_game(&["--help"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Exit codes:"));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{run_game, run_pipe};
+use std::fs;
+
+#[test]
+fn test_print_leaderboard() {
+    let dir = std::env::temp_dir().join(format!("tile-game-scores-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("leaderboard.jsonl");
+    let entry = |name: &str, size: usize, time_ms: u64| {
+        format!(
+            "{{\"name\":\"{}\",\"size\":{},\"mode\":\"classic\",\"time_ms\":{},\"moves\":30,\
+             \"date\":\"2024-03-09T17:05:42Z\",\"seed\":7}}\n",
+            name, size, time_ms
+        )
+    };
+    let text =
+        entry("Ann", 3, 9000) + "{\"truncated\n" + &entry("Bo", 3, 5000) + &entry("Cy", 4, 1000);
+    fs::write(&path, text).unwrap();
+
+    let file = path.to_str().unwrap();
+    let all = run_game(&["leaderboard", "--file", file], "");
+    let small = run_game(
+        &["leaderboard", "--file", file, "--size", "3", "--top", "1"],
+        "",
+    );
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(all.status.code(), Some(0));
+    let stdout = String::from_utf8(all.stdout).unwrap();
+    assert!(
+        stdout.contains("3x3 classic\n  1. Bo      0:05.0  30 moves  2024-03-09  seed 7\n  2. Ann")
+    );
+    assert!(stdout.contains("4x4 classic\n  1. Cy"));
+    assert!(String::from_utf8(all.stderr).unwrap().contains("line 2:"));
+
+    let stdout = String::from_utf8(small.stdout).unwrap();
+    assert!(stdout.contains("  1. Bo"));
+    assert!(!stdout.contains("Ann") && !stdout.contains("4x4"));
+}
+
+#[test]
+fn test_stats() {
+    let dir = std::env::temp_dir().join(format!("tile-game-stats-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("leaderboard.jsonl");
+    let entry = |time_ms: u64, moves: u32| {
+        format!(
+            "{{\"name\":\"Ann\",\"size\":3,\"mode\":\"classic\",\"time_ms\":{},\"moves\":{},\
+             \"date\":\"2024-03-09T17:05:42Z\",\"seed\":7}}\n",
+            time_ms, moves
+        )
+    };
+    fs::write(&path, entry(9000, 30) + &entry(5000, 40)).unwrap();
+    let results = dir.join("results.jsonl");
+    let played = run_pipe(&["--state", "1,2,3,4,5,6,7,0,8", "--json"], "L");
+    fs::write(&results, played.stdout).unwrap();
+
+    let file = path.to_str().unwrap();
+    let output = run_game(
+        &[
+            "stats",
+            "--file",
+            file,
+            "--results",
+            results.to_str().unwrap(),
+        ],
+        "",
+    );
+    let missing = run_game(&["stats", "--file", file, "--results", "/nonexistent"], "");
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let line = stdout.lines().nth(1).unwrap();
+    assert!(
+        line.starts_with("3x3 classic") && line.contains(" 2 "),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("35.0"));
+    assert!(
+        stdout.contains("1 results, 1 solved, 1.0 moves"),
+        "{}",
+        stdout
+    );
+    assert_eq!(missing.status.code(), Some(5));
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::run_game;
+
+// The test harness captures stdout, so the game falls back to line mode
+
+#[test]
+fn test_line_mode_plays_to_a_win() {
+    let output = run_game(&["--state", "1,2,3,4,0,5,7,8,6"], "l\nu\n");
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("1 2 3\n4 . 5\n7 8 6\nMoves: 0"));
+    assert!(stdout.contains("1 2 3\n4 5 .\n7 8 6\nMoves: 1"));
+    assert!(stdout.ends_with("1 2 3\n4 5 6\n7 8 .\nYou won in 2 moves!\n\n"));
+}
+
+#[test]
+fn test_line_mode_rotation() {
+    // The blank's column turns up, then 5 picks the middle row to turn back
+    let output = run_game(
+        &["--mode", "rotation", "--state", "1,2,0,6,4,3,7,8,5"],
+        "u\n5 l\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1 2 3\n6 4 5\n7 8 .\nMoves: 1"));
+    assert!(stdout.contains("Arrows turn tile 5's row and column"));
+    assert!(stdout.ends_with("1 2 3\n4 5 6\n7 8 .\nYou won in 2 moves!\n\n"));
+}
+
+#[test]
+fn test_line_mode_quit_and_bad_input() {
+    let output = run_game(&["--state", "1,2,3,4,0,5,7,8,6"], "x\nq\nl\n");
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("'x' is not a move (use u, d, l, r or q)"));
+    assert!(!stdout.contains("Moves: 1"));
+}
+
+#[test]
+fn test_line_mode_replay() {
+    let output = run_game(&["--state", "1,2,3,4,0,5,7,8,6", "--replay", "L"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("Warning: sequence does not reach solved state\n"));
+}
+
+#[test]
+fn test_line_mode_lang() {
+    let output = run_game(
+        &["--state", "1,2,3,4,0,5,7,8,6", "--lang", "es"],
+        "x\nl\nu\n",
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Movimientos: 0  (u, d, l, r para mover, q para salir)"));
+    assert!(stdout.contains("'x' no es un movimiento (usa u, d, l, r o q)"));
+    assert!(stdout.ends_with("¡Ganaste en 2 movimientos!\n\n"));
+}
+
+#[test]
+fn test_line_mode_hex() {
+    let output = run_game(&["--hex", "1", "--seed", "3"], "up\nse\ne\nq\nw\n");
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("  2   ⬡\n1   4   6\n  3   5\nMoves: 0  (ne, e, se, sw, w, nw"));
+    assert!(stdout.contains("'up' is not a move (use ne, e, se, sw, w, nw or q)"));
+    assert!(stdout.contains("No tile can move that way"));
+    assert!(stdout.ends_with(
+        "  ⬡   2\n1   4   6\n  3   5\nMoves: 1  (ne, e, se, sw, w, nw to move, q to quit)\n\n"
+    ));
+    assert_eq!(
+        run_game(&["--hex", "1", "--size", "4"], "").status.code(),
+        Some(4)
+    );
+}
@@ -0,0 +1,285 @@
+use tile_game_rs::{
+    board::Board,
+    result::{GameResult, RESULT_SCHEMA_VERSION},
+    snapshot::load_snapshots,
+};
+
+mod common;
+
+use common::run_pipe;
+
+#[test]
+fn test_pipe_solves_board() {
+    let output = run_pipe(&["--state", "1,2,3,4,0,5,7,8,6"], "L\nU\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1 2 3\n4 5 6\n7 8 .\nMoves: 2, solved: yes\n"
+    );
+}
+
+#[test]
+fn test_pipe_counts_only_effective_moves() {
+    let output = run_pipe(&["--no-shuffle"], "U D");
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("Moves: 1, solved: no\n"));
+}
+
+#[test]
+fn test_pipe_seed_is_reproducible() {
+    let first = run_pipe(&["--seed", "42"], "");
+    let second = run_pipe(&["--seed", "42"], "");
+    assert_eq!(first.stdout, second.stdout);
+}
+
+#[test]
+fn test_pipe_rejects_bad_moves() {
+    let output = run_pipe(&["--no-shuffle"], "UD X");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("'X' at byte 3"));
+}
+
+#[test]
+fn test_pipe_json_output() {
+    let output = run_pipe(&["--state", "1,2,3,4,0,5,7,8,6", "--json"], "LU");
+    assert!(output.status.success());
+
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.schema_version, RESULT_SCHEMA_VERSION);
+    assert_eq!(result.initial_board.tiles(), [1, 2, 3, 4, 0, 5, 7, 8, 6]);
+    assert_eq!(result.final_board, Board::goal_state(3));
+    assert_eq!(result.moves, "LU");
+    assert_eq!(result.move_count, 2);
+    assert!(result.solved);
+    assert_eq!(result.mode, "classic");
+    assert_eq!(result.tile_moves, [0, 0, 0, 0, 1, 1, 0, 0]);
+    // Worked out from a solution, since --state doesn't come with one
+    assert_eq!(result.scramble.as_deref(), Some("D R"));
+}
+
+#[test]
+fn test_pipe_json_scramble() {
+    let output = run_pipe(&["--seed", "5", "--size", "4", "--json"], "");
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    let scramble = result.scramble.unwrap();
+    let replayed = Board::goal_state(4).scrambled_by(&scramble).unwrap();
+    assert_eq!(replayed, result.initial_board);
+
+    let output = run_pipe(&["--scramble", "D R D", "--json"], "");
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.scramble.as_deref(), Some("D R D"));
+}
+
+#[test]
+fn test_pipe_gravity_mode() {
+    // 3 falls into the blank 5 leaves, then 3 and 6 slide up and hold on
+    let output = run_pipe(
+        &["--mode", "gravity", "--state", "1,2,3,4,0,5,7,8,6"],
+        "LUU",
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("Moves: 3, solved: yes\n"));
+
+    // The shuffle's scramble is plain slides, falls and all
+    let output = run_pipe(&["--mode", "gravity", "--seed", "5", "--json"], "");
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.mode, "gravity");
+    let replayed = Board::goal_state(3)
+        .scrambled_by(&result.scramble.unwrap())
+        .unwrap();
+    assert_eq!(replayed, result.initial_board);
+}
+
+#[test]
+fn test_pipe_magnetic_mode() {
+    // 7 slides left and pulls 8 along behind it
+    let output = run_pipe(
+        &[
+            "--mode",
+            "magnetic",
+            "--state",
+            "1,2,3,4,5,6,0,7,8",
+            "--json",
+        ],
+        "L",
+    );
+    assert!(output.status.success());
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.mode, "magnetic");
+    assert_eq!(result.final_board, Board::goal_state(3));
+    assert!(result.solved);
+    assert_eq!(result.move_count, 1);
+    assert_eq!(result.moves, "LL");
+    assert_eq!(result.tile_moves, [0, 0, 0, 0, 0, 0, 1, 1]);
+
+    // The shuffle is a walk of magnetic moves, recorded as plain slides
+    let output = run_pipe(&["--mode", "magnetic", "--seed", "3", "--json"], "");
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    let replayed = Board::goal_state(3)
+        .scrambled_by(&result.scramble.unwrap())
+        .unwrap();
+    assert_eq!(replayed, result.initial_board);
+}
+
+#[test]
+fn test_pipe_wraparound_mode() {
+    // 7 slides round the edge from the far corner
+    let output = run_pipe(
+        &[
+            "--mode",
+            "wraparound",
+            "--state",
+            "1,2,3,4,5,6,0,8,7",
+            "--json",
+        ],
+        "R",
+    );
+    assert!(output.status.success());
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.mode, "wraparound");
+    assert!(result.solved);
+    assert_eq!(result.move_count, 1);
+    assert_eq!(result.tile_moves, [0, 0, 0, 0, 0, 0, 1, 0]);
+}
+
+#[test]
+fn test_pipe_rotation_mode() {
+    // The moves turn the blank's row or column, and the result keeps them
+    let output = run_pipe(
+        &[
+            "--mode",
+            "rotation",
+            "--state",
+            "1,2,0,4,5,3,7,8,6",
+            "--json",
+        ],
+        "U",
+    );
+    assert!(output.status.success());
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result.mode, "rotation");
+    assert!(result.solved);
+    assert_eq!(result.moves, "C3-");
+    assert_eq!(result.tile_moves, [0, 0, 1, 0, 0, 1, 0, 0]);
+    assert_eq!(result.scramble, None);
+
+    // A shuffle comes with its rotations
+    let output = run_pipe(&["--mode", "rotation", "--seed", "4", "--json"], "");
+    let result: GameResult = serde_json::from_slice(&output.stdout).unwrap();
+    let scramble = result.scramble.unwrap();
+    assert!(scramble.split(' ').all(|rotation| rotation.len() == 3));
+}
+
+#[test]
+fn test_pipe_log_file() {
+    let path = std::env::temp_dir().join(format!("tile-game-{}.log", std::process::id()));
+    let log_file = path.to_str().unwrap();
+    let output = run_pipe(
+        &[
+            "--state",
+            "1,2,3,4,0,5,7,8,6",
+            "--log-file",
+            log_file,
+            "--log-level",
+            "debug",
+        ],
+        "LUU",
+    );
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(log.contains("move Left applied, 1 moves"));
+    assert!(log.contains("move Up ignored"));
+}
+
+#[test]
+fn test_pipe_snapshots() {
+    let path = std::env::temp_dir().join(format!("tile-game-{}.json", std::process::id()));
+    let output = run_pipe(
+        &[
+            "--state",
+            "1,2,3,4,0,5,7,8,6",
+            "--snapshots",
+            path.to_str().unwrap(),
+        ],
+        "LU",
+    );
+    let snapshots = load_snapshots(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(output.status.success());
+    let snapshots = snapshots.unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].move_count, 2);
+    assert_eq!(snapshots[0].board, Board::goal_state(3));
+    assert!(snapshots[0].timestamp_utc.ends_with('Z'));
+}
+
+#[test]
+fn test_pipe_event_log() {
+    let path = std::env::temp_dir().join(format!("tile-game-{}-events.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let args = [
+        "--state",
+        "1,2,3,4,0,5,7,8,6",
+        "--event-log",
+        path.to_str().unwrap(),
+    ];
+    let solved = run_pipe(&args, "LU");
+    // The log is appended to, so a second game follows the first
+    let quit = run_pipe(&args, "D");
+    let log = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(solved.status.success());
+    assert!(!quit.status.success());
+    let events: Vec<serde_json::Value> = log
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let types: Vec<_> = events
+        .iter()
+        .map(|event| event["event_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, ["move", "move", "win", "move", "quit"]);
+
+    assert_eq!(events[0]["direction"], "left");
+    assert_eq!(events[0]["move_count"], 1);
+    assert_eq!(
+        events[2]["board_state"],
+        serde_json::json!([1, 2, 3, 4, 5, 6, 7, 8, 0])
+    );
+    assert_eq!(events[4]["move_count"], 1);
+    assert!(events[4].get("direction").is_none());
+    assert!(events[4]["timestamp"].as_str().unwrap().ends_with('Z'));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_pipe_tracing_events_reach_log() {
+    let path = std::env::temp_dir().join(format!("tile-game-{}-tracing.log", std::process::id()));
+    let output = run_pipe(
+        &[
+            "--state",
+            "1,2,3,4,0,5,7,8,6",
+            "--log-file",
+            path.to_str().unwrap(),
+        ],
+        "LU",
+    );
+    let log = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(output.status.success());
+    let log = log.unwrap();
+    assert!(log.contains("move applied moves=1 distance=1"));
+    assert!(log.contains("move applied moves=2 distance=0"));
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::{run_game, run_pipe};
+use tile_game_rs::result::BoardSummary;
+
+#[test]
+fn test_print_board() {
+    let output = run_game(&["--print-board", "1 2 3 4 0 5 7 8 6"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1 2 3\n4 . 5\n7 8 6\n"
+    );
+}
+
+#[test]
+fn test_print_board_json() {
+    let output = run_game(&["--print-board", "1,2,3,4,5,6,7,8,0", "--json"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"tiles\":[1,2,3,4,5,6,7,8,0],\"solved\":true,\"distance\":0}\n"
+    );
+
+    let output = run_game(&["--print-board", "1 2 3 4 0 5 7 8 6", "--json"], "");
+    let summary: BoardSummary = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(!summary.solved);
+    assert_eq!(summary.distance, 2);
+}
+
+#[test]
+fn test_print_board_unsolvable_still_prints() {
+    let output = run_game(&["--print-board", "2 1 3 0"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "2 1\n3 .\n");
+}
+
+#[test]
+fn test_dot_graph() {
+    let output = run_game(&["--state", "1,2,3,4,0,5,7,8,6", "--dot", "2"], "");
+    assert_eq!(output.status.code(), Some(0));
+    let dot = String::from_utf8(output.stdout).unwrap();
+    assert!(dot.starts_with("digraph {\n"));
+    assert_eq!(dot.matches(" -> ").count(), 12);
+
+    let output = run_game(&["--dot", "13"], "");
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_solve_analyze() {
+    let output = run_game(&["solve", "--state", "1 2 3 4 0 5 7 8 6", "--analyze"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Manhattan distance: 2\nLinear conflicts: 0\nInversions: 2\nLower bound: 2 moves\n\
+         Optimal: 2 moves\nRating: Trivial\nLU (2 moves)\n"
+    );
+
+    // Too big to solve, but still rated on the cheap signals
+    let state = "2,1,3,4,5,6,7,8,9,10,11,12,13,14,0,15";
+    let output = run_game(&["solve", "--state", state, "--analyze"], "");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Linear conflicts: 1\n"));
+    assert!(stdout.contains("Optimal: unknown\n"));
+}
+
+#[test]
+fn test_solve_interactive_prints_without_a_terminal() {
+    let output = run_game(
+        &["solve", "--state", "1 2 3 4 0 5 7 8 6", "--interactive"],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "LU (2 moves)\n");
+}
+
+#[test]
+fn test_solve_print_inverse() {
+    let output = run_game(
+        &["solve", "--state", "1 2 3 4 0 5 7 8 6", "--print-inverse"],
+        "",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Scramble: D R\nInverse: L U\nLU (2 moves)\n"
+    );
+}
+
+#[test]
+fn test_solve_puzzle_text() {
+    let puzzle = "# exported elsewhere\npuzzle 3x3 seed=5\n1 2 3\n4 . 5\n7 8 6\n";
+    let output = run_game(&["solve", "--puzzle", "-", "--format", "text"], puzzle);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "puzzle 3x3 seed=5\n1 2 3\n4 . 5\n7 8 6\nSOLUTION LU\n"
+    );
+
+    let output = run_game(&["solve", "--puzzle", "-"], "puzzle 2x2\n1 2\n3 3\n");
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: -: line 3, column 3: tile 3 appears more than once\n"
+    );
+}
+
+#[test]
+fn test_export_snapshots() {
+    let path = std::env::temp_dir().join(format!("tile-game-export-{}.json", std::process::id()));
+    let output = run_pipe(
+        &[
+            "--state",
+            "1 2 3 4 0 5 7 8 6",
+            "--snapshots",
+            path.to_str().unwrap(),
+        ],
+        "LU",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let file = path.to_str().unwrap();
+    let last = run_game(&["export", file], "");
+    let missing = run_game(&["export", file, "--at-move", "3"], "");
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(
+        String::from_utf8(last.stdout).unwrap(),
+        "puzzle 3x3\n1 2 3\n4 5 6\n7 8 .\n"
+    );
+    assert_eq!(missing.status.code(), Some(4));
+    assert!(String::from_utf8(missing.stderr)
+        .unwrap()
+        .contains("has no snapshot at that move (it has 2)"));
+}
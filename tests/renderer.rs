@@ -0,0 +1,87 @@
+use std::{cell::RefCell, rc::Rc};
+use tile_game_rs::{
+    board::{Board, Direction},
+    game::{GameSession, StartKind},
+    renderer::{LineRenderer, MockRenderer, Renderer, RendererHandle},
+};
+
+fn session_with_mock() -> (GameSession, Rc<RefCell<MockRenderer>>) {
+    let mock = Rc::new(RefCell::new(MockRenderer::new()));
+    let board: Board = "1 2 3 4 0 5 7 8 6".parse().unwrap();
+    let session = GameSession::new(board, StartKind::Custom, None).with_renderer(
+        RendererHandle::from(mock.clone() as Rc<RefCell<dyn Renderer>>),
+    );
+    (session, mock)
+}
+
+#[test]
+fn test_render_records_frames() {
+    let (mut session, mock) = session_with_mock();
+    session.render().unwrap();
+    session.apply_move(Direction::Left);
+    session.render().unwrap();
+    session.apply_move(Direction::Up);
+    session.render().unwrap();
+
+    assert_eq!(
+        mock.borrow().frames,
+        [
+            "1 2 3\n4 . 5\n7 8 6\n",
+            "1 2 3\n4 5 .\n7 8 6\n",
+            "1 2 3\n4 5 6\n7 8 .\n",
+        ]
+    );
+}
+
+#[test]
+fn test_render_split_records_both_boards() {
+    let (session, mock) = session_with_mock();
+    let other = GameSession::new(Board::goal_state(2), StartKind::Custom, None);
+    let renderer = session.renderer.clone().unwrap();
+    renderer.render_split([&session, &other]).unwrap();
+
+    assert_eq!(mock.borrow().frames, ["1 2 3\n4 . 5\n7 8 6\n\n1 2\n3 .\n"]);
+}
+
+#[test]
+fn test_line_renderer_output() {
+    let mut session = GameSession::new(Board::goal_state(2), StartKind::Debug, None);
+    let mut renderer = LineRenderer::new(Vec::new());
+    renderer.render(&session.board, &session).unwrap();
+    session.apply_move(Direction::Down);
+    session.apply_move(Direction::Up);
+    renderer.render(&session.board, &session).unwrap();
+
+    assert_eq!(
+        String::from_utf8(renderer.into_inner()).unwrap(),
+        "1 2\n3 .\nMoves: 0  (u, d, l, r to move, q to quit)\n\n\
+         1 2\n3 .\nYou won in 2 moves!\n\n"
+    );
+}
+
+#[test]
+fn test_clear_records_empty_frame() {
+    let (session, mock) = session_with_mock();
+    session.render().unwrap();
+    session.renderer.as_ref().unwrap().clear().unwrap();
+    assert_eq!(mock.borrow().frames.last().unwrap(), "");
+}
+
+#[test]
+fn test_checkpoint_shares_renderer() {
+    let (mut session, mock) = session_with_mock();
+    let checkpoint = session.checkpoint();
+    assert_eq!(checkpoint.renderer, session.renderer);
+
+    session.apply_move(Direction::Left);
+    session = checkpoint;
+    session.render().unwrap();
+
+    assert_eq!(mock.borrow().frames, ["1 2 3\n4 . 5\n7 8 6\n"]);
+}
+
+#[test]
+fn test_render_without_renderer_is_noop() {
+    let session = GameSession::new(Board::new(), StartKind::Debug, None);
+    assert!(session.render().is_ok());
+}